@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use zellij_tile::prelude::*;
 
 // This is a fixture plugin used only for tests in Zellij
@@ -35,7 +36,7 @@ register_plugin!(State);
 register_worker!(TestWorker, test_worker, TEST_WORKER);
 
 impl ZellijPlugin for State {
-    fn load(&mut self) {
+    fn load(&mut self, _configuration: BTreeMap<String, String>) {
         subscribe(&[
             EventType::InputReceived,
             EventType::Key,
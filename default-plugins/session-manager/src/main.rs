@@ -0,0 +1,298 @@
+mod session_list;
+mod ui;
+
+use session_list::{Item, SessionList};
+use std::collections::BTreeMap;
+use zellij_tile::prelude::*;
+
+/// What the user is asked for instead of browsing the sessions
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Prompt {
+    KillSession(String),             // the name of the session to kill
+    NewSessionName(String),          // the name typed so far
+    NewSessionLayout(String, usize), // the session name and the index of the selected layout
+    RenameSession(String),           // the new name typed so far
+}
+
+#[derive(Default)]
+struct State {
+    sessions: SessionList,
+    available_layouts: Vec<String>,
+    prompt: Option<Prompt>,
+    error: Option<String>,
+    colors: Palette,
+}
+
+register_plugin!(State);
+
+impl ZellijPlugin for State {
+    fn load(&mut self, _configuration: BTreeMap<String, String>) {
+        subscribe(&[
+            EventType::ModeUpdate,
+            EventType::SessionUpdate,
+            EventType::Key,
+        ]);
+    }
+
+    fn update(&mut self, event: Event) -> bool {
+        let mut should_render = false;
+        match event {
+            Event::ModeUpdate(mode_info) => {
+                self.colors = mode_info.style.colors;
+                self.sessions
+                    .set_current_session_name(mode_info.session_name);
+                should_render = true;
+            },
+            Event::SessionUpdate(session_infos, available_layouts) => {
+                self.sessions.set_session_infos(session_infos);
+                self.available_layouts = available_layouts;
+                should_render = true;
+            },
+            Event::Key(key) => {
+                self.error = None;
+                match self.prompt.take() {
+                    Some(prompt) => self.handle_prompt_key(key, prompt),
+                    None => self.handle_key(key),
+                }
+                should_render = true;
+            },
+            _ => (),
+        };
+        should_render
+    }
+
+    fn render(&mut self, rows: usize, cols: usize) {
+        let (title_line, list_lines, controls_line) = match &self.prompt {
+            Some(Prompt::NewSessionName(session_name)) => (
+                ui::prompt_line("New session name: ", session_name, self.colors),
+                vec![],
+                match &self.error {
+                    Some(error) => ui::error_line(error, self.colors),
+                    None => {
+                        ui::controls_line(&[("ENTER", "Choose layout"), ("ESC", "Cancel")], cols)
+                    },
+                },
+            ),
+            Some(Prompt::RenameSession(new_name)) => (
+                ui::prompt_line("Rename session to: ", new_name, self.colors),
+                vec![],
+                match &self.error {
+                    Some(error) => ui::error_line(error, self.colors),
+                    None => ui::controls_line(&[("ENTER", "Rename"), ("ESC", "Cancel")], cols),
+                },
+            ),
+            Some(Prompt::NewSessionLayout(session_name, selected_layout)) => (
+                ui::title_line(&format!("Layout for {}:", session_name), self.colors),
+                ui::layout_lines(
+                    &self.available_layouts,
+                    *selected_layout,
+                    rows.saturating_sub(2),
+                    cols,
+                    self.colors,
+                ),
+                ui::controls_line(&[("ENTER", "Create"), ("ESC", "Back")], cols),
+            ),
+            prompt => (
+                ui::prompt_line("Search: ", self.sessions.search_term(), self.colors),
+                ui::session_lines(&self.sessions, rows.saturating_sub(2), cols, self.colors),
+                match (prompt, &self.error) {
+                    (Some(Prompt::KillSession(session_name)), _) => ui::title_line(
+                        &format!("Kill session {}? (y/n)", session_name),
+                        self.colors,
+                    ),
+                    (_, Some(error)) => ui::error_line(error, self.colors),
+                    _ => ui::controls_line(
+                        &[
+                            ("↓↑", "Navigate"),
+                            ("→←", "Expand/Collapse"),
+                            ("ENTER", "Switch"),
+                            ("Ctrl n", "New"),
+                            ("Ctrl r", "Rename"),
+                            ("Ctrl x", "Kill"),
+                            ("ESC", "Close"),
+                        ],
+                        cols,
+                    ),
+                },
+            ),
+        };
+        println!("{}", title_line);
+        for line_index in 0..rows.saturating_sub(2) {
+            println!(
+                "{}",
+                list_lines.get(line_index).map(|l| l.as_str()).unwrap_or("")
+            );
+        }
+        print!("{}", controls_line);
+    }
+}
+
+impl State {
+    fn handle_key(&mut self, key: Key) {
+        match key {
+            Key::Esc => {
+                if self.sessions.search_term().is_empty() {
+                    hide_self();
+                } else {
+                    self.sessions.set_search_term("");
+                }
+            },
+            Key::Up => self.sessions.move_selection_up(),
+            Key::Down => self.sessions.move_selection_down(),
+            Key::Right => self.sessions.expand_selected(),
+            Key::Left => self.sessions.collapse_selected(),
+            Key::Char('\n') => self.switch_to_selected(),
+            Key::Ctrl('n') => {
+                let session_name = self.sessions.search_term().to_owned();
+                self.prompt = Some(Prompt::NewSessionName(session_name));
+            },
+            Key::Ctrl('r') => {
+                // only the session the plugin runs in can be renamed
+                if let Some(current_session_name) = self.sessions.current_session_name() {
+                    self.prompt = Some(Prompt::RenameSession(current_session_name.to_owned()));
+                }
+            },
+            Key::Ctrl('x') => {
+                if let Some(selected_item) = self.sessions.selected_item() {
+                    let session_name = selected_item.session_name().to_owned();
+                    self.prompt = Some(Prompt::KillSession(session_name));
+                }
+            },
+            Key::Backspace => {
+                let mut search_term = self.sessions.search_term().to_owned();
+                search_term.pop();
+                self.sessions.set_search_term(&search_term);
+            },
+            Key::Char(character) if !character.is_control() => {
+                let search_term = format!("{}{}", self.sessions.search_term(), character);
+                self.sessions.set_search_term(&search_term);
+            },
+            _ => {},
+        }
+    }
+    fn handle_prompt_key(&mut self, key: Key, prompt: Prompt) {
+        self.prompt = match (prompt, key) {
+            (Prompt::KillSession(session_name), Key::Char('y')) => {
+                kill_sessions(&[&session_name]);
+                self.sessions.remove_session(&session_name);
+                None
+            },
+            (Prompt::KillSession(_), _) => None,
+            (Prompt::NewSessionName(_), Key::Esc) => None,
+            (Prompt::NewSessionName(session_name), Key::Char('\n')) => {
+                match invalid_session_name_reason(&session_name) {
+                    Some(reason) => {
+                        self.error = Some(reason.to_owned());
+                        Some(Prompt::NewSessionName(session_name))
+                    },
+                    None if self.sessions.has_session(&session_name) => {
+                        // there is nothing to create, we go to the session as it is
+                        self.switch_to(&Item::Session(session_name));
+                        None
+                    },
+                    None => Some(Prompt::NewSessionLayout(session_name, 0)),
+                }
+            },
+            (Prompt::NewSessionName(mut session_name), Key::Backspace) => {
+                session_name.pop();
+                Some(Prompt::NewSessionName(session_name))
+            },
+            (Prompt::NewSessionName(mut session_name), Key::Char(character))
+                if !character.is_control() =>
+            {
+                session_name.push(character);
+                Some(Prompt::NewSessionName(session_name))
+            },
+            (Prompt::RenameSession(_), Key::Esc) => None,
+            (Prompt::RenameSession(new_name), Key::Char('\n')) => {
+                match invalid_session_name_reason(&new_name) {
+                    Some(reason) => {
+                        self.error = Some(reason.to_owned());
+                        Some(Prompt::RenameSession(new_name))
+                    },
+                    None if self.sessions.is_current_session(&new_name) => None,
+                    None if self.sessions.has_session(&new_name) => {
+                        self.error = Some(format!("A session named {} already exists", new_name));
+                        Some(Prompt::RenameSession(new_name))
+                    },
+                    None => {
+                        rename_session(&new_name);
+                        None
+                    },
+                }
+            },
+            (Prompt::RenameSession(mut new_name), Key::Backspace) => {
+                new_name.pop();
+                Some(Prompt::RenameSession(new_name))
+            },
+            (Prompt::RenameSession(mut new_name), Key::Char(character))
+                if !character.is_control() =>
+            {
+                new_name.push(character);
+                Some(Prompt::RenameSession(new_name))
+            },
+            (Prompt::NewSessionLayout(session_name, ..), Key::Esc) => {
+                Some(Prompt::NewSessionName(session_name))
+            },
+            (Prompt::NewSessionLayout(session_name, selected_layout), Key::Up) => Some(
+                Prompt::NewSessionLayout(session_name, selected_layout.saturating_sub(1)),
+            ),
+            (Prompt::NewSessionLayout(session_name, selected_layout), Key::Down) => {
+                let last_layout = self.available_layouts.len().saturating_sub(1);
+                Some(Prompt::NewSessionLayout(
+                    session_name,
+                    (selected_layout + 1).min(last_layout),
+                ))
+            },
+            (Prompt::NewSessionLayout(session_name, selected_layout), Key::Char('\n')) => {
+                match self.available_layouts.get(selected_layout) {
+                    Some(layout) => {
+                        hide_self();
+                        switch_session_with_layout(&session_name, layout);
+                        None
+                    },
+                    None => Some(Prompt::NewSessionLayout(session_name, selected_layout)),
+                }
+            },
+            (prompt, _) => Some(prompt),
+        };
+    }
+    fn switch_to_selected(&mut self) {
+        if let Some(selected_item) = self.sessions.selected_item().cloned() {
+            self.switch_to(&selected_item);
+        }
+    }
+    fn switch_to(&mut self, item: &Item) {
+        hide_self();
+        if self.sessions.is_current_session(item.session_name()) {
+            match item {
+                Item::Session(_) => {},
+                Item::Tab(_, tab_position) => go_to_tab(*tab_position as i32 + 1),
+                Item::Pane(_, _, pane_id) => focus_pane_with_id(*pane_id, true),
+            }
+        } else {
+            match item {
+                Item::Session(session_name) => switch_session(session_name),
+                Item::Tab(session_name, tab_position) => {
+                    switch_session_with_focus(session_name, Some(*tab_position), None)
+                },
+                Item::Pane(session_name, tab_position, pane_id) => {
+                    switch_session_with_focus(session_name, Some(*tab_position), Some(*pane_id))
+                },
+            }
+        }
+    }
+}
+
+// the server refuses these names too, we only check them here to tell the user why
+fn invalid_session_name_reason(session_name: &str) -> Option<&'static str> {
+    if session_name.is_empty() {
+        Some("The session name cannot be empty")
+    } else if session_name == "." || session_name == ".." {
+        Some("The session name cannot be \".\" or \"..\"")
+    } else if session_name.contains('/') {
+        Some("The session name cannot contain \"/\"")
+    } else {
+        None
+    }
+}
@@ -0,0 +1,302 @@
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+use zellij_tile::prelude::*;
+
+/// Something in a session that can be switched to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Item {
+    Session(String),
+    Tab(String, usize),          // session name, tab position
+    Pane(String, usize, PaneId), // session name, tab position, pane id
+}
+
+impl Item {
+    pub fn session_name(&self) -> &str {
+        match self {
+            Item::Session(session_name)
+            | Item::Tab(session_name, _)
+            | Item::Pane(session_name, _, _) => session_name,
+        }
+    }
+}
+
+/// A line in the list of sessions
+#[derive(Debug, Clone)]
+pub struct ListEntry {
+    pub item: Item,
+    pub name: String,
+    pub description: String,
+    pub indentation: usize,
+    pub is_expanded: Option<bool>, // None if the entry has nothing to expand
+    pub indices: Vec<usize>,       // the characters of the name that matched the search term
+}
+
+#[derive(Debug, Default)]
+pub struct SessionList {
+    session_infos: Vec<SessionInfo>,
+    current_session_name: Option<String>,
+    expanded_items: Vec<Item>,
+    search_term: String,
+    entries: Vec<ListEntry>,
+    selected_index: usize,
+}
+
+impl SessionList {
+    pub fn set_session_infos(&mut self, mut session_infos: Vec<SessionInfo>) {
+        // the current session always comes first
+        session_infos.sort_by_key(|session_info| {
+            Some(&session_info.name) != self.current_session_name.as_ref()
+        });
+        self.session_infos = session_infos;
+        self.update_entries();
+    }
+    pub fn set_current_session_name(&mut self, current_session_name: Option<String>) {
+        if self.current_session_name != current_session_name {
+            self.current_session_name = current_session_name;
+            let session_infos = std::mem::take(&mut self.session_infos);
+            self.set_session_infos(session_infos);
+        }
+    }
+    pub fn current_session_name(&self) -> Option<&str> {
+        self.current_session_name.as_deref()
+    }
+    pub fn is_current_session(&self, session_name: &str) -> bool {
+        self.current_session_name.as_deref() == Some(session_name)
+    }
+    pub fn has_session(&self, session_name: &str) -> bool {
+        self.session_infos
+            .iter()
+            .any(|session_info| session_info.name == session_name)
+    }
+    pub fn remove_session(&mut self, session_name: &str) {
+        self.session_infos
+            .retain(|session_info| session_info.name != session_name);
+        self.update_entries();
+    }
+    pub fn search_term(&self) -> &str {
+        &self.search_term
+    }
+    pub fn set_search_term(&mut self, search_term: &str) {
+        // the best match is selected rather than what was selected before
+        self.search_term = search_term.to_owned();
+        self.entries.clear();
+        self.selected_index = 0;
+        self.update_entries();
+    }
+    pub fn entries(&self) -> &[ListEntry] {
+        &self.entries
+    }
+    pub fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+    pub fn selected_item(&self) -> Option<&Item> {
+        self.entries
+            .get(self.selected_index)
+            .map(|list_entry| &list_entry.item)
+    }
+    pub fn move_selection_up(&mut self) {
+        self.selected_index = self.selected_index.saturating_sub(1);
+    }
+    pub fn move_selection_down(&mut self) {
+        if self.selected_index + 1 < self.entries.len() {
+            self.selected_index += 1;
+        }
+    }
+    pub fn expand_selected(&mut self) {
+        if let Some(list_entry) = self.entries.get(self.selected_index) {
+            if list_entry.is_expanded == Some(false) {
+                self.expanded_items.push(list_entry.item.clone());
+                self.update_entries();
+            }
+        }
+    }
+    pub fn collapse_selected(&mut self) {
+        let selected_item = match self.selected_item() {
+            Some(selected_item) => selected_item.clone(),
+            None => return,
+        };
+        // collapsing a pane collapses the tab it is in, and so on
+        let item_to_collapse = match selected_item {
+            Item::Pane(session_name, tab_position, _) => Item::Tab(session_name, tab_position),
+            Item::Tab(session_name, tab_position)
+                if !self
+                    .expanded_items
+                    .contains(&Item::Tab(session_name.clone(), tab_position)) =>
+            {
+                Item::Session(session_name)
+            },
+            item => item,
+        };
+        self.expanded_items.retain(|item| item != &item_to_collapse);
+        self.update_entries();
+        if let Some(index) = self
+            .entries
+            .iter()
+            .position(|list_entry| list_entry.item == item_to_collapse)
+        {
+            self.selected_index = index;
+        }
+    }
+    fn update_entries(&mut self) {
+        let selected_item = self.selected_item().cloned();
+        self.entries = if self.search_term.is_empty() {
+            self.tree_entries()
+        } else {
+            self.matching_entries()
+        };
+        // keep the selection on the same item when the sessions change under it
+        if let Some(index) = selected_item.and_then(|selected_item| {
+            self.entries
+                .iter()
+                .position(|list_entry| list_entry.item == selected_item)
+        }) {
+            self.selected_index = index;
+        }
+        self.selected_index = self
+            .selected_index
+            .min(self.entries.len().saturating_sub(1));
+    }
+    fn tree_entries(&self) -> Vec<ListEntry> {
+        let mut entries = vec![];
+        for session_info in &self.session_infos {
+            let session_item = Item::Session(session_info.name.clone());
+            let is_session_expanded = self.expanded_items.contains(&session_item);
+            entries.push(ListEntry {
+                item: session_item,
+                name: session_info.name.clone(),
+                description: self.session_description(session_info),
+                indentation: 0,
+                is_expanded: Some(is_session_expanded),
+                indices: vec![],
+            });
+            if !is_session_expanded {
+                continue;
+            }
+            for (tab_position, tab_name) in session_info.tabs.iter().enumerate() {
+                let tab_item = Item::Tab(session_info.name.clone(), tab_position);
+                let is_tab_expanded = self.expanded_items.contains(&tab_item);
+                let panes = panes_in_tab(session_info, tab_position);
+                entries.push(ListEntry {
+                    item: tab_item,
+                    name: tab_name.clone(),
+                    description: count(panes.len(), "pane"),
+                    indentation: 1,
+                    is_expanded: if panes.is_empty() {
+                        None
+                    } else {
+                        Some(is_tab_expanded)
+                    },
+                    indices: vec![],
+                });
+                if !is_tab_expanded {
+                    continue;
+                }
+                for pane_info in panes {
+                    entries.push(ListEntry {
+                        item: Item::Pane(
+                            session_info.name.clone(),
+                            tab_position,
+                            pane_id(pane_info),
+                        ),
+                        name: pane_info.title.clone(),
+                        description: String::new(),
+                        indentation: 2,
+                        is_expanded: None,
+                        indices: vec![],
+                    });
+                }
+            }
+        }
+        entries
+    }
+    fn matching_entries(&self) -> Vec<ListEntry> {
+        let matcher = SkimMatcherV2::default().use_cache(true);
+        let mut matches: Vec<(i64, ListEntry)> = vec![];
+        let mut add_if_matching = |item: Item, name: &str, description: String| {
+            if let Some((score, indices)) = matcher.fuzzy_indices(name, &self.search_term) {
+                matches.push((
+                    score,
+                    ListEntry {
+                        item,
+                        name: name.to_owned(),
+                        description,
+                        indentation: 0,
+                        is_expanded: None,
+                        indices,
+                    },
+                ));
+            }
+        };
+        for session_info in &self.session_infos {
+            add_if_matching(
+                Item::Session(session_info.name.clone()),
+                &session_info.name,
+                self.session_description(session_info),
+            );
+            for (tab_position, tab_name) in session_info.tabs.iter().enumerate() {
+                add_if_matching(
+                    Item::Tab(session_info.name.clone(), tab_position),
+                    tab_name,
+                    format!("tab in {}", session_info.name),
+                );
+                for pane_info in panes_in_tab(session_info, tab_position) {
+                    add_if_matching(
+                        Item::Pane(session_info.name.clone(), tab_position, pane_id(pane_info)),
+                        &pane_info.title,
+                        format!("pane in {} > {}", session_info.name, tab_name),
+                    );
+                }
+            }
+        }
+        // the sort is stable, so equally good matches stay in session order
+        matches.sort_by(|(a, _), (b, _)| b.cmp(a));
+        matches
+            .into_iter()
+            .map(|(_, list_entry)| list_entry)
+            .collect()
+    }
+    fn session_description(&self, session_info: &SessionInfo) -> String {
+        let current = if self.is_current_session(&session_info.name) {
+            "current, "
+        } else {
+            ""
+        };
+        format!(
+            "{}{}, {}, {}",
+            current,
+            count(session_info.tabs.len(), "tab"),
+            count(session_info.panes, "pane"),
+            count(session_info.connected_clients, "client"),
+        )
+    }
+}
+
+fn panes_in_tab(session_info: &SessionInfo, tab_position: usize) -> Vec<&PaneInfo> {
+    session_info
+        .pane_manifest
+        .panes
+        .get(&tab_position)
+        .map(|panes| {
+            panes
+                .iter()
+                .filter(|pane_info| pane_info.is_selectable)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn pane_id(pane_info: &PaneInfo) -> PaneId {
+    if pane_info.is_plugin {
+        PaneId::Plugin(pane_info.id)
+    } else {
+        PaneId::Terminal(pane_info.id)
+    }
+}
+
+fn count(amount: usize, thing: &str) -> String {
+    if amount == 1 {
+        format!("{} {}", amount, thing)
+    } else {
+        format!("{} {}s", amount, thing)
+    }
+}
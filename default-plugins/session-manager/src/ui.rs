@@ -0,0 +1,143 @@
+use ansi_term::{
+    Color::{Fixed, RGB},
+    Style,
+};
+use unicode_width::UnicodeWidthChar;
+use zellij_tile::prelude::*;
+use zellij_tile_utils::palette_match;
+
+use crate::session_list::SessionList;
+
+pub fn prompt_line(label: &str, text: &str, palette: Palette) -> String {
+    let label_style = Style::new().fg(palette_match!(palette.cyan)).bold();
+    format!(" {}{}", label_style.paint(label), text)
+}
+
+pub fn title_line(text: &str, palette: Palette) -> String {
+    let title_style = Style::new().fg(palette_match!(palette.cyan)).bold();
+    format!(" {}", title_style.paint(text))
+}
+
+pub fn error_line(text: &str, palette: Palette) -> String {
+    let error_style = Style::new().fg(palette_match!(palette.red)).bold();
+    format!(" {}", error_style.paint(text))
+}
+
+/// The key hints, leaving out those that do not fit in `cols`
+pub fn controls_line(controls: &[(&str, &str)], cols: usize) -> String {
+    let key_style = Style::new().bold();
+    let mut line = String::new();
+    let mut line_width = 0;
+    for (key, action) in controls {
+        let control_width = key.chars().count() + action.chars().count() + 4; // " <" "> " " "
+        if line_width + control_width > cols {
+            break;
+        }
+        line.push_str(&format!(" <{}> {}", key_style.paint(*key), action));
+        line_width += control_width;
+    }
+    line
+}
+
+pub fn session_lines(
+    sessions: &SessionList,
+    rows: usize,
+    cols: usize,
+    palette: Palette,
+) -> Vec<String> {
+    let entries = sessions.entries();
+    if entries.is_empty() {
+        let message = if sessions.search_term().is_empty() {
+            "No sessions found"
+        } else {
+            "No matches"
+        };
+        return vec![format!(" {}", Style::new().dimmed().paint(message))];
+    }
+    let selected_index = sessions.selected_index();
+    let first_visible = (selected_index + 1).saturating_sub(rows);
+    let selected_style = Style::new().fg(palette_match!(palette.green)).bold();
+    let match_style = Style::new().fg(palette_match!(palette.orange)).bold();
+    let description_style = Style::new().dimmed();
+    entries
+        .iter()
+        .enumerate()
+        .skip(first_visible)
+        .take(rows)
+        .map(|(index, list_entry)| {
+            let is_selected = index == selected_index;
+            let expansion_marker = match list_entry.is_expanded {
+                Some(true) => "▼ ",
+                Some(false) => "▶ ",
+                None => "  ",
+            };
+            let prefix = format!(
+                "{}{}{}",
+                if is_selected { ">" } else { " " },
+                "  ".repeat(list_entry.indentation),
+                expansion_marker
+            );
+            let mut width_left = cols.saturating_sub(prefix.chars().count());
+            let mut line = if is_selected {
+                selected_style.paint(prefix).to_string()
+            } else {
+                prefix
+            };
+            for (char_index, character) in list_entry.name.chars().enumerate() {
+                let character_width = character.width().unwrap_or(0);
+                if character_width > width_left {
+                    return line;
+                }
+                width_left -= character_width;
+                let character = character.to_string();
+                if list_entry.indices.contains(&char_index) {
+                    line.push_str(&match_style.paint(character).to_string());
+                } else if is_selected {
+                    line.push_str(&selected_style.paint(character).to_string());
+                } else {
+                    line.push_str(&character);
+                }
+            }
+            if !list_entry.description.is_empty() {
+                let description = truncate(&format!(" ({})", list_entry.description), width_left);
+                line.push_str(&description_style.paint(description).to_string());
+            }
+            line
+        })
+        .collect()
+}
+
+pub fn layout_lines(
+    layouts: &[String],
+    selected_layout: usize,
+    rows: usize,
+    cols: usize,
+    palette: Palette,
+) -> Vec<String> {
+    let selected_style = Style::new().fg(palette_match!(palette.green)).bold();
+    let first_visible = (selected_layout + 1).saturating_sub(rows);
+    layouts
+        .iter()
+        .enumerate()
+        .skip(first_visible)
+        .take(rows)
+        .map(|(index, layout)| {
+            if index == selected_layout {
+                let line = truncate(&format!(">  {}", layout), cols);
+                selected_style.paint(line).to_string()
+            } else {
+                truncate(&format!("   {}", layout), cols)
+            }
+        })
+        .collect()
+}
+
+fn truncate(text: &str, max_width: usize) -> String {
+    let mut width = 0;
+    text.chars()
+        .take_while(|character| {
+            width += character.width().unwrap_or(0);
+            width <= max_width
+        })
+        .collect()
+}
@@ -438,7 +438,11 @@ fn get_key_shortcut_for_mode<'a>(
         InputMode::Tab | InputMode::RenameTab => KeyAction::Tab,
         InputMode::Resize => KeyAction::Resize,
         InputMode::Move => KeyAction::Move,
-        InputMode::Scroll | InputMode::Search | InputMode::EnterSearch => KeyAction::Search,
+        InputMode::Scroll
+        | InputMode::Search
+        | InputMode::EnterSearch
+        | InputMode::Copy
+        | InputMode::Hint => KeyAction::Search,
         InputMode::Session => KeyAction::Session,
     };
     for shortcut in shortcuts.iter_mut() {
@@ -112,6 +112,8 @@ fn get_keys_and_hints(mi: &ModeInfo) -> Vec<(String, String, Vec<Key>)> {
     use Direction as Dir;
     use actions::SearchDirection as SDir;
     use actions::SearchOption as SOpt;
+    use actions::CopyModeMotion as CMove;
+    use actions::CopyModeSelection as CSel;
 
     let mut old_keymap = mi.get_mode_keybinds();
     let s = |string: &str| string.to_string();
@@ -222,6 +224,8 @@ fn get_keys_and_hints(mi: &ModeInfo) -> Vec<(String, String, Vec<Key>)> {
             action_key(&km, &[Action::EditScrollback, TO_NORMAL])),
         (s("Enter search term"), s("Search"),
             action_key(&km, &[A::SwitchToMode(IM::EnterSearch), A::SearchInput(vec![0])])),
+        (s("Copy mode"), s("Copy"), action_key(&km, &[A::SwitchToMode(IM::Copy)])),
+        (s("Hint mode"), s("Hint"), action_key(&km, &[A::SwitchToMode(IM::Hint)])),
         (s("Select pane"), s("Select"), to_normal_key),
     ]} else if mi.mode == IM::EnterSearch { vec![
         (s("When done"), s("Done"), action_key(&km, &[A::SwitchToMode(IM::Search)])),
@@ -244,8 +248,40 @@ fn get_keys_and_hints(mi: &ModeInfo) -> Vec<(String, String, Vec<Key>)> {
             action_key(&km, &[A::SearchToggleOption(SOpt::Wrap)])),
         (s("Whole words"), s("Whole"),
             action_key(&km, &[A::SearchToggleOption(SOpt::WholeWord)])),
-    ]} else if mi.mode == IM::Session { vec![
+        (s("Regex"), s("Regex"),
+            action_key(&km, &[A::SearchToggleOption(SOpt::Regex)])),
+        (s("Copy mode"), s("Copy"), action_key(&km, &[A::SwitchToMode(IM::Copy)])),
+        (s("Hint mode"), s("Hint"), action_key(&km, &[A::SwitchToMode(IM::Hint)])),
+    ]} else if mi.mode == IM::Copy { vec![
+        (s("Move"), s("Move"), action_key_group(&km, &[
+            &[A::CopyModeMove(CMove::Left)], &[A::CopyModeMove(CMove::Down)],
+            &[A::CopyModeMove(CMove::Up)], &[A::CopyModeMove(CMove::Right)]])),
+        (s("Word forward/back/end"), s("Word"), action_key_group(&km, &[
+            &[A::CopyModeMove(CMove::NextWordStart)], &[A::CopyModeMove(CMove::PreviousWordStart)],
+            &[A::CopyModeMove(CMove::NextWordEnd)]])),
+        (s("Line start/end"), s("Line"), action_key_group(&km, &[
+            &[A::CopyModeMove(CMove::LineStart)], &[A::CopyModeMove(CMove::LineEnd)]])),
+        (s("Top/Bottom"), s("Top/Bottom"), action_key_group(&km, &[
+            &[A::CopyModeMove(CMove::Top)], &[A::CopyModeMove(CMove::Bottom)]])),
+        (s("Search down/up"), s("Search"),
+            action_key_group(&km, &[&[A::Search(SDir::Down)], &[A::Search(SDir::Up)]])),
+        (s("Select characters/words/lines/block"), s("Select"), action_key_group(&km, &[
+            &[A::CopyModeSelect(CSel::Character)], &[A::CopyModeSelect(CSel::Word)],
+            &[A::CopyModeSelect(CSel::Line)], &[A::CopyModeSelect(CSel::Block)]])),
+        (s("Copy selection"), s("Copy"), action_key(&km, &[A::Copy, TO_NORMAL])),
+    ]} else if mi.mode == IM::Hint { vec![
+        (s("Cancel"), s("Cancel"), to_normal_key),
+    ]} else if mi.mode == IM::Session {
+        // the plugin can be launched with any set of options, so we only look at its location
+        let session_manager_keys: Vec<Key> = km.iter()
+            .filter(|(_, acvec)| acvec.iter().any(|action| matches!(action,
+                A::LaunchOrFocusPlugin(run_plugin, _)
+                    if run_plugin.location.to_string() == "session-manager")))
+            .map(|(key, _)| *key)
+            .collect();
+        vec![
         (s("Detach"), s("Detach"), action_key(&km, &[Action::Detach])),
+        (s("Session manager"), s("Manager"), session_manager_keys),
         (s("Select pane"), s("Select"), to_normal_key),
     ]} else if mi.mode == IM::Tmux { vec![
         (s("Move focus"), s("Move"), action_key_group(&km, &[
@@ -4,13 +4,23 @@ use unicode_width::UnicodeWidthStr;
 use zellij_tile::prelude::*;
 use zellij_tile_utils::style;
 
-fn cursors(focused_clients: &[ClientId], palette: Palette) -> (Vec<ANSIString>, usize) {
+fn cursors(
+    focused_clients: &[ClientId],
+    read_only_clients: &[ClientId],
+    palette: Palette,
+) -> (Vec<ANSIString<'static>>, usize) {
     // cursor section, text length
     let mut len = 0;
     let mut cursors = vec![];
     for client_id in focused_clients.iter() {
         if let Some(color) = client_id_to_colors(*client_id, palette) {
-            cursors.push(style!(color.1, color.0).paint(" "));
+            // read-only clients are marked so that they can be told apart from those who can type
+            let marker = if read_only_clients.contains(client_id) {
+                "r"
+            } else {
+                " "
+            };
+            cursors.push(style!(color.1, color.0).paint(marker));
             len += 1;
         }
     }
@@ -50,7 +60,8 @@ pub fn render_tab(
 
     let right_separator = style!(background_color, foreground_color).paint(separator);
     let tab_styled_text = if !focused_clients.is_empty() {
-        let (cursor_section, extra_length) = cursors(focused_clients, palette);
+        let (cursor_section, extra_length) =
+            cursors(focused_clients, &tab.read_only_clients, palette);
         tab_text_len += extra_length;
         let mut s = String::new();
         let cursor_beginning = style!(foreground_color, background_color)
@@ -20,11 +20,14 @@ use zellij_utils::{
     input::{
         actions::Action,
         config::{Config, ConfigError},
+        layout::Layout,
         options::Options,
     },
+    ipc::ClientType,
     miette::{Report, Result},
     nix,
-    setup::Setup,
+    session_serialization::session_layout_file_path,
+    setup::{get_default_data_dir, Setup},
 };
 
 pub(crate) use crate::sessions::list_sessions;
@@ -99,11 +102,12 @@ fn create_new_client() -> ClientInfo {
 fn find_indexed_session(
     sessions: Vec<String>,
     config_options: Options,
+    client_type: ClientType,
     index: usize,
     create: bool,
 ) -> ClientInfo {
     match sessions.get(index) {
-        Some(session) => ClientInfo::Attach(session.clone(), config_options),
+        Some(session) => ClientInfo::Attach(session.clone(), config_options, client_type),
         None if create => create_new_client(),
         None => {
             println!(
@@ -251,7 +255,12 @@ fn attach_with_cli_client(
     }
 }
 
-fn attach_with_session_index(config_options: Options, index: usize, create: bool) -> ClientInfo {
+fn attach_with_session_index(
+    config_options: Options,
+    client_type: ClientType,
+    index: usize,
+    create: bool,
+) -> ClientInfo {
     // Ignore the session_name when `--index` is provided
     match get_sessions_sorted_by_mtime() {
         Ok(sessions) if sessions.is_empty() => {
@@ -262,7 +271,7 @@ fn attach_with_session_index(config_options: Options, index: usize, create: bool
                 process::exit(1);
             }
         },
-        Ok(sessions) => find_indexed_session(sessions, config_options, index, create),
+        Ok(sessions) => find_indexed_session(sessions, config_options, client_type, index, create),
         Err(e) => {
             eprintln!("Error occurred: {:?}", e);
             process::exit(1);
@@ -273,19 +282,20 @@ fn attach_with_session_index(config_options: Options, index: usize, create: bool
 fn attach_with_session_name(
     session_name: Option<String>,
     config_options: Options,
+    client_type: ClientType,
     create: bool,
 ) -> ClientInfo {
     match &session_name {
         Some(session) if create => {
             if session_exists(session).unwrap() {
-                ClientInfo::Attach(session_name.unwrap(), config_options)
+                ClientInfo::Attach(session_name.unwrap(), config_options, client_type)
             } else {
                 ClientInfo::New(session_name.unwrap())
             }
         },
         Some(prefix) => match match_session_name(prefix).unwrap() {
             SessionNameMatch::UniquePrefix(s) | SessionNameMatch::Exact(s) => {
-                ClientInfo::Attach(s, config_options)
+                ClientInfo::Attach(s, config_options, client_type)
             },
             SessionNameMatch::AmbiguousPrefix(sessions) => {
                 println!(
@@ -306,7 +316,9 @@ fn attach_with_session_name(
                 eprintln!("No active zellij sessions found.");
                 process::exit(1);
             },
-            ActiveSession::One(session_name) => ClientInfo::Attach(session_name, config_options),
+            ActiveSession::One(session_name) => {
+                ClientInfo::Attach(session_name, config_options, client_type)
+            },
             ActiveSession::Many => {
                 println!("Please specify the session to attach to, either by using the full name or a unique prefix.\nThe following sessions are active:");
                 print_sessions(get_sessions().unwrap());
@@ -316,6 +328,75 @@ fn attach_with_session_name(
     }
 }
 
+/// If `session_name` does not belong to a running session but a serialized layout of it was left
+/// behind in the data dir, offer to resurrect it and return the layout to start it with.
+/// With `force_run_commands` it is resurrected without asking and its commands are not held.
+fn resurrection_layout(
+    session_name: &str,
+    opts: &CliArgs,
+    layout: &Layout,
+    force_run_commands: bool,
+) -> Option<Layout> {
+    if !matches!(
+        match_session_name(session_name),
+        Ok(SessionNameMatch::None) | Err(_)
+    ) {
+        return None;
+    }
+    let data_dir = opts.data_dir.clone().unwrap_or_else(get_default_data_dir);
+    let layout_file_path = session_layout_file_path(&data_dir, session_name);
+    if !layout_file_path.exists() {
+        return None;
+    }
+    // without a terminal to ask in (eg. in scripts) we do not resurrect
+    if !force_run_commands
+        && !Confirm::new()
+            .with_prompt(format!(
+                "Session '{session_name}' is no longer running. Do you want to resurrect it?"
+            ))
+            .interact()
+            .unwrap_or(false)
+    {
+        return None;
+    }
+    let raw_layout = match std::fs::read_to_string(&layout_file_path) {
+        Ok(raw_layout) => raw_layout,
+        Err(e) => {
+            eprintln!("Failed to read {}: {}", layout_file_path.display(), e);
+            return None;
+        },
+    };
+    match Layout::from_str(
+        &raw_layout,
+        layout_file_path.display().to_string(),
+        None,
+        None,
+    ) {
+        Ok(mut resurrection_layout) => {
+            // keep the chrome (eg. the tab-bar) and swap layouts of the default layout for any new
+            // tabs opened in the resurrected session
+            resurrection_layout.template = layout.template.clone();
+            resurrection_layout.swap_layouts = layout.swap_layouts.clone();
+            resurrection_layout.swap_tiled_layouts = layout.swap_tiled_layouts.clone();
+            resurrection_layout.swap_floating_layouts = layout.swap_floating_layouts.clone();
+            if force_run_commands {
+                for (_tab_name, tiled_panes, floating_panes) in resurrection_layout.tabs.iter_mut()
+                {
+                    tiled_panes.add_start_suspended(Some(false));
+                    for floating_pane in floating_panes.iter_mut() {
+                        floating_pane.add_start_suspended(Some(false));
+                    }
+                }
+            }
+            Some(resurrection_layout)
+        },
+        Err(e) => {
+            eprintln!("Failed to parse the layout of session '{session_name}': {e}");
+            None
+        },
+    }
+}
+
 pub(crate) fn start_client(opts: CliArgs) {
     // look for old YAML config/layout/theme files and convert them to KDL
     convert_old_yaml_files(&opts);
@@ -341,6 +422,8 @@ pub(crate) fn start_client(opts: CliArgs) {
         session_name,
         create,
         index,
+        read_only,
+        force_run_commands,
         options,
     })) = opts.command.clone()
     {
@@ -349,8 +432,30 @@ pub(crate) fn start_client(opts: CliArgs) {
             None => config_options,
         };
 
+        if let Some(session_name) = session_name.as_ref().filter(|_| index.is_none()) {
+            if let Some(resurrection_layout) =
+                resurrection_layout(session_name, &opts, &layout, force_run_commands)
+            {
+                start_client_plan(session_name.clone());
+                start_client_impl(
+                    Box::new(os_input),
+                    opts,
+                    config,
+                    config_options,
+                    ClientInfo::New(session_name.clone()),
+                    Some(resurrection_layout),
+                );
+                return;
+            }
+        }
+
+        let client_type = if read_only {
+            ClientType::Reader
+        } else {
+            ClientType::Writer
+        };
         let client = if let Some(idx) = index {
-            attach_with_session_index(config_options.clone(), idx, create)
+            attach_with_session_index(config_options.clone(), client_type, idx, create)
         } else {
             let session_exists = session_name
                 .as_ref()
@@ -359,7 +464,7 @@ pub(crate) fn start_client(opts: CliArgs) {
             if create && !session_exists {
                 session_name.clone().map(start_client_plan);
             }
-            attach_with_session_name(session_name, config_options.clone(), create)
+            attach_with_session_name(session_name, config_options.clone(), client_type, create)
         };
 
         if let Ok(val) = std::env::var(envs::SESSION_NAME_ENV_KEY) {
@@ -369,18 +474,14 @@ pub(crate) fn start_client(opts: CliArgs) {
             }
         }
 
-        let attach_layout = match client {
-            ClientInfo::Attach(_, _) => None,
-            ClientInfo::New(_) => Some(layout),
-        };
-
+        // the layout is also used for the sessions the client switches to if they don't exist
         start_client_impl(
             Box::new(os_input),
             opts,
             config,
             config_options,
             client,
-            attach_layout,
+            Some(layout),
         );
     } else {
         if let Some(session_name) = opts.session.clone() {
@@ -413,19 +514,16 @@ pub(crate) fn start_client(opts: CliArgs) {
                         let client = attach_with_session_name(
                             Some(session_name.clone()),
                             config_options.clone(),
+                            ClientType::Writer,
                             true,
                         );
-                        let attach_layout = match client {
-                            ClientInfo::Attach(_, _) => None,
-                            ClientInfo::New(_) => Some(layout),
-                        };
                         start_client_impl(
                             Box::new(os_input),
                             opts,
                             config,
                             config_options,
                             client,
-                            attach_layout,
+                            Some(layout),
                         );
                     },
                     _ => {
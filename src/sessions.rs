@@ -1,5 +1,5 @@
 use std::os::unix::fs::FileTypeExt;
-use std::time::SystemTime;
+use std::time::{SystemTime, UNIX_EPOCH};
 use std::{fs, io, process};
 use suggest::Suggest;
 use zellij_utils::{
@@ -8,6 +8,7 @@ use zellij_utils::{
     envs,
     interprocess::local_socket::LocalSocketStream,
     ipc::{ClientToServerMsg, IpcReceiverWithContext, IpcSenderWithContext, ServerToClientMsg},
+    serde_json, sessions,
 };
 
 pub(crate) fn get_sessions() -> Result<Vec<String>, io::ErrorKind> {
@@ -70,6 +71,19 @@ fn assert_socket(name: &str) -> bool {
     }
 }
 
+fn format_duration(seconds: u64) -> String {
+    let (days, hours, minutes) = (seconds / 86400, seconds / 3600 % 24, seconds / 60 % 60);
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else if minutes > 0 {
+        format!("{}m {}s", minutes, seconds % 60)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
 pub(crate) fn print_sessions(sessions: Vec<String>) {
     let curr_session = envs::get_session_name().unwrap_or_else(|_| "".into());
     sessions.iter().for_each(|session| {
@@ -94,6 +108,56 @@ pub(crate) fn print_sessions_with_index(sessions: Vec<String>) {
     }
 }
 
+pub(crate) fn print_sessions_with_info(sessions: Vec<String>, as_json: bool) {
+    let curr_session = envs::get_session_name().unwrap_or_else(|_| "".into());
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+    let mut sessions_as_json = vec![];
+    for session in sessions {
+        // sessions that are still starting up (or do not answer in time) have no details to report
+        let info = sessions::get_session_info(&session);
+        let is_current = curr_session == session;
+        if as_json {
+            sessions_as_json.push(match info {
+                Some(info) => serde_json::json!({
+                    "name": session,
+                    "current": is_current,
+                    "connected_clients": info.connected_clients,
+                    "tabs": info.tabs,
+                    "panes": info.panes,
+                    "created_at": info.created_at,
+                    "uptime": now.saturating_sub(info.created_at),
+                    "last_activity": info.last_activity,
+                }),
+                None => serde_json::json!({
+                    "name": session,
+                    "current": is_current,
+                }),
+            });
+            continue;
+        }
+        let suffix = if is_current { " (current)" } else { "" };
+        match info {
+            Some(info) => println!(
+                "{}{}\n    clients: {}, panes: {}, created {} ago, last active {} ago\n    tabs: {}",
+                session,
+                suffix,
+                info.connected_clients,
+                info.panes,
+                format_duration(now.saturating_sub(info.created_at)),
+                format_duration(now.saturating_sub(info.last_activity)),
+                info.tabs.join(", "),
+            ),
+            None => println!("{}{}\n    (no details available)", session, suffix),
+        }
+    }
+    if as_json {
+        println!("{}", serde_json::Value::Array(sessions_as_json));
+    }
+}
+
 pub(crate) enum ActiveSession {
     None,
     One(String),
@@ -113,22 +177,25 @@ pub(crate) fn get_active_session() -> ActiveSession {
 }
 
 pub(crate) fn kill_session(name: &str) {
-    let path = &*ZELLIJ_SOCK_DIR.join(name);
-    match LocalSocketStream::connect(path) {
-        Ok(stream) => {
-            let _ = IpcSenderWithContext::new(stream).send(ClientToServerMsg::KillSession);
-        },
-        Err(e) => {
-            eprintln!("Error occurred: {:?}", e);
-            process::exit(1);
-        },
-    };
+    if let Err(e) = sessions::kill_session(name) {
+        eprintln!("Error occurred: {:?}", e);
+        process::exit(1);
+    }
 }
 
-pub(crate) fn list_sessions() {
+pub(crate) fn list_sessions(long: bool, json: bool) {
     let exit_code = match get_sessions() {
+        Ok(sessions) if json => {
+            // an empty list is still a valid answer for scripts
+            print_sessions_with_info(sessions, true);
+            0
+        },
         Ok(sessions) if !sessions.is_empty() => {
-            print_sessions(sessions);
+            if long {
+                print_sessions_with_info(sessions, false);
+            } else {
+                print_sessions(sessions);
+            }
             0
         },
         Ok(_) => {
@@ -219,3 +286,7 @@ pub(crate) fn assert_session_ne(name: &str) {
     };
     process::exit(1);
 }
+
+#[path = "./unit/sessions_tests.rs"]
+#[cfg(test)]
+mod sessions_tests;
@@ -0,0 +1,13 @@
+use super::*;
+
+#[test]
+fn format_duration_uses_the_two_largest_units() {
+    assert_eq!(format_duration(0), "0s");
+    assert_eq!(format_duration(59), "59s");
+    assert_eq!(format_duration(60), "1m 0s");
+    assert_eq!(format_duration(3599), "59m 59s");
+    assert_eq!(format_duration(3600), "1h 0m");
+    assert_eq!(format_duration(86399), "23h 59m");
+    assert_eq!(format_duration(86400), "1d 0h");
+    assert_eq!(format_duration(3 * 86400 + 5 * 3600 + 7 * 60), "3d 5h");
+}
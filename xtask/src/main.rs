@@ -31,6 +31,7 @@ pub struct WorkspaceMember {
 lazy_static::lazy_static! {
     pub static ref WORKSPACE_MEMBERS: Vec<WorkspaceMember> = vec![
         WorkspaceMember{crate_name: "default-plugins/compact-bar", build: true},
+        WorkspaceMember{crate_name: "default-plugins/session-manager", build: true},
         WorkspaceMember{crate_name: "default-plugins/status-bar", build: true},
         WorkspaceMember{crate_name: "default-plugins/strider", build: true},
         WorkspaceMember{crate_name: "default-plugins/tab-bar", build: true},
@@ -1,15 +1,20 @@
 //! The `[cli_client]` is used to attach to a running server session
 //! and dispatch actions, that are specified through the command line.
+use std::io::{self, Read, Write};
 use std::process;
 use std::{fs, path::PathBuf};
 
 use crate::os_input_output::ClientOsApi;
 use zellij_utils::{
     input::actions::Action,
-    ipc::{ClientToServerMsg, ServerToClientMsg},
+    ipc::{ClientToServerMsg, ExitReason, ServerToClientMsg},
 };
 
-pub fn start_cli_client(os_input: Box<dyn ClientOsApi>, session_name: &str, actions: Vec<Action>) {
+pub fn start_cli_client(
+    os_input: Box<dyn ClientOsApi>,
+    session_name: &str,
+    mut actions: Vec<Action>,
+) {
     let zellij_ipc_pipe: PathBuf = {
         let mut sock_dir = zellij_utils::consts::ZELLIJ_SOCK_DIR.clone();
         fs::create_dir_all(&sock_dir).unwrap();
@@ -17,6 +22,16 @@ pub fn start_cli_client(os_input: Box<dyn ClientOsApi>, session_name: &str, acti
         sock_dir.push(session_name);
         sock_dir
     };
+    let is_pipe = actions
+        .iter()
+        .any(|action| matches!(action, Action::CliPipe { .. }));
+    for action in actions.iter_mut() {
+        if let Action::CliPipe { payload, .. } = action {
+            if payload.is_none() {
+                *payload = Some(read_stdin());
+            }
+        }
+    }
     os_input.connect_to_server(&*zellij_ipc_pipe);
     for action in actions {
         let msg = ClientToServerMsg::Action(action, None);
@@ -24,10 +39,25 @@ pub fn start_cli_client(os_input: Box<dyn ClientOsApi>, session_name: &str, acti
     }
     loop {
         match os_input.recv_from_server() {
-            Some((ServerToClientMsg::UnblockInputThread, _)) => {
+            Some((ServerToClientMsg::UnblockInputThread, _)) if !is_pipe => {
                 os_input.send_to_server(ClientToServerMsg::ClientExited);
                 process::exit(0);
             },
+            Some((ServerToClientMsg::CliPipeOutput(output), _)) => {
+                let mut stdout = io::stdout();
+                let _ = stdout.write_all(output.as_bytes());
+                let _ = stdout.flush();
+            },
+            Some((ServerToClientMsg::Exit(exit_reason), _)) if is_pipe => {
+                os_input.send_to_server(ClientToServerMsg::ClientExited);
+                match exit_reason {
+                    ExitReason::Error(e) => {
+                        eprintln!("{}", e);
+                        process::exit(1);
+                    },
+                    _ => process::exit(0),
+                }
+            },
             Some((ServerToClientMsg::Log(log_lines), _)) => {
                 log_lines.iter().for_each(|line| println!("{line}"));
                 process::exit(0);
@@ -36,3 +66,12 @@ pub fn start_cli_client(os_input: Box<dyn ClientOsApi>, session_name: &str, acti
         }
     }
 }
+
+fn read_stdin() -> String {
+    let mut input = String::new();
+    if let Err(e) = io::stdin().read_to_string(&mut input) {
+        eprintln!("Failed to read from STDIN: {}", e);
+        process::exit(1);
+    }
+    input
+}
@@ -5,17 +5,18 @@ use crate::{
 };
 use zellij_utils::{
     channels::{Receiver, SenderWithContext, OPENCALLS},
-    data::{InputMode, Key},
+    data::{InputMode, Key, KeyEventType},
     errors::{ContextType, ErrorContext, FatalError},
     input::{
         actions::Action,
         cast_termwiz_key,
         config::Config,
+        kitty_keyboard,
         mouse::{MouseButton, MouseEvent},
         options::Options,
     },
     ipc::{ClientToServerMsg, ExitReason},
-    termwiz::input::InputEvent,
+    termwiz::input::{InputEvent, Modifiers},
 };
 
 #[derive(Debug, Clone, Copy)]
@@ -95,9 +96,10 @@ impl InputHandler {
                             self.handle_key(&key, raw_bytes);
                         },
                         InputEvent::Mouse(mouse_event) => {
+                            let block_selection = mouse_event.modifiers.contains(Modifiers::ALT);
                             let mouse_event =
                                 zellij_utils::input::mouse::MouseEvent::from(mouse_event);
-                            self.handle_mouse_event(&mouse_event);
+                            self.handle_mouse_event(&mouse_event, block_selection);
                         },
                         InputEvent::Paste(pasted_text) => {
                             if self.mode == InputMode::Normal || self.mode == InputMode::Locked {
@@ -136,6 +138,9 @@ impl InputHandler {
                         _ => {},
                     }
                 },
+                Ok((InputInstruction::KittyKeyEvent(key, event_type), _error_context)) => {
+                    self.handle_kitty_key(&key, event_type);
+                },
                 Ok((InputInstruction::SwitchToMode(input_mode), _error_context)) => {
                     self.mode = input_mode;
                 },
@@ -172,6 +177,27 @@ impl InputHandler {
             }
         }
     }
+    fn handle_kitty_key(&mut self, key: &Key, event_type: KeyEventType) {
+        let is_bound = self
+            .config
+            .keybinds
+            .get_actions_for_key_in_mode(&self.mode, key)
+            .is_some();
+        let writes_to_panes = self.mode == InputMode::Normal || self.mode == InputMode::Locked;
+        if event_type == KeyEventType::Release {
+            // releasing a bound key is swallowed along with its press
+            if !is_bound && writes_to_panes {
+                self.dispatch_action(Action::WriteKey(*key, event_type), None);
+            }
+        } else if is_bound || !writes_to_panes {
+            // modes that take text as input (eg. renaming a tab) expect the legacy encoding
+            let raw_bytes = kitty_keyboard::legacy_encoding(key, event_type).unwrap_or_default();
+            self.handle_key(key, raw_bytes);
+        } else {
+            // the server encodes the key for each pane according to what its application requested
+            self.dispatch_action(Action::WriteKey(*key, event_type), None);
+        }
+    }
     fn handle_stdin_ansi_instruction(&mut self, ansi_stdin_instructions: AnsiStdinInstruction) {
         match ansi_stdin_instructions {
             AnsiStdinInstruction::PixelDimensions(pixel_dimensions) => {
@@ -194,9 +220,14 @@ impl InputHandler {
                 self.os_input
                     .send_to_server(ClientToServerMsg::ColorRegisters(color_registers));
             },
+            AnsiStdinInstruction::KittyKeyboardProtocolSupport => {
+                if self.options.support_kitty_keyboard_protocol.unwrap_or(true) {
+                    self.os_input.enable_kitty_keyboard_protocol().non_fatal();
+                }
+            },
         }
     }
-    fn handle_mouse_event(&mut self, mouse_event: &MouseEvent) {
+    fn handle_mouse_event(&mut self, mouse_event: &MouseEvent, block_selection: bool) {
         match *mouse_event {
             MouseEvent::Press(button, point) => match button {
                 MouseButton::WheelUp => {
@@ -208,6 +239,8 @@ impl InputHandler {
                 MouseButton::Left => {
                     if self.holding_mouse.is_some() {
                         self.dispatch_action(Action::MouseHoldLeft(point), None);
+                    } else if block_selection {
+                        self.dispatch_action(Action::LeftClickWithBlockSelection(point), None);
                     } else {
                         self.dispatch_action(Action::LeftClick(point), None);
                     }
@@ -9,8 +9,9 @@ mod stdin_handler;
 
 use log::info;
 use std::env::current_exe;
+use std::fs;
 use std::io::{self, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::sync::{Arc, Mutex};
 use std::thread;
@@ -23,12 +24,14 @@ use crate::{
 };
 use zellij_utils::{
     channels::{self, ChannelWithContext, SenderWithContext},
-    consts::ZELLIJ_IPC_PIPE,
-    data::{ClientId, InputMode, Style},
+    consts::ZELLIJ_SOCK_DIR,
+    data::{ClientId, ConnectToSession, InputMode, Key, KeyEventType, SessionInfo, Style},
     envs,
     errors::{ClientContext, ContextType, ErrorInstruction},
     input::{config::Config, options::Options},
-    ipc::{ClientAttributes, ClientToServerMsg, ExitReason, ServerToClientMsg},
+    ipc::{ClientAttributes, ClientToServerMsg, ClientType, ExitReason, ServerToClientMsg},
+    setup::{find_default_config_dir, get_layout_dir},
+    shared::set_permissions,
     termwiz::input::InputEvent,
 };
 use zellij_utils::{cli::CliArgs, input::layout::Layout};
@@ -46,6 +49,8 @@ pub(crate) enum ClientInstruction {
     StartedParsingStdinQuery,
     DoneParsingStdinQuery,
     Log(Vec<String>),
+    SwitchSession(ConnectToSession),
+    SessionInfo(SessionInfo),
 }
 
 impl From<ServerToClientMsg> for ClientInstruction {
@@ -60,6 +65,14 @@ impl From<ServerToClientMsg> for ClientInstruction {
             ServerToClientMsg::Connected => ClientInstruction::Connected,
             ServerToClientMsg::ActiveClients(clients) => ClientInstruction::ActiveClients(clients),
             ServerToClientMsg::Log(log_lines) => ClientInstruction::Log(log_lines),
+            // only sent to the cli clients, which do not go through here
+            ServerToClientMsg::CliPipeOutput(output) => ClientInstruction::Log(vec![output]),
+            ServerToClientMsg::SwitchSession(connect_to_session) => {
+                ClientInstruction::SwitchSession(connect_to_session)
+            },
+            ServerToClientMsg::SessionInfo(session_info) => {
+                ClientInstruction::SessionInfo(session_info)
+            },
         }
     }
 }
@@ -77,6 +90,8 @@ impl From<&ClientInstruction> for ClientContext {
             ClientInstruction::Log(_) => ClientContext::Log,
             ClientInstruction::StartedParsingStdinQuery => ClientContext::StartedParsingStdinQuery,
             ClientInstruction::DoneParsingStdinQuery => ClientContext::DoneParsingStdinQuery,
+            ClientInstruction::SwitchSession(..) => ClientContext::SwitchSession,
+            ClientInstruction::SessionInfo(..) => ClientContext::SessionInfo,
         }
     }
 }
@@ -87,6 +102,9 @@ impl ErrorInstruction for ClientInstruction {
     }
 }
 
+const BEGIN_SYNCHRONIZED_UPDATE: &str = "\u{1b}[?2026h";
+const END_SYNCHRONIZED_UPDATE: &str = "\u{1b}[?2026l";
+
 fn spawn_server(socket_path: &Path, debug: bool) -> io::Result<()> {
     let mut cmd = Command::new(current_exe()?);
     cmd.arg("--server");
@@ -108,16 +126,65 @@ fn spawn_server(socket_path: &Path, debug: bool) -> io::Result<()> {
     }
 }
 
+fn ipc_pipe_for_session(session_name: &str) -> PathBuf {
+    let mut sock_dir = ZELLIJ_SOCK_DIR.clone();
+    fs::create_dir_all(&sock_dir).unwrap();
+    set_permissions(&sock_dir, 0o700).unwrap();
+    sock_dir.push(session_name);
+    sock_dir
+}
+
+fn spawn_router_thread(
+    os_input: Box<dyn ClientOsApi>,
+    send_client_instructions: SenderWithContext<ClientInstruction>,
+) -> thread::JoinHandle<()> {
+    thread::Builder::new()
+        .name("router".to_string())
+        .spawn({
+            let mut should_break = false;
+            move || loop {
+                match os_input.recv_from_server() {
+                    Some((instruction, err_ctx)) => {
+                        err_ctx.update_thread_ctx();
+                        // the server lets go of clients that exit or switch to another session
+                        if let ServerToClientMsg::Exit(_) | ServerToClientMsg::SwitchSession(_) =
+                            instruction
+                        {
+                            should_break = true;
+                        }
+                        send_client_instructions.send(instruction.into()).unwrap();
+                        if should_break {
+                            break;
+                        }
+                    },
+                    None => {
+                        send_client_instructions
+                            .send(ClientInstruction::UnblockInputThread)
+                            .unwrap();
+                        log::error!("Received empty message from server");
+                        send_client_instructions
+                            .send(ClientInstruction::Error(
+                                "Received empty message from server".to_string(),
+                            ))
+                            .unwrap();
+                        break;
+                    },
+                }
+            }
+        })
+        .unwrap()
+}
+
 #[derive(Debug, Clone)]
 pub enum ClientInfo {
-    Attach(String, Options),
+    Attach(String, Options, ClientType),
     New(String),
 }
 
 impl ClientInfo {
     pub fn get_session_name(&self) -> &str {
         match self {
-            Self::Attach(ref name, _) => name,
+            Self::Attach(ref name, _, _) => name,
             Self::New(ref name) => name,
         }
     }
@@ -126,6 +193,7 @@ impl ClientInfo {
 #[derive(Debug, Clone)]
 pub(crate) enum InputInstruction {
     KeyEvent(InputEvent, Vec<u8>),
+    KittyKeyEvent(Key, KeyEventType),
     SwitchToMode(InputMode),
     AnsiStdinInstructions(Vec<AnsiStdinInstruction>),
     StartedParsing,
@@ -172,28 +240,50 @@ pub fn start_client(
         keybinds: config.keybinds.clone(),
     };
 
+    // kept to start the sessions this client switches to if they do not exist yet
+    let plugins_config = config.plugins.clone();
+    let new_session_layout = layout.clone();
+    let new_session_config = config.clone();
+    let layout_dir = config_options
+        .layout_dir
+        .clone()
+        .or_else(|| get_layout_dir(opts.config_dir.clone().or_else(find_default_config_dir)));
+
+    // sessions this client switches to are attached to the same way
+    let client_type = match info {
+        ClientInfo::Attach(_, _, client_type) => client_type,
+        ClientInfo::New(_) => ClientType::Writer,
+    };
+
+    let ipc_pipe = ipc_pipe_for_session(info.get_session_name());
     let first_msg = match info {
-        ClientInfo::Attach(name, config_options) => {
+        ClientInfo::Attach(name, config_options, client_type) => {
             envs::set_session_name(name);
 
-            ClientToServerMsg::AttachClient(client_attributes, config_options)
+            ClientToServerMsg::AttachClient(
+                client_attributes.clone(),
+                config_options,
+                client_type,
+                None,
+                None,
+            )
         },
         ClientInfo::New(name) => {
             envs::set_session_name(name);
 
-            spawn_server(&*ZELLIJ_IPC_PIPE, opts.debug).unwrap();
+            spawn_server(&ipc_pipe, opts.debug).unwrap();
 
             ClientToServerMsg::NewClient(
-                client_attributes,
-                Box::new(opts),
+                client_attributes.clone(),
+                Box::new(opts.clone()),
                 Box::new(config_options.clone()),
                 Box::new(layout.unwrap()),
-                Some(config.plugins.clone()),
+                Some(plugins_config.clone()),
             )
         },
     };
 
-    os_input.connect_to_server(&*ZELLIJ_IPC_PIPE);
+    os_input.connect_to_server(&ipc_pipe);
     os_input.send_to_server(first_msg);
 
     let mut command_is_executing = CommandIsExecuting::new();
@@ -226,6 +316,7 @@ pub fn start_client(
     });
 
     let on_force_close = config_options.on_force_close.unwrap_or_default();
+    let default_mode = config_options.default_mode.unwrap_or_default();
     let stdin_ansi_parser = Arc::new(Mutex::new(StdinAnsiParser::new()));
 
     let _stdin_thread = thread::Builder::new()
@@ -234,7 +325,17 @@ pub fn start_client(
             let os_input = os_input.clone();
             let send_input_instructions = send_input_instructions.clone();
             let stdin_ansi_parser = stdin_ansi_parser.clone();
-            move || stdin_loop(os_input, send_input_instructions, stdin_ansi_parser)
+            let support_kitty_keyboard_protocol = config_options
+                .support_kitty_keyboard_protocol
+                .unwrap_or(true);
+            move || {
+                stdin_loop(
+                    os_input,
+                    send_input_instructions,
+                    stdin_ansi_parser,
+                    support_kitty_keyboard_protocol,
+                )
+            }
         });
 
     let _input_thread = thread::Builder::new()
@@ -243,7 +344,7 @@ pub fn start_client(
             let send_client_instructions = send_client_instructions.clone();
             let command_is_executing = command_is_executing.clone();
             let os_input = os_input.clone();
-            let default_mode = config_options.default_mode.unwrap_or_default();
+            let config_options = config_options.clone();
             move || {
                 input_loop(
                     os_input,
@@ -285,45 +386,14 @@ pub fn start_client(
         })
         .unwrap();
 
-    let router_thread = thread::Builder::new()
-        .name("router".to_string())
-        .spawn({
-            let os_input = os_input.clone();
-            let mut should_break = false;
-            move || loop {
-                match os_input.recv_from_server() {
-                    Some((instruction, err_ctx)) => {
-                        err_ctx.update_thread_ctx();
-                        if let ServerToClientMsg::Exit(_) = instruction {
-                            should_break = true;
-                        }
-                        send_client_instructions.send(instruction.into()).unwrap();
-                        if should_break {
-                            break;
-                        }
-                    },
-                    None => {
-                        send_client_instructions
-                            .send(ClientInstruction::UnblockInputThread)
-                            .unwrap();
-                        log::error!("Received empty message from server");
-                        send_client_instructions
-                            .send(ClientInstruction::Error(
-                                "Received empty message from server".to_string(),
-                            ))
-                            .unwrap();
-                        break;
-                    },
-                }
-            }
-        })
-        .unwrap();
+    let mut router_thread = spawn_router_thread(os_input.clone(), send_client_instructions.clone());
 
     let handle_error = |backtrace: String| {
         os_input.unset_raw_mode(0).unwrap();
         let goto_start_of_last_line = format!("\u{1b}[{};{}H", full_screen_ws.rows, 1);
         let restore_snapshot = "\u{1b}[?1049l";
         os_input.disable_mouse().non_fatal();
+        os_input.disable_kitty_keyboard_protocol().non_fatal();
         let error = format!(
             "{}\n{}{}\n",
             restore_snapshot, goto_start_of_last_line, backtrace
@@ -397,9 +467,17 @@ pub fn start_client(
             },
             ClientInstruction::Render(output) => {
                 let mut stdout = os_input.get_stdout_writer();
+                // terminal emulators supporting synchronized output (DEC mode 2026) will draw
+                // each frame at once, the others ignore these
+                stdout
+                    .write_all(BEGIN_SYNCHRONIZED_UPDATE.as_bytes())
+                    .expect("cannot write to stdout");
                 stdout
                     .write_all(output.as_bytes())
                     .expect("cannot write to stdout");
+                stdout
+                    .write_all(END_SYNCHRONIZED_UPDATE.as_bytes())
+                    .expect("cannot write to stdout");
                 stdout.flush().expect("could not flush");
             },
             ClientInstruction::UnblockInputThread => {
@@ -415,6 +493,63 @@ pub fn start_client(
                     log::info!("{line}");
                 }
             },
+            ClientInstruction::SwitchSession(connect_to_session) => {
+                // the server already let go of this client, we keep the terminal as it is and
+                // only swap the connection underneath
+                router_thread.join().unwrap();
+                let ConnectToSession {
+                    name,
+                    tab_position,
+                    pane_id,
+                    layout,
+                } = connect_to_session;
+                let ipc_pipe = ipc_pipe_for_session(&name);
+                envs::set_session_name(name);
+                let client_attributes = ClientAttributes {
+                    size: os_input.get_terminal_size_using_fd(0),
+                    ..client_attributes.clone()
+                };
+                let msg = if ipc_pipe.exists() {
+                    ClientToServerMsg::AttachClient(
+                        client_attributes,
+                        config_options.clone(),
+                        client_type,
+                        tab_position,
+                        pane_id,
+                    )
+                } else {
+                    // only the layout itself is used, this client keeps its own config
+                    let layout = layout
+                        .and_then(|layout| {
+                            Layout::from_path_or_default(
+                                Some(&PathBuf::from(&layout)),
+                                layout_dir.clone(),
+                                new_session_config.clone(),
+                            )
+                            .map(|(layout, _config)| layout)
+                            .map_err(|e| log::error!("Failed to load layout {}: {}", layout, e))
+                            .ok()
+                        })
+                        .or_else(|| new_session_layout.clone())
+                        .unwrap_or_default();
+                    spawn_server(&ipc_pipe, opts.debug).unwrap();
+                    ClientToServerMsg::NewClient(
+                        client_attributes,
+                        Box::new(opts.clone()),
+                        Box::new(config_options.clone()),
+                        Box::new(layout),
+                        Some(plugins_config.clone()),
+                    )
+                };
+                os_input.connect_to_server(&ipc_pipe);
+                os_input.send_to_server(msg);
+                router_thread =
+                    spawn_router_thread(os_input.clone(), send_client_instructions.clone());
+                send_input_instructions
+                    .send(InputInstruction::SwitchToMode(default_mode))
+                    .unwrap();
+                command_is_executing.unblock_input_thread();
+            },
             _ => {},
         }
     }
@@ -432,6 +567,7 @@ pub fn start_client(
     );
 
     os_input.disable_mouse().non_fatal();
+    os_input.disable_kitty_keyboard_protocol().non_fatal();
     info!("{}", exit_msg);
     os_input.unset_raw_mode(0).unwrap();
     let mut stdout = os_input.get_stdout_writer();
@@ -23,6 +23,9 @@ const SIGWINCH_CB_THROTTLE_DURATION: time::Duration = time::Duration::from_milli
 
 const ENABLE_MOUSE_SUPPORT: &str = "\u{1b}[?1000h\u{1b}[?1002h\u{1b}[?1015h\u{1b}[?1006h";
 const DISABLE_MOUSE_SUPPORT: &str = "\u{1b}[?1006l\u{1b}[?1015l\u{1b}[?1002l\u{1b}[?1000l";
+// push the "disambiguate escape codes" and "report event types" kitty keyboard protocol flags
+const ENABLE_KITTY_KEYBOARD_PROTOCOL: &str = "\u{1b}[>3u";
+const DISABLE_KITTY_KEYBOARD_PROTOCOL: &str = "\u{1b}[<u";
 
 fn into_raw_mode(pid: RawFd) {
     let mut tio = termios::tcgetattr(pid).expect("could not get terminal attribute");
@@ -109,6 +112,8 @@ pub trait ClientOsApi: Send + Sync {
     fn load_palette(&self) -> Palette;
     fn enable_mouse(&self) -> Result<()>;
     fn disable_mouse(&self) -> Result<()>;
+    fn enable_kitty_keyboard_protocol(&self) -> Result<()>;
+    fn disable_kitty_keyboard_protocol(&self) -> Result<()>;
     // Repeatedly send action, until stdin is readable again
     fn stdin_poller(&self) -> StdinPoller;
 }
@@ -244,6 +249,26 @@ impl ClientOsApi for ClientOsInputOutput {
         Ok(())
     }
 
+    fn enable_kitty_keyboard_protocol(&self) -> Result<()> {
+        let err_context = "failed to enable kitty keyboard protocol";
+        let mut stdout = self.get_stdout_writer();
+        stdout
+            .write_all(ENABLE_KITTY_KEYBOARD_PROTOCOL.as_bytes())
+            .context(err_context)?;
+        stdout.flush().context(err_context)?;
+        Ok(())
+    }
+
+    fn disable_kitty_keyboard_protocol(&self) -> Result<()> {
+        let err_context = "failed to disable kitty keyboard protocol";
+        let mut stdout = self.get_stdout_writer();
+        stdout
+            .write_all(DISABLE_KITTY_KEYBOARD_PROTOCOL.as_bytes())
+            .context(err_context)?;
+        stdout.flush().context(err_context)?;
+        Ok(())
+    }
+
     fn stdin_poller(&self) -> StdinPoller {
         StdinPoller::default()
     }
@@ -3,7 +3,11 @@ use zellij_utils::consts::{VERSION, ZELLIJ_CACHE_DIR};
 
 const STARTUP_PARSE_DEADLINE_MS: u64 = 500;
 use zellij_utils::{
-    ipc::PixelDimensions, lazy_static::lazy_static, pane_size::SizeInPixels, regex::Regex,
+    data::{BareKey, Key, KeyEventType, KeyModifiers},
+    ipc::PixelDimensions,
+    lazy_static::lazy_static,
+    pane_size::SizeInPixels,
+    regex::Regex,
 };
 
 use serde::{Deserialize, Serialize};
@@ -37,8 +41,10 @@ impl StdinAnsiParser {
         // <ESC>[16t => get character cell size in pixels
         // <ESC>]11;?<ESC>\ => get background color
         // <ESC>]10;?<ESC>\ => get foreground color
-        let mut query_string =
-            String::from("\u{1b}[14t\u{1b}[16t\u{1b}]11;?\u{1b}\u{5c}\u{1b}]10;?\u{1b}\u{5c}");
+        // <ESC>[?u => get kitty keyboard protocol flags (only answered if it is supported)
+        let mut query_string = String::from(
+            "\u{1b}[14t\u{1b}[16t\u{1b}]11;?\u{1b}\u{5c}\u{1b}]10;?\u{1b}\u{5c}\u{1b}[?u",
+        );
 
         // query colors
         // eg. <ESC>]4;5;?<ESC>\ => query color register number 5
@@ -105,7 +111,15 @@ impl StdinAnsiParser {
         };
     }
     fn parse_byte(&mut self, byte: u8) {
-        if byte == b't' {
+        if byte == b'u' {
+            self.raw_buffer.push(byte);
+            if let Ok(ansi_sequence) =
+                AnsiStdinInstruction::kitty_keyboard_protocol_from_bytes(&self.raw_buffer)
+            {
+                self.pending_events.push(ansi_sequence);
+            }
+            self.raw_buffer.clear();
+        } else if byte == b't' {
             self.raw_buffer.push(byte);
             match AnsiStdinInstruction::pixel_dimensions_from_bytes(&self.raw_buffer) {
                 Ok(ansi_sequence) => {
@@ -145,6 +159,7 @@ pub enum AnsiStdinInstruction {
     BackgroundColor(String),
     ForegroundColor(String),
     ColorRegisters(Vec<(usize, String)>),
+    KittyKeyboardProtocolSupport,
 }
 
 impl AnsiStdinInstruction {
@@ -218,6 +233,18 @@ impl AnsiStdinInstruction {
             Err("invalid_instruction")
         }
     }
+    pub fn kitty_keyboard_protocol_from_bytes(bytes: &[u8]) -> Result<Self, &'static str> {
+        // eg. <ESC>[?0u
+        lazy_static! {
+            static ref RE: Regex = Regex::new(r"\u{1b}\[\?\d+u$").unwrap();
+        }
+        let key_string = String::from_utf8_lossy(bytes);
+        if RE.is_match(&key_string) {
+            Ok(AnsiStdinInstruction::KittyKeyboardProtocolSupport)
+        } else {
+            Err("invalid_instruction")
+        }
+    }
     pub fn color_registers_from_bytes(color_sequences: &mut Vec<(usize, String)>) -> Option<Self> {
         if color_sequences.is_empty() {
             return None;
@@ -260,3 +287,162 @@ fn color_sequence_from_bytes(bytes: &[u8]) -> Result<(usize, String), &'static s
         Err("invalid_instruction")
     }
 }
+
+const BRACKETED_PASTE_START: &[u8] = &[27, 91, 50, 48, 48, 126]; // \u{1b}[200~
+const BRACKETED_PASTE_END: &[u8] = &[27, 91, 50, 48, 49, 126]; // \u{1b}[201~
+
+/// STDIN as sent by a terminal emulator using the kitty keyboard protocol
+#[derive(Debug, Clone, PartialEq)]
+pub enum KittyKeyboardInput {
+    Key(Key, KeyEventType),
+    Other(Vec<u8>), // anything that is not a key, eg. mouse events or text
+}
+
+/// Splits the keys encoded by the kitty keyboard protocol out of `bytes`, leaving everything
+/// else (eg. text, mouse events or pastes) in between as is
+pub fn parse_kitty_keyboard_input(bytes: &[u8]) -> Vec<KittyKeyboardInput> {
+    let mut input = vec![];
+    let mut other_bytes = vec![];
+    let mut index = 0;
+    while index < bytes.len() {
+        let remaining_bytes = &bytes[index..];
+        if remaining_bytes.starts_with(BRACKETED_PASTE_START) {
+            // pasted text is never parsed as keys
+            let paste_length = remaining_bytes
+                .windows(BRACKETED_PASTE_END.len())
+                .position(|window| window == BRACKETED_PASTE_END)
+                .map(|end_index| end_index + BRACKETED_PASTE_END.len())
+                .unwrap_or(remaining_bytes.len());
+            other_bytes.extend_from_slice(&remaining_bytes[..paste_length]);
+            index += paste_length;
+            continue;
+        }
+        if let Some(sequence_length) = csi_key_sequence_length(remaining_bytes) {
+            let sequence = &remaining_bytes[..sequence_length];
+            if let Some((key, event_type)) = kitty_key_from_bytes(sequence) {
+                if !other_bytes.is_empty() {
+                    input.push(KittyKeyboardInput::Other(other_bytes.drain(..).collect()));
+                }
+                input.push(KittyKeyboardInput::Key(key, event_type));
+                index += sequence_length;
+                continue;
+            }
+        }
+        other_bytes.push(bytes[index]);
+        index += 1;
+    }
+    if !other_bytes.is_empty() {
+        input.push(KittyKeyboardInput::Other(other_bytes));
+    }
+    input
+}
+
+// the length of the CSI sequence at the start of `bytes` if it can encode a key
+fn csi_key_sequence_length(bytes: &[u8]) -> Option<usize> {
+    if !bytes.starts_with(&[27, 91]) {
+        return None;
+    }
+    let parameters_length = bytes[2..]
+        .iter()
+        .take_while(|byte| byte.is_ascii_digit() || **byte == b';' || **byte == b':')
+        .count();
+    match bytes.get(2 + parameters_length) {
+        Some(b'u' | b'~' | b'A'..=b'D' | b'F' | b'H' | b'P' | b'Q' | b'S') => {
+            Some(3 + parameters_length)
+        },
+        _ => None,
+    }
+}
+
+// eg. <ESC>[97;5u => Ctrl a, <ESC>[13;2u => Shift Enter, <ESC>[1;3:3D => Alt Left released
+fn kitty_key_from_bytes(bytes: &[u8]) -> Option<(Key, KeyEventType)> {
+    let sequence = std::str::from_utf8(bytes).ok()?;
+    let final_character = sequence.chars().last()?;
+    let parameters = &sequence[2..sequence.len() - 1];
+    let mut fields = parameters.split(';');
+    // alternate key codes after a ':' are ignored
+    let key_code = fields.next().and_then(|field| field.split(':').next());
+    let mut modifiers_and_event_type = fields.next().unwrap_or("").split(':');
+    let modifiers = modifiers_and_event_type
+        .next()
+        .filter(|field| !field.is_empty())
+        .map(|field| field.parse::<u8>())
+        .transpose()
+        .ok()?
+        .unwrap_or(1)
+        .saturating_sub(1);
+    let event_type = match modifiers_and_event_type.next() {
+        None | Some("1") => KeyEventType::Press,
+        Some("2") => KeyEventType::Repeat,
+        Some("3") => KeyEventType::Release,
+        Some(_) => return None,
+    };
+    let key_code = key_code
+        .filter(|code| !code.is_empty())
+        .map(|code| code.parse::<u32>())
+        .transpose()
+        .ok()?;
+    let bare_key = match (final_character, key_code) {
+        ('u', Some(key_code)) => bare_key_from_key_code(key_code)?,
+        ('~', Some(2)) => BareKey::Insert,
+        ('~', Some(3)) => BareKey::Delete,
+        ('~', Some(5)) => BareKey::PageUp,
+        ('~', Some(6)) => BareKey::PageDown,
+        ('~', Some(7)) => BareKey::Home,
+        ('~', Some(8)) => BareKey::End,
+        ('~', Some(key_code @ 11..=15)) => BareKey::F(key_code as u8 - 10),
+        ('~', Some(key_code @ 17..=21)) => BareKey::F(key_code as u8 - 11),
+        ('~', Some(key_code @ 23..=24)) => BareKey::F(key_code as u8 - 12),
+        ('A', None | Some(1)) => BareKey::Up,
+        ('B', None | Some(1)) => BareKey::Down,
+        ('C', None | Some(1)) => BareKey::Right,
+        ('D', None | Some(1)) => BareKey::Left,
+        ('F', None | Some(1)) => BareKey::End,
+        ('H', None | Some(1)) => BareKey::Home,
+        ('P', None | Some(1)) => BareKey::F(1),
+        ('Q', None | Some(1)) => BareKey::F(2),
+        ('S', None | Some(1)) => BareKey::F(4),
+        _ => return None,
+    };
+    // hyper, meta, caps lock and num lock are ignored
+    let modifiers = KeyModifiers {
+        shift: modifiers & 0b1 != 0,
+        alt: modifiers & 0b10 != 0,
+        ctrl: modifiers & 0b100 != 0,
+        super_key: modifiers & 0b1000 != 0,
+    };
+    Some((
+        Key::from_bare_key_and_modifiers(bare_key, modifiers),
+        event_type,
+    ))
+}
+
+fn bare_key_from_key_code(key_code: u32) -> Option<BareKey> {
+    match key_code {
+        9 => Some(BareKey::Tab),
+        13 | 57414 => Some(BareKey::Enter),
+        27 => Some(BareKey::Esc),
+        127 => Some(BareKey::Backspace),
+        // keypad
+        57399..=57408 => char::from_digit(key_code - 57399, 10).map(BareKey::Char),
+        57409 => Some(BareKey::Char('.')),
+        57410 => Some(BareKey::Char('/')),
+        57411 => Some(BareKey::Char('*')),
+        57412 => Some(BareKey::Char('-')),
+        57413 => Some(BareKey::Char('+')),
+        57415 => Some(BareKey::Char('=')),
+        57417 => Some(BareKey::Left),
+        57418 => Some(BareKey::Right),
+        57419 => Some(BareKey::Up),
+        57420 => Some(BareKey::Down),
+        57421 => Some(BareKey::PageUp),
+        57422 => Some(BareKey::PageDown),
+        57423 => Some(BareKey::Home),
+        57424 => Some(BareKey::End),
+        57425 => Some(BareKey::Insert),
+        57426 => Some(BareKey::Delete),
+        // the rest of the private use area holds keys we do not handle (eg. media keys)
+        57344..=63743 => None,
+        _ => char::from_u32(key_code).map(BareKey::Char),
+    }
+}
@@ -1,5 +1,7 @@
 use crate::os_input_output::ClientOsApi;
-use crate::stdin_ansi_parser::StdinAnsiParser;
+use crate::stdin_ansi_parser::{
+    parse_kitty_keyboard_input, AnsiStdinInstruction, KittyKeyboardInput, StdinAnsiParser,
+};
 use crate::InputInstruction;
 use std::sync::{Arc, Mutex};
 use zellij_utils::channels::SenderWithContext;
@@ -23,8 +25,11 @@ pub(crate) fn stdin_loop(
     mut os_input: Box<dyn ClientOsApi>,
     send_input_instructions: SenderWithContext<InputInstruction>,
     stdin_ansi_parser: Arc<Mutex<StdinAnsiParser>>,
+    support_kitty_keyboard_protocol: bool,
 ) {
     let mut holding_mouse = false;
+    // set once the terminal emulator answers our query for the kitty keyboard protocol flags
+    let mut kitty_keyboard_protocol_supported = false;
     let mut input_parser = InputParser::new();
     let mut current_buffer = vec![];
     {
@@ -33,6 +38,7 @@ pub(crate) fn stdin_loop(
         let mut stdin_ansi_parser = stdin_ansi_parser.lock().unwrap();
         match stdin_ansi_parser.read_cache() {
             Some(events) => {
+                kitty_keyboard_protocol_supported = supports_kitty_keyboard_protocol(&events);
                 let _ =
                     send_input_instructions.send(InputInstruction::AnsiStdinInstructions(events));
                 let _ = send_input_instructions
@@ -70,6 +76,7 @@ pub(crate) fn stdin_loop(
             if stdin_ansi_parser.should_parse() {
                 let events = stdin_ansi_parser.parse(buf);
                 if !events.is_empty() {
+                    kitty_keyboard_protocol_supported |= supports_kitty_keyboard_protocol(&events);
                     ansi_stdin_events.append(&mut events.clone());
                     let _ = send_input_instructions
                         .send(InputInstruction::AnsiStdinInstructions(events));
@@ -83,46 +90,92 @@ pub(crate) fn stdin_loop(
                 .unwrap()
                 .write_cache(ansi_stdin_events.drain(..).collect());
         }
-        current_buffer.append(&mut buf.to_vec());
-        let maybe_more = false; // read_from_stdin should (hopefully) always empty the STDIN buffer completely
-        let mut events = vec![];
-        input_parser.parse(
-            &buf,
-            |input_event: InputEvent| {
-                events.push(input_event);
-            },
-            maybe_more,
-        );
-
-        let event_count = events.len();
-        for (i, input_event) in events.into_iter().enumerate() {
-            if holding_mouse && is_mouse_press_or_hold(&input_event) && i == event_count - 1 {
-                let mut poller = os_input.stdin_poller();
-                loop {
-                    if poller.ready() {
-                        break;
-                    }
-                    send_input_instructions
-                        .send(InputInstruction::KeyEvent(
-                            input_event.clone(),
-                            current_buffer.clone(),
-                        ))
-                        .unwrap();
+        if support_kitty_keyboard_protocol && kitty_keyboard_protocol_supported {
+            for input in parse_kitty_keyboard_input(&buf) {
+                match input {
+                    KittyKeyboardInput::Key(key, event_type) => {
+                        send_input_instructions
+                            .send(InputInstruction::KittyKeyEvent(key, event_type))
+                            .unwrap();
+                    },
+                    KittyKeyboardInput::Other(bytes) => {
+                        parse_input(
+                            &bytes,
+                            &mut input_parser,
+                            &mut current_buffer,
+                            &mut holding_mouse,
+                            os_input.as_ref(),
+                            &send_input_instructions,
+                        );
+                    },
                 }
             }
+        } else {
+            parse_input(
+                &buf,
+                &mut input_parser,
+                &mut current_buffer,
+                &mut holding_mouse,
+                os_input.as_ref(),
+                &send_input_instructions,
+            );
+        }
+    }
+}
 
-            holding_mouse = is_mouse_press_or_hold(&input_event);
+fn parse_input(
+    buf: &[u8],
+    input_parser: &mut InputParser,
+    current_buffer: &mut Vec<u8>,
+    holding_mouse: &mut bool,
+    os_input: &dyn ClientOsApi,
+    send_input_instructions: &SenderWithContext<InputInstruction>,
+) {
+    current_buffer.append(&mut buf.to_vec());
+    let maybe_more = false; // read_from_stdin should (hopefully) always empty the STDIN buffer completely
+    let mut events = vec![];
+    input_parser.parse(
+        buf,
+        |input_event: InputEvent| {
+            events.push(input_event);
+        },
+        maybe_more,
+    );
 
-            send_input_instructions
-                .send(InputInstruction::KeyEvent(
-                    input_event,
-                    current_buffer.drain(..).collect(),
-                ))
-                .unwrap();
+    let event_count = events.len();
+    for (i, input_event) in events.into_iter().enumerate() {
+        if *holding_mouse && is_mouse_press_or_hold(&input_event) && i == event_count - 1 {
+            let mut poller = os_input.stdin_poller();
+            loop {
+                if poller.ready() {
+                    break;
+                }
+                send_input_instructions
+                    .send(InputInstruction::KeyEvent(
+                        input_event.clone(),
+                        current_buffer.clone(),
+                    ))
+                    .unwrap();
+            }
         }
+
+        *holding_mouse = is_mouse_press_or_hold(&input_event);
+
+        send_input_instructions
+            .send(InputInstruction::KeyEvent(
+                input_event,
+                current_buffer.drain(..).collect(),
+            ))
+            .unwrap();
     }
 }
 
+fn supports_kitty_keyboard_protocol(events: &[AnsiStdinInstruction]) -> bool {
+    events
+        .iter()
+        .any(|event| matches!(event, AnsiStdinInstruction::KittyKeyboardProtocolSupport))
+}
+
 fn is_mouse_press_or_hold(input_event: &InputEvent) -> bool {
     if let InputEvent::Mouse(mouse_event) = input_event {
         if mouse_event.mouse_buttons.contains(MouseButtons::LEFT)
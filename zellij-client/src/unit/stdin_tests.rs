@@ -1,8 +1,10 @@
 use super::input_loop;
-use crate::stdin_ansi_parser::StdinAnsiParser;
+use crate::stdin_ansi_parser::{parse_kitty_keyboard_input, KittyKeyboardInput, StdinAnsiParser};
 use crate::stdin_loop;
 use zellij_utils::anyhow::Result;
-use zellij_utils::data::{Direction, InputMode, Palette};
+use zellij_utils::data::{
+    BareKey, CharOrArrow, Direction, InputMode, Key, KeyEventType, KeyModifiers, Palette,
+};
 use zellij_utils::input::actions::Action;
 use zellij_utils::input::config::Config;
 use zellij_utils::input::options::Options;
@@ -188,6 +190,12 @@ impl ClientOsApi for FakeClientOsApi {
     fn disable_mouse(&self) -> Result<()> {
         Ok(())
     }
+    fn enable_kitty_keyboard_protocol(&self) -> Result<()> {
+        Ok(())
+    }
+    fn disable_kitty_keyboard_protocol(&self) -> Result<()> {
+        Ok(())
+    }
     fn stdin_poller(&self) -> StdinPoller {
         unimplemented!()
     }
@@ -317,3 +325,117 @@ pub fn move_focus_left_in_normal_mode() {
         "All actions sent to server properly"
     );
 }
+
+#[test]
+pub fn kitty_keys_are_written_to_panes_or_trigger_keybindings() {
+    let shift_enter = Key::from_bare_key_and_modifiers(
+        BareKey::Enter,
+        KeyModifiers {
+            shift: true,
+            ..Default::default()
+        },
+    );
+    let stdin_events = vec![
+        (shift_enter, KeyEventType::Press),
+        (shift_enter, KeyEventType::Release),
+        (Key::Alt(CharOrArrow::Char('h')), KeyEventType::Press),
+        (Key::Alt(CharOrArrow::Char('h')), KeyEventType::Release),
+        (Key::Ctrl('q'), KeyEventType::Press),
+    ];
+
+    let events_sent_to_server = Arc::new(Mutex::new(vec![]));
+    let command_is_executing = CommandIsExecuting::new();
+    let client_os_api = Box::new(FakeClientOsApi::new(
+        events_sent_to_server.clone(),
+        command_is_executing.clone(),
+    ));
+    let config = Config::from_default_assets().unwrap();
+    let options = Options::default();
+
+    let (send_client_instructions, _receive_client_instructions): ChannelWithContext<
+        ClientInstruction,
+    > = channels::bounded(50);
+    let send_client_instructions = SenderWithContext::new(send_client_instructions);
+
+    let (send_input_instructions, receive_input_instructions): ChannelWithContext<
+        InputInstruction,
+    > = channels::bounded(50);
+    let send_input_instructions = SenderWithContext::new(send_input_instructions);
+    for (key, event_type) in stdin_events {
+        send_input_instructions
+            .send(InputInstruction::KittyKeyEvent(key, event_type))
+            .unwrap();
+    }
+
+    let default_mode = InputMode::Normal;
+    input_loop(
+        client_os_api,
+        config,
+        options,
+        command_is_executing,
+        send_client_instructions,
+        default_mode,
+        receive_input_instructions,
+    );
+    let expected_actions_sent_to_server = vec![
+        Action::WriteKey(shift_enter, KeyEventType::Press),
+        Action::WriteKey(shift_enter, KeyEventType::Release),
+        Action::MoveFocusOrTab(Direction::Left),
+        Action::Quit,
+    ];
+    let received_actions = extract_actions_sent_to_server(events_sent_to_server);
+    assert_eq!(
+        expected_actions_sent_to_server, received_actions,
+        "All actions sent to server properly"
+    );
+}
+
+#[test]
+pub fn parse_kitty_keyboard_input_from_stdin() {
+    let stdin_bytes = "a\u{1b}[13;2u\u{1b}[97;7:3u\u{1b}[1;9A\u{1b}[200~\u{1b}[13;2u\u{1b}[201~\u{1b}[<0;1;1M\u{1b}[57428u";
+    let expected_input = vec![
+        KittyKeyboardInput::Other("a".as_bytes().to_vec()),
+        KittyKeyboardInput::Key(
+            Key::Modified(
+                BareKey::Enter,
+                KeyModifiers {
+                    shift: true,
+                    ..Default::default()
+                },
+            ),
+            KeyEventType::Press,
+        ),
+        KittyKeyboardInput::Key(
+            Key::Modified(
+                BareKey::Char('a'),
+                KeyModifiers {
+                    alt: true,
+                    ctrl: true,
+                    ..Default::default()
+                },
+            ),
+            KeyEventType::Release,
+        ),
+        KittyKeyboardInput::Key(
+            Key::Modified(
+                BareKey::Up,
+                KeyModifiers {
+                    super_key: true,
+                    ..Default::default()
+                },
+            ),
+            KeyEventType::Press,
+        ),
+        // pastes, mouse events and keys we do not handle (here: media play) are left as is
+        KittyKeyboardInput::Other(
+            "\u{1b}[200~\u{1b}[13;2u\u{1b}[201~\u{1b}[<0;1;1M\u{1b}[57428u"
+                .as_bytes()
+                .to_vec(),
+        ),
+    ];
+    assert_eq!(
+        parse_kitty_keyboard_input(stdin_bytes.as_bytes()),
+        expected_input,
+        "kitty keyboard input parsed properly"
+    );
+}
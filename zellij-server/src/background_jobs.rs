@@ -1,7 +1,15 @@
 use zellij_utils::async_std::task;
+use zellij_utils::data::{Event, HttpVerb};
 use zellij_utils::errors::{prelude::*, BackgroundJobContext, ContextType};
+use zellij_utils::input::layout::Layout;
+use zellij_utils::isahc::{self, AsyncReadResponseExt, Request, RequestExt};
+use zellij_utils::session_serialization::session_layout_file_path;
+use zellij_utils::sessions::get_all_session_infos;
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
 use std::sync::{
     atomic::{AtomicBool, Ordering},
     Arc,
@@ -9,14 +17,40 @@ use std::sync::{
 use std::time::{Duration, Instant};
 
 use crate::panes::PaneId;
+use crate::plugins::{PluginId, PluginInstruction};
 use crate::screen::ScreenInstruction;
 use crate::thread_bus::Bus;
+use crate::ClientId;
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub enum BackgroundJob {
     DisplayPaneError(Vec<PaneId>, String),
-    AnimatePluginLoading(u32),       // u32 - plugin_id
-    StopPluginLoadingAnimation(u32), // u32 - plugin_id
+    AnimatePluginLoading(u32),          // u32 - plugin_id
+    StopPluginLoadingAnimation(u32),    // u32 - plugin_id
+    WriteSessionLayout(String, String), // session name, the serialized KDL layout
+    MoveSessionLayout(String, String),  // old session name, new session name
+    RemoveSessionLayout(String),        // session name
+    StartPollingSessionInfos,
+    StopPollingSessionInfos,
+    RenderAfter(Duration),
+    WebRequest(
+        PluginId,
+        ClientId,
+        String, // url
+        HttpVerb,
+        BTreeMap<String, String>, // headers
+        Vec<u8>,                  // body
+        BTreeMap<String, String>, // context
+    ),
+    RunCommand(
+        PluginId,
+        ClientId,
+        String,                   // command
+        Vec<String>,              // args
+        BTreeMap<String, String>, // env
+        PathBuf,                  // cwd
+        BTreeMap<String, String>, // context
+    ),
     Exit,
 }
 
@@ -28,6 +62,16 @@ impl From<&BackgroundJob> for BackgroundJobContext {
             BackgroundJob::StopPluginLoadingAnimation(..) => {
                 BackgroundJobContext::StopPluginLoadingAnimation
             },
+            BackgroundJob::WriteSessionLayout(..) => BackgroundJobContext::WriteSessionLayout,
+            BackgroundJob::MoveSessionLayout(..) => BackgroundJobContext::MoveSessionLayout,
+            BackgroundJob::RemoveSessionLayout(..) => BackgroundJobContext::RemoveSessionLayout,
+            BackgroundJob::StartPollingSessionInfos => {
+                BackgroundJobContext::StartPollingSessionInfos
+            },
+            BackgroundJob::StopPollingSessionInfos => BackgroundJobContext::StopPollingSessionInfos,
+            BackgroundJob::RenderAfter(..) => BackgroundJobContext::RenderAfter,
+            BackgroundJob::WebRequest(..) => BackgroundJobContext::WebRequest,
+            BackgroundJob::RunCommand(..) => BackgroundJobContext::RunCommand,
             BackgroundJob::Exit => BackgroundJobContext::Exit,
         }
     }
@@ -35,11 +79,39 @@ impl From<&BackgroundJob> for BackgroundJobContext {
 
 static FLASH_DURATION_MS: u64 = 1000;
 static PLUGIN_ANIMATION_OFFSET_DURATION_MD: u64 = 500;
+static SESSION_LAYOUT_SERIALIZATION_INTERVAL_MS: u64 = 60_000;
+static SESSION_INFO_UPDATE_INTERVAL_MS: u64 = 1000;
 
-pub(crate) fn background_jobs_main(bus: Bus<BackgroundJob>) -> Result<()> {
+pub(crate) fn background_jobs_main(
+    bus: Bus<BackgroundJob>,
+    data_dir: PathBuf,
+    layout_dir: Option<PathBuf>,
+) -> Result<()> {
     let err_context = || "failed to write to pty".to_string();
     let mut running_jobs: HashMap<BackgroundJob, Instant> = HashMap::new();
     let mut loading_plugins: HashMap<u32, Arc<AtomicBool>> = HashMap::new(); // u32 - plugin_id
+    let serializing_session_layout = Arc::new(AtomicBool::new(true));
+    let mut session_layout_removed = false;
+    // only polled while a plugin is subscribed to SessionUpdate, since it means querying every
+    // session on the machine
+    let mut polling_session_infos: Option<Arc<AtomicBool>> = None;
+
+    task::spawn({
+        let senders = bus.senders.clone();
+        let serializing_session_layout = serializing_session_layout.clone();
+        async move {
+            loop {
+                task::sleep(std::time::Duration::from_millis(
+                    SESSION_LAYOUT_SERIALIZATION_INTERVAL_MS,
+                ))
+                .await;
+                if !serializing_session_layout.load(Ordering::SeqCst) {
+                    break;
+                }
+                let _ = senders.send_to_screen(ScreenInstruction::DumpLayoutToHd);
+            }
+        }
+    });
 
     loop {
         let (event, mut err_ctx) = bus.recv().with_context(err_context)?;
@@ -93,10 +165,144 @@ pub(crate) fn background_jobs_main(bus: Bus<BackgroundJob>) -> Result<()> {
                     loading_plugin.store(false, Ordering::SeqCst);
                 }
             },
+            BackgroundJob::WriteSessionLayout(..) if session_layout_removed => {
+                // dumped before the session ended, it should not bring the layout back
+            },
+            BackgroundJob::WriteSessionLayout(session_name, kdl_layout) => {
+                let layout_file_path = session_layout_file_path(&data_dir, &session_name);
+                if let Err(e) = write_session_layout(&layout_file_path, &kdl_layout) {
+                    log::error!(
+                        "Failed to write session layout to {:?}: {}",
+                        layout_file_path,
+                        e
+                    );
+                }
+            },
+            BackgroundJob::MoveSessionLayout(old_session_name, new_session_name) => {
+                let old_layout_file_path = session_layout_file_path(&data_dir, &old_session_name);
+                let new_layout_file_path = session_layout_file_path(&data_dir, &new_session_name);
+                if let Err(e) = move_session_layout(&old_layout_file_path, &new_layout_file_path) {
+                    log::error!(
+                        "Failed to move session layout from {:?} to {:?}: {}",
+                        old_layout_file_path,
+                        new_layout_file_path,
+                        e
+                    );
+                }
+            },
+            BackgroundJob::RemoveSessionLayout(session_name) => {
+                serializing_session_layout.store(false, Ordering::SeqCst);
+                session_layout_removed = true;
+                let layout_file_path = session_layout_file_path(&data_dir, &session_name);
+                if let Err(e) = remove_session_layout(&layout_file_path) {
+                    log::error!(
+                        "Failed to remove session layout {:?}: {}",
+                        layout_file_path,
+                        e
+                    );
+                }
+            },
+            BackgroundJob::StartPollingSessionInfos => {
+                if polling_session_infos.is_some() {
+                    continue;
+                }
+                let polling = Arc::new(AtomicBool::new(true));
+                task::spawn({
+                    let senders = bus.senders.clone();
+                    let polling = polling.clone();
+                    let layout_dir = layout_dir.clone();
+                    async move {
+                        while polling.load(Ordering::SeqCst) {
+                            // every session (this one included) is queried over its socket, which
+                            // blocks until it answers or times out
+                            let layout_dir = layout_dir.clone();
+                            let (session_infos, available_layouts) =
+                                task::spawn_blocking(move || {
+                                    (
+                                        get_all_session_infos(),
+                                        Layout::available_layout_names(layout_dir.as_ref()),
+                                    )
+                                })
+                                .await;
+                            if !polling.load(Ordering::SeqCst) {
+                                break;
+                            }
+                            let _ = senders.send_to_plugin(PluginInstruction::Update(vec![(
+                                None,
+                                None,
+                                Event::SessionUpdate(session_infos, available_layouts),
+                            )]));
+                            task::sleep(std::time::Duration::from_millis(
+                                SESSION_INFO_UPDATE_INTERVAL_MS,
+                            ))
+                            .await;
+                        }
+                    }
+                });
+                polling_session_infos = Some(polling);
+            },
+            BackgroundJob::StopPollingSessionInfos => {
+                if let Some(polling) = polling_session_infos.take() {
+                    polling.store(false, Ordering::SeqCst);
+                }
+            },
+            BackgroundJob::RenderAfter(delay) => {
+                task::spawn({
+                    let senders = bus.senders.clone();
+                    async move {
+                        task::sleep(delay).await;
+                        let _ = senders.send_to_screen(ScreenInstruction::Render);
+                    }
+                });
+            },
+            BackgroundJob::WebRequest(plugin_id, client_id, url, verb, headers, body, context) => {
+                task::spawn({
+                    let senders = bus.senders.clone();
+                    async move {
+                        let event = match web_request(&url, verb, headers, body).await {
+                            Ok((status, headers, body)) => {
+                                Event::WebRequestResult(status, headers, body, context)
+                            },
+                            Err(e) => {
+                                log::error!("Failed to send web request to {}: {:?}", url, e);
+                                // requests that never got a response (eg. the server could not
+                                // be reached) have no status code, they are reported with a
+                                // status of 0 and the error as their body
+                                let error_body = format!("{e:#}").into_bytes();
+                                Event::WebRequestResult(0, BTreeMap::new(), error_body, context)
+                            },
+                        };
+                        let _ = senders.send_to_plugin(PluginInstruction::Update(vec![(
+                            Some(plugin_id),
+                            Some(client_id),
+                            event,
+                        )]));
+                    }
+                });
+            },
+            BackgroundJob::RunCommand(plugin_id, client_id, command, args, env, cwd, context) => {
+                // commands can take a long time to finish, so we run them on the blocking pool
+                // rather than on the executor
+                task::spawn_blocking({
+                    let senders = bus.senders.clone();
+                    move || {
+                        let (exit_code, stdout, stderr) = run_command(&command, &args, env, &cwd);
+                        let _ = senders.send_to_plugin(PluginInstruction::Update(vec![(
+                            Some(plugin_id),
+                            Some(client_id),
+                            Event::RunCommandResult(exit_code, stdout, stderr, context),
+                        )]));
+                    }
+                });
+            },
             BackgroundJob::Exit => {
                 for loading_plugin in loading_plugins.values() {
                     loading_plugin.store(false, Ordering::SeqCst);
                 }
+                serializing_session_layout.store(false, Ordering::SeqCst);
+                if let Some(polling) = polling_session_infos.take() {
+                    polling.store(false, Ordering::SeqCst);
+                }
                 return Ok(());
             },
         }
@@ -122,3 +328,99 @@ fn job_already_running(
         },
     }
 }
+
+async fn web_request(
+    url: &str,
+    verb: HttpVerb,
+    headers: BTreeMap<String, String>,
+    body: Vec<u8>,
+) -> Result<(u16, BTreeMap<String, String>, Vec<u8>)> {
+    let err_context = || format!("failed to send web request to {url}");
+    let method = match verb {
+        HttpVerb::Get => isahc::http::Method::GET,
+        HttpVerb::Post => isahc::http::Method::POST,
+        HttpVerb::Put => isahc::http::Method::PUT,
+        HttpVerb::Delete => isahc::http::Method::DELETE,
+    };
+    let mut request = Request::builder().method(method).uri(url);
+    for (header_name, header_value) in headers {
+        request = request.header(header_name, header_value);
+    }
+    let mut response = request
+        .body(body)
+        .with_context(err_context)?
+        .send_async()
+        .await
+        .with_context(err_context)?;
+    let status = response.status().as_u16();
+    let headers = response
+        .headers()
+        .iter()
+        .filter_map(|(header_name, header_value)| {
+            header_value
+                .to_str()
+                .ok()
+                .map(|header_value| (header_name.to_string(), header_value.to_owned()))
+        })
+        .collect();
+    let body = response.bytes().await.with_context(err_context)?;
+    Ok((status, headers, body))
+}
+
+// returns the exit code, stdout and stderr of the command, failing to start it is reported as
+// having no exit code with the error in stderr
+fn run_command(
+    command: &str,
+    args: &[String],
+    env: BTreeMap<String, String>,
+    cwd: &Path,
+) -> (Option<i32>, Vec<u8>, Vec<u8>) {
+    match Command::new(command)
+        .args(args)
+        .envs(env)
+        .current_dir(cwd)
+        .output()
+    {
+        Ok(output) => (output.status.code(), output.stdout, output.stderr),
+        Err(e) => {
+            log::error!("Failed to run command {} for plugin: {}", command, e);
+            (None, vec![], e.to_string().into_bytes())
+        },
+    }
+}
+
+fn write_session_layout(layout_file_path: &Path, kdl_layout: &str) -> std::io::Result<()> {
+    if let Some(layout_dir) = layout_file_path.parent() {
+        fs::create_dir_all(layout_dir)?;
+    }
+    // write to a temporary file first so that a crash mid-write does not corrupt the last layout
+    let temp_file_path = layout_file_path.with_extension("kdl.tmp");
+    fs::write(&temp_file_path, kdl_layout)?;
+    fs::rename(&temp_file_path, layout_file_path)
+}
+
+// a layout written under the new name since the rename is newer than the one being moved, so we
+// drop the old one instead of replacing it
+fn move_session_layout(
+    old_layout_file_path: &Path,
+    new_layout_file_path: &Path,
+) -> std::io::Result<()> {
+    if !old_layout_file_path.exists() {
+        Ok(())
+    } else if new_layout_file_path.exists() {
+        fs::remove_file(old_layout_file_path)
+    } else {
+        fs::rename(old_layout_file_path, new_layout_file_path)
+    }
+}
+
+fn remove_session_layout(layout_file_path: &Path) -> std::io::Result<()> {
+    match fs::remove_file(layout_file_path) {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        result => result,
+    }
+}
+
+#[path = "./unit/background_jobs_tests.rs"]
+#[cfg(test)]
+mod background_jobs_tests;
@@ -10,6 +10,7 @@ mod pty;
 mod pty_writer;
 mod route;
 mod screen;
+mod session_layout_metadata;
 mod terminal_bytes;
 mod thread_bus;
 mod ui;
@@ -19,7 +20,7 @@ use log::info;
 use pty_writer::{pty_writer_main, PtyWriteInstruction};
 use std::collections::{HashMap, HashSet};
 use std::{
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::{Arc, RwLock},
     thread,
 };
@@ -40,8 +41,8 @@ use route::route_thread_main;
 use zellij_utils::{
     channels::{self, ChannelWithContext, SenderWithContext},
     cli::CliArgs,
-    consts::{DEFAULT_SCROLL_BUFFER_SIZE, SCROLL_BUFFER_SIZE},
-    data::{Event, PluginCapabilities},
+    consts::{DEFAULT_SCROLL_BUFFER_SIZE, SCROLL_BUFFER_SIZE, SCROLL_BUFFER_SPILL_TO_DISK},
+    data::{ConnectToSession, Event, PaneId, PluginCapabilities, SessionInfo},
     errors::{prelude::*, ContextType, ErrorInstruction, FatalError, ServerContext},
     input::{
         command::{RunCommand, TerminalAction},
@@ -50,8 +51,8 @@ use zellij_utils::{
         options::Options,
         plugins::PluginsConfig,
     },
-    ipc::{ClientAttributes, ExitReason, ServerToClientMsg},
-    setup::get_default_data_dir,
+    ipc::{ClientAttributes, ClientType, ExitReason, ServerToClientMsg},
+    setup::{find_default_config_dir, get_default_data_dir, get_layout_dir},
 };
 
 pub type ClientId = u16;
@@ -74,10 +75,22 @@ pub enum ServerInstruction {
     Error(String),
     KillSession,
     DetachSession(Vec<ClientId>),
-    AttachClient(ClientAttributes, Options, ClientId),
+    SwitchSession(ConnectToSession, ClientId),
+    RenameSession(String), // String - the new session name
+    AttachClient(
+        ClientAttributes,
+        Options,
+        Option<usize>,  // tab position to focus
+        Option<PaneId>, // pane to focus
+        ClientId,
+    ),
     ConnStatus(ClientId),
     ActiveClients(ClientId),
+    QuerySessionInfo(ClientId),
+    SessionInfo(SessionInfo, ClientId),
     Log(Vec<String>, ClientId),
+    CliPipeOutput(String, ClientId), // String is the output to write to the cli client
+    CloseCliPipe(ClientId, ExitReason),
 }
 
 impl From<&ServerInstruction> for ServerContext {
@@ -91,10 +104,16 @@ impl From<&ServerInstruction> for ServerContext {
             ServerInstruction::Error(_) => ServerContext::Error,
             ServerInstruction::KillSession => ServerContext::KillSession,
             ServerInstruction::DetachSession(..) => ServerContext::DetachSession,
+            ServerInstruction::SwitchSession(..) => ServerContext::SwitchSession,
+            ServerInstruction::RenameSession(..) => ServerContext::RenameSession,
             ServerInstruction::AttachClient(..) => ServerContext::AttachClient,
             ServerInstruction::ConnStatus(..) => ServerContext::ConnStatus,
             ServerInstruction::ActiveClients(_) => ServerContext::ActiveClients,
+            ServerInstruction::QuerySessionInfo(..) => ServerContext::QuerySessionInfo,
+            ServerInstruction::SessionInfo(..) => ServerContext::SessionInfo,
             ServerInstruction::Log(..) => ServerContext::Log,
+            ServerInstruction::CliPipeOutput(..) => ServerContext::CliPipeOutput,
+            ServerInstruction::CloseCliPipe(..) => ServerContext::CloseCliPipe,
         }
     }
 }
@@ -177,12 +196,14 @@ macro_rules! send_to_client {
 #[derive(Clone, Debug, PartialEq)]
 pub(crate) struct SessionState {
     clients: HashMap<ClientId, Option<Size>>,
+    read_only_clients: HashSet<ClientId>,
 }
 
 impl SessionState {
     pub fn new() -> Self {
         SessionState {
             clients: HashMap::new(),
+            read_only_clients: HashSet::new(),
         }
     }
     pub fn new_client(&mut self) -> ClientId {
@@ -200,6 +221,17 @@ impl SessionState {
     }
     pub fn remove_client(&mut self, client_id: ClientId) {
         self.clients.remove(&client_id);
+        self.read_only_clients.remove(&client_id);
+    }
+    pub fn set_client_read_only(&mut self, client_id: ClientId) {
+        self.read_only_clients.insert(client_id);
+    }
+    pub fn client_is_read_only(&self, client_id: ClientId) -> bool {
+        self.read_only_clients.contains(&client_id)
+    }
+    pub fn client_is_attached(&self, client_id: ClientId) -> bool {
+        // clients that only send actions from the command line never report their size
+        matches!(self.clients.get(&client_id), Some(Some(_)))
     }
     pub fn set_client_size(&mut self, client_id: ClientId, size: Size) {
         self.clients.insert(client_id, Some(size));
@@ -233,7 +265,7 @@ impl SessionState {
     }
 }
 
-pub fn start_server(mut os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
+pub fn start_server(mut os_input: Box<dyn ServerOsApi>, mut socket_path: PathBuf) {
     info!("Starting Zellij server!");
 
     // preserve the current umask: read current value by setting to another mode, and then restoring it
@@ -312,6 +344,8 @@ pub fn start_server(mut os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
             }
         });
 
+    // the names the session had before it was renamed
+    let mut socket_aliases: Vec<PathBuf> = vec![];
     loop {
         let (instruction, mut err_ctx) = server_receiver.recv().unwrap();
         err_ctx.add_call(ContextType::IPCServer((&instruction).into()));
@@ -415,7 +449,7 @@ pub fn start_server(mut os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
                     .send_to_plugin(PluginInstruction::AddClient(client_id))
                     .unwrap();
             },
-            ServerInstruction::AttachClient(attrs, options, client_id) => {
+            ServerInstruction::AttachClient(attrs, options, tab_position, pane_id, client_id) => {
                 let rlock = session_data.read().unwrap();
                 let session_data = rlock.as_ref().unwrap();
                 session_state
@@ -431,9 +465,14 @@ pub fn start_server(mut os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
                     .senders
                     .send_to_screen(ScreenInstruction::TerminalResize(min_size))
                     .unwrap();
+                let client_type = if session_state.read().unwrap().client_is_read_only(client_id) {
+                    ClientType::Reader
+                } else {
+                    ClientType::Writer
+                };
                 session_data
                     .senders
-                    .send_to_screen(ScreenInstruction::AddClient(client_id))
+                    .send_to_screen(ScreenInstruction::AddClient(client_id, client_type))
                     .unwrap();
                 session_data
                     .senders
@@ -460,6 +499,24 @@ pub fn start_server(mut os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
                     ServerToClientMsg::SwitchToMode(mode),
                     session_state
                 );
+                if let Some(pane_id) = pane_id {
+                    session_data
+                        .senders
+                        .send_to_screen(ScreenInstruction::FocusPaneWithId(
+                            pane_id.into(),
+                            true,
+                            client_id,
+                        ))
+                        .unwrap();
+                } else if let Some(tab_position) = tab_position {
+                    session_data
+                        .senders
+                        .send_to_screen(ScreenInstruction::GoToTab(
+                            tab_position as u32 + 1,
+                            Some(client_id),
+                        ))
+                        .unwrap();
+                }
             },
             ServerInstruction::UnblockInputThread => {
                 for client_id in session_state.read().unwrap().clients.keys() {
@@ -502,6 +559,7 @@ pub fn start_server(mut os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
                     .send_to_plugin(PluginInstruction::RemoveClient(client_id))
                     .unwrap();
                 if session_state.read().unwrap().clients.is_empty() {
+                    remove_session_layout(&session_data);
                     *session_data.write().unwrap() = None;
                     break;
                 }
@@ -542,6 +600,7 @@ pub fn start_server(mut os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
                         .send_to_client(client_id, ServerToClientMsg::Exit(ExitReason::Normal));
                     remove_client!(client_id, os_input, session_state);
                 }
+                remove_session_layout(&session_data);
                 break;
             },
             ServerInstruction::DetachSession(client_ids) => {
@@ -578,6 +637,93 @@ pub fn start_server(mut os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
                         .unwrap();
                 }
             },
+            ServerInstruction::SwitchSession(_connect_to_session, client_id)
+                if !session_state.read().unwrap().client_is_attached(client_id) =>
+            {
+                // actions sent from the command line come from clients that are not attached to
+                // the session, there is no client of theirs to switch
+                let _ = os_input.send_to_client(
+                    client_id,
+                    ServerToClientMsg::Log(vec![
+                        "Only attached clients can switch sessions".to_owned()
+                    ]),
+                );
+                remove_client!(client_id, os_input, session_state);
+            },
+            ServerInstruction::SwitchSession(connect_to_session, client_id) => {
+                let _ = os_input.send_to_client(
+                    client_id,
+                    ServerToClientMsg::SwitchSession(connect_to_session),
+                );
+                remove_client!(client_id, os_input, session_state);
+                if let Some(min_size) = session_state.read().unwrap().min_client_terminal_size() {
+                    session_data
+                        .write()
+                        .unwrap()
+                        .as_ref()
+                        .unwrap()
+                        .senders
+                        .send_to_screen(ScreenInstruction::TerminalResize(min_size))
+                        .unwrap();
+                }
+                session_data
+                    .write()
+                    .unwrap()
+                    .as_ref()
+                    .unwrap()
+                    .senders
+                    .send_to_screen(ScreenInstruction::RemoveClient(client_id))
+                    .unwrap();
+                session_data
+                    .write()
+                    .unwrap()
+                    .as_ref()
+                    .unwrap()
+                    .senders
+                    .send_to_plugin(PluginInstruction::RemoveClient(client_id))
+                    .unwrap();
+            },
+            ServerInstruction::RenameSession(new_name) => {
+                let new_socket_path = socket_path.with_file_name(&new_name);
+                let renamed = new_socket_path != socket_path
+                    && match rename_socket(&socket_path, &new_socket_path, &mut socket_aliases) {
+                        Ok(()) => true,
+                        Err(e) => {
+                            log::error!("Failed to rename session to {:?}: {}", new_name, e);
+                            false
+                        },
+                    };
+                if renamed {
+                    let old_name = envs::get_session_name().unwrap_or_default();
+                    envs::set_session_name(new_name.clone());
+                    socket_path = new_socket_path;
+                    let rlock = session_data.read().unwrap();
+                    let session_data = rlock.as_ref().unwrap();
+                    session_data
+                        .senders
+                        .send_to_background_jobs(BackgroundJob::MoveSessionLayout(
+                            old_name,
+                            new_name.clone(),
+                        ))
+                        .unwrap();
+                    // the screen tells the clients and plugins about the new name and unblocks
+                    // their input once it has
+                    session_data
+                        .senders
+                        .send_to_screen(ScreenInstruction::RenameSession(new_name))
+                        .unwrap();
+                } else {
+                    let client_ids = session_state.read().unwrap().client_ids();
+                    for client_id in client_ids {
+                        send_to_client!(
+                            client_id,
+                            os_input,
+                            ServerToClientMsg::UnblockInputThread,
+                            session_state
+                        );
+                    }
+                }
+            },
             ServerInstruction::Render(serialized_output) => {
                 let client_ids = session_state.read().unwrap().client_ids();
                 // If `Some(_)`- unwrap it and forward it to the clients to render.
@@ -600,6 +746,7 @@ pub fn start_server(mut os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
                             .send_to_client(client_id, ServerToClientMsg::Exit(ExitReason::Normal));
                         remove_client!(client_id, os_input, session_state);
                     }
+                    remove_session_layout(&session_data);
                     break;
                 }
             },
@@ -618,6 +765,25 @@ pub fn start_server(mut os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
                 let _ = os_input.send_to_client(client_id, ServerToClientMsg::Connected);
                 remove_client!(client_id, os_input, session_state);
             },
+            ServerInstruction::QuerySessionInfo(client_id) => {
+                // the screen knows about the tabs and panes, it answers with a SessionInfo
+                match session_data.read().unwrap().as_ref() {
+                    Some(session_data) => {
+                        session_data
+                            .senders
+                            .send_to_screen(ScreenInstruction::QuerySessionInfo(client_id))
+                            .unwrap();
+                    },
+                    None => {
+                        remove_client!(client_id, os_input, session_state);
+                    },
+                }
+            },
+            ServerInstruction::SessionInfo(session_info, client_id) => {
+                let _ = os_input
+                    .send_to_client(client_id, ServerToClientMsg::SessionInfo(session_info));
+                remove_client!(client_id, os_input, session_state);
+            },
             ServerInstruction::ActiveClients(client_id) => {
                 let client_ids = session_state.read().unwrap().client_ids();
                 send_to_client!(
@@ -635,15 +801,111 @@ pub fn start_server(mut os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
                     session_state
                 );
             },
+            ServerInstruction::CliPipeOutput(output, client_id) => {
+                send_to_client!(
+                    client_id,
+                    os_input,
+                    ServerToClientMsg::CliPipeOutput(output),
+                    session_state
+                );
+            },
+            ServerInstruction::CloseCliPipe(client_id, exit_reason) => {
+                send_to_client!(
+                    client_id,
+                    os_input,
+                    ServerToClientMsg::Exit(exit_reason),
+                    session_state
+                );
+            },
         }
     }
 
     // Drop cached session data before exit.
     *session_data.write().unwrap() = None;
 
+    remove_socket_aliases(&socket_aliases, &socket_path);
     drop(std::fs::remove_file(&socket_path));
 }
 
+/// The serialized layout of a session is kept after it crashed, so that it can be brought back,
+/// but not once it was quit or killed.
+fn remove_session_layout(session_data: &RwLock<Option<SessionMetaData>>) {
+    if let (Some(session_data), Ok(session_name)) = (
+        session_data.read().unwrap().as_ref(),
+        envs::get_session_name(),
+    ) {
+        let _ = session_data
+            .senders
+            .send_to_background_jobs(BackgroundJob::RemoveSessionLayout(session_name));
+    }
+}
+
+/// Moves the session socket to `new_path` without replacing another session's socket: linking
+/// fails if `new_path` exists. The listener keeps accepting connections through the new path.
+fn move_socket(old_path: &Path, new_path: &Path) -> std::io::Result<()> {
+    std::fs::hard_link(old_path, new_path)?;
+    std::fs::remove_file(old_path)
+}
+
+/// Moves the session socket to `new_socket_path` (see [`move_socket`]). Panes started before the
+/// rename still know the session by its old name (through `ZELLIJ_SESSION_NAME`), so the old
+/// names (`aliases`) are kept as symlinks to the socket for as long as the session runs.
+fn rename_socket(
+    socket_path: &Path,
+    new_socket_path: &Path,
+    aliases: &mut Vec<PathBuf>,
+) -> std::io::Result<()> {
+    if aliases.iter().any(|alias| alias == new_socket_path) {
+        // the session goes back to one of its old names
+        remove_socket_aliases(&[new_socket_path.to_path_buf()], socket_path);
+        aliases.retain(|alias| alias != new_socket_path);
+    }
+    move_socket(socket_path, new_socket_path)?;
+    aliases.push(socket_path.to_path_buf());
+    update_socket_aliases(aliases, socket_path, new_socket_path);
+    Ok(())
+}
+
+// whether `alias` is a symlink to the socket at `socket_path`, rather than eg. the socket of a
+// session that took this name since
+fn is_socket_alias_of(alias: &Path, socket_path: &Path) -> bool {
+    std::fs::read_link(alias)
+        .map(|target| Some(target.as_os_str()) == socket_path.file_name())
+        .unwrap_or(false)
+}
+
+// points the old names of a session that was just renamed (including `old_socket_path` itself)
+// at its socket, now at `socket_path` - the ones that do not lead to the session anymore are
+// dropped
+fn update_socket_aliases(aliases: &mut Vec<PathBuf>, old_socket_path: &Path, socket_path: &Path) {
+    let socket_name = match socket_path.file_name() {
+        Some(socket_name) => socket_name,
+        None => return,
+    };
+    aliases.retain(|alias| {
+        if is_socket_alias_of(alias, old_socket_path) {
+            drop(std::fs::remove_file(alias));
+        } else if alias != old_socket_path {
+            return false;
+        }
+        match std::os::unix::fs::symlink(socket_name, alias) {
+            Ok(()) => true,
+            Err(e) => {
+                log::error!("Failed to keep the old session name {:?}: {}", alias, e);
+                false
+            },
+        }
+    });
+}
+
+fn remove_socket_aliases(aliases: &[PathBuf], socket_path: &Path) {
+    for alias in aliases {
+        if is_socket_alias_of(alias, socket_path) {
+            drop(std::fs::remove_file(alias));
+        }
+    }
+}
+
 pub struct SessionOptions {
     pub opts: Box<CliArgs>,
     pub config_options: Box<Options>,
@@ -671,6 +933,9 @@ fn init_session(
                 .unwrap_or(DEFAULT_SCROLL_BUFFER_SIZE),
         )
         .unwrap();
+    SCROLL_BUFFER_SPILL_TO_DISK
+        .set(config_options.scroll_buffer_spill_to_disk.unwrap_or(false))
+        .unwrap();
 
     let (to_screen, screen_receiver): ChannelWithContext<ScreenInstruction> = channels::unbounded();
     let to_screen = SenderWithContext::new(to_screen);
@@ -694,6 +959,10 @@ fn init_session(
 
     // Determine and initialize the data directory
     let data_dir = opts.data_dir.unwrap_or_else(get_default_data_dir);
+    let layout_dir = config_options
+        .layout_dir
+        .clone()
+        .or_else(|| get_layout_dir(opts.config_dir.clone().or_else(find_default_config_dir)));
 
     let capabilities = PluginCapabilities {
         arrow_fonts: config_options.simplified_ui.unwrap_or_default(),
@@ -778,6 +1047,7 @@ fn init_session(
             let store = get_store();
 
             let layout = layout.clone();
+            let data_dir = data_dir.clone();
             let client_attributes = client_attributes.clone();
             let default_shell = default_shell.clone();
             let capabilities = capabilities.clone();
@@ -829,7 +1099,7 @@ fn init_session(
                 None,
                 Some(os_input.clone()),
             );
-            || background_jobs_main(background_jobs_bus).fatal()
+            move || background_jobs_main(background_jobs_bus, data_dir, layout_dir).fatal()
         })
         .unwrap();
 
@@ -866,3 +1136,7 @@ fn get_store() -> Store {
     log::info!("Compiling plugins using Singlepass");
     Store::new(&wasmer::Universal::new(wasmer::Singlepass::default()).engine())
 }
+
+#[path = "./unit/server_tests.rs"]
+#[cfg(test)]
+mod server_tests;
@@ -4,6 +4,7 @@ use crate::panes::selection::Selection;
 use crate::panes::Row;
 
 use crate::{
+    panes::kitty_graphics::{crop_image_pixels, KittyImage, KittyImageStore},
     panes::sixel::SixelImageStore,
     panes::terminal_character::{AnsiCode, CharacterStyles},
     panes::{LinkHandler, TerminalCharacter, EMPTY_TERMINAL_CHARACTER},
@@ -61,13 +62,17 @@ fn write_changed_styles(
     current_character_styles: CharacterStyles,
     chunk_changed_colors: Option<[Option<AnsiCode>; 256]>,
     link_handler: Option<&std::cell::Ref<LinkHandler>>,
+    styled_underlines: bool,
     vte_output: &mut String,
 ) -> Result<()> {
     let err_context = "failed to format changed styles to VTE string";
 
-    if let Some(new_styles) =
+    if let Some(mut new_styles) =
         character_styles.update_and_return_diff(&current_character_styles, chunk_changed_colors)
     {
+        if !styled_underlines {
+            new_styles.remove_styled_underlines();
+        }
         if let Some(osc8_link) =
             link_handler.and_then(|l_h| l_h.output_osc8(new_styles.link_anchor))
         {
@@ -84,6 +89,7 @@ fn serialize_chunks(
     sixel_chunks: Option<&Vec<SixelImageChunk>>,
     link_handler: Option<&mut Rc<RefCell<LinkHandler>>>,
     sixel_image_store: &mut SixelImageStore,
+    styled_underlines: bool,
 ) -> Result<String> {
     let err_context = || "failed to serialize input chunks".to_string();
 
@@ -108,6 +114,7 @@ fn serialize_chunks(
                 current_character_styles,
                 chunk_changed_colors,
                 link_handler.as_ref(),
+                styled_underlines,
                 &mut vte_output,
             )
             .with_context(err_context)?;
@@ -203,20 +210,27 @@ pub struct Output {
     post_vte_instructions: HashMap<ClientId, Vec<String>>,
     client_character_chunks: HashMap<ClientId, Vec<CharacterChunk>>,
     sixel_chunks: HashMap<ClientId, Vec<SixelImageChunk>>,
+    kitty_image_chunks: HashMap<ClientId, Vec<KittyImageChunk>>,
     link_handler: Option<Rc<RefCell<LinkHandler>>>,
     sixel_image_store: Rc<RefCell<SixelImageStore>>,
+    kitty_image_store: Rc<RefCell<KittyImageStore>>,
     character_cell_size: Rc<RefCell<Option<SizeInPixels>>>,
     floating_panes_stack: Option<FloatingPanesStack>,
+    styled_underlines: bool,
 }
 
 impl Output {
     pub fn new(
         sixel_image_store: Rc<RefCell<SixelImageStore>>,
+        kitty_image_store: Rc<RefCell<KittyImageStore>>,
         character_cell_size: Rc<RefCell<Option<SizeInPixels>>>,
+        styled_underlines: bool,
     ) -> Self {
         Output {
             sixel_image_store,
+            kitty_image_store,
             character_cell_size,
+            styled_underlines,
             ..Default::default()
         }
     }
@@ -355,6 +369,25 @@ impl Output {
             }
         }
     }
+    pub fn add_kitty_image_chunks_to_multiple_clients(
+        &mut self,
+        kitty_image_chunks: Vec<KittyImageChunk>,
+        client_ids: impl Iterator<Item = ClientId>,
+        z_index: Option<usize>,
+    ) {
+        let kitty_image_chunks = if let Some(floating_panes_stack) = &self.floating_panes_stack {
+            floating_panes_stack.visible_kitty_image_chunks(kitty_image_chunks, z_index)
+        } else {
+            kitty_image_chunks
+        };
+        for client_id in client_ids {
+            let entry = self
+                .kitty_image_chunks
+                .entry(client_id)
+                .or_insert_with(Vec::new);
+            entry.append(&mut kitty_image_chunks.clone());
+        }
+    }
     pub fn serialize(&mut self) -> Result<HashMap<ClientId, String>> {
         let err_context = || "failed to serialize output to clients".to_string();
 
@@ -362,12 +395,15 @@ impl Output {
 
         for (client_id, client_character_chunks) in self.client_character_chunks.drain() {
             let mut client_serialized_render_instructions = String::new();
+            let mut display_was_cleared = false;
 
             // append pre-vte instructions for this client
             if let Some(pre_vte_instructions_for_client) =
                 self.pre_vte_instructions.remove(&client_id)
             {
                 for vte_instruction in pre_vte_instructions_for_client {
+                    // clearing the display also deletes the kitty image placements
+                    display_was_cleared |= vte_instruction.contains("\u{1b}[2J");
                     client_serialized_render_instructions.push_str(&vte_instruction);
                 }
             }
@@ -379,10 +415,22 @@ impl Output {
                     self.sixel_chunks.get(&client_id),
                     self.link_handler.as_mut(),
                     &mut self.sixel_image_store.borrow_mut(),
+                    self.styled_underlines,
                 )
                 .with_context(err_context)?,
             ); // TODO: less allocations?
 
+            // kitty image placements are not part of the text, so they are sent separately
+            if let Some(kitty_vte) = self.kitty_image_store.borrow_mut().serialize_placements(
+                client_id,
+                self.kitty_image_chunks
+                    .remove(&client_id)
+                    .unwrap_or_default(),
+                display_was_cleared,
+            ) {
+                client_serialized_render_instructions.push_str(&kitty_vte);
+            }
+
             // append post-vte instructions for this client
             if let Some(post_vte_instructions_for_client) =
                 self.post_vte_instructions.remove(&client_id)
@@ -481,6 +529,24 @@ impl FloatingPanesStack {
         }
         chunks_to_check
     }
+    pub fn visible_kitty_image_chunks(
+        &self,
+        mut kitty_image_chunks: Vec<KittyImageChunk>,
+        z_index: Option<usize>,
+    ) -> Vec<KittyImageChunk> {
+        let z_index = z_index.unwrap_or(0);
+        let mut chunks_to_check: Vec<KittyImageChunk> = kitty_image_chunks.drain(..).collect();
+        let panes_to_check = self.layers.iter().skip(z_index);
+        for pane_geom in panes_to_check {
+            let chunks_to_check_against_this_pane: Vec<KittyImageChunk> =
+                chunks_to_check.drain(..).collect();
+            for k_chunk in chunks_to_check_against_this_pane {
+                let mut uncovered_chunks = self.remove_covered_kitty_parts(pane_geom, &k_chunk);
+                chunks_to_check.append(&mut uncovered_chunks);
+            }
+        }
+        chunks_to_check
+    }
     fn remove_covered_parts(
         &self,
         pane_geom: &PaneGeom,
@@ -698,6 +764,118 @@ impl FloatingPanesStack {
         }
         uncovered_chunks
     }
+    fn remove_covered_kitty_parts(
+        &self,
+        pane_geom: &PaneGeom,
+        k_chunk: &KittyImageChunk,
+    ) -> Vec<KittyImageChunk> {
+        let pane_top_edge = pane_geom.y;
+        let pane_bottom_edge = pane_geom.y + pane_geom.rows.as_usize();
+        let pane_left_edge = pane_geom.x;
+        let pane_right_edge = pane_geom.x + pane_geom.cols.as_usize();
+        let k_chunk_top_edge = k_chunk.cell_y;
+        let k_chunk_bottom_edge = k_chunk.cell_y + k_chunk.rows;
+        let k_chunk_left_edge = k_chunk.cell_x;
+        let k_chunk_right_edge = k_chunk.cell_x + k_chunk.columns;
+        if pane_top_edge >= k_chunk_bottom_edge
+            || pane_bottom_edge <= k_chunk_top_edge
+            || pane_left_edge >= k_chunk_right_edge
+            || pane_right_edge <= k_chunk_left_edge
+        {
+            // the pane doesn't cover the chunk at all, so we return it as is
+            return vec![k_chunk.clone()];
+        }
+        // the uncovered parts above and below the pane span the whole width of the chunk, those
+        // to its left and right only the lines it covers
+        let covered_top_edge = std::cmp::max(pane_top_edge, k_chunk_top_edge);
+        let covered_bottom_edge = std::cmp::min(pane_bottom_edge, k_chunk_bottom_edge);
+        let mut uncovered_chunks = vec![];
+        if pane_top_edge > k_chunk_top_edge {
+            uncovered_chunks.push(k_chunk.crop(
+                k_chunk_left_edge,
+                k_chunk_top_edge,
+                k_chunk.columns,
+                pane_top_edge - k_chunk_top_edge,
+            ));
+        }
+        if pane_bottom_edge < k_chunk_bottom_edge {
+            uncovered_chunks.push(k_chunk.crop(
+                k_chunk_left_edge,
+                pane_bottom_edge,
+                k_chunk.columns,
+                k_chunk_bottom_edge - pane_bottom_edge,
+            ));
+        }
+        if pane_left_edge > k_chunk_left_edge {
+            uncovered_chunks.push(k_chunk.crop(
+                k_chunk_left_edge,
+                covered_top_edge,
+                pane_left_edge - k_chunk_left_edge,
+                covered_bottom_edge - covered_top_edge,
+            ));
+        }
+        if pane_right_edge < k_chunk_right_edge {
+            uncovered_chunks.push(k_chunk.crop(
+                pane_right_edge,
+                covered_top_edge,
+                k_chunk_right_edge - pane_right_edge,
+                covered_bottom_edge - covered_top_edge,
+            ));
+        }
+        uncovered_chunks.into_iter().flatten().collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct KittyImageChunk {
+    pub cell_x: usize,
+    pub cell_y: usize,
+    pub columns: usize,
+    pub rows: usize,
+    pub image_pixel_x: usize,
+    pub image_pixel_y: usize,
+    pub image_pixel_width: usize,
+    pub image_pixel_height: usize,
+    pub pixels_per_column: f64,
+    pub pixels_per_row: f64,
+    pub is_scaled: bool, // whether the image is stretched to its cells or displayed at its size
+    pub z_index: i32,
+    pub image: Rc<KittyImage>,
+}
+
+impl KittyImageChunk {
+    /// The part of this chunk displayed in the given cells (which should be inside it)
+    pub fn crop(&self, cell_x: usize, cell_y: usize, columns: usize, rows: usize) -> Option<Self> {
+        let (image_pixel_x, image_pixel_width) = crop_image_pixels(
+            cell_x.saturating_sub(self.cell_x),
+            columns,
+            self.image_pixel_x,
+            self.image_pixel_width,
+            self.pixels_per_column,
+        );
+        let (image_pixel_y, image_pixel_height) = crop_image_pixels(
+            cell_y.saturating_sub(self.cell_y),
+            rows,
+            self.image_pixel_y,
+            self.image_pixel_height,
+            self.pixels_per_row,
+        );
+        if columns == 0 || rows == 0 || image_pixel_width == 0 || image_pixel_height == 0 {
+            return None;
+        }
+        Some(KittyImageChunk {
+            cell_x,
+            cell_y,
+            columns,
+            rows,
+            image_pixel_x,
+            image_pixel_y,
+            image_pixel_width,
+            image_pixel_height,
+            image: self.image.clone(),
+            ..*self
+        })
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -0,0 +1,510 @@
+use crate::panes::Grid;
+use zellij_utils::input::actions::{CopyModeMotion, CopyModeSelection};
+use zellij_utils::position::Position;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CharacterClass {
+    Whitespace,
+    Word,
+    Punctuation,
+}
+
+impl CharacterClass {
+    fn of(character: Option<char>) -> Self {
+        match character {
+            Some(c) if c.is_alphanumeric() || c == '_' => CharacterClass::Word,
+            Some(c) if !c.is_whitespace() => CharacterClass::Punctuation,
+            _ => CharacterClass::Whitespace,
+        }
+    }
+}
+
+// Positions are relative to the viewport (like those of `Selection`), and move along with the
+// content when it is scrolled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CopyMode {
+    pub cursor: Position,
+    selection: Option<(CopyModeSelection, Position)>, // the type of selection and where it started
+}
+
+impl CopyMode {
+    pub fn new(cursor: Position) -> Self {
+        CopyMode {
+            cursor,
+            selection: None,
+        }
+    }
+    pub fn clear_selection(&mut self) {
+        self.selection = None;
+    }
+    pub fn move_up(&mut self, lines: usize) {
+        self.cursor.line.0 -= lines as isize;
+        if let Some((_, anchor)) = self.selection.as_mut() {
+            anchor.line.0 -= lines as isize;
+        }
+    }
+    pub fn move_down(&mut self, lines: usize) {
+        self.cursor.line.0 += lines as isize;
+        if let Some((_, anchor)) = self.selection.as_mut() {
+            anchor.line.0 += lines as isize;
+        }
+    }
+}
+
+impl Grid {
+    pub fn enter_copy_mode(&mut self) {
+        let cursor = match self.search_results.active {
+            Some(active_search_result) => active_search_result.start,
+            None => Position::new(
+                std::cmp::min(self.cursor.y, self.last_copy_mode_line()) as i32,
+                std::cmp::min(self.cursor.x, self.width.saturating_sub(1)) as u16,
+            ),
+        };
+        self.reset_selection();
+        self.copy_mode = Some(CopyMode::new(cursor));
+        self.mark_for_rerender();
+    }
+
+    pub fn exit_copy_mode(&mut self) {
+        if self.copy_mode.take().is_some() {
+            self.reset_selection();
+            self.mark_for_rerender();
+        }
+    }
+
+    pub fn is_in_copy_mode(&self) -> bool {
+        self.copy_mode.is_some()
+    }
+
+    /// The copy mode cursor, if it is inside the viewport
+    pub(crate) fn copy_mode_cursor_coordinates(&self) -> Option<(usize, usize)> {
+        let cursor = self.copy_mode.as_ref()?.cursor;
+        let line = usize::try_from(cursor.line()).ok()?;
+        if line < self.height && cursor.column() < self.width {
+            Some((cursor.column(), line))
+        } else {
+            None
+        }
+    }
+
+    pub fn copy_mode_move(&mut self, motion: CopyModeMotion) {
+        if self.copy_mode.is_none() {
+            return;
+        }
+        self.scroll_copy_mode_cursor_into_view();
+        match motion {
+            CopyModeMotion::Left => self.move_copy_mode_cursor_left(),
+            CopyModeMotion::Right => self.move_copy_mode_cursor_right(),
+            CopyModeMotion::Up => self.move_copy_mode_cursor_up(),
+            CopyModeMotion::Down => self.move_copy_mode_cursor_down(),
+            CopyModeMotion::NextWordStart => self.move_copy_mode_cursor_to_next_word_start(),
+            CopyModeMotion::PreviousWordStart => {
+                self.move_copy_mode_cursor_to_previous_word_start()
+            },
+            CopyModeMotion::NextWordEnd => self.move_copy_mode_cursor_to_next_word_end(),
+            CopyModeMotion::LineStart => {
+                let line = self.copy_mode_cursor().line();
+                self.set_copy_mode_cursor(line, 0);
+            },
+            CopyModeMotion::LineEnd => {
+                let line = self.copy_mode_cursor().line();
+                let last_character_column = self.last_character_column(line);
+                self.set_copy_mode_cursor(line, last_character_column);
+            },
+            CopyModeMotion::Top => {
+                // lines spilled to disk are paged in one block at a time, rather than reading
+                // the whole history into memory
+                if self.lines_above.is_empty() {
+                    self.scroll_copy_mode_up();
+                }
+                while !self.lines_above.is_empty() && self.scroll_copy_mode_up() {}
+                self.set_copy_mode_cursor(0, 0);
+            },
+            CopyModeMotion::Bottom => {
+                while self.scroll_copy_mode_down() {}
+                let line = std::cmp::min(self.cursor.y, self.last_copy_mode_line());
+                self.set_copy_mode_cursor(line as isize, 0);
+            },
+        }
+        self.update_copy_mode_selection();
+    }
+
+    /// Starts a selection of the given type at the cursor, changes the type of the current
+    /// selection or, if it is already of this type, stops selecting
+    pub fn copy_mode_select(&mut self, selection_type: CopyModeSelection) {
+        if let Some(copy_mode) = self.copy_mode.as_mut() {
+            copy_mode.selection = match copy_mode.selection {
+                Some((current_type, _)) if current_type == selection_type => None,
+                Some((_, anchor)) => Some((selection_type, anchor)),
+                None => Some((selection_type, copy_mode.cursor)),
+            };
+            self.update_copy_mode_selection();
+        }
+    }
+
+    pub(crate) fn move_copy_mode_cursor_to_active_search_result(&mut self) {
+        if let Some(active_search_result) = self.search_results.active {
+            if let Some(copy_mode) = self.copy_mode.as_mut() {
+                copy_mode.cursor = active_search_result.start;
+                self.update_copy_mode_selection();
+            }
+        }
+    }
+
+    fn update_copy_mode_selection(&mut self) {
+        let (selection_type, anchor, cursor) = match self.copy_mode {
+            Some(CopyMode {
+                cursor,
+                selection: Some((selection_type, anchor)),
+            }) => (selection_type, anchor, cursor),
+            Some(_) => {
+                self.reset_selection();
+                return;
+            },
+            None => return,
+        };
+        let (first, last) = if anchor <= cursor {
+            (anchor, cursor)
+        } else {
+            (cursor, anchor)
+        };
+        let (start, end) = match selection_type {
+            CopyModeSelection::Character => {
+                let end_column = match self.character_at(last) {
+                    Some((column, _, width)) => column + width,
+                    None => last.column() + 1,
+                };
+                (first, position(last.line(), end_column))
+            },
+            CopyModeSelection::Word => (self.word_start(first), self.word_end(last)),
+            CopyModeSelection::Line => {
+                (position(first.line(), 0), position(last.line(), self.width))
+            },
+            CopyModeSelection::Block => {
+                let (left, right) = if anchor.column() <= cursor.column() {
+                    (anchor, cursor)
+                } else {
+                    (cursor, anchor)
+                };
+                let end_column = match self.character_at(right) {
+                    Some((column, _, width)) => column + width,
+                    None => right.column() + 1,
+                };
+                (
+                    position(first.line(), left.column()),
+                    position(last.line(), end_column),
+                )
+            },
+        };
+        let old_selection = self.selection;
+        if selection_type == CopyModeSelection::Block {
+            self.selection.start_block(start);
+        } else {
+            self.selection.start(start);
+        }
+        self.selection.end(end);
+        self.update_selected_lines(&old_selection, &self.selection.clone());
+        self.mark_for_rerender();
+    }
+
+    fn copy_mode_cursor(&self) -> Position {
+        self.copy_mode
+            .map(|copy_mode| copy_mode.cursor)
+            .unwrap_or_else(|| Position::new(0, 0))
+    }
+
+    fn set_copy_mode_cursor(&mut self, line: isize, column: usize) {
+        if let Some(copy_mode) = self.copy_mode.as_mut() {
+            copy_mode.cursor = position(line, column);
+        }
+        self.mark_for_rerender();
+    }
+
+    // the cursor can end up outside the viewport if the content moved (eg. new lines were added
+    // or the pane was scrolled with the mouse), so we bring it back before moving it
+    fn scroll_copy_mode_cursor_into_view(&mut self) {
+        while self.copy_mode_cursor().line() < 0 && self.scroll_copy_mode_up() {}
+        while self.copy_mode_cursor().line() > self.last_copy_mode_line() as isize
+            && self.scroll_copy_mode_down()
+        {}
+        let cursor = self.copy_mode_cursor();
+        let line = if cursor.line() < 0 {
+            0
+        } else {
+            std::cmp::min(cursor.line(), self.last_copy_mode_line() as isize)
+        };
+        let column = std::cmp::min(cursor.column(), self.width.saturating_sub(1));
+        self.set_copy_mode_cursor(line, column);
+    }
+
+    fn scroll_copy_mode_up(&mut self) -> bool {
+        if self.has_lines_above() && self.viewport.len() == self.height {
+            self.scroll_up_one_line();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn scroll_copy_mode_down(&mut self) -> bool {
+        if !self.lines_below.is_empty() && self.viewport.len() == self.height {
+            self.scroll_down_one_line();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn last_copy_mode_line(&self) -> usize {
+        std::cmp::min(self.viewport.len(), self.height).saturating_sub(1)
+    }
+
+    fn move_copy_mode_cursor_left(&mut self) {
+        let cursor = self.copy_mode_cursor();
+        if cursor.column() > 0 {
+            let column = self
+                .character_at(position(cursor.line(), cursor.column() - 1))
+                .map(|(column, _, _)| column)
+                .unwrap_or(cursor.column() - 1);
+            self.set_copy_mode_cursor(cursor.line(), column);
+        }
+    }
+
+    fn move_copy_mode_cursor_right(&mut self) {
+        let cursor = self.copy_mode_cursor();
+        let column = match self.character_at(cursor) {
+            Some((column, _, width)) => column + width,
+            None => cursor.column() + 1,
+        };
+        if column < self.width {
+            self.set_copy_mode_cursor(cursor.line(), column);
+        }
+    }
+
+    fn move_copy_mode_cursor_up(&mut self) {
+        let cursor = self.copy_mode_cursor();
+        if cursor.line() > 0 {
+            self.set_copy_mode_cursor(cursor.line() - 1, cursor.column());
+        } else if self.scroll_copy_mode_up() {
+            // scrolling moved the cursor down along with the content
+            self.set_copy_mode_cursor(0, cursor.column());
+        }
+    }
+
+    fn move_copy_mode_cursor_down(&mut self) {
+        let cursor = self.copy_mode_cursor();
+        let last_line = self.last_copy_mode_line() as isize;
+        if cursor.line() < last_line {
+            self.set_copy_mode_cursor(cursor.line() + 1, cursor.column());
+        } else if self.scroll_copy_mode_down() {
+            // scrolling moved the cursor up along with the content
+            self.set_copy_mode_cursor(last_line, cursor.column());
+        }
+    }
+
+    fn move_copy_mode_cursor_to_next_word_start(&mut self) {
+        let start_class = CharacterClass::of(self.character_under_copy_mode_cursor());
+        let mut passed_whitespace = start_class == CharacterClass::Whitespace;
+        while let Some(crossed_line_break) = self.step_copy_mode_cursor_forward() {
+            let class = CharacterClass::of(self.character_under_copy_mode_cursor());
+            if crossed_line_break || class == CharacterClass::Whitespace {
+                passed_whitespace = true;
+            }
+            if class != CharacterClass::Whitespace && (passed_whitespace || class != start_class) {
+                break;
+            }
+        }
+    }
+
+    fn move_copy_mode_cursor_to_next_word_end(&mut self) {
+        // we always move at least one character, so that repeating the motion moves to the end
+        // of the next word
+        let mut class = CharacterClass::Whitespace;
+        while self.step_copy_mode_cursor_forward().is_some() {
+            class = CharacterClass::of(self.character_under_copy_mode_cursor());
+            if class != CharacterClass::Whitespace {
+                break;
+            }
+        }
+        if class == CharacterClass::Whitespace {
+            return;
+        }
+        while let Some(crossed_line_break) = self.step_copy_mode_cursor_forward() {
+            let next_class = CharacterClass::of(self.character_under_copy_mode_cursor());
+            if crossed_line_break || next_class != class {
+                self.step_copy_mode_cursor_backward();
+                break;
+            }
+        }
+    }
+
+    fn move_copy_mode_cursor_to_previous_word_start(&mut self) {
+        let mut class = CharacterClass::Whitespace;
+        while self.step_copy_mode_cursor_backward().is_some() {
+            class = CharacterClass::of(self.character_under_copy_mode_cursor());
+            if class != CharacterClass::Whitespace {
+                break;
+            }
+        }
+        if class == CharacterClass::Whitespace {
+            return;
+        }
+        while let Some(crossed_line_break) = self.step_copy_mode_cursor_backward() {
+            let previous_class = CharacterClass::of(self.character_under_copy_mode_cursor());
+            if crossed_line_break || previous_class != class {
+                self.step_copy_mode_cursor_forward();
+                break;
+            }
+        }
+    }
+
+    // Moves the cursor to the next character, scrolling if needed. Returns whether this moved it
+    // to a new canonical line or None if it's already at the end
+    fn step_copy_mode_cursor_forward(&mut self) -> Option<bool> {
+        let cursor = self.copy_mode_cursor();
+        let next_column = match self.character_at(cursor) {
+            Some((column, _, width)) => column + width,
+            None => self.row_width(cursor.line()),
+        };
+        if next_column < self.row_width(cursor.line()) {
+            self.set_copy_mode_cursor(cursor.line(), next_column);
+            return Some(false);
+        }
+        let next_line = if cursor.line() < self.last_copy_mode_line() as isize {
+            cursor.line() + 1
+        } else if self.scroll_copy_mode_down() {
+            // scrolling moved the cursor up along with the content
+            cursor.line()
+        } else {
+            return None;
+        };
+        self.set_copy_mode_cursor(next_line, 0);
+        Some(self.is_canonical(next_line))
+    }
+
+    // Moves the cursor to the previous character, scrolling if needed. Returns whether this moved
+    // it to a previous canonical line or None if it's already at the beginning
+    fn step_copy_mode_cursor_backward(&mut self) -> Option<bool> {
+        let cursor = self.copy_mode_cursor();
+        let column = match self.character_at(cursor) {
+            Some((column, _, _)) => column,
+            None => std::cmp::min(cursor.column(), self.row_width(cursor.line())),
+        };
+        if column > 0 {
+            let previous_column = self
+                .character_at(position(cursor.line(), column - 1))
+                .map(|(column, _, _)| column)
+                .unwrap_or(column - 1);
+            self.set_copy_mode_cursor(cursor.line(), previous_column);
+            return Some(false);
+        }
+        let crossed_line_break = self.is_canonical(cursor.line());
+        let previous_line = if cursor.line() > 0 {
+            cursor.line() - 1
+        } else if self.scroll_copy_mode_up() {
+            // scrolling moved the cursor down along with the content
+            0
+        } else {
+            return None;
+        };
+        let last_character_column = self.last_character_column(previous_line);
+        self.set_copy_mode_cursor(previous_line, last_character_column);
+        Some(crossed_line_break)
+    }
+
+    fn character_under_copy_mode_cursor(&self) -> Option<char> {
+        self.character_at(self.copy_mode_cursor())
+            .map(|(_, character, _)| character)
+    }
+
+    // the column the character at this position starts in, the character itself and its width
+    fn character_at(&self, position: Position) -> Option<(usize, char, usize)> {
+        let row = self.viewport.get(usize::try_from(position.line()).ok()?)?;
+        let mut column = 0;
+        for terminal_character in &row.columns {
+            if position.column() < column + terminal_character.width {
+                return Some((
+                    column,
+                    terminal_character.character,
+                    terminal_character.width,
+                ));
+            }
+            column += terminal_character.width;
+        }
+        None
+    }
+
+    fn row_width(&self, line: isize) -> usize {
+        usize::try_from(line)
+            .ok()
+            .and_then(|line| self.viewport.get(line))
+            .map(|row| row.width())
+            .unwrap_or(0)
+    }
+
+    fn is_canonical(&self, line: isize) -> bool {
+        usize::try_from(line)
+            .ok()
+            .and_then(|line| self.viewport.get(line))
+            .map(|row| row.is_canonical)
+            .unwrap_or(true)
+    }
+
+    fn last_character_column(&self, line: isize) -> usize {
+        let row_width = self.row_width(line);
+        if row_width == 0 {
+            return 0;
+        }
+        self.character_at(position(line, row_width - 1))
+            .map(|(column, _, _)| column)
+            .unwrap_or(0)
+    }
+
+    fn word_start(&self, position_in_word: Position) -> Position {
+        let (mut column, character, _) = match self.character_at(position_in_word) {
+            Some(character_at_position) => character_at_position,
+            None => return position_in_word,
+        };
+        let class = CharacterClass::of(Some(character));
+        if class == CharacterClass::Whitespace {
+            return position(position_in_word.line(), column);
+        }
+        while column > 0 {
+            match self.character_at(position(position_in_word.line(), column - 1)) {
+                Some((previous_column, previous_character, _))
+                    if CharacterClass::of(Some(previous_character)) == class =>
+                {
+                    column = previous_column;
+                },
+                _ => break,
+            }
+        }
+        position(position_in_word.line(), column)
+    }
+
+    // the position right after the end of the word (the end of a selection is exclusive)
+    fn word_end(&self, position_in_word: Position) -> Position {
+        let (column, character, width) = match self.character_at(position_in_word) {
+            Some(character_at_position) => character_at_position,
+            None => return position(position_in_word.line(), position_in_word.column() + 1),
+        };
+        let class = CharacterClass::of(Some(character));
+        let mut end_column = column + width;
+        if class == CharacterClass::Whitespace {
+            return position(position_in_word.line(), end_column);
+        }
+        while let Some((_, next_character, next_width)) =
+            self.character_at(position(position_in_word.line(), end_column))
+        {
+            if CharacterClass::of(Some(next_character)) != class {
+                break;
+            }
+            end_column += next_width;
+        }
+        position(position_in_word.line(), end_column)
+    }
+}
+
+fn position(line: isize, column: usize) -> Position {
+    Position::new(line as i32, column as u16)
+}
@@ -400,19 +400,18 @@ impl FloatingPanes {
         Ok(())
     }
 
-    pub fn resize_active_pane(
+    pub fn resize_pane_with_id(
         &mut self,
-        client_id: ClientId,
+        pane_id: PaneId,
         os_api: &mut Box<dyn ServerOsApi>,
         strategy: &ResizeStrategy,
     ) -> Result<bool> {
         // true => successfully resized
-        let err_context =
-            || format!("failed to {strategy} for active floating pane for client {client_id}");
+        let err_context = || format!("failed to {strategy} for floating pane with id {pane_id:?}");
 
         let display_area = *self.display_area.borrow();
         let viewport = *self.viewport.borrow();
-        if let Some(active_floating_pane_id) = self.active_panes.get(&client_id) {
+        if self.panes.contains_key(&pane_id) {
             let mut floating_pane_grid = FloatingPaneGrid::new(
                 &mut self.panes,
                 &mut self.desired_pane_positions,
@@ -421,7 +420,7 @@ impl FloatingPanes {
             );
             floating_pane_grid
                 .change_pane_size(
-                    active_floating_pane_id,
+                    &pane_id,
                     strategy,
                     (RESIZE_INCREMENT_WIDTH, RESIZE_INCREMENT_HEIGHT),
                 )
@@ -1,7 +1,10 @@
+use super::kitty_graphics::{KittyGraphicsCommand, KittyGraphicsGrid, KittyGraphicsParser};
+use super::scrollback_spill::{ScrollbackSpill, SPILL_BLOCK_SIZE};
 use super::sixel::{PixelRect, SixelGrid, SixelImageStore};
 use std::borrow::Cow;
 use std::cell::RefCell;
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::rc::Rc;
 use unicode_width::UnicodeWidthChar;
 use zellij_utils::data::Style;
@@ -13,24 +16,34 @@ use std::{
     collections::{BTreeSet, VecDeque},
     fmt::{self, Debug, Formatter},
     str,
+    time::{Duration, Instant},
 };
 
 use zellij_utils::{
-    consts::{DEFAULT_SCROLL_BUFFER_SIZE, SCROLL_BUFFER_SIZE},
+    consts::{DEFAULT_SCROLL_BUFFER_SIZE, SCROLL_BUFFER_SIZE, SCROLL_BUFFER_SPILL_TO_DISK},
     data::{Palette, PaletteColor},
+    input::kitty_keyboard,
     pane_size::SizeInPixels,
     position::Position,
-    vte,
+    serde, vte,
 };
 
+use serde::{Deserialize, Serialize};
+
 const TABSTOP_WIDTH: usize = 8; // TODO: is this always right?
 pub const MAX_TITLE_STACK_SIZE: usize = 1000;
+pub const MAX_KITTY_KEYBOARD_FLAGS_STACK_SIZE: usize = 16;
+// we stop holding back the output of an application that did not end its synchronized update in
+// time, so that a misbehaving application cannot freeze its pane
+pub const SYNCHRONIZED_OUTPUT_TIMEOUT: Duration = Duration::from_millis(150);
 
 use vte::{Params, Perform};
 use zellij_utils::{consts::VERSION, shared::version_number};
 
-use crate::output::{CharacterChunk, OutputBuffer, SixelImageChunk};
+use crate::output::{CharacterChunk, KittyImageChunk, OutputBuffer, SixelImageChunk};
 use crate::panes::alacritty_functions::{parse_number, xparse_color};
+use crate::panes::copy_mode::CopyMode;
+use crate::panes::hints::Hints;
 use crate::panes::link_handler::LinkHandler;
 use crate::panes::search::SearchResult;
 use crate::panes::selection::Selection;
@@ -119,6 +132,8 @@ fn transfer_rows_from_lines_above_to_viewport(
     lines_above: &mut VecDeque<Row>,
     viewport: &mut Vec<Row>,
     sixel_grid: &mut SixelGrid,
+    kitty_graphics_grid: &mut KittyGraphicsGrid,
+    scrollback_spill: &mut Option<ScrollbackSpill>,
     count: usize,
     max_viewport_width: usize,
 ) -> usize {
@@ -150,7 +165,13 @@ fn transfer_rows_from_lines_above_to_viewport(
     }
     if !next_lines.is_empty() {
         let excess_row = Row::from_rows(next_lines, 0);
-        bounded_push(lines_above, sixel_grid, excess_row);
+        bounded_push(
+            lines_above,
+            sixel_grid,
+            kitty_graphics_grid,
+            scrollback_spill,
+            excess_row,
+        );
     }
     match usize::try_from(lines_added_to_viewport) {
         Ok(n) => n,
@@ -162,6 +183,8 @@ fn transfer_rows_from_viewport_to_lines_above(
     viewport: &mut Vec<Row>,
     lines_above: &mut VecDeque<Row>,
     sixel_grid: &mut SixelGrid,
+    kitty_graphics_grid: &mut KittyGraphicsGrid,
+    scrollback_spill: &mut Option<ScrollbackSpill>,
     count: usize,
     max_viewport_width: usize,
 ) -> isize {
@@ -184,7 +207,13 @@ fn transfer_rows_from_viewport_to_lines_above(
                 break; // no more rows
             }
         }
-        let dropped_line_width = bounded_push(lines_above, sixel_grid, next_lines.remove(0));
+        let dropped_line_width = bounded_push(
+            lines_above,
+            sixel_grid,
+            kitty_graphics_grid,
+            scrollback_spill,
+            next_lines.remove(0),
+        );
         if let Some(width) = dropped_line_width {
             transferred_rows_count -=
                 calculate_row_display_height(width, max_viewport_width) as isize;
@@ -240,19 +269,47 @@ fn transfer_rows_from_lines_below_to_viewport(
     }
 }
 
-fn bounded_push(vec: &mut VecDeque<Row>, sixel_grid: &mut SixelGrid, value: Row) -> Option<usize> {
+fn bounded_push(
+    vec: &mut VecDeque<Row>,
+    sixel_grid: &mut SixelGrid,
+    kitty_graphics_grid: &mut KittyGraphicsGrid,
+    scrollback_spill: &mut Option<ScrollbackSpill>,
+    value: Row,
+) -> Option<usize> {
     let mut dropped_line_width = None;
     if vec.len() >= *SCROLL_BUFFER_SIZE.get().unwrap() {
         let line = vec.pop_front();
         if let Some(line) = line {
             sixel_grid.offset_grid_top();
-            dropped_line_width = Some(line.width());
+            kitty_graphics_grid.offset_grid_top();
+            match scrollback_spill {
+                // the line is still part of the scrollback, so it is not reported as dropped
+                Some(scrollback_spill) => scrollback_spill
+                    .push(line)
+                    .context("failed to spill scrollback line to disk")
+                    .non_fatal(),
+                None => dropped_line_width = Some(line.width()),
+            }
         }
     }
     vec.push_back(value);
     dropped_line_width
 }
 
+fn new_scrollback_spill() -> Option<ScrollbackSpill> {
+    if *SCROLL_BUFFER_SPILL_TO_DISK.get().unwrap_or(&false) {
+        // blocks are no larger than the scroll buffer, so that paging one back in never
+        // overflows it
+        let scroll_buffer_size = *SCROLL_BUFFER_SIZE.get_or_init(|| DEFAULT_SCROLL_BUFFER_SIZE);
+        Some(ScrollbackSpill::new(std::cmp::min(
+            SPILL_BLOCK_SIZE,
+            scroll_buffer_size,
+        )))
+    } else {
+        None
+    }
+}
+
 pub fn create_horizontal_tabstops(columns: usize) -> BTreeSet<usize> {
     let mut i = TABSTOP_WIDTH;
     let mut horizontal_tabstops = BTreeSet::new();
@@ -266,7 +323,7 @@ pub fn create_horizontal_tabstops(columns: usize) -> BTreeSet<usize> {
     horizontal_tabstops
 }
 
-fn calculate_row_display_height(row_width: usize, viewport_width: usize) -> usize {
+pub fn calculate_row_display_height(row_width: usize, viewport_width: usize) -> usize {
     if row_width <= viewport_width {
         return 1;
     }
@@ -301,6 +358,26 @@ macro_rules! dump_screen {
     }};
 }
 
+// OSC 7 reports the cwd as a file:// url, eg. file://hostname/home/user/some%20dir
+fn parse_osc7_cwd(params: &[&[u8]]) -> Option<PathBuf> {
+    // the path itself might contain semicolons, which vte splits into separate params
+    let url = params
+        .iter()
+        .map(|param| str::from_utf8(param))
+        .collect::<Result<Vec<_>, _>>()
+        .ok()?
+        .join(";");
+    let url = url::Url::parse(&url).ok()?;
+    if url.scheme() != "file" {
+        return None;
+    }
+    // the hostname is dropped so that the path is decoded regardless of where the shell runs
+    url::Url::parse(&format!("file://{}", url.path()))
+        .ok()?
+        .to_file_path()
+        .ok()
+}
+
 fn utf8_mouse_coordinates(column: usize, line: isize) -> Vec<u8> {
     let mut coordinates = vec![];
     let mouse_pos_encode = |pos: usize| -> Vec<u8> {
@@ -330,7 +407,7 @@ pub struct Grid {
     pub(crate) lines_below: Vec<Row>,
     horizontal_tabstops: BTreeSet<usize>,
     alternate_screen_state: Option<AlternateScreenState>,
-    cursor: Cursor,
+    pub(crate) cursor: Cursor,
     cursor_is_hidden: bool,
     saved_cursor_position: Option<Cursor>,
     // FIXME: change scroll_region to be (usize, usize) - where the top line is always the first
@@ -346,6 +423,9 @@ pub struct Grid {
     title_stack: Vec<String>,
     character_cell_size: Rc<RefCell<Option<SizeInPixels>>>,
     sixel_grid: SixelGrid,
+    kitty_graphics_grid: KittyGraphicsGrid,
+    kitty_graphics_parser: KittyGraphicsParser,
+    pub(crate) scrollback_spill: Option<ScrollbackSpill>, // lines scrolled out of lines_above, when spilling to disk
     pub changed_colors: Option<[Option<AnsiCode>; 256]>,
     pub should_render: bool,
     pub cursor_key_mode: bool, // DECCKM - when set, cursor keys should send ANSI direction codes (eg. "OD") instead of the arrow keys (eg. "[D")
@@ -369,7 +449,15 @@ pub struct Grid {
     pub mouse_tracking: MouseTracking,
     pub focus_event_tracking: bool,
     pub search_results: SearchResult,
+    pub(crate) copy_mode: Option<CopyMode>,
+    pub(crate) hints: Option<Hints>,
     pub pending_clipboard_update: Option<String>,
+    pub pending_cwd_update: Option<PathBuf>,
+    // progressive enhancements of the kitty keyboard protocol, each screen has its own stack
+    kitty_keyboard_flags: Vec<u8>,
+    alternate_screen_kitty_keyboard_flags: Vec<u8>,
+    synchronized_output_started_at: Option<Instant>, // DEC mode 2026
+    full_viewport_render_requested: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -503,12 +591,23 @@ impl Grid {
             focus_event_tracking: false,
             character_cell_size,
             search_results: Default::default(),
+            copy_mode: None,
+            hints: None,
             sixel_grid,
+            kitty_graphics_grid: KittyGraphicsGrid::default(),
+            kitty_graphics_parser: KittyGraphicsParser::default(),
+            scrollback_spill: new_scrollback_spill(),
             pending_clipboard_update: None,
+            pending_cwd_update: None,
+            kitty_keyboard_flags: vec![],
+            alternate_screen_kitty_keyboard_flags: vec![],
+            synchronized_output_started_at: None,
+            full_viewport_render_requested: false,
         }
     }
     pub fn render_full_viewport(&mut self) {
         self.output_buffer.update_all_lines();
+        self.full_viewport_render_requested = true;
     }
     pub fn update_line_for_rendering(&mut self, line_index: usize) {
         self.output_buffer.update_line(line_index);
@@ -570,6 +669,9 @@ impl Grid {
                 scrollback_buffer_count += 1;
             }
         }
+        if let Some(scrollback_spill) = &self.scrollback_spill {
+            scrollback_buffer_count += scrollback_spill.display_height(self.width);
+        }
         scrollback_buffer_count
     }
 
@@ -664,6 +766,9 @@ impl Grid {
 
     pub fn scroll_up_one_line(&mut self) -> bool {
         let mut found_something = false;
+        if self.lines_above.is_empty() {
+            self.page_in_spilled_lines();
+        }
         if !self.lines_above.is_empty() && self.viewport.len() == self.height {
             self.is_scrolled = true;
             let line_to_push_down = self.viewport.pop().unwrap();
@@ -673,6 +778,8 @@ impl Grid {
                 &mut self.lines_above,
                 &mut self.viewport,
                 &mut self.sixel_grid,
+                &mut self.kitty_graphics_grid,
+                &mut self.scrollback_spill,
                 1,
                 self.width,
             );
@@ -681,6 +788,12 @@ impl Grid {
                 .saturating_sub(transferred_rows_height);
 
             self.selection.move_down(1);
+            if let Some(copy_mode) = self.copy_mode.as_mut() {
+                copy_mode.move_down(1);
+            }
+            if let Some(hints) = self.hints.as_mut() {
+                hints.move_down(1);
+            }
             // Move all search-selections down one line as well
             found_something = self
                 .search_results
@@ -689,6 +802,33 @@ impl Grid {
         self.output_buffer.update_all_lines();
         found_something
     }
+    /// Whether there are any lines above the viewport, including ones spilled to disk
+    pub fn has_lines_above(&self) -> bool {
+        !self.lines_above.is_empty()
+            || self
+                .scrollback_spill
+                .as_ref()
+                .map(|scrollback_spill| !scrollback_spill.is_empty())
+                .unwrap_or(false)
+    }
+    fn page_in_spilled_lines(&mut self) {
+        let paged_in_rows = match self.scrollback_spill.as_mut() {
+            Some(scrollback_spill) => scrollback_spill
+                .pop_rows()
+                .context("failed to page in scrollback lines spilled to disk")
+                .to_log()
+                .unwrap_or_default(),
+            None => return,
+        };
+        // these lines are already counted in scrollback_buffer_lines, but the images are placed
+        // relative to the top of lines_above
+        self.sixel_grid.offset_grid_bottom(paged_in_rows.len());
+        self.kitty_graphics_grid
+            .offset_grid_bottom(paged_in_rows.len());
+        for row in paged_in_rows.into_iter().rev() {
+            self.lines_above.push_front(row);
+        }
+    }
     pub fn scroll_down_one_line(&mut self) -> bool {
         let mut found_something = false;
         if !self.lines_below.is_empty() && self.viewport.len() == self.height {
@@ -703,10 +843,18 @@ impl Grid {
                 let mut last_line_above = self.lines_above.pop_back().unwrap();
                 last_line_above.append(&mut line_to_push_up.columns);
                 last_line_above
+                    .prompt_marks
+                    .merge(line_to_push_up.prompt_marks);
+                last_line_above
             };
 
-            let dropped_line_width =
-                bounded_push(&mut self.lines_above, &mut self.sixel_grid, line_to_push_up);
+            let dropped_line_width = bounded_push(
+                &mut self.lines_above,
+                &mut self.sixel_grid,
+                &mut self.kitty_graphics_grid,
+                &mut self.scrollback_spill,
+                line_to_push_up,
+            );
             if let Some(width) = dropped_line_width {
                 let dropped_line_height = calculate_row_display_height(width, self.width);
 
@@ -723,6 +871,12 @@ impl Grid {
             );
 
             self.selection.move_up(1);
+            if let Some(copy_mode) = self.copy_mode.as_mut() {
+                copy_mode.move_up(1);
+            }
+            if let Some(hints) = self.hints.as_mut() {
+                hints.move_up(1);
+            }
             // Move all search-selections up one line as well
             found_something =
                 self.search_results
@@ -758,6 +912,9 @@ impl Grid {
             return;
         }
         self.selection.reset();
+        if let Some(copy_mode) = self.copy_mode.as_mut() {
+            copy_mode.clear_selection();
+        }
         self.sixel_grid.character_cell_size_possibly_changed();
         if new_columns != self.width && self.alternate_screen_state.is_none() {
             self.horizontal_tabstops = create_horizontal_tabstops(new_columns);
@@ -772,6 +929,7 @@ impl Grid {
                 {
                     let mut first_line_above = self.lines_above.pop_back().unwrap();
                     first_line_above.append(&mut row.columns);
+                    first_line_above.prompt_marks.merge(row.prompt_marks);
                     viewport_canonical_lines.push(first_line_above);
                     cursor_canonical_line_index += 1;
                 } else if row.is_canonical {
@@ -780,6 +938,7 @@ impl Grid {
                     match viewport_canonical_lines.last_mut() {
                         Some(last_line) => {
                             last_line.append(&mut row.columns);
+                            last_line.prompt_marks.merge(row.prompt_marks);
                         },
                         None => {
                             // the state is corrupted somehow
@@ -833,6 +992,9 @@ impl Grid {
                     };
                     canonical_line_parts.push(row);
                 }
+                if let Some(first_part) = canonical_line_parts.first_mut() {
+                    first_part.prompt_marks = canonical_line.prompt_marks;
+                }
                 new_viewport_rows.append(&mut canonical_line_parts);
             }
 
@@ -867,6 +1029,8 @@ impl Grid {
                         &mut self.lines_above,
                         &mut self.viewport,
                         &mut self.sixel_grid,
+                        &mut self.kitty_graphics_grid,
+                        &mut self.scrollback_spill,
                         row_count_to_transfer,
                         new_columns,
                     );
@@ -894,6 +1058,8 @@ impl Grid {
                         &mut self.viewport,
                         &mut self.lines_above,
                         &mut self.sixel_grid,
+                        &mut self.kitty_graphics_grid,
+                        &mut self.scrollback_spill,
                         row_count_to_transfer,
                         new_columns,
                     );
@@ -932,6 +1098,8 @@ impl Grid {
                         &mut self.lines_above,
                         &mut self.viewport,
                         &mut self.sixel_grid,
+                        &mut self.kitty_graphics_grid,
+                        &mut self.scrollback_spill,
                         row_count_to_transfer,
                         new_columns,
                     );
@@ -961,6 +1129,8 @@ impl Grid {
                             &mut self.viewport,
                             &mut self.lines_above,
                             &mut self.sixel_grid,
+                            &mut self.kitty_graphics_grid,
+                            &mut self.scrollback_spill,
                             row_count_to_transfer,
                             new_columns,
                         );
@@ -982,6 +1152,10 @@ impl Grid {
         self.search_viewport();
         // If we have thrown out the active element, set it to None
         self.search_results.unset_active_selection_if_nonexistent();
+        // the hints moved along with the content, so look for them again
+        if let Some(hint_patterns) = self.hint_patterns() {
+            self.enter_hint_mode(&hint_patterns);
+        }
         self.output_buffer.update_all_lines();
     }
     pub fn as_character_lines(&self) -> Vec<Vec<TerminalCharacter>> {
@@ -1045,6 +1219,7 @@ impl Grid {
     ) -> Result<Option<(Vec<CharacterChunk>, Option<String>, Vec<SixelImageChunk>)>> {
         let mut raw_vte_output = String::new();
 
+        self.full_viewport_render_requested = false;
         let (mut character_chunks, sixel_image_chunks) = self.read_changes(content_x, content_y);
         for character_chunk in character_chunks.iter_mut() {
             character_chunk.add_changed_colors(self.changed_colors);
@@ -1091,6 +1266,7 @@ impl Grid {
                 }
             }
         }
+        self.render_hints(&mut character_chunks, content_x, content_y, style);
         if self.ring_bell {
             let ring_bell = '\u{7}';
             raw_vte_output.push(ring_bell);
@@ -1103,7 +1279,9 @@ impl Grid {
         )));
     }
     pub fn cursor_coordinates(&self) -> Option<(usize, usize)> {
-        if self.cursor_is_hidden {
+        if self.is_in_copy_mode() {
+            self.copy_mode_cursor_coordinates()
+        } else if self.cursor_is_hidden {
             None
         } else {
             Some((self.cursor.x, self.cursor.y))
@@ -1124,13 +1302,143 @@ impl Grid {
         if !full {
             return viewport;
         }
-        let mut scrollback: String = dump_screen!(self.lines_above);
+        let mut lines_above: Vec<Row> = match self.scrollback_spill.as_mut() {
+            Some(scrollback_spill) => scrollback_spill
+                .rows()
+                .context("failed to read scrollback lines spilled to disk")
+                .to_log()
+                .unwrap_or_default(),
+            None => vec![],
+        };
+        lines_above.extend(self.lines_above.iter().cloned());
+        let mut scrollback: String = dump_screen!(lines_above);
         if !scrollback.is_empty() {
             scrollback.push('\n');
         }
         scrollback.push_str(&viewport);
         scrollback
     }
+    pub fn dump_last_command_output(&self) -> String {
+        let rows: Vec<Row> = match self.last_command_output_line_range() {
+            Some((start, end)) => (start..end)
+                .filter_map(|line_index| self.row_at_line_index(line_index))
+                .cloned()
+                .collect(),
+            None => vec![],
+        };
+        dump_screen!(rows)
+    }
+    pub fn select_last_command_output(&mut self) {
+        if let Some((start, end)) = self.last_command_output_line_range() {
+            let old_selection = self.selection;
+            self.selection.start(Position::new(start as i32, 0));
+            self.selection.end(Position::new(end as i32, 0));
+            self.update_selected_lines(&old_selection, &self.selection.clone());
+            self.mark_for_rerender();
+        }
+    }
+    pub fn move_viewport_to_previous_prompt(&mut self) {
+        if !self
+            .lines_above
+            .iter()
+            .any(|row| row.prompt_marks.prompt_start)
+        {
+            return;
+        }
+        while !self.lines_above.is_empty() && self.viewport.len() == self.height {
+            self.scroll_up_one_line();
+            if self
+                .viewport
+                .first()
+                .map(|row| row.prompt_marks.prompt_start)
+                .unwrap_or(false)
+            {
+                break;
+            }
+        }
+        self.output_buffer.update_all_lines();
+    }
+    pub fn move_viewport_to_next_prompt(&mut self) {
+        if !self
+            .viewport
+            .iter()
+            .skip(1)
+            .chain(self.lines_below.iter())
+            .any(|row| row.prompt_marks.prompt_start)
+        {
+            return;
+        }
+        while !self.lines_below.is_empty() && self.viewport.len() == self.height {
+            self.scroll_down_one_line();
+            if self
+                .viewport
+                .first()
+                .map(|row| row.prompt_marks.prompt_start)
+                .unwrap_or(false)
+            {
+                break;
+            }
+        }
+        self.output_buffer.update_all_lines();
+    }
+    fn mark_prompt(&mut self, mark: u8) {
+        if self.alternate_screen_state.is_some() {
+            // prompts are never in the alternate screen, so these marks would be lost anyway
+            return;
+        }
+        if self.viewport.get(self.cursor.y).is_none() {
+            self.pad_lines_until(self.cursor.y, EMPTY_TERMINAL_CHARACTER);
+        }
+        if let Some(row) = self.viewport.get_mut(self.cursor.y) {
+            match mark {
+                b'A' => row.prompt_marks.prompt_start = true,
+                b'B' => row.prompt_marks.command_start = true,
+                b'C' => row.prompt_marks.output_start = true,
+                b'D' => row.prompt_marks.command_end = true,
+                _ => log::warn!("Unhandled semantic prompt mark: {:?}", mark as char),
+            }
+        }
+    }
+    // line indices here are the same as those used by the selection: negative for lines_above,
+    // and counting on from the top of the viewport into lines_below
+    fn row_at_line_index(&self, line_index: isize) -> Option<&Row> {
+        if line_index < 0 {
+            self.lines_above
+                .len()
+                .checked_sub(line_index.unsigned_abs())
+                .and_then(|index| self.lines_above.get(index))
+        } else if (line_index as usize) < self.viewport.len() {
+            self.viewport.get(line_index as usize)
+        } else {
+            self.lines_below
+                .get(line_index as usize - self.viewport.len())
+        }
+    }
+    // returns the (start, end) line indices (end exclusive) of the output of the last command,
+    // running or finished, as marked by the shell with OSC 133
+    fn last_command_output_line_range(&self) -> Option<(isize, isize)> {
+        let first_line_index = -(self.lines_above.len() as isize);
+        let end_line_index = (self.viewport.len() + self.lines_below.len()) as isize;
+        let output_start = (first_line_index..end_line_index)
+            .rev()
+            .find(|line_index| {
+                self.row_at_line_index(*line_index)
+                    .map(|row| row.prompt_marks.output_start)
+                    .unwrap_or(false)
+            })?;
+        let output_end = (output_start..end_line_index)
+            .find(|line_index| {
+                self.row_at_line_index(*line_index)
+                    .map(|row| row.prompt_marks.ends_command_output())
+                    .unwrap_or(false)
+            })
+            .unwrap_or(end_line_index);
+        if output_start < output_end {
+            Some((output_start, output_end))
+        } else {
+            None
+        }
+    }
     pub fn move_viewport_up(&mut self, count: usize) {
         for _ in 0..count {
             self.scroll_up_one_line();
@@ -1231,6 +1539,12 @@ impl Grid {
                     let columns = VecDeque::from(vec![pad_character; self.width]);
                     self.viewport.push(Row::from_columns(columns).canonical());
                     self.selection.move_up(1);
+                    if let Some(copy_mode) = self.copy_mode.as_mut() {
+                        copy_mode.move_up(1);
+                    }
+                    if let Some(hints) = self.hints.as_mut() {
+                        hints.move_up(1);
+                    }
                 } else {
                     self.viewport.remove(scroll_region_top);
                     let mut pad_character = EMPTY_TERMINAL_CHARACTER;
@@ -1260,10 +1574,17 @@ impl Grid {
                     self.transfer_rows_to_lines_above(1);
                 } else {
                     self.sixel_grid.offset_grid_top();
+                    self.kitty_graphics_grid.offset_grid_top();
                     self.viewport.remove(0);
                 }
 
                 self.selection.move_up(1);
+                if let Some(copy_mode) = self.copy_mode.as_mut() {
+                    copy_mode.move_up(1);
+                }
+                if let Some(hints) = self.hints.as_mut() {
+                    hints.move_up(1);
+                }
             }
             self.output_buffer.update_all_lines();
         } else {
@@ -1415,6 +1736,12 @@ impl Grid {
             let wrapped_row = Row::new(self.width);
             self.viewport.push(wrapped_row);
             self.selection.move_up(1);
+            if let Some(copy_mode) = self.copy_mode.as_mut() {
+                copy_mode.move_up(1);
+            }
+            if let Some(hints) = self.hints.as_mut() {
+                hints.move_up(1);
+            }
             self.output_buffer.update_all_lines();
         } else {
             self.cursor.y += 1;
@@ -1427,6 +1754,9 @@ impl Grid {
     }
     fn clear_lines_above(&mut self) {
         self.lines_above.clear();
+        if let Some(scrollback_spill) = self.scrollback_spill.as_mut() {
+            scrollback_spill.clear();
+        }
         self.scrollback_buffer_lines = self.recalculate_scrollback_buffer_count();
     }
 
@@ -1656,11 +1986,62 @@ impl Grid {
     pub fn mark_for_rerender(&mut self) {
         self.should_render = true;
     }
+    pub fn kitty_keyboard_flags(&self) -> u8 {
+        let flags_stack = if self.alternate_screen_state.is_some() {
+            &self.alternate_screen_kitty_keyboard_flags
+        } else {
+            &self.kitty_keyboard_flags
+        };
+        flags_stack.last().copied().unwrap_or(0)
+    }
+    fn kitty_keyboard_flags_stack_mut(&mut self) -> &mut Vec<u8> {
+        if self.alternate_screen_state.is_some() {
+            &mut self.alternate_screen_kitty_keyboard_flags
+        } else {
+            &mut self.kitty_keyboard_flags
+        }
+    }
+    pub fn is_holding_synchronized_output(&self) -> bool {
+        self.synchronized_output_started_at
+            .map(|started_at| started_at.elapsed() < SYNCHRONIZED_OUTPUT_TIMEOUT)
+            .unwrap_or(false)
+    }
+    /// Whether our changes should be held back from rendering: a synchronized update is in
+    /// progress and nothing (eg. a resize or a newly attached client) needs the whole viewport
+    /// to be drawn again in the meantime.
+    pub fn should_hold_back_output(&self) -> bool {
+        self.is_holding_synchronized_output() && !self.full_viewport_render_requested
+    }
+    // the DECRPM state of a private mode: 0 - not recognized, 1 - set, 2 - reset
+    fn private_mode_state(&self, mode: u16) -> u8 {
+        let is_set = match mode {
+            1 => self.cursor_key_mode,
+            6 => self.erasure_mode,
+            7 => !self.disable_linewrap,
+            25 => !self.cursor_is_hidden,
+            80 => self.sixel_scrolling,
+            1000 => matches!(self.mouse_tracking, MouseTracking::Normal),
+            1002 => matches!(self.mouse_tracking, MouseTracking::ButtonEventTracking),
+            1004 => self.focus_event_tracking,
+            1005 => matches!(self.mouse_mode, MouseMode::Utf8),
+            1006 => matches!(self.mouse_mode, MouseMode::Sgr),
+            1049 => self.alternate_screen_state.is_some(),
+            2004 => self.bracketed_paste_mode,
+            2026 => self.is_holding_synchronized_output(),
+            _ => return 0,
+        };
+        if is_set {
+            1
+        } else {
+            2
+        }
+    }
     pub fn reset_terminal_state(&mut self) {
         self.lines_above = VecDeque::with_capacity(*SCROLL_BUFFER_SIZE.get().unwrap());
         self.lines_below = vec![];
         self.viewport = vec![Row::new(self.width).canonical()];
         self.alternate_screen_state = None;
+        self.scrollback_spill = new_scrollback_spill();
         self.cursor_key_mode = false;
         self.scroll_region = None;
         self.clear_viewport_before_rendering = true;
@@ -1680,9 +2061,13 @@ impl Grid {
         self.mouse_tracking = MouseTracking::Off;
         self.focus_event_tracking = false;
         self.cursor_is_hidden = false;
+        self.kitty_keyboard_flags.clear();
+        self.alternate_screen_kitty_keyboard_flags.clear();
+        self.synchronized_output_started_at = None;
         if let Some(images_to_reap) = self.sixel_grid.clear() {
             self.sixel_grid.reap_images(images_to_reap);
         }
+        self.kitty_graphics_grid.clear();
     }
     fn set_preceding_character(&mut self, terminal_character: TerminalCharacter) {
         self.preceding_char = Some(terminal_character);
@@ -1693,6 +2078,12 @@ impl Grid {
         self.update_selected_lines(&old_selection, &self.selection.clone());
         self.mark_for_rerender();
     }
+    pub fn start_block_selection(&mut self, start: &Position) {
+        let old_selection = self.selection;
+        self.selection.start_block(*start);
+        self.update_selected_lines(&old_selection, &self.selection.clone());
+        self.mark_for_rerender();
+    }
     pub fn update_selection(&mut self, to: &Position) {
         let old_selection = self.selection;
         self.selection.to(*to);
@@ -1727,10 +2118,15 @@ impl Grid {
 
             // on the first line of the selection, use the selection start column
             // otherwise, start at the beginning of the line
-            let start_column = if l == start.line.0 { start.column.0 } else { 0 };
+            // (block selections use the same columns on every line)
+            let start_column = if l == start.line.0 || sorted_selection.is_block() {
+                start.column.0
+            } else {
+                0
+            };
 
             // same thing on the last line, but with the selection end column
-            let end_column = if l == end.line.0 {
+            let end_column = if l == end.line.0 || sorted_selection.is_block() {
                 end.column.0
             } else {
                 self.width
@@ -1744,7 +2140,7 @@ impl Grid {
                 Row::from_columns(VecDeque::from(vec![EMPTY_TERMINAL_CHARACTER; self.width]));
 
             // get the row from lines_above, viewport, or lines below depending on index
-            let row = if l < 0 && self.lines_above.len() > l.abs() as usize {
+            let row = if l < 0 && self.lines_above.len() >= l.abs() as usize {
                 let offset_from_end = l.abs();
                 &self.lines_above[self
                     .lines_above
@@ -1772,7 +2168,7 @@ impl Grid {
                 terminal_col += terminal_character.width;
             }
 
-            if row.is_canonical {
+            if row.is_canonical || sorted_selection.is_block() {
                 selection.push(line_selection);
             } else {
                 // rejoin wrapped lines if possible
@@ -1798,7 +2194,11 @@ impl Grid {
         self.lines_above.len() + self.cursor.y
     }
 
-    fn update_selected_lines(&mut self, old_selection: &Selection, new_selection: &Selection) {
+    pub(crate) fn update_selected_lines(
+        &mut self,
+        old_selection: &Selection,
+        new_selection: &Selection,
+    ) {
         for l in old_selection.diff(new_selection, self.height) {
             self.output_buffer.update_line(l as usize);
         }
@@ -1824,6 +2224,8 @@ impl Grid {
             &mut self.viewport,
             &mut self.lines_above,
             &mut self.sixel_grid,
+            &mut self.kitty_graphics_grid,
+            &mut self.scrollback_spill,
             count,
             self.width,
         );
@@ -1856,6 +2258,43 @@ impl Grid {
             None
         }
     }
+    pub fn advance_kitty_graphics_parser(&mut self, byte: u8) {
+        if let Some(command) = self.kitty_graphics_parser.advance(byte) {
+            self.handle_kitty_graphics_command(command);
+        }
+    }
+    fn handle_kitty_graphics_command(&mut self, command: KittyGraphicsCommand) {
+        let character_cell_size = *self.character_cell_size.borrow();
+        let result = self.kitty_graphics_grid.handle_command(
+            command,
+            self.lines_above.len(),
+            self.cursor.x,
+            self.cursor.y,
+            character_cell_size,
+        );
+        if let Some(response) = result.response {
+            self.pending_messages_to_pty.push(response.into_bytes());
+        }
+        if let Some((columns, rows)) = result.placement_size {
+            // the cursor is moved to the last line of the image, after its last column
+            for _ in 1..rows {
+                self.add_canonical_line();
+            }
+            self.cursor.x = std::cmp::min(self.cursor.x + columns, self.width);
+        }
+        if result.placements_changed {
+            self.render_full_viewport();
+        }
+    }
+    pub fn kitty_image_chunks(&self, x_offset: usize, y_offset: usize) -> Vec<KittyImageChunk> {
+        self.kitty_graphics_grid.image_chunks_in_viewport(
+            self.lines_above.len(),
+            self.height,
+            self.width,
+            x_offset,
+            y_offset,
+        )
+    }
     fn create_sixel_image(&mut self) {
         if let Some((x_pixel_coordinates, y_pixel_coordinates)) =
             self.current_cursor_pixel_coordinates()
@@ -2104,6 +2543,9 @@ impl Grid {
     }
     pub fn delete_viewport_and_scroll(&mut self) {
         self.lines_above.clear();
+        if let Some(scrollback_spill) = self.scrollback_spill.as_mut() {
+            scrollback_spill.clear();
+        }
         self.viewport.clear();
         self.lines_below.clear();
     }
@@ -2385,6 +2827,20 @@ impl Perform for Grid {
                 // TBD - reset text cursor color - currently unimplemented
             },
 
+            // Set current working directory.
+            b"7" => {
+                if let Some(cwd) = parse_osc7_cwd(&params[1..]) {
+                    self.pending_cwd_update = Some(cwd);
+                }
+            },
+
+            // Semantic prompt marks (shell integration)
+            b"133" => {
+                if let Some(mark) = params.get(1).and_then(|p| p.first()) {
+                    self.mark_prompt(*mark);
+                }
+            },
+
             _ => {
                 log::warn!("Unhandled osc: {:?}", params);
             },
@@ -2439,6 +2895,9 @@ impl Perform for Grid {
                 } else if clear_type == 2 {
                     self.set_scroll_region_to_viewport_size();
                     self.fill_viewport(char_to_replace);
+                    // like kitty, we delete the image placements on the cleared screen
+                    self.kitty_graphics_grid
+                        .clear_lines(self.lines_above.len(), self.height);
                 } else if clear_type == 3 {
                     self.clear_lines_above();
                 }
@@ -2473,6 +2932,11 @@ impl Perform for Grid {
                         2004 => {
                             self.bracketed_paste_mode = false;
                         },
+                        2026 => {
+                            // end synchronized update, our output buffer still holds all the
+                            // changes made in the meantime so they will be rendered together
+                            self.synchronized_output_started_at = None;
+                        },
                         1049 => {
                             if let Some(mut alternate_screen_state) =
                                 self.alternate_screen_state.take()
@@ -2488,9 +2952,12 @@ impl Perform for Grid {
                                     &mut self.viewport,
                                     &mut self.cursor,
                                     &mut self.sixel_grid,
+                                    &mut self.kitty_graphics_grid,
+                                    &mut self.scrollback_spill,
                                 );
                             }
                             self.alternate_screen_state = None;
+                            self.alternate_screen_kitty_keyboard_flags.clear();
                             self.clear_viewport_before_rendering = true;
                             self.force_change_size(self.height, self.width); // the alternative_viewport might have been of a different size...
                             self.mark_for_rerender();
@@ -2568,6 +3035,12 @@ impl Perform for Grid {
                         2004 => {
                             self.bracketed_paste_mode = true;
                         },
+                        2026 => {
+                            // begin synchronized update, restarting it only if it timed out
+                            if !self.is_holding_synchronized_output() {
+                                self.synchronized_output_started_at = Some(Instant::now());
+                            }
+                        },
                         1049 => {
                             // enter alternate buffer
                             let current_lines_above = std::mem::replace(
@@ -2585,11 +3058,18 @@ impl Perform for Grid {
                                 &mut self.sixel_grid,
                                 SixelGrid::new(self.character_cell_size.clone(), sixel_image_store),
                             );
+                            // each screen has its own kitty graphics images and placements
+                            let alternate_kitty_graphics_grid =
+                                std::mem::take(&mut self.kitty_graphics_grid);
+                            // the alternate screen does not spill its (short lived) scrollback
+                            let current_scrollback_spill = self.scrollback_spill.take();
                             self.alternate_screen_state = Some(AlternateScreenState::new(
                                 current_lines_above,
                                 current_viewport,
                                 current_cursor,
                                 alternate_sixelgrid,
+                                alternate_kitty_graphics_grid,
+                                current_scrollback_spill,
                             ));
                             self.clear_viewport_before_rendering = true;
                             self.scrollback_buffer_lines =
@@ -2649,6 +3129,26 @@ impl Perform for Grid {
                     }
                 }
             }
+        } else if c == 'p' && intermediates.last() == Some(&b'$') {
+            // DECRQM - request mode
+            let mode = next_param_or(0);
+            let response = if intermediates.get(0) == Some(&b'?') {
+                format!(
+                    "\u{1b}[?{};{}$y",
+                    mode,
+                    self.private_mode_state(mode as u16)
+                )
+            } else {
+                let state = match mode {
+                    4 if self.insert_mode => 1,
+                    20 if self.new_line_mode => 1,
+                    4 | 20 => 2,
+                    _ => 0,
+                };
+                format!("\u{1b}[{};{}$y", mode, state)
+            };
+            self.pending_messages_to_pty
+                .push(response.as_bytes().to_vec());
         } else if c == 'r' {
             if params.len() > 1 {
                 let top = (next_param_or(1) as usize).saturating_sub(1);
@@ -2752,7 +3252,49 @@ impl Perform for Grid {
         } else if c == 's' {
             self.save_cursor_position();
         } else if c == 'u' {
-            self.restore_cursor_position();
+            match intermediates.get(0) {
+                Some(b'>') => {
+                    // push kitty keyboard protocol flags
+                    let flags = next_param_or(0) as u8;
+                    let flags_stack = self.kitty_keyboard_flags_stack_mut();
+                    if flags_stack.len() >= MAX_KITTY_KEYBOARD_FLAGS_STACK_SIZE {
+                        flags_stack.remove(0);
+                    }
+                    flags_stack.push(flags);
+                },
+                Some(b'<') => {
+                    // pop kitty keyboard protocol flags
+                    let count = next_param_or(1);
+                    let flags_stack = self.kitty_keyboard_flags_stack_mut();
+                    let remaining = flags_stack.len().saturating_sub(count);
+                    flags_stack.truncate(remaining);
+                },
+                Some(b'=') => {
+                    // set kitty keyboard protocol flags
+                    let flags = next_param_or(0) as u8;
+                    let mode = next_param_or(1);
+                    let flags_stack = self.kitty_keyboard_flags_stack_mut();
+                    if flags_stack.is_empty() {
+                        flags_stack.push(0);
+                    }
+                    if let Some(current_flags) = flags_stack.last_mut() {
+                        match mode {
+                            2 => *current_flags |= flags,
+                            3 => *current_flags &= !flags,
+                            _ => *current_flags = flags,
+                        }
+                    }
+                },
+                Some(b'?') => {
+                    // query kitty keyboard protocol flags
+                    let text = format!(
+                        "\u{1b}[?{}u",
+                        self.kitty_keyboard_flags() & kitty_keyboard::SUPPORTED_FLAGS
+                    );
+                    self.pending_messages_to_pty.push(text.as_bytes().to_vec());
+                },
+                _ => self.restore_cursor_position(),
+            }
         } else if c == '@' {
             let count = next_param_or(1);
             for _ in 0..count {
@@ -3006,6 +3548,8 @@ pub struct AlternateScreenState {
     viewport: Vec<Row>,
     cursor: Cursor,
     sixel_grid: SixelGrid,
+    kitty_graphics_grid: KittyGraphicsGrid,
+    scrollback_spill: Option<ScrollbackSpill>,
 }
 impl AlternateScreenState {
     pub fn new(
@@ -3013,12 +3557,16 @@ impl AlternateScreenState {
         viewport: Vec<Row>,
         cursor: Cursor,
         sixel_grid: SixelGrid,
+        kitty_graphics_grid: KittyGraphicsGrid,
+        scrollback_spill: Option<ScrollbackSpill>,
     ) -> Self {
         AlternateScreenState {
             lines_above,
             viewport,
             cursor,
             sixel_grid,
+            kitty_graphics_grid,
+            scrollback_spill,
         }
     }
     pub fn apply_contents_to(
@@ -3027,11 +3575,15 @@ impl AlternateScreenState {
         viewport: &mut Vec<Row>,
         cursor: &mut Cursor,
         sixel_grid: &mut SixelGrid,
+        kitty_graphics_grid: &mut KittyGraphicsGrid,
+        scrollback_spill: &mut Option<ScrollbackSpill>,
     ) {
         std::mem::swap(&mut self.lines_above, lines_above);
         std::mem::swap(&mut self.viewport, viewport);
         std::mem::swap(&mut self.cursor, cursor);
         std::mem::swap(&mut self.sixel_grid, sixel_grid);
+        std::mem::swap(&mut self.kitty_graphics_grid, kitty_graphics_grid);
+        std::mem::swap(&mut self.scrollback_spill, scrollback_spill);
     }
 }
 
@@ -3039,9 +3591,32 @@ impl AlternateScreenState {
 pub struct Row {
     pub columns: VecDeque<TerminalCharacter>,
     pub is_canonical: bool,
+    pub prompt_marks: PromptMarks,
     width: Option<usize>,
 }
 
+/// Semantic prompt marks (OSC 133) emitted on this row by a shell with shell-integration enabled
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(crate = "self::serde")]
+pub struct PromptMarks {
+    pub prompt_start: bool,  // OSC 133;A
+    pub command_start: bool, // OSC 133;B
+    pub output_start: bool,  // OSC 133;C
+    pub command_end: bool,   // OSC 133;D
+}
+
+impl PromptMarks {
+    pub fn merge(&mut self, other: PromptMarks) {
+        self.prompt_start |= other.prompt_start;
+        self.command_start |= other.command_start;
+        self.output_start |= other.output_start;
+        self.command_end |= other.command_end;
+    }
+    pub fn ends_command_output(&self) -> bool {
+        self.prompt_start || self.command_end
+    }
+}
+
 impl Debug for Row {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         for character in &self.columns {
@@ -3056,6 +3631,7 @@ impl Row {
         Row {
             columns: VecDeque::with_capacity(width),
             is_canonical: false,
+            prompt_marks: PromptMarks::default(),
             width: None,
         }
     }
@@ -3063,6 +3639,7 @@ impl Row {
         Row {
             columns,
             is_canonical: false,
+            prompt_marks: PromptMarks::default(),
             width: None,
         }
     }
@@ -3073,6 +3650,7 @@ impl Row {
             let mut first_row = rows.remove(0);
             for row in &mut rows {
                 first_row.append(&mut row.columns);
+                first_row.prompt_marks.merge(row.prompt_marks);
             }
             first_row
         }
@@ -3361,6 +3939,9 @@ impl Row {
         if !parts.is_empty() && self.is_canonical {
             parts.get_mut(0).unwrap().is_canonical = true;
         }
+        if let Some(first_part) = parts.get_mut(0) {
+            first_part.prompt_marks = self.prompt_marks;
+        }
         if parts.is_empty() {
             parts.push(self.clone());
         }
@@ -0,0 +1,461 @@
+use std::ops::Range;
+use std::path::PathBuf;
+
+use crate::output::CharacterChunk;
+use crate::panes::selection::Selection;
+use crate::panes::terminal_character::{AnsiCode, CharacterStyles, LinkAnchor, TerminalCharacter};
+use crate::panes::Grid;
+use zellij_utils::data::Style;
+use zellij_utils::errors::prelude::*;
+use zellij_utils::lazy_static::lazy_static;
+use zellij_utils::position::Position;
+use zellij_utils::regex::Regex;
+
+// home row first, so that the labels that are handed out first are the easiest to type
+const HINT_ALPHABET: &str = "asdfghjklqwertyuiopzxcvbnm";
+
+lazy_static! {
+    static ref URL_REGEX: Regex = Regex::new(r#"(?:https?|ftp|file)://[^\s<>"'`]+"#).unwrap();
+    // eg. src/main.rs:10 or ../lib.rs:4:12, the extension keeps us from matching times and ports
+    static ref PATH_REGEX: Regex = Regex::new(
+        r"(?P<path>(?:~|\.{1,2})?/?(?:[\w.@+-]+/)*[\w@+-][\w.@+-]*\.[A-Za-z]\w*):(?P<line>\d+)(?::\d+)?"
+    )
+    .unwrap();
+}
+
+// used when no hint_patterns are configured, earlier patterns win when matches overlap
+const DEFAULT_HINT_PATTERNS: &[&str] = &[
+    // uuids
+    r"\b[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}\b",
+    // git hashes and container ids
+    r"\b[0-9a-f]{7,64}\b",
+    // ipv4 addresses
+    r"\b\d{1,3}(?:\.\d{1,3}){3}\b",
+    // ipv6 addresses, full and compressed
+    r"\b[0-9a-fA-F]{1,4}(?::[0-9a-fA-F]{1,4}){7}\b",
+    r"\b(?:[0-9a-fA-F]{1,4}:)+:(?:[0-9a-fA-F]{1,4}:)*[0-9a-fA-F]{1,4}\b",
+];
+
+/// Compiles the configured hint patterns, or the default ones if there are none. Invalid patterns
+/// are logged and skipped.
+pub fn compile_hint_patterns(hint_patterns: Option<Vec<String>>) -> Vec<Regex> {
+    let hint_patterns = hint_patterns.unwrap_or_else(|| {
+        DEFAULT_HINT_PATTERNS
+            .iter()
+            .map(|hint_pattern| hint_pattern.to_string())
+            .collect()
+    });
+    hint_patterns
+        .iter()
+        .filter_map(|hint_pattern| match Regex::new(hint_pattern) {
+            Ok(regex) => Some(regex),
+            Err(e) => {
+                Err::<(), _>(e)
+                    .with_context(|| format!("invalid hint pattern '{}'", hint_pattern))
+                    .non_fatal();
+                None
+            },
+        })
+        .collect()
+}
+
+/// What a hint points at, and so what happens when its label is typed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HintTarget {
+    Url(String),
+    Path {
+        path: PathBuf,
+        line_number: Option<usize>,
+    },
+    Text(String), // a token matched by one of the hint patterns
+}
+
+/// What to do once the label of a hint has been typed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HintAction {
+    /// Open a url or a path, or copy a token (the label was typed in lower case)
+    Activate(HintTarget),
+    /// Write the text of the hint to the pane (the label was typed in upper case)
+    Write(String),
+}
+
+#[derive(Debug, Clone)]
+struct Hint {
+    label: String,
+    target: HintTarget,
+    text: String,
+    selection: Selection, // where the hint is, relative to the viewport
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Hints {
+    hints: Vec<Hint>,
+    typed_label: String,
+    typed_in_upper_case: bool,
+    patterns: Vec<Regex>, // kept so that the hints can be found again when the pane is resized
+}
+
+impl Hints {
+    pub fn move_up(&mut self, lines: usize) {
+        for hint in self.hints.iter_mut() {
+            hint.selection.move_up(lines);
+        }
+    }
+    pub fn move_down(&mut self, lines: usize) {
+        for hint in self.hints.iter_mut() {
+            hint.selection.move_down(lines);
+        }
+    }
+    fn visible_hints(&self) -> impl Iterator<Item = &Hint> {
+        self.hints
+            .iter()
+            .filter(move |hint| hint.label.starts_with(&self.typed_label))
+    }
+    fn type_character(&mut self, character: char) -> Option<HintAction> {
+        let upper_case = character.is_uppercase();
+        let character = character.to_ascii_lowercase();
+        self.typed_label.push(character);
+        self.typed_in_upper_case |= upper_case;
+        if self.visible_hints().next().is_none() {
+            // a typo, start over with this character
+            self.typed_label = character.to_string();
+            self.typed_in_upper_case = upper_case;
+            if self.visible_hints().next().is_none() {
+                self.typed_label.clear();
+                self.typed_in_upper_case = false;
+            }
+        }
+        let hint = self
+            .hints
+            .iter()
+            .find(|hint| hint.label == self.typed_label)?;
+        let hint_action = if self.typed_in_upper_case {
+            HintAction::Write(hint.text.clone())
+        } else {
+            HintAction::Activate(hint.target.clone())
+        };
+        self.typed_label.clear();
+        self.typed_in_upper_case = false;
+        Some(hint_action)
+    }
+}
+
+// A line of the viewport along with the rows it wrapped into, flattened into a string so that it
+// can be matched against
+#[derive(Debug, Default)]
+struct ViewportLine {
+    text: String,
+    characters: Vec<(usize, Position, usize)>, // byte offset in text, position and width
+    links: Vec<(u16, Range<usize>)>,           // explicit (OSC 8) links and their byte ranges
+}
+
+impl ViewportLine {
+    fn selection(&self, bytes: &Range<usize>) -> Option<Selection> {
+        let first = self
+            .characters
+            .partition_point(|(offset, _, _)| *offset < bytes.start);
+        let last = self
+            .characters
+            .partition_point(|(offset, _, _)| *offset < bytes.end)
+            .checked_sub(1)?;
+        let (_, start, _) = *self.characters.get(first)?;
+        let (_, mut end, width) = *self.characters.get(last)?;
+        end.column.0 += width;
+        let mut selection = Selection::default();
+        selection.start(start);
+        selection.end(end);
+        Some(selection)
+    }
+    fn hint_targets(
+        &self,
+        link_uri: impl Fn(u16) -> Option<String>,
+        patterns: &[Regex],
+    ) -> Vec<(Range<usize>, HintTarget)> {
+        let mut targets: Vec<(Range<usize>, HintTarget)> = vec![];
+        let mut add_target = |bytes: Range<usize>, target: HintTarget| {
+            let overlaps_existing_target = targets
+                .iter()
+                .any(|(existing, _)| existing.start < bytes.end && bytes.start < existing.end);
+            if !overlaps_existing_target && !bytes.is_empty() {
+                targets.push((bytes, target));
+            }
+        };
+        for (link_index, bytes) in &self.links {
+            // explicit links can point anywhere, we only open the ones we would have detected
+            if let Some(uri) = link_uri(*link_index).filter(|uri| is_openable_url(uri)) {
+                add_target(bytes.clone(), HintTarget::Url(uri));
+            }
+        }
+        for url_match in URL_REGEX.find_iter(&self.text) {
+            let url = trim_url(url_match.as_str());
+            let bytes = url_match.start()..url_match.start() + url.len();
+            add_target(bytes, HintTarget::Url(url.to_owned()));
+        }
+        for path_match in PATH_REGEX.captures_iter(&self.text) {
+            if let (Some(whole_match), Some(path)) = (path_match.get(0), path_match.name("path")) {
+                let line_number = path_match
+                    .name("line")
+                    .and_then(|line_number| line_number.as_str().parse().ok());
+                add_target(
+                    whole_match.range(),
+                    HintTarget::Path {
+                        path: PathBuf::from(path.as_str()),
+                        line_number,
+                    },
+                );
+            }
+        }
+        for pattern in patterns {
+            for token_match in pattern.find_iter(&self.text) {
+                add_target(
+                    token_match.range(),
+                    HintTarget::Text(token_match.as_str().to_owned()),
+                );
+            }
+        }
+        targets.sort_by_key(|(bytes, _)| bytes.start);
+        targets
+    }
+}
+
+impl Grid {
+    pub fn enter_hint_mode(&mut self, patterns: &[Regex]) {
+        let hint_targets = self.find_hint_targets(patterns);
+        let labels = hint_labels(hint_targets.len());
+        let hints = hint_targets
+            .into_iter()
+            .zip(labels)
+            .map(|((selection, target, text), label)| Hint {
+                label,
+                target,
+                text,
+                selection,
+            })
+            .collect();
+        self.hints = Some(Hints {
+            hints,
+            typed_label: String::new(),
+            typed_in_upper_case: false,
+            patterns: patterns.to_vec(),
+        });
+        self.output_buffer.update_all_lines();
+        self.mark_for_rerender();
+    }
+    pub fn exit_hint_mode(&mut self) {
+        if self.hints.take().is_some() {
+            self.output_buffer.update_all_lines();
+            self.mark_for_rerender();
+        }
+    }
+    pub fn is_in_hint_mode(&self) -> bool {
+        self.hints.is_some()
+    }
+    /// Returns what to do with the hint whose label was completed by this input, if any
+    pub fn hint_input(&mut self, input: &[u8]) -> Option<HintAction> {
+        let hints = self.hints.as_mut()?;
+        let mut hint_action = None;
+        for character in String::from_utf8_lossy(input).chars() {
+            hint_action = hints.type_character(character);
+            if hint_action.is_some() {
+                break;
+            }
+        }
+        self.output_buffer.update_all_lines();
+        self.mark_for_rerender();
+        hint_action
+    }
+    pub(crate) fn hint_patterns(&self) -> Option<Vec<Regex>> {
+        self.hints.as_ref().map(|hints| hints.patterns.clone())
+    }
+    pub(crate) fn render_hints(
+        &self,
+        character_chunks: &mut [CharacterChunk],
+        content_x: usize,
+        content_y: usize,
+        style: &Style,
+    ) {
+        let hints = match self.hints.as_ref() {
+            Some(hints) => hints,
+            None => return,
+        };
+        let highlight_background = AnsiCode::from(style.colors.green);
+        let label_background = AnsiCode::from(style.colors.orange);
+        let foreground = AnsiCode::from(style.colors.black);
+        let label_styles = CharacterStyles::new()
+            .foreground(Some(foreground))
+            .background(Some(label_background))
+            .bold(Some(AnsiCode::On));
+        for character_chunk in character_chunks.iter_mut() {
+            let line = character_chunk.y.saturating_sub(content_y);
+            for hint in hints.visible_hints() {
+                if !hint.selection.contains_row(line) {
+                    continue;
+                }
+                // the label is drawn over the start of the hint, so only highlight the rest of it
+                let mut highlight = hint.selection;
+                if hint.selection.start.line() == line as isize {
+                    highlight.start.column.0 += hint.label.chars().count();
+                    let label_column = hint
+                        .selection
+                        .start
+                        .column()
+                        .saturating_sub(character_chunk.x.saturating_sub(content_x));
+                    draw_label(
+                        &mut character_chunk.terminal_characters,
+                        label_column,
+                        &hint.label,
+                        label_styles,
+                    );
+                }
+                if !highlight.is_empty() && highlight.start <= highlight.end {
+                    character_chunk.add_selection_and_colors(
+                        highlight,
+                        highlight_background,
+                        Some(foreground),
+                        content_x,
+                        content_y,
+                    );
+                }
+            }
+        }
+    }
+    fn find_hint_targets(&self, patterns: &[Regex]) -> Vec<(Selection, HintTarget, String)> {
+        let link_handler = self.link_handler.borrow();
+        let mut hint_targets = vec![];
+        for viewport_line in self.viewport_lines() {
+            for (bytes, target) in
+                viewport_line.hint_targets(|index| link_handler.uri(index), patterns)
+            {
+                if let Some(selection) = viewport_line.selection(&bytes) {
+                    // urls are written as they are rather than the text of an explicit link
+                    let text = match &target {
+                        HintTarget::Url(url) => url.clone(),
+                        _ => viewport_line.text[bytes].to_owned(),
+                    };
+                    hint_targets.push((selection, target, text));
+                }
+            }
+        }
+        hint_targets
+    }
+    fn viewport_lines(&self) -> Vec<ViewportLine> {
+        let mut viewport_lines: Vec<ViewportLine> = vec![];
+        for (line_index, row) in self.viewport.iter().enumerate() {
+            if row.is_canonical || viewport_lines.is_empty() {
+                viewport_lines.push(ViewportLine::default());
+            }
+            let viewport_line = match viewport_lines.last_mut() {
+                Some(viewport_line) => viewport_line,
+                None => continue,
+            };
+            let mut column = 0;
+            for terminal_character in row.columns.iter() {
+                let offset = viewport_line.text.len();
+                viewport_line.text.push(terminal_character.character);
+                viewport_line.characters.push((
+                    offset,
+                    Position::new(line_index as i32, column as u16),
+                    terminal_character.width,
+                ));
+                if let Some(LinkAnchor::Start(link_index)) = terminal_character.styles.link_anchor {
+                    match viewport_line.links.last_mut() {
+                        Some((index, bytes)) if *index == link_index && bytes.end == offset => {
+                            bytes.end = viewport_line.text.len();
+                        },
+                        _ => viewport_line
+                            .links
+                            .push((link_index, offset..viewport_line.text.len())),
+                    }
+                }
+                column += terminal_character.width;
+            }
+        }
+        viewport_lines
+    }
+}
+
+/// Whether `url` is a whole url of one of the schemes we detect, and so safe to hand to the link
+/// opener (it can't be mistaken for one of its options, for example)
+pub fn is_openable_url(url: &str) -> bool {
+    URL_REGEX
+        .find(url)
+        .map(|url_match| url_match.start() == 0 && url_match.end() == url.len())
+        .unwrap_or(false)
+}
+
+fn hint_labels(count: usize) -> Vec<String> {
+    let alphabet: Vec<char> = HINT_ALPHABET.chars().collect();
+    if count <= alphabet.len() {
+        alphabet.iter().take(count).map(|c| c.to_string()).collect()
+    } else {
+        // all labels have the same length so that none of them is the prefix of another
+        alphabet
+            .iter()
+            .flat_map(|first| {
+                alphabet
+                    .iter()
+                    .map(move |second| format!("{}{}", first, second))
+            })
+            .take(count)
+            .collect()
+    }
+}
+
+// drop trailing punctuation that is more likely to belong to the surrounding text than to the url
+fn trim_url(url: &str) -> &str {
+    let mut url = url;
+    loop {
+        let trimmed = url.trim_end_matches(|c| matches!(c, '.' | ',' | ':' | ';' | '!' | '?'));
+        let trimmed = match trimmed.chars().last() {
+            Some(closing @ (')' | ']' | '}')) => {
+                let opening = match closing {
+                    ')' => '(',
+                    ']' => '[',
+                    _ => '{',
+                };
+                if trimmed.matches(opening).count() < trimmed.matches(closing).count() {
+                    &trimmed[..trimmed.len() - 1]
+                } else {
+                    trimmed
+                }
+            },
+            _ => trimmed,
+        };
+        if trimmed.len() == url.len() {
+            return url;
+        }
+        url = trimmed;
+    }
+}
+
+fn draw_label(
+    terminal_characters: &mut Vec<TerminalCharacter>,
+    label_column: usize,
+    label: &str,
+    label_styles: CharacterStyles,
+) {
+    let mut column = 0;
+    let mut index = 0;
+    while index < terminal_characters.len() && column < label_column {
+        column += terminal_characters[index].width;
+        index += 1;
+    }
+    for character in label.chars() {
+        let mut label_character = TerminalCharacter::new(character);
+        label_character.styles = label_styles;
+        match terminal_characters.get(index).map(|c| c.width) {
+            Some(replaced_width) => {
+                terminal_characters[index] = label_character;
+                // keep the row the same width if we replaced a wide character
+                for _ in 1..replaced_width {
+                    index += 1;
+                    let mut padding = TerminalCharacter::new(' ');
+                    padding.styles = label_styles;
+                    terminal_characters.insert(index, padding);
+                }
+            },
+            None => terminal_characters.push(label_character),
+        }
+        index += 1;
+    }
+}
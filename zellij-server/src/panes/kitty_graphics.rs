@@ -0,0 +1,896 @@
+//! Support for the kitty graphics protocol: https://sw.kovidgoyal.net/kitty/graphics-protocol/
+//!
+//! Images transmitted by the applications running in our panes are kept (in their base64 encoded
+//! form) along with their placements in the pane's scrollback. When rendering, the placements
+//! visible to each client are sent to their terminal, transmitting the images it does not yet
+//! have.
+use crate::output::KittyImageChunk;
+use crate::ClientId;
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use zellij_utils::pane_size::SizeInPixels;
+
+// the ids we give images in the terminal of our clients, these are unique across panes
+static NEXT_IMAGE_ID: AtomicUsize = AtomicUsize::new(1);
+
+const ESC: u8 = 0x1b;
+// we give up on commands bigger than this rather than buffer them indefinitely
+const MAX_COMMAND_LENGTH: usize = 64 * 1024 * 1024;
+// the maximum payload size of one chunk as mandated by the protocol
+const TRANSMISSION_CHUNK_SIZE: usize = 4096;
+// the largest (base64 encoded) image we keep, whether it arrives in chunks or from a file
+const MAX_IMAGE_DATA_SIZE: usize = 64 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ParserState {
+    Ground,
+    Escape,
+    ApcStart,
+    Command,
+    CommandEscape,
+}
+
+impl Default for ParserState {
+    fn default() -> Self {
+        ParserState::Ground
+    }
+}
+
+/// Picks the graphics commands (`ESC _ G ... ESC \`) out of the pty output. This runs alongside
+/// our vte parser, which does not report APC sequences and so ignores them.
+#[derive(Debug, Clone, Default)]
+pub struct KittyGraphicsParser {
+    state: ParserState,
+    buffer: Vec<u8>,
+}
+
+impl KittyGraphicsParser {
+    pub fn advance(&mut self, byte: u8) -> Option<KittyGraphicsCommand> {
+        match self.state {
+            ParserState::Ground => {
+                if byte == ESC {
+                    self.state = ParserState::Escape;
+                }
+            },
+            ParserState::Escape => {
+                self.state = match byte {
+                    b'_' => ParserState::ApcStart,
+                    ESC => ParserState::Escape,
+                    _ => ParserState::Ground,
+                };
+            },
+            ParserState::ApcStart => {
+                self.state = match byte {
+                    b'G' => ParserState::Command,
+                    ESC => ParserState::Escape,
+                    _ => ParserState::Ground,
+                };
+            },
+            ParserState::Command => {
+                if byte == ESC {
+                    self.state = ParserState::CommandEscape;
+                } else if self.buffer.len() < MAX_COMMAND_LENGTH {
+                    self.buffer.push(byte);
+                } else {
+                    log::error!("Kitty graphics command too long, ignoring it");
+                    self.buffer.clear();
+                    self.state = ParserState::Ground;
+                }
+            },
+            ParserState::CommandEscape => {
+                let command = std::mem::take(&mut self.buffer);
+                if byte == b'\\' {
+                    self.state = ParserState::Ground;
+                    return Some(KittyGraphicsCommand::parse(&command));
+                }
+                // an escape that is not a string terminator aborts the command
+                self.state = ParserState::Escape;
+                return self.advance(byte);
+            },
+        }
+        None
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KittyGraphicsCommand {
+    pub action: char,
+    pub quiet: u8,
+    pub format: u32,
+    pub medium: char,
+    pub compression: Option<char>,
+    pub pixel_width: usize,
+    pub pixel_height: usize,
+    pub image_id: u32,
+    pub image_number: u32,
+    pub placement_id: u32,
+    pub source_x: usize,
+    pub source_y: usize,
+    pub source_width: usize,
+    pub source_height: usize,
+    pub columns: usize,
+    pub rows: usize,
+    pub z_index: i32,
+    pub do_not_move_cursor: bool,
+    pub more_chunks: bool,
+    pub delete: char,
+    pub payload: Vec<u8>,
+}
+
+impl Default for KittyGraphicsCommand {
+    fn default() -> Self {
+        KittyGraphicsCommand {
+            action: 't',
+            quiet: 0,
+            format: 32,
+            medium: 'd',
+            compression: None,
+            pixel_width: 0,
+            pixel_height: 0,
+            image_id: 0,
+            image_number: 0,
+            placement_id: 0,
+            source_x: 0,
+            source_y: 0,
+            source_width: 0,
+            source_height: 0,
+            columns: 0,
+            rows: 0,
+            z_index: 0,
+            do_not_move_cursor: false,
+            more_chunks: false,
+            delete: 'a',
+            payload: vec![],
+        }
+    }
+}
+
+impl KittyGraphicsCommand {
+    /// Parses the contents of the APC sequence after the `G`, eg. `a=T,f=100;<payload>`.
+    pub fn parse(bytes: &[u8]) -> Self {
+        let mut command = KittyGraphicsCommand::default();
+        let (control_data, payload) = match bytes.iter().position(|b| *b == b';') {
+            Some(separator) => (&bytes[..separator], &bytes[separator + 1..]),
+            None => (bytes, &[][..]),
+        };
+        command.payload = payload.to_vec();
+        for key_value in control_data.split(|b| *b == b',') {
+            let mut key_value = key_value.splitn(2, |b| *b == b'=');
+            let (key, value) = match (key_value.next(), key_value.next()) {
+                (Some(&[key]), Some(value)) => (key, String::from_utf8_lossy(value)),
+                _ => continue,
+            };
+            let as_char = || value.chars().next();
+            let as_number = || value.parse::<usize>().ok();
+            match key {
+                b'a' => command.action = as_char().unwrap_or(command.action),
+                b'q' => command.quiet = as_number().unwrap_or(0) as u8,
+                b'f' => command.format = as_number().unwrap_or(32) as u32,
+                b't' => command.medium = as_char().unwrap_or(command.medium),
+                b'o' => command.compression = as_char(),
+                b's' => command.pixel_width = as_number().unwrap_or(0),
+                b'v' => command.pixel_height = as_number().unwrap_or(0),
+                b'i' => command.image_id = as_number().unwrap_or(0) as u32,
+                b'I' => command.image_number = as_number().unwrap_or(0) as u32,
+                b'p' => command.placement_id = as_number().unwrap_or(0) as u32,
+                b'x' => command.source_x = as_number().unwrap_or(0),
+                b'y' => command.source_y = as_number().unwrap_or(0),
+                b'w' => command.source_width = as_number().unwrap_or(0),
+                b'h' => command.source_height = as_number().unwrap_or(0),
+                b'c' => command.columns = as_number().unwrap_or(0),
+                b'r' => command.rows = as_number().unwrap_or(0),
+                b'z' => command.z_index = value.parse().unwrap_or(0),
+                b'C' => command.do_not_move_cursor = value == "1",
+                b'm' => command.more_chunks = value == "1",
+                b'd' => command.delete = as_char().unwrap_or(command.delete),
+                _ => {},
+            }
+        }
+        command
+    }
+    fn response(&self, message: &str) -> Option<String> {
+        let is_ok = message == "OK";
+        if self.quiet >= 2 || (self.quiet == 1 && is_ok) {
+            return None;
+        }
+        // applications that do not identify their images do not get a response
+        if self.image_id == 0 && self.image_number == 0 {
+            return None;
+        }
+        let mut keys = vec![];
+        if self.image_id != 0 {
+            keys.push(format!("i={}", self.image_id));
+        }
+        if self.image_number != 0 {
+            keys.push(format!("I={}", self.image_number));
+        }
+        if self.placement_id != 0 {
+            keys.push(format!("p={}", self.placement_id));
+        }
+        Some(format!("\u{1b}_G{};{}\u{1b}\\", keys.join(","), message))
+    }
+    fn image(&self) -> Result<KittyImage, String> {
+        let data = match self.medium {
+            'd' => String::from_utf8_lossy(&self.payload).to_string(),
+            'f' | 't' => base64::encode(read_image_file(&self.file_path()?)?),
+            _ => return Err("EINVAL:unsupported transmission medium".to_owned()),
+        };
+        let (pixel_width, pixel_height) = if self.format == 100 && self.compression.is_none() {
+            png_size(&data).ok_or_else(|| "EINVAL:invalid PNG data".to_owned())?
+        } else {
+            (self.pixel_width, self.pixel_height)
+        };
+        if pixel_width == 0 || pixel_height == 0 {
+            return Err("EINVAL:the image size must be specified".to_owned());
+        }
+        Ok(KittyImage {
+            id: NEXT_IMAGE_ID.fetch_add(1, Ordering::Relaxed),
+            format: self.format,
+            compression: self.compression,
+            pixel_width,
+            pixel_height,
+            data,
+        })
+    }
+    fn file_path(&self) -> Result<PathBuf, String> {
+        let path = base64::decode(&self.payload)
+            .map(|path| PathBuf::from(String::from_utf8_lossy(&path).to_string()))
+            .map_err(|_| "EINVAL:invalid file path".to_owned())?;
+        // symlinks and .. are resolved so that they cannot get us past the checks below
+        let path = std::fs::canonicalize(&path)
+            .map_err(|e| format!("EBADF:failed to read {}: {}", path.display(), e))?;
+        if is_system_path(&path) {
+            return Err(format!("EPERM:{} cannot be read", path.display()));
+        }
+        Ok(path)
+    }
+    /// Deletes the temporary file (`t=t`) the image was read from, once the image was accepted.
+    fn remove_temporary_file(&self) {
+        if self.medium != 't' {
+            return;
+        }
+        // like kitty, we only delete files in the temporary directories, and only if they contain
+        // the string the protocol requires in their name, so that we do not delete arbitrary files
+        if let Ok(path) = self.file_path() {
+            if is_in_temp_dir(&path) && path.to_string_lossy().contains("tty-graphics-protocol") {
+                let _ = std::fs::remove_file(&path);
+            }
+        }
+    }
+}
+
+// the state of the system (eg. /proc/self/environ) is not for the applications to read through us
+fn is_system_path(path: &Path) -> bool {
+    !path.starts_with("/dev/shm")
+        && ["/proc", "/sys", "/dev"]
+            .iter()
+            .any(|d| path.starts_with(d))
+}
+
+fn is_in_temp_dir(path: &Path) -> bool {
+    let temp_dir = std::env::temp_dir();
+    let temp_dir = std::fs::canonicalize(&temp_dir).unwrap_or(temp_dir);
+    path.starts_with(temp_dir) || path.starts_with("/dev/shm")
+}
+
+// only regular files are read, and only up to the size of the largest image we keep - reading
+// eg. a fifo or a device file could otherwise block us or exhaust our memory
+fn read_image_file(path: &Path) -> Result<Vec<u8>, String> {
+    let read_error = |e: std::io::Error| format!("EBADF:failed to read {}: {}", path.display(), e);
+    let file = File::open(path).map_err(read_error)?;
+    let metadata = file.metadata().map_err(read_error)?;
+    if !metadata.is_file() {
+        return Err(format!("EBADF:{} is not a regular file", path.display()));
+    }
+    let max_file_size = (MAX_IMAGE_DATA_SIZE / 4 * 3) as u64;
+    if metadata.len() > max_file_size {
+        return Err(format!("EFBIG:{} is too big", path.display()));
+    }
+    let mut file_contents = vec![];
+    file.take(max_file_size + 1)
+        .read_to_end(&mut file_contents)
+        .map_err(read_error)?;
+    if file_contents.len() as u64 > max_file_size {
+        // the file grew since we looked at it
+        return Err(format!("EFBIG:{} is too big", path.display()));
+    }
+    Ok(file_contents)
+}
+
+// reads the image size from the IHDR chunk of the base64 encoded PNG
+fn png_size(data: &str) -> Option<(usize, usize)> {
+    let header = base64::decode(data.get(..32)?).ok()?;
+    let size = |bytes: &[u8]| bytes.iter().fold(0, |size, b| (size << 8) + *b as usize);
+    if header.get(1..4)? != b"PNG" || header.get(12..16)? != b"IHDR" {
+        return None;
+    }
+    Some((size(header.get(16..20)?), size(header.get(20..24)?)))
+}
+
+#[derive(Debug, Clone)]
+pub struct KittyImage {
+    pub id: usize,
+    pub format: u32,
+    pub compression: Option<char>,
+    pub pixel_width: usize,
+    pub pixel_height: usize,
+    pub data: String, // base64
+}
+
+impl PartialEq for KittyImage {
+    fn eq(&self, other: &Self) -> bool {
+        // ids are unique, this saves us from comparing the data
+        self.id == other.id
+    }
+}
+
+impl KittyImage {
+    pub fn serialize_transmission(&self) -> String {
+        let mut vte = String::new();
+        let compression = self
+            .compression
+            .map(|c| format!(",o={}", c))
+            .unwrap_or_default();
+        let mut chunks = self
+            .data
+            .as_bytes()
+            .chunks(TRANSMISSION_CHUNK_SIZE)
+            .peekable();
+        let mut is_first_chunk = true;
+        loop {
+            let chunk = String::from_utf8_lossy(chunks.next().unwrap_or(&[]));
+            let more_chunks = chunks.peek().is_some();
+            if is_first_chunk {
+                let _ = write!(
+                    vte,
+                    "\u{1b}_Ga=t,t=d,i={},f={},s={},v={}{},q=2,m={};{}\u{1b}\\",
+                    self.id,
+                    self.format,
+                    self.pixel_width,
+                    self.pixel_height,
+                    compression,
+                    u8::from(more_chunks),
+                    chunk
+                );
+                is_first_chunk = false;
+            } else {
+                let _ = write!(vte, "\u{1b}_Gm={};{}\u{1b}\\", u8::from(more_chunks), chunk);
+            }
+            if !more_chunks {
+                break;
+            }
+        }
+        vte
+    }
+}
+
+/// Given a part of a placement `cell_offset` cells into it and `cell_count` cells long, returns
+/// the part of the image it displays (as a pixel offset and length).
+pub fn crop_image_pixels(
+    cell_offset: usize,
+    cell_count: usize,
+    image_pixel_offset: usize,
+    image_pixel_length: usize,
+    pixels_per_cell: f64,
+) -> (usize, usize) {
+    let start = std::cmp::min(
+        (cell_offset as f64 * pixels_per_cell).round() as usize,
+        image_pixel_length,
+    );
+    let end = std::cmp::min(
+        ((cell_offset + cell_count) as f64 * pixels_per_cell).round() as usize,
+        image_pixel_length,
+    );
+    (image_pixel_offset + start, end.saturating_sub(start))
+}
+
+#[derive(Debug, Clone)]
+struct KittyPlacement {
+    image: Rc<KittyImage>,
+    image_id: u32, // as given by the application, 0 if it did not give one
+    placement_id: u32,
+    line: isize, // from the top of the scrollback, negative if the top scrolled off
+    column: usize,
+    columns: usize,
+    rows: usize,
+    pixel_x: usize,
+    pixel_y: usize,
+    pixel_width: usize,
+    pixel_height: usize,
+    pixels_per_column: f64,
+    pixels_per_row: f64,
+    is_scaled: bool,
+    z_index: i32,
+}
+
+impl KittyPlacement {
+    fn contains_cell(&self, line: isize, column: usize) -> bool {
+        self.contains_line(line) && self.contains_column(column)
+    }
+    fn contains_line(&self, line: isize) -> bool {
+        line >= self.line && line < self.line + self.rows as isize
+    }
+    fn contains_column(&self, column: usize) -> bool {
+        column >= self.column && column < self.column + self.columns
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct KittyGraphicsCommandResult {
+    pub response: Option<String>,
+    pub placement_size: Option<(usize, usize)>, // columns, rows - if the cursor should be moved
+    pub placements_changed: bool,
+}
+
+impl KittyGraphicsCommandResult {
+    fn response(command: &KittyGraphicsCommand, message: &str) -> Self {
+        KittyGraphicsCommandResult {
+            response: command.response(message),
+            ..Default::default()
+        }
+    }
+}
+
+/// The images and placements of one screen (main or alternate) of a pane
+#[derive(Debug, Clone, Default)]
+pub struct KittyGraphicsGrid {
+    images: HashMap<u32, Rc<KittyImage>>, // by the id the application gave them
+    image_numbers: HashMap<u32, u32>,     // image number => image id
+    placements: Vec<KittyPlacement>,
+    pending_transmission: Option<KittyGraphicsCommand>,
+    pending_transmission_is_too_big: bool,
+}
+
+impl KittyGraphicsGrid {
+    pub fn handle_command(
+        &mut self,
+        command: KittyGraphicsCommand,
+        scrollback_size_in_lines: usize,
+        cursor_x: usize,
+        cursor_y: usize,
+        character_cell_size: Option<SizeInPixels>,
+    ) -> KittyGraphicsCommandResult {
+        let cursor_line = (scrollback_size_in_lines + cursor_y) as isize;
+        let command = match self.pending_transmission.take() {
+            Some(mut pending_transmission) => {
+                // the rest of a chunked transmission only carries the payload
+                pending_transmission.more_chunks = command.more_chunks;
+                if !self.pending_transmission_is_too_big {
+                    if pending_transmission.payload.len() + command.payload.len()
+                        > MAX_IMAGE_DATA_SIZE
+                    {
+                        // we drop what we have, the rest of the chunks are dropped as they come
+                        self.pending_transmission_is_too_big = true;
+                        pending_transmission.payload = vec![];
+                    } else {
+                        pending_transmission.payload.extend(command.payload);
+                    }
+                }
+                pending_transmission
+            },
+            None => command,
+        };
+        if command.more_chunks {
+            self.pending_transmission = Some(command);
+            return KittyGraphicsCommandResult::default();
+        }
+        if std::mem::replace(&mut self.pending_transmission_is_too_big, false) {
+            return KittyGraphicsCommandResult::response(&command, "EFBIG:the image is too big");
+        }
+        match command.action {
+            't' | 'T' => self.transmit(command, cursor_line, cursor_x, character_cell_size),
+            'p' => {
+                let image = self.image_for_command(&command);
+                match image {
+                    Some(image) => {
+                        self.place(&command, image, cursor_line, cursor_x, character_cell_size)
+                    },
+                    None => {
+                        KittyGraphicsCommandResult::response(&command, "ENOENT:image not found")
+                    },
+                }
+            },
+            'q' => match command.image() {
+                Ok(_) => {
+                    command.remove_temporary_file();
+                    KittyGraphicsCommandResult::response(&command, "OK")
+                },
+                Err(e) => KittyGraphicsCommandResult::response(&command, &e),
+            },
+            'd' => self.delete(&command, scrollback_size_in_lines, cursor_line, cursor_x),
+            _ => {
+                log::warn!("Unsupported kitty graphics action: {}", command.action);
+                KittyGraphicsCommandResult::response(&command, "EINVAL:unsupported action")
+            },
+        }
+    }
+    fn transmit(
+        &mut self,
+        mut command: KittyGraphicsCommand,
+        cursor_line: isize,
+        cursor_column: usize,
+        character_cell_size: Option<SizeInPixels>,
+    ) -> KittyGraphicsCommandResult {
+        let image = match command.image() {
+            Ok(image) => Rc::new(image),
+            Err(e) => return KittyGraphicsCommandResult::response(&command, &e),
+        };
+        command.remove_temporary_file();
+        if command.image_number != 0 && command.image_id == 0 {
+            command.image_id = self.next_free_image_id();
+            self.image_numbers
+                .insert(command.image_number, command.image_id);
+        }
+        if command.image_id != 0 {
+            // placements of the image being replaced are deleted with it
+            self.placements
+                .retain(|placement| placement.image_id != command.image_id);
+            self.images.insert(command.image_id, image.clone());
+        }
+        if command.action == 'T' {
+            self.place(
+                &command,
+                image,
+                cursor_line,
+                cursor_column,
+                character_cell_size,
+            )
+        } else {
+            KittyGraphicsCommandResult::response(&command, "OK")
+        }
+    }
+    fn place(
+        &mut self,
+        command: &KittyGraphicsCommand,
+        image: Rc<KittyImage>,
+        cursor_line: isize,
+        cursor_column: usize,
+        character_cell_size: Option<SizeInPixels>,
+    ) -> KittyGraphicsCommandResult {
+        let pixel_x = std::cmp::min(command.source_x, image.pixel_width);
+        let pixel_y = std::cmp::min(command.source_y, image.pixel_height);
+        let pixel_width = match command.source_width {
+            0 => image.pixel_width - pixel_x,
+            width => std::cmp::min(width, image.pixel_width - pixel_x),
+        };
+        let pixel_height = match command.source_height {
+            0 => image.pixel_height - pixel_y,
+            height => std::cmp::min(height, image.pixel_height - pixel_y),
+        };
+        if pixel_width == 0 || pixel_height == 0 {
+            return KittyGraphicsCommandResult::response(command, "EINVAL:empty source rectangle");
+        }
+        let cells = |pixels: usize, cell_pixels: usize| {
+            (pixels as f64 / cell_pixels as f64).ceil() as usize
+        };
+        // if only one of the dimensions is given, the other one keeps the aspect ratio
+        let (columns, rows) = match (command.columns, command.rows, character_cell_size) {
+            (columns, rows, _) if columns > 0 && rows > 0 => (columns, rows),
+            (columns, _, Some(cell_size)) if columns > 0 => (
+                columns,
+                cells(
+                    columns * cell_size.width * pixel_height / pixel_width,
+                    cell_size.height,
+                ),
+            ),
+            (_, rows, Some(cell_size)) if rows > 0 => (
+                cells(
+                    rows * cell_size.height * pixel_width / pixel_height,
+                    cell_size.width,
+                ),
+                rows,
+            ),
+            (_, _, Some(cell_size)) => (
+                cells(pixel_width, cell_size.width),
+                cells(pixel_height, cell_size.height),
+            ),
+            // we can't reliably display images at their own size without knowing the size of
+            // the cells
+            (_, _, None) => {
+                return KittyGraphicsCommandResult::response(
+                    command,
+                    "EINVAL:unknown character cell size",
+                );
+            },
+        };
+        let is_scaled = command.columns > 0 || command.rows > 0;
+        let (pixels_per_column, pixels_per_row) = match character_cell_size {
+            Some(cell_size) if !is_scaled => (cell_size.width as f64, cell_size.height as f64),
+            _ => (
+                pixel_width as f64 / columns as f64,
+                pixel_height as f64 / rows as f64,
+            ),
+        };
+        if command.placement_id != 0 {
+            self.placements.retain(|placement| {
+                placement.image_id != command.image_id
+                    || placement.placement_id != command.placement_id
+            });
+        }
+        self.placements.push(KittyPlacement {
+            image,
+            image_id: command.image_id,
+            placement_id: command.placement_id,
+            line: cursor_line,
+            column: cursor_column,
+            columns,
+            rows,
+            pixel_x,
+            pixel_y,
+            pixel_width,
+            pixel_height,
+            pixels_per_column,
+            pixels_per_row,
+            is_scaled,
+            z_index: command.z_index,
+        });
+        KittyGraphicsCommandResult {
+            response: command.response("OK"),
+            placement_size: if command.do_not_move_cursor {
+                None
+            } else {
+                Some((columns, rows))
+            },
+            placements_changed: true,
+        }
+    }
+    fn delete(
+        &mut self,
+        command: &KittyGraphicsCommand,
+        scrollback_size_in_lines: usize,
+        cursor_line: isize,
+        cursor_column: usize,
+    ) -> KittyGraphicsCommandResult {
+        let image_id = match command.delete.to_ascii_lowercase() {
+            'n' => self
+                .image_numbers
+                .get(&command.image_number)
+                .copied()
+                .unwrap_or(0),
+            _ => command.image_id,
+        };
+        // for the position based deletions, x and y are 1 based cell coordinates
+        let column = command.source_x.saturating_sub(1);
+        let line = (scrollback_size_in_lines + command.source_y.saturating_sub(1)) as isize;
+        let placement_count = self.placements.len();
+        let mut deleted_image_ids = HashSet::new();
+        if command.delete == 'I' || command.delete == 'N' {
+            // the image is deleted even if it was never placed
+            deleted_image_ids.insert(image_id);
+        }
+        self.placements.retain(|placement| {
+            let should_delete = match command.delete.to_ascii_lowercase() {
+                'a' => true,
+                'i' | 'n' => {
+                    image_id != 0
+                        && placement.image_id == image_id
+                        && (command.placement_id == 0
+                            || placement.placement_id == command.placement_id)
+                },
+                'c' => placement.contains_cell(cursor_line, cursor_column),
+                'p' => placement.contains_cell(line, column),
+                'x' => placement.contains_column(column),
+                'y' => placement.contains_line(line),
+                'z' => placement.z_index == command.z_index,
+                _ => false,
+            };
+            if should_delete {
+                deleted_image_ids.insert(placement.image_id);
+            }
+            !should_delete
+        });
+        if command.delete == 'A' {
+            self.images.clear();
+            self.image_numbers.clear();
+        } else if command.delete.is_ascii_uppercase() {
+            // the upper case variants also delete the image data unless it is still displayed
+            for image_id in deleted_image_ids {
+                if !self
+                    .placements
+                    .iter()
+                    .any(|placement| placement.image_id == image_id)
+                {
+                    self.images.remove(&image_id);
+                    self.image_numbers.retain(|_, id| *id != image_id);
+                }
+            }
+        }
+        KittyGraphicsCommandResult {
+            placements_changed: placement_count != self.placements.len(),
+            ..Default::default()
+        }
+    }
+    fn image_for_command(&self, command: &KittyGraphicsCommand) -> Option<Rc<KittyImage>> {
+        let image_id = if command.image_id == 0 {
+            *self.image_numbers.get(&command.image_number)?
+        } else {
+            command.image_id
+        };
+        self.images.get(&image_id).cloned()
+    }
+    fn next_free_image_id(&self) -> u32 {
+        let mut image_id = self.images.len() as u32 + 1;
+        while self.images.contains_key(&image_id) {
+            image_id += 1;
+        }
+        image_id
+    }
+    pub fn offset_grid_top(&mut self) {
+        for placement in self.placements.iter_mut() {
+            placement.line -= 1;
+        }
+        self.placements
+            .retain(|placement| placement.line + placement.rows as isize > 0);
+    }
+    /// Moves the placements down to make room for lines added above the top of the scrollback
+    pub fn offset_grid_bottom(&mut self, line_count: usize) {
+        for placement in self.placements.iter_mut() {
+            placement.line += line_count as isize;
+        }
+    }
+    /// Deletes the placements on the given lines (counted from the top of the scrollback)
+    pub fn clear_lines(&mut self, first_line: usize, line_count: usize) -> bool {
+        let placement_count = self.placements.len();
+        let first_line = first_line as isize;
+        let last_line = first_line + line_count as isize;
+        self.placements.retain(|placement| {
+            placement.line + placement.rows as isize <= first_line || placement.line >= last_line
+        });
+        placement_count != self.placements.len()
+    }
+    pub fn clear(&mut self) {
+        self.images.clear();
+        self.image_numbers.clear();
+        self.placements.clear();
+        self.pending_transmission = None;
+        self.pending_transmission_is_too_big = false;
+    }
+    pub fn image_chunks_in_viewport(
+        &self,
+        scrollback_size_in_lines: usize,
+        viewport_height: usize,
+        viewport_width: usize,
+        viewport_x_offset: usize,
+        viewport_y_offset: usize,
+    ) -> Vec<KittyImageChunk> {
+        let viewport_top = scrollback_size_in_lines as isize;
+        let viewport_bottom = viewport_top + viewport_height as isize;
+        let mut placements: Vec<&KittyPlacement> = self.placements.iter().collect();
+        // stable, so that placements with the same z-index keep their order
+        placements.sort_by_key(|placement| placement.z_index);
+        placements
+            .into_iter()
+            .filter_map(|placement| {
+                let top = std::cmp::max(placement.line, viewport_top);
+                let bottom =
+                    std::cmp::min(placement.line + placement.rows as isize, viewport_bottom);
+                let right = std::cmp::min(placement.column + placement.columns, viewport_width);
+                if top >= bottom || placement.column >= right {
+                    return None;
+                }
+                let rows = (bottom - top) as usize;
+                let columns = right - placement.column;
+                let (image_pixel_y, image_pixel_height) = crop_image_pixels(
+                    (top - placement.line) as usize,
+                    rows,
+                    placement.pixel_y,
+                    placement.pixel_height,
+                    placement.pixels_per_row,
+                );
+                let (image_pixel_x, image_pixel_width) = crop_image_pixels(
+                    0,
+                    columns,
+                    placement.pixel_x,
+                    placement.pixel_width,
+                    placement.pixels_per_column,
+                );
+                if image_pixel_width == 0 || image_pixel_height == 0 {
+                    return None;
+                }
+                Some(KittyImageChunk {
+                    cell_x: viewport_x_offset + placement.column,
+                    cell_y: viewport_y_offset + (top - viewport_top) as usize,
+                    columns,
+                    rows,
+                    image_pixel_x,
+                    image_pixel_y,
+                    image_pixel_width,
+                    image_pixel_height,
+                    pixels_per_column: placement.pixels_per_column,
+                    pixels_per_row: placement.pixels_per_row,
+                    is_scaled: placement.is_scaled,
+                    z_index: placement.z_index,
+                    image: placement.image.clone(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Keeps track of the images and placements we sent to the terminal of each client
+#[derive(Debug, Clone, Default)]
+pub struct KittyImageStore {
+    transmitted_images: HashMap<ClientId, HashSet<usize>>,
+    placements: HashMap<ClientId, Vec<KittyImageChunk>>,
+}
+
+impl KittyImageStore {
+    /// Returns the instructions that bring the terminal of this client up to date with the
+    /// placements it should display, or None if it already displays them. `display_was_cleared`
+    /// means the terminal deleted the placements it had.
+    pub fn serialize_placements(
+        &mut self,
+        client_id: ClientId,
+        image_chunks: Vec<KittyImageChunk>,
+        display_was_cleared: bool,
+    ) -> Option<String> {
+        let placements_changed = match self.placements.get(&client_id) {
+            Some(previous_placements) => previous_placements != &image_chunks,
+            None => !image_chunks.is_empty(),
+        };
+        if !placements_changed && !(display_was_cleared && !image_chunks.is_empty()) {
+            return None;
+        }
+        let mut vte = String::new();
+        let transmitted_images = self.transmitted_images.entry(client_id).or_default();
+        let displayed_images: HashSet<usize> = image_chunks.iter().map(|c| c.image.id).collect();
+        // images that are no longer displayed are freed from the terminal's memory, if they
+        // are displayed again we transmit them again
+        transmitted_images.retain(|image_id| {
+            let is_displayed = displayed_images.contains(image_id);
+            if !is_displayed {
+                let _ = write!(vte, "\u{1b}_Ga=d,d=I,i={},q=2\u{1b}\\", image_id);
+            }
+            is_displayed
+        });
+        for image_chunk in &image_chunks {
+            if transmitted_images.insert(image_chunk.image.id) {
+                vte.push_str(&image_chunk.image.serialize_transmission());
+            }
+        }
+        vte.push_str("\u{1b}_Ga=d,d=a,q=2\u{1b}\\");
+        for (index, image_chunk) in image_chunks.iter().enumerate() {
+            let _ = write!(
+                vte,
+                "\u{1b}[{};{}H",
+                image_chunk.cell_y + 1,
+                image_chunk.cell_x + 1
+            );
+            let size_in_cells = if image_chunk.is_scaled {
+                format!(",c={},r={}", image_chunk.columns, image_chunk.rows)
+            } else {
+                String::new()
+            };
+            let _ = write!(
+                vte,
+                "\u{1b}_Ga=p,i={},p={},x={},y={},w={},h={}{},z={},C=1,q=2\u{1b}\\",
+                image_chunk.image.id,
+                index + 1,
+                image_chunk.image_pixel_x,
+                image_chunk.image_pixel_y,
+                image_chunk.image_pixel_width,
+                image_chunk.image_pixel_height,
+                size_in_cells,
+                image_chunk.z_index,
+            );
+        }
+        self.placements.insert(client_id, image_chunks);
+        let save_cursor_position = "\u{1b}[s";
+        let restore_cursor_position = "\u{1b}[u";
+        Some(format!(
+            "{}{}{}",
+            save_cursor_position, vte, restore_cursor_position
+        ))
+    }
+    pub fn remove_client(&mut self, client_id: ClientId) {
+        self.transmitted_images.remove(&client_id);
+        self.placements.remove(&client_id);
+    }
+}
@@ -49,6 +49,10 @@ impl LinkHandler {
         }
     }
 
+    pub fn uri(&self, index: u16) -> Option<String> {
+        self.links.get(&index).map(|link| link.uri.clone())
+    }
+
     pub fn output_osc8(&self, link_anchor: Option<LinkAnchor>) -> Option<String> {
         link_anchor.and_then(|link| match link {
             LinkAnchor::Start(index) => {
@@ -1,12 +1,16 @@
 pub mod alacritty_functions;
 pub mod grid;
+pub mod kitty_graphics;
 pub mod link_handler;
+pub mod scrollback_spill;
 pub mod selection;
 pub mod sixel;
 pub mod terminal_character;
 
 mod active_panes;
+mod copy_mode;
 mod floating_panes;
+mod hints;
 mod plugin_pane;
 mod search;
 mod terminal_pane;
@@ -16,8 +20,11 @@ pub use active_panes::*;
 pub use alacritty_functions::*;
 pub use floating_panes::*;
 pub use grid::*;
+pub use hints::*;
+pub use kitty_graphics::*;
 pub use link_handler::*;
 pub(crate) use plugin_pane::*;
+pub use scrollback_spill::*;
 pub use sixel::*;
 pub(crate) use terminal_character::*;
 pub use terminal_pane::*;
@@ -533,9 +533,27 @@ impl Pane for PluginPane {
     fn invoked_with(&self) -> &Option<Run> {
         &self.invoked_with
     }
+    fn custom_title(&self) -> Option<String> {
+        if self.pane_name.is_empty() {
+            None
+        } else {
+            Some(self.pane_name.clone())
+        }
+    }
+    fn current_title(&self) -> String {
+        if self.pane_name.is_empty() {
+            self.pane_title.clone()
+        } else {
+            self.pane_name.clone()
+        }
+    }
     fn set_title(&mut self, title: String) {
         self.pane_title = title;
     }
+    fn rename(&mut self, name: String) {
+        self.pane_name = name;
+        self.set_should_render(true);
+    }
     fn update_loading_indication(&mut self, loading_indication: LoadingIndication) {
         if self.loading_indication.ended && !loading_indication.is_error() {
             return;
@@ -0,0 +1,318 @@
+//! An optional disk-backed tier for the scrollback buffer.
+//!
+//! Rows evicted from the top of the in-memory scrollback are gathered into blocks, compressed and
+//! appended to a spill file in the cache directory. Once the in-memory scrollback runs out,
+//! scrolling (and searching) pages these blocks back in, newest first.
+
+use std::fs::{self, DirBuilder, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
+
+use miniz_oxide::{deflate::compress_to_vec, inflate::decompress_to_vec};
+use uuid::Uuid;
+use zellij_utils::{consts::ZELLIJ_CACHE_DIR, errors::prelude::*, serde};
+
+use serde::{Deserialize, Serialize};
+
+use crate::panes::grid::{calculate_row_display_height, PromptMarks, Row};
+use crate::panes::terminal_character::{CharacterStyles, TerminalCharacter};
+
+pub const SPILL_BLOCK_SIZE: usize = 1000;
+const COMPRESSION_LEVEL: u8 = 6;
+// the spilled rows are whatever was printed in the pane, so only its user may read them
+const SPILL_DIR_MODE: u32 = 0o700;
+const SPILL_FILE_MODE: u32 = 0o600;
+
+/// Consecutive characters of a row that share their styles and width
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "self::serde")]
+struct SpilledSegment {
+    styles: CharacterStyles,
+    character_width: usize,
+    text: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "self::serde")]
+struct SpilledRow {
+    segments: Vec<SpilledSegment>,
+    is_canonical: bool,
+    prompt_marks: PromptMarks,
+}
+
+impl From<&Row> for SpilledRow {
+    fn from(row: &Row) -> Self {
+        let mut segments: Vec<SpilledSegment> = vec![];
+        for terminal_character in &row.columns {
+            match segments.last_mut() {
+                Some(segment)
+                    if segment.styles == terminal_character.styles
+                        && segment.character_width == terminal_character.width =>
+                {
+                    segment.text.push(terminal_character.character);
+                },
+                _ => segments.push(SpilledSegment {
+                    styles: terminal_character.styles,
+                    character_width: terminal_character.width,
+                    text: terminal_character.character.to_string(),
+                }),
+            }
+        }
+        SpilledRow {
+            segments,
+            is_canonical: row.is_canonical,
+            prompt_marks: row.prompt_marks,
+        }
+    }
+}
+
+impl From<SpilledRow> for Row {
+    fn from(spilled_row: SpilledRow) -> Self {
+        let columns = spilled_row
+            .segments
+            .iter()
+            .flat_map(|segment| {
+                segment.text.chars().map(|character| TerminalCharacter {
+                    character,
+                    styles: segment.styles,
+                    width: segment.character_width,
+                })
+            })
+            .collect();
+        let mut row = Row::from_columns(columns);
+        row.is_canonical = spilled_row.is_canonical;
+        row.prompt_marks = spilled_row.prompt_marks;
+        row
+    }
+}
+
+#[derive(Debug, Clone)]
+struct SpilledBlock {
+    offset: u64,
+    length: usize,
+    // kept in memory so that the display height of the spilled rows can be recalculated when the
+    // pane is resized without reading them back
+    row_widths: Vec<u32>,
+}
+
+pub struct ScrollbackSpill {
+    path: PathBuf,
+    file: Option<File>, // created lazily, when the first block is written
+    blocks: Vec<SpilledBlock>,
+    pending_rows: Vec<Row>,
+    block_size: usize,
+}
+
+impl ScrollbackSpill {
+    pub fn new(block_size: usize) -> Self {
+        ScrollbackSpill::new_in(ZELLIJ_CACHE_DIR.join("scrollback"), block_size)
+    }
+    /// A spill whose file is created in the given directory
+    pub fn new_in(spill_dir: PathBuf, block_size: usize) -> Self {
+        ScrollbackSpill {
+            path: spill_dir.join(format!("{}", Uuid::new_v4())),
+            file: None,
+            blocks: vec![],
+            pending_rows: vec![],
+            block_size: std::cmp::max(block_size, 1),
+        }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty() && self.pending_rows.is_empty()
+    }
+    /// The number of spilled rows
+    pub fn len(&self) -> usize {
+        self.blocks
+            .iter()
+            .map(|block| block.row_widths.len())
+            .sum::<usize>()
+            + self.pending_rows.len()
+    }
+    /// The number of lines the spilled rows would take up when wrapped to the given width
+    pub fn display_height(&self, viewport_width: usize) -> usize {
+        self.blocks
+            .iter()
+            .flat_map(|block| block.row_widths.iter())
+            .map(|row_width| calculate_row_display_height(*row_width as usize, viewport_width))
+            .chain(
+                self.pending_rows
+                    .iter()
+                    .map(|row| calculate_row_display_height(row.width(), viewport_width)),
+            )
+            .sum()
+    }
+    /// Adds a row below all other spilled rows
+    pub fn push(&mut self, row: Row) -> Result<()> {
+        self.pending_rows.push(row);
+        if self.pending_rows.len() >= self.block_size {
+            self.flush_pending_rows()
+        } else {
+            Ok(())
+        }
+    }
+    /// Removes and returns the newest spilled rows (in order, top to bottom), at most one block's
+    /// worth of them
+    pub fn pop_rows(&mut self) -> Result<Vec<Row>> {
+        if !self.pending_rows.is_empty() {
+            return Ok(self.pending_rows.drain(..).collect());
+        }
+        match self.blocks.pop() {
+            Some(block) => {
+                let path = self.path.clone();
+                let err_context = || format!("failed to page in scrollback from {:?}", path);
+
+                let rows = self.read_block(&block).with_context(err_context)?;
+                self.file()
+                    .and_then(|file| Ok(file.set_len(block.offset)?))
+                    .with_context(err_context)?;
+                Ok(rows)
+            },
+            None => Ok(vec![]),
+        }
+    }
+    /// All spilled rows, top to bottom, leaving them in place
+    pub fn rows(&mut self) -> Result<Vec<Row>> {
+        let mut rows = vec![];
+        for block in self.blocks.clone() {
+            rows.append(&mut self.read_block(&block)?);
+        }
+        rows.extend(self.pending_rows.iter().cloned());
+        Ok(rows)
+    }
+    /// The index (from the top) of the first spilled row matching the predicate, reading the
+    /// blocks one at a time rather than paging them back in
+    pub fn position_of_first_row(
+        &mut self,
+        mut predicate: impl FnMut(&Row) -> bool,
+    ) -> Result<Option<usize>> {
+        let mut rows_before_block = 0;
+        for block in self.blocks.clone() {
+            let rows = self.read_block(&block)?;
+            if let Some(index) = rows.iter().position(&mut predicate) {
+                return Ok(Some(rows_before_block + index));
+            }
+            rows_before_block += rows.len();
+        }
+        Ok(self
+            .pending_rows
+            .iter()
+            .position(predicate)
+            .map(|index| rows_before_block + index))
+    }
+    /// The index (from the top) of the last spilled row matching the predicate, reading the
+    /// blocks one at a time (newest first) rather than paging them back in
+    pub fn position_of_last_row(
+        &mut self,
+        mut predicate: impl FnMut(&Row) -> bool,
+    ) -> Result<Option<usize>> {
+        let mut rows_up_to_block = self.len() - self.pending_rows.len();
+        if let Some(index) = self.pending_rows.iter().rposition(&mut predicate) {
+            return Ok(Some(rows_up_to_block + index));
+        }
+        for block in self.blocks.clone().iter().rev() {
+            rows_up_to_block -= block.row_widths.len();
+            let rows = self.read_block(block)?;
+            if let Some(index) = rows.iter().rposition(&mut predicate) {
+                return Ok(Some(rows_up_to_block + index));
+            }
+        }
+        Ok(None)
+    }
+    pub fn clear(&mut self) {
+        self.blocks.clear();
+        self.pending_rows.clear();
+        if self.file.take().is_some() {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+    fn flush_pending_rows(&mut self) -> Result<()> {
+        let path = self.path.clone();
+        let err_context = || format!("failed to spill scrollback to {:?}", path);
+
+        let rows: Vec<Row> = self.pending_rows.drain(..).collect();
+        let row_widths = rows.iter().map(|row| row.width() as u32).collect();
+        let spilled_rows: Vec<SpilledRow> = rows.iter().map(SpilledRow::from).collect();
+        let serialized = serde_json::to_vec(&spilled_rows).with_context(err_context)?;
+        let compressed = compress_to_vec(&serialized, COMPRESSION_LEVEL);
+        let offset = self
+            .blocks
+            .last()
+            .map(|block| block.offset + block.length as u64)
+            .unwrap_or(0);
+        let file = self.file().with_context(err_context)?;
+        file.seek(SeekFrom::Start(offset))
+            .and_then(|_| file.write_all(&compressed))
+            .with_context(err_context)?;
+        self.blocks.push(SpilledBlock {
+            offset,
+            length: compressed.len(),
+            row_widths,
+        });
+        Ok(())
+    }
+    fn read_block(&mut self, block: &SpilledBlock) -> Result<Vec<Row>> {
+        let mut compressed = vec![0; block.length];
+        let file = self.file()?;
+        file.seek(SeekFrom::Start(block.offset))?;
+        file.read_exact(&mut compressed)?;
+        let serialized = decompress_to_vec(&compressed)
+            .map_err(|e| anyhow!("failed to decompress scrollback block: {:?}", e))?;
+        let spilled_rows: Vec<SpilledRow> = serde_json::from_slice(&serialized)?;
+        Ok(spilled_rows.into_iter().map(Row::from).collect())
+    }
+    // the spill file is copied rather than shared, so that each copy can page rows back in (and
+    // truncate its file) independently
+    fn copy_blocks_from(&mut self, other: &ScrollbackSpill) -> Result<()> {
+        let err_context = || format!("failed to copy scrollback spill {:?}", other.path);
+
+        let mut other_file = File::open(&other.path).with_context(err_context)?;
+        let file = self.file().with_context(err_context)?;
+        io::copy(&mut other_file, file).with_context(err_context)?;
+        self.blocks = other.blocks.clone();
+        Ok(())
+    }
+    fn file(&mut self) -> Result<&mut File> {
+        if self.file.is_none() {
+            self.file = Some(create_spill_file(&self.path)?);
+        }
+        self.file
+            .as_mut()
+            .ok_or_else(|| anyhow!("no scrollback spill file"))
+    }
+}
+
+fn create_spill_file(path: &Path) -> Result<File> {
+    if let Some(spill_dir) = path.parent() {
+        DirBuilder::new()
+            .recursive(true)
+            .mode(SPILL_DIR_MODE)
+            .create(spill_dir)?;
+    }
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(SPILL_FILE_MODE)
+        .open(path)?;
+    Ok(file)
+}
+
+impl Clone for ScrollbackSpill {
+    fn clone(&self) -> Self {
+        let spill_dir = self.path.parent().map(PathBuf::from).unwrap_or_default();
+        let mut scrollback_spill = ScrollbackSpill::new_in(spill_dir, self.block_size);
+        scrollback_spill.pending_rows = self.pending_rows.clone();
+        if self.file.is_some() {
+            scrollback_spill.copy_blocks_from(self).non_fatal();
+        }
+        scrollback_spill
+    }
+}
+
+impl Drop for ScrollbackSpill {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
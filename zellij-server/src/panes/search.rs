@@ -3,14 +3,28 @@ use crate::panes::terminal_character::TerminalCharacter;
 use crate::panes::{Grid, Row};
 use std::borrow::Cow;
 use std::fmt::Debug;
+use zellij_utils::errors::prelude::*;
 use zellij_utils::input::actions::SearchDirection;
 use zellij_utils::position::Position;
+use zellij_utils::regex::{Regex, RegexBuilder};
 
 // If char is neither alphanumeric nor an underscore do we consider it a word-boundary
 fn is_word_boundary(x: &Option<char>) -> bool {
     x.map_or(true, |c| !c.is_ascii_alphanumeric() && c != '_')
 }
 
+// The tail may have not been wrapped yet (when coming from lines_below),
+// so it could be that the end extends across more characters than the row is wide.
+// Therefore we need to reflow the end:
+fn reflow_selection_ends(selections: &mut [Selection], row: &Row) {
+    for s in selections.iter_mut() {
+        while s.end.column() > row.width() {
+            s.end.column.0 -= row.width();
+            s.end.line.0 += 1;
+        }
+    }
+}
+
 #[derive(Debug)]
 enum SearchSource<'a> {
     Main(&'a Row),
@@ -98,6 +112,10 @@ pub struct SearchResult {
     pub whole_word_only: bool, // TODO
     // Jump from the bottom to the top (or vice versa), if we run out of lines to search
     pub wrap_search: bool,
+    // Treat the needle as a regular expression
+    pub regex_search: bool,
+    // The needle compiled with the current search-options (None if it is not a valid regex)
+    regex: Option<Regex>,
 }
 
 impl SearchResult {
@@ -141,7 +159,25 @@ impl SearchResult {
     }
 
     pub fn has_modifiers_set(&self) -> bool {
-        self.wrap_search || self.whole_word_only || self.case_insensitive
+        self.wrap_search || self.whole_word_only || self.case_insensitive || self.regex_search
+    }
+
+    /// Recompiles the regex needle, needs to be called whenever the needle or one of the
+    /// search-options changes
+    pub(crate) fn update_regex(&mut self) {
+        self.regex = if self.regex_search && !self.needle.is_empty() {
+            let pattern = if self.whole_word_only {
+                format!(r"\b(?:{})\b", self.needle)
+            } else {
+                self.needle.clone()
+            };
+            RegexBuilder::new(&pattern)
+                .case_insensitive(self.case_insensitive)
+                .build()
+                .ok()
+        } else {
+            None
+        };
     }
 
     fn check_if_haystack_char_matches_needle(
@@ -181,6 +217,9 @@ impl SearchResult {
         if self.needle.is_empty() || row.columns.is_empty() {
             return res;
         }
+        if self.regex_search {
+            return self.search_row_with_regex(ridx, row, tail);
+        }
 
         let mut tailit = tail.iter();
         let mut source = SearchSource::Main(row); // Where we currently get the haystack-characters from
@@ -267,15 +306,53 @@ impl SearchResult {
             }
         }
 
-        // The tail may have not been wrapped yet (when coming from lines_below),
-        // so it could be that the end extends across more characters than the row is wide.
-        // Therefore we need to reflow the end:
-        for s in res.iter_mut() {
-            while s.end.column() > row.width() {
-                s.end.column.0 -= row.width();
-                s.end.line.0 += 1;
+        reflow_selection_ends(&mut res, row);
+        res
+    }
+
+    /// Search a row and its tail for matches of the regex needle that start in the row.
+    /// The regex is matched against the row and its tail as a whole, so that matches can span
+    /// the wrapped lines.
+    fn search_row_with_regex(&self, ridx: usize, row: &Row, tail: &[&Row]) -> Vec<Selection> {
+        let mut res = Vec::new();
+        let regex = match &self.regex {
+            Some(regex) => regex,
+            None => return res,
+        };
+
+        // The haystack and, for every char in it, its byte offset and (line, column) position
+        let mut haystack = String::new();
+        let mut char_positions = Vec::new();
+        for (line, searched_row) in std::iter::once(row).chain(tail.iter().copied()).enumerate() {
+            for (column, terminal_character) in searched_row.columns.iter().enumerate() {
+                char_positions.push((haystack.len(), ridx + line, column));
+                haystack.push(terminal_character.character);
+            }
+        }
+        let char_index_at = |byte_offset: usize| {
+            char_positions
+                .binary_search_by_key(&byte_offset, |(offset, _, _)| *offset)
+                .unwrap_or_else(|index| index)
+        };
+
+        for regex_match in regex.find_iter(&haystack) {
+            if regex_match.start() == regex_match.end() {
+                // Empty matches cannot be highlighted
+                continue;
+            }
+            let (_, start_line, start_column) = char_positions[char_index_at(regex_match.start())];
+            if start_line != ridx {
+                // Matches starting in the tail are found when searching the tail rows themselves
+                break;
             }
+            let (_, end_line, end_column) = char_positions[char_index_at(regex_match.end()) - 1];
+            let mut selection = Selection::default();
+            selection.start(Position::new(start_line as i32, start_column as u16));
+            selection.end(Position::new(end_line as i32, (end_column + 1) as u16));
+            res.push(selection);
         }
+
+        reflow_selection_ends(&mut res, row);
         res
     }
 
@@ -395,10 +472,12 @@ impl SearchResult {
 impl Grid {
     pub fn search_down(&mut self) {
         self.search_scrollbuffer(SearchDirection::Down);
+        self.move_copy_mode_cursor_to_active_search_result();
     }
 
     pub fn search_up(&mut self) {
         self.search_scrollbuffer(SearchDirection::Up);
+        self.move_copy_mode_cursor_to_active_search_result();
     }
 
     pub fn clear_search(&mut self) {
@@ -412,6 +491,7 @@ impl Grid {
 
     pub fn set_search_string(&mut self, needle: &str) {
         self.search_results.needle = needle.to_string();
+        self.search_results.update_regex();
         self.search_viewport();
         // If the current viewport does not contain any hits,
         // we jump around until we find something. Starting
@@ -454,6 +534,7 @@ impl Grid {
 
     pub fn toggle_search_case_sensitivity(&mut self) {
         self.search_results.case_insensitive = !self.search_results.case_insensitive;
+        self.search_results.update_regex();
         for line in self.search_results.selections.drain(..) {
             self.output_buffer
                 .update_lines(line.start.line() as usize, line.end.line() as usize);
@@ -469,6 +550,20 @@ impl Grid {
 
     pub fn toggle_search_whole_words(&mut self) {
         self.search_results.whole_word_only = !self.search_results.whole_word_only;
+        self.search_results.update_regex();
+        for line in self.search_results.selections.drain(..) {
+            self.output_buffer
+                .update_lines(line.start.line() as usize, line.end.line() as usize);
+        }
+        self.search_results.active = None;
+        self.search_viewport();
+        // Maybe the selection we had is now gone
+        self.search_results.unset_active_selection_if_nonexistent();
+    }
+
+    pub fn toggle_search_regex(&mut self) {
+        self.search_results.regex_search = !self.search_results.regex_search;
+        self.search_results.update_regex();
         for line in self.search_results.selections.drain(..) {
             self.output_buffer
                 .update_lines(line.start.line() as usize, line.end.line() as usize);
@@ -537,13 +632,48 @@ impl Grid {
         }
     }
 
-    fn search_reached_opposite_end(&mut self, dir: SearchDirection) -> bool {
+    fn search_reached_opposite_end(
+        &mut self,
+        dir: SearchDirection,
+        rows_above_to_skip: usize,
+    ) -> bool {
         match dir {
-            SearchDirection::Up => self.lines_above.is_empty(),
+            SearchDirection::Up => self.rows_above_count() <= rows_above_to_skip,
             SearchDirection::Down => self.lines_below.is_empty(),
         }
     }
 
+    fn rows_above_count(&self) -> usize {
+        self.lines_above.len()
+            + self
+                .scrollback_spill
+                .as_ref()
+                .map(|scrollback_spill| scrollback_spill.len())
+                .unwrap_or(0)
+    }
+
+    /// How many of the rows above the viewport we can skip when looking for the closest result
+    /// above it (Up) or the one closest to the top (Down).
+    /// Rows spilled to disk are searched in place, so that only the ones up to that result are
+    /// paged back in, and none at all if they do not contain any.
+    fn rows_above_to_skip(&mut self, dir: SearchDirection) -> usize {
+        let search_results = &self.search_results;
+        let matches = |row: &Row| !search_results.search_row(0, row, &[]).is_empty();
+        let scrollback_spill = match self.scrollback_spill.as_mut() {
+            Some(scrollback_spill) => scrollback_spill,
+            None => return 0,
+        };
+        let position = match dir {
+            SearchDirection::Up => scrollback_spill.position_of_last_row(matches),
+            SearchDirection::Down => scrollback_spill.position_of_first_row(matches),
+        };
+        position
+            .context("failed to search scrollback lines spilled to disk")
+            .to_log()
+            .unwrap_or_default()
+            .unwrap_or_else(|| scrollback_spill.len())
+    }
+
     fn search_viewport_move(&mut self, dir: SearchDirection) -> bool {
         // We need to move the viewport
         let mut rows = 0;
@@ -551,7 +681,11 @@ impl Grid {
 
         // We might loose the current selection, if we can't find anything
         let current_active_selection = self.search_results.active;
-        while !found_something && !self.search_reached_opposite_end(dir) {
+        let rows_above_to_skip = match dir {
+            SearchDirection::Up => self.rows_above_to_skip(dir),
+            SearchDirection::Down => 0,
+        };
+        while !found_something && !self.search_reached_opposite_end(dir, rows_above_to_skip) {
             rows += 1;
             found_something = match dir {
                 SearchDirection::Up => self.scroll_up_one_line(),
@@ -651,8 +785,9 @@ impl Grid {
                 }
             },
             SearchDirection::Down => {
-                // Go to the top
-                while !self.lines_above.is_empty() {
+                // Go to the top, or as close to it as the first result spilled to disk
+                let rows_above_to_skip = self.rows_above_to_skip(dir);
+                while self.rows_above_count() > rows_above_to_skip {
                     rows += 1;
                     self.scroll_up_one_line();
                 }
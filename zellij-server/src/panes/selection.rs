@@ -4,11 +4,14 @@ use zellij_utils::position::Position;
 
 // The selection is empty when start == end
 // it includes the character at start, and everything before end.
+// A block selection covers the rectangle spanned by start and end instead of
+// following the text from one to the other.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Selection {
     pub start: Position,
     pub end: Position,
     active: bool, // used to handle moving the selection up and down
+    block: bool,
 }
 
 impl Default for Selection {
@@ -17,6 +20,7 @@ impl Default for Selection {
             start: Position::new(0, 0),
             end: Position::new(0, 0),
             active: false,
+            block: false,
         }
     }
 }
@@ -24,10 +28,20 @@ impl Default for Selection {
 impl Selection {
     pub fn start(&mut self, start: Position) {
         self.active = true;
+        self.block = false;
         self.start = start;
         self.end = start;
     }
 
+    pub fn start_block(&mut self, start: Position) {
+        self.start(start);
+        self.block = true;
+    }
+
+    pub fn is_block(&self) -> bool {
+        self.block
+    }
+
     pub fn to(&mut self, to: Position) {
         self.end = to
     }
@@ -39,6 +53,13 @@ impl Selection {
 
     pub fn contains(&self, row: usize, col: usize) -> bool {
         let row = row as isize;
+        if self.block {
+            let Selection { start, end, .. } = self.sorted();
+            return start.line.0 <= row
+                && row <= end.line.0
+                && start.column.0 <= col
+                && col < end.column.0;
+        }
         let (start, end) = if self.start <= self.end {
             (self.start, self.end)
         } else {
@@ -77,6 +98,20 @@ impl Selection {
     }
 
     pub fn sorted(&self) -> Self {
+        if self.block {
+            let mut start = self.start;
+            let mut end = self.end;
+            start.line.0 = self.start.line.0.min(self.end.line.0);
+            start.column.0 = self.start.column.0.min(self.end.column.0);
+            end.line.0 = self.start.line.0.max(self.end.line.0);
+            end.column.0 = self.start.column.0.max(self.end.column.0);
+            return Self {
+                start,
+                end,
+                active: self.active,
+                block: self.block,
+            };
+        }
         let (start, end) = if self.start <= self.end {
             (self.start, self.end)
         } else {
@@ -86,6 +121,7 @@ impl Selection {
             start,
             end,
             active: self.active,
+            block: self.block,
         }
     }
 
@@ -117,6 +153,7 @@ impl Selection {
 
     /// Return an iterator over the line indices, up to max, that are not present in both self and other,
     /// except for the indices of the first and last line of both self and s2, that are always included.
+    /// If either selection is a block, all of its lines are included since its columns may have changed.
     pub fn diff(&self, other: &Self, max: usize) -> impl Iterator<Item = isize> {
         let mut lines_to_update = HashSet::new();
 
@@ -131,6 +168,9 @@ impl Selection {
         old_lines.symmetric_difference(&new_lines).for_each(|&l| {
             let _ = lines_to_update.insert(l);
         });
+        if self.block || other.block {
+            lines_to_update.extend(old_lines.union(&new_lines));
+        }
 
         lines_to_update
             .into_iter()
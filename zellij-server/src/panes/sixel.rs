@@ -224,6 +224,15 @@ impl SixelGrid {
             }
         }
     }
+    /// Moves the images down to make room for lines added above the top of the grid
+    pub fn offset_grid_bottom(&mut self, line_count: usize) {
+        if let Some(character_cell_size) = *self.character_cell_size.borrow() {
+            let height_to_add = (character_cell_size.height * line_count) as isize;
+            for pixel_rect in self.sixel_image_locations.values_mut() {
+                pixel_rect.y += height_to_add;
+            }
+        }
+    }
     pub fn drain_image_ids_to_reap(&mut self) -> Option<Vec<usize>> {
         let images_to_reap = self.image_ids_to_reap.drain(..);
         if images_to_reap.len() > 0 {
@@ -7,9 +7,12 @@ use unicode_width::UnicodeWidthStr;
 use zellij_utils::input::command::RunCommand;
 use zellij_utils::{
     data::{PaletteColor, Style},
+    serde,
     vte::ParamsIter,
 };
 
+use serde::{Deserialize, Serialize};
+
 use crate::panes::alacritty_functions::parse_sgr_color;
 
 pub const EMPTY_TERMINAL_CHARACTER: TerminalCharacter = TerminalCharacter {
@@ -27,19 +30,53 @@ pub const RESET_STYLES: CharacterStyles = CharacterStyles {
     slow_blink: Some(AnsiCode::Reset),
     fast_blink: Some(AnsiCode::Reset),
     underline: Some(AnsiCode::Reset),
+    underline_color: Some(AnsiCode::Reset),
     bold: Some(AnsiCode::Reset),
     dim: Some(AnsiCode::Reset),
     italic: Some(AnsiCode::Reset),
     link_anchor: Some(LinkAnchor::End),
 };
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(crate = "self::serde")]
 pub enum AnsiCode {
     On,
     Reset,
     NamedColor(NamedColor),
     RgbCode((u8, u8, u8)),
     ColorIndex(u8),
+    StyledUnderline(UnderlineStyle),
+}
+
+/// Underline styles other than the single underline (which is [`AnsiCode::On`]), set with the
+/// `4:x` sub-parameters
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(crate = "self::serde")]
+pub enum UnderlineStyle {
+    Double,
+    Curly,
+    Dotted,
+    Dashed,
+}
+
+impl UnderlineStyle {
+    fn from_sub_parameter(sub_parameter: u16) -> Option<Self> {
+        match sub_parameter {
+            2 => Some(UnderlineStyle::Double),
+            3 => Some(UnderlineStyle::Curly),
+            4 => Some(UnderlineStyle::Dotted),
+            5 => Some(UnderlineStyle::Dashed),
+            _ => None,
+        }
+    }
+    fn to_sub_parameter(self) -> u16 {
+        match self {
+            UnderlineStyle::Double => 2,
+            UnderlineStyle::Curly => 3,
+            UnderlineStyle::Dotted => 4,
+            UnderlineStyle::Dashed => 5,
+        }
+    }
 }
 
 impl From<PaletteColor> for AnsiCode {
@@ -51,7 +88,8 @@ impl From<PaletteColor> for AnsiCode {
     }
 }
 
-#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(crate = "self::serde")]
 pub enum NamedColor {
     Black,
     Red,
@@ -118,7 +156,8 @@ impl NamedColor {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Default)]
+#[derive(Clone, Copy, Debug, PartialEq, Default, Serialize, Deserialize)]
+#[serde(crate = "self::serde")]
 pub struct CharacterStyles {
     pub foreground: Option<AnsiCode>,
     pub background: Option<AnsiCode>,
@@ -128,6 +167,7 @@ pub struct CharacterStyles {
     pub slow_blink: Option<AnsiCode>,
     pub fast_blink: Option<AnsiCode>,
     pub underline: Option<AnsiCode>,
+    pub underline_color: Option<AnsiCode>,
     pub bold: Option<AnsiCode>,
     pub dim: Option<AnsiCode>,
     pub italic: Option<AnsiCode>,
@@ -162,6 +202,10 @@ impl CharacterStyles {
         self.underline = underline_code;
         self
     }
+    pub fn underline_color(mut self, underline_color_code: Option<AnsiCode>) -> Self {
+        self.underline_color = underline_color_code;
+        self
+    }
     pub fn blink_slow(mut self, slow_blink_code: Option<AnsiCode>) -> Self {
         self.slow_blink = slow_blink_code;
         self
@@ -195,6 +239,7 @@ impl CharacterStyles {
         self.slow_blink = None;
         self.fast_blink = None;
         self.underline = None;
+        self.underline_color = None;
         self.bold = None;
         self.dim = None;
         self.italic = None;
@@ -241,6 +286,9 @@ impl CharacterStyles {
         if self.underline != new_styles.underline {
             diff.underline = new_styles.underline;
         }
+        if self.underline_color != new_styles.underline_color {
+            diff.underline_color = new_styles.underline_color;
+        }
         if self.bold != new_styles.bold {
             diff.bold = new_styles.bold;
         }
@@ -268,9 +316,24 @@ impl CharacterStyles {
                     diff.background = Some(changed_color);
                 }
             }
+            if let Some(AnsiCode::ColorIndex(color_index)) = diff.underline_color {
+                if let Some(changed_color) = changed_colors[color_index as usize] {
+                    diff.underline_color = Some(changed_color);
+                }
+            }
         }
         Some(diff)
     }
+    // for terminal emulators that do not support them, styled underlines become single ones and
+    // underline colors are dropped (resetting them is harmless since unknown SGRs are ignored)
+    pub fn remove_styled_underlines(&mut self) {
+        if let Some(AnsiCode::StyledUnderline(_)) = self.underline {
+            self.underline = Some(AnsiCode::On);
+        }
+        if self.underline_color != Some(AnsiCode::Reset) {
+            self.underline_color = None;
+        }
+    }
     pub fn reset_all(&mut self) {
         self.foreground = Some(AnsiCode::Reset);
         self.background = Some(AnsiCode::Reset);
@@ -278,6 +341,7 @@ impl CharacterStyles {
         self.dim = Some(AnsiCode::Reset);
         self.italic = Some(AnsiCode::Reset);
         self.underline = Some(AnsiCode::Reset);
+        self.underline_color = Some(AnsiCode::Reset);
         self.slow_blink = Some(AnsiCode::Reset);
         self.fast_blink = Some(AnsiCode::Reset);
         self.reverse = Some(AnsiCode::Reset);
@@ -291,7 +355,15 @@ impl CharacterStyles {
                 [1] => *self = self.bold(Some(AnsiCode::On)),
                 [2] => *self = self.dim(Some(AnsiCode::On)),
                 [3] => *self = self.italic(Some(AnsiCode::On)),
-                [4] => *self = self.underline(Some(AnsiCode::On)),
+                [4] | [4, 1] => *self = self.underline(Some(AnsiCode::On)),
+                [4, 0] => *self = self.underline(Some(AnsiCode::Reset)),
+                [4, sub_parameter] => {
+                    if let Some(underline_style) =
+                        UnderlineStyle::from_sub_parameter(*sub_parameter)
+                    {
+                        *self = self.underline(Some(AnsiCode::StyledUnderline(underline_style)));
+                    }
+                },
                 [5] => *self = self.blink_slow(Some(AnsiCode::On)),
                 [6] => *self = self.blink_fast(Some(AnsiCode::On)),
                 [7] => *self = self.reverse(Some(AnsiCode::On)),
@@ -357,6 +429,21 @@ impl CharacterStyles {
                     }
                 },
                 [49] => *self = self.background(Some(AnsiCode::Reset)),
+                [58] => {
+                    let mut iter = params.map(|param| param[0]);
+                    if let Some(ansi_code) = parse_sgr_color(&mut iter) {
+                        *self = self.underline_color(Some(ansi_code));
+                    }
+                },
+                [58, params @ ..] => {
+                    let rgb_start = if params.len() > 4 { 2 } else { 1 };
+                    let rgb_iter = params[rgb_start..].iter().copied();
+                    let mut iter = std::iter::once(params[0]).chain(rgb_iter);
+                    if let Some(ansi_code) = parse_sgr_color(&mut iter) {
+                        *self = self.underline_color(Some(ansi_code));
+                    }
+                },
+                [59] => *self = self.underline_color(Some(AnsiCode::Reset)),
                 [90] => {
                     *self = self.foreground(Some(AnsiCode::NamedColor(NamedColor::BrightBlack)))
                 },
@@ -416,6 +503,7 @@ impl Display for CharacterStyles {
             && self.fast_blink == Some(AnsiCode::Reset)
             && self.slow_blink == Some(AnsiCode::Reset)
             && self.underline == Some(AnsiCode::Reset)
+            && self.underline_color == Some(AnsiCode::Reset)
             && self.bold == Some(AnsiCode::Reset)
             && self.dim == Some(AnsiCode::Reset)
             && self.italic == Some(AnsiCode::Reset)
@@ -536,6 +624,23 @@ impl Display for CharacterStyles {
                 AnsiCode::Reset => {
                     write!(f, "\u{1b}[24m")?;
                 },
+                AnsiCode::StyledUnderline(underline_style) => {
+                    write!(f, "\u{1b}[4:{}m", underline_style.to_sub_parameter())?;
+                },
+                _ => {},
+            }
+        }
+        if let Some(ansi_code) = self.underline_color {
+            match ansi_code {
+                AnsiCode::RgbCode((r, g, b)) => {
+                    write!(f, "\u{1b}[58:2::{}:{}:{}m", r, g, b)?;
+                },
+                AnsiCode::ColorIndex(color_index) => {
+                    write!(f, "\u{1b}[58:5:{}m", color_index)?;
+                },
+                AnsiCode::Reset => {
+                    write!(f, "\u{1b}[59m")?;
+                },
                 _ => {},
             }
         }
@@ -569,7 +674,8 @@ impl Display for CharacterStyles {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "self::serde")]
 pub enum LinkAnchor {
     Start(u16),
     End,
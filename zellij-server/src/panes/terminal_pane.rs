@@ -1,27 +1,33 @@
-use crate::output::{CharacterChunk, SixelImageChunk};
+use crate::output::{CharacterChunk, KittyImageChunk, SixelImageChunk};
 use crate::panes::sixel::SixelImageStore;
-use crate::panes::LinkHandler;
 use crate::panes::{
     grid::Grid,
     terminal_character::{render_first_run_banner, TerminalCharacter, EMPTY_TERMINAL_CHARACTER},
 };
+use crate::panes::{HintAction, LinkHandler};
 use crate::pty::VteBytes;
 use crate::tab::{AdjustedInput, Pane};
 use crate::ClientId;
 use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
+use std::path::PathBuf;
 use std::rc::Rc;
 use std::time::{self, Instant};
 use zellij_utils::input::command::RunCommand;
 use zellij_utils::pane_size::Offset;
 use zellij_utils::{
-    data::{InputMode, Palette, PaletteColor, Style},
+    data::{InputMode, Key, KeyEventType, Palette, PaletteColor, Style},
     errors::prelude::*,
-    input::layout::Run,
+    input::{
+        actions::{CopyModeMotion, CopyModeSelection},
+        kitty_keyboard,
+        layout::Run,
+    },
     pane_size::PaneGeom,
     pane_size::SizeInPixels,
     position::Position,
+    regex::Regex,
     shared::make_terminal_title,
     vte,
 };
@@ -84,6 +90,16 @@ pub enum PaneId {
     Plugin(u32), // FIXME: Drop the trait object, make this a wrapper for the struct?
 }
 
+// because crate::panes::PaneId is so widely used in Zellij
+impl From<zellij_utils::data::PaneId> for PaneId {
+    fn from(zellij_utils_pane_id: zellij_utils::data::PaneId) -> Self {
+        match zellij_utils_pane_id {
+            zellij_utils::data::PaneId::Terminal(terminal_id) => PaneId::Terminal(terminal_id),
+            zellij_utils::data::PaneId::Plugin(plugin_id) => PaneId::Plugin(plugin_id),
+        }
+    }
+}
+
 type IsFirstRun = bool;
 
 // FIXME: This should hold an os_api handle so that terminal panes can set their own size via FD in
@@ -114,6 +130,8 @@ pub struct TerminalPane {
     // held on startup and can possibly be used to display some errors
     pane_frame_color_override: Option<(PaletteColor, Option<String>)>,
     invoked_with: Option<Run>,
+    reported_cwd: Option<PathBuf>,
+    process_cwd: Option<PathBuf>, // as last queried by the pty thread
 }
 
 impl Pane for TerminalPane {
@@ -164,6 +182,9 @@ impl Pane for TerminalPane {
         self.set_should_render(true);
         for &byte in &bytes {
             self.vte_parser.advance(&mut self.grid, byte);
+            // our vte parser ignores APC sequences, so we look for kitty graphics commands
+            // separately
+            self.grid.advance_kitty_graphics_parser(byte);
         }
     }
     fn cursor_coordinates(&self) -> Option<(usize, usize)> {
@@ -182,15 +203,10 @@ impl Pane for TerminalPane {
         // needs to be adjusted.
         // here we match against those cases - if need be, we adjust the input and if not
         // we send back the original input
-        if let Some((_exit_status, _is_first_run, run_command)) = &self.is_held {
+        if self.is_held.is_some() {
             match input_bytes.as_slice() {
                 ENTER_CARRIAGE_RETURN | ENTER_NEWLINE | SPACE => {
-                    let run_command = run_command.clone();
-                    self.is_held = None;
-                    self.grid.reset_terminal_state();
-                    self.set_should_render(true);
-                    self.remove_banner();
-                    Some(AdjustedInput::ReRunCommandInThisPane(run_command))
+                    self.rerun().map(AdjustedInput::ReRunCommandInThisPane)
                 },
                 CTRL_C => Some(AdjustedInput::CloseThisPane),
                 _ => None,
@@ -256,6 +272,9 @@ impl Pane for TerminalPane {
             Some(AdjustedInput::WriteBytesToTerminal(input_bytes))
         }
     }
+    fn encode_key(&self, key: &Key, event_type: KeyEventType) -> Option<Vec<u8>> {
+        kitty_keyboard::encode_key(key, event_type, self.grid.kitty_keyboard_flags())
+    }
     fn position_and_size(&self) -> PaneGeom {
         self.geom
     }
@@ -287,7 +306,10 @@ impl Pane for TerminalPane {
         &mut self,
         _client_id: Option<ClientId>,
     ) -> Result<Option<(Vec<CharacterChunk>, Option<String>, Vec<SixelImageChunk>)>> {
-        if self.should_render() {
+        // while the application is in the middle of a synchronized update, we hold back the
+        // changes it made so far and render them all together once it's done, unless we were
+        // asked to draw the whole pane again
+        if self.should_render() && !self.grid.should_hold_back_output() {
             let content_x = self.get_content_x();
             let content_y = self.get_content_y();
             let rows = self.get_content_rows();
@@ -306,6 +328,13 @@ impl Pane for TerminalPane {
             Ok(None)
         }
     }
+    fn kitty_image_chunks(&self) -> Vec<KittyImageChunk> {
+        if self.get_content_rows() < 1 || self.get_content_columns() < 1 {
+            return vec![];
+        }
+        self.grid
+            .kitty_image_chunks(self.get_content_x(), self.get_content_y())
+    }
     fn render_frame(
         &mut self,
         client_id: ClientId,
@@ -346,6 +375,9 @@ impl Pane for TerminalPane {
                 if self.grid.search_results.wrap_search {
                     modifiers.push("w")
                 }
+                if self.grid.search_results.regex_search {
+                    modifiers.push("r")
+                }
                 modifier_text.push_str(&modifiers.join(", "));
                 modifier_text.push(']');
             }
@@ -500,6 +532,21 @@ impl Pane for TerminalPane {
     fn dump_screen(&mut self, _client_id: ClientId, full: bool) -> String {
         self.grid.dump_screen(full)
     }
+    fn dump_last_command_output(&self) -> String {
+        self.grid.dump_last_command_output()
+    }
+    fn scroll_to_previous_prompt(&mut self) {
+        self.grid.move_viewport_to_previous_prompt();
+        self.set_should_render(true);
+    }
+    fn scroll_to_next_prompt(&mut self) {
+        self.grid.move_viewport_to_next_prompt();
+        self.set_should_render(true);
+    }
+    fn select_last_command_output(&mut self) {
+        self.grid.select_last_command_output();
+        self.set_should_render(true);
+    }
     fn clear_screen(&mut self) {
         self.grid.clear_screen()
     }
@@ -537,11 +584,40 @@ impl Pane for TerminalPane {
         self.grid.pending_clipboard_update.take()
     }
 
+    fn drain_cwd_update(&mut self) -> Option<PathBuf> {
+        match self.grid.pending_cwd_update.take() {
+            Some(cwd) if self.reported_cwd.as_ref() != Some(&cwd) => {
+                self.reported_cwd = Some(cwd.clone());
+                Some(cwd)
+            },
+            _ => None,
+        }
+    }
+
+    fn cwd(&self) -> Option<PathBuf> {
+        self.reported_cwd
+            .clone()
+            .or_else(|| self.process_cwd.clone())
+    }
+
+    fn update_process_cwd(&mut self, cwd: PathBuf) -> bool {
+        if self.process_cwd.as_ref() == Some(&cwd) {
+            return false;
+        }
+        self.process_cwd = Some(cwd);
+        true
+    }
+
     fn start_selection(&mut self, start: &Position, _client_id: ClientId) {
         self.grid.start_selection(start);
         self.set_should_render(true);
     }
 
+    fn start_block_selection(&mut self, start: &Position, _client_id: ClientId) {
+        self.grid.start_block_selection(start);
+        self.set_should_render(true);
+    }
+
     fn update_selection(&mut self, to: &Position, _client_id: ClientId) {
         let should_scroll = self.selection_scrolled_at.elapsed()
             >= time::Duration::from_millis(SELECTION_SCROLL_INTERVAL_MS);
@@ -689,13 +765,49 @@ impl Pane for TerminalPane {
     fn toggle_search_wrap(&mut self) {
         self.grid.toggle_search_wrap();
     }
+    fn toggle_search_regex(&mut self) {
+        self.grid.toggle_search_regex();
+        self.set_should_render(true);
+    }
     fn clear_search(&mut self) {
         self.grid.clear_search();
         self.search_term.clear();
     }
+    fn enter_copy_mode(&mut self) {
+        self.grid.enter_copy_mode();
+        self.set_should_render(true);
+    }
+    fn exit_copy_mode(&mut self) {
+        self.grid.exit_copy_mode();
+        self.set_should_render(true);
+    }
+    fn copy_mode_move(&mut self, motion: CopyModeMotion) {
+        self.grid.copy_mode_move(motion);
+        self.set_should_render(true);
+    }
+    fn copy_mode_select(&mut self, selection: CopyModeSelection) {
+        self.grid.copy_mode_select(selection);
+        self.set_should_render(true);
+    }
+    fn enter_hint_mode(&mut self, hint_patterns: &[Regex]) {
+        self.grid.enter_hint_mode(hint_patterns);
+        self.set_should_render(true);
+    }
+    fn exit_hint_mode(&mut self) {
+        self.grid.exit_hint_mode();
+        self.set_should_render(true);
+    }
+    fn hint_input(&mut self, input: &[u8]) -> Option<HintAction> {
+        let hint_action = self.grid.hint_input(input);
+        self.set_should_render(true);
+        hint_action
+    }
     fn is_alternate_mode_active(&self) -> bool {
         self.grid.is_alternate_mode_active()
     }
+    fn is_holding_synchronized_output(&self) -> bool {
+        self.grid.is_holding_synchronized_output()
+    }
     fn hold(&mut self, exit_status: Option<i32>, is_first_run: bool, run_command: RunCommand) {
         self.is_held = Some((exit_status, is_first_run, run_command));
         if is_first_run {
@@ -703,6 +815,22 @@ impl Pane for TerminalPane {
         }
         self.set_should_render(true);
     }
+    fn is_held(&self) -> bool {
+        self.is_held.is_some()
+    }
+    fn rerun(&mut self) -> Option<RunCommand> {
+        // if this is a held command pane, resets it and returns the command to run it again
+        let (_exit_status, _is_first_run, run_command) = self.is_held.take()?;
+        self.grid.reset_terminal_state();
+        self.set_should_render(true);
+        self.remove_banner();
+        Some(run_command)
+    }
+    fn exit_status(&self) -> Option<i32> {
+        self.is_held
+            .as_ref()
+            .and_then(|(exit_status, _is_first_run, _run_command)| *exit_status)
+    }
     fn add_red_pane_frame_color_override(&mut self, error_text: Option<String>) {
         self.pane_frame_color_override = Some((self.style.colors.red, error_text));
     }
@@ -717,9 +845,31 @@ impl Pane for TerminalPane {
     fn invoked_with(&self) -> &Option<Run> {
         &self.invoked_with
     }
+    fn custom_title(&self) -> Option<String> {
+        if self.pane_name.is_empty() {
+            None
+        } else {
+            Some(self.pane_name.clone())
+        }
+    }
+    fn current_title(&self) -> String {
+        if self.pane_name.is_empty() {
+            self.grid
+                .title
+                .as_deref()
+                .unwrap_or(&self.pane_title)
+                .into()
+        } else {
+            self.pane_name.clone()
+        }
+    }
     fn set_title(&mut self, title: String) {
         self.pane_title = title;
     }
+    fn rename(&mut self, name: String) {
+        self.pane_name = name;
+        self.set_should_render(true);
+    }
 }
 
 impl TerminalPane {
@@ -772,6 +922,8 @@ impl TerminalPane {
             banner: None,
             pane_frame_color_override: None,
             invoked_with,
+            reported_cwd: None,
+            process_cwd: None,
         }
     }
     pub fn get_x(&self) -> usize {
@@ -786,15 +786,14 @@ impl TiledPanes {
         self.set_pane_frames(self.draw_pane_frames);
     }
 
-    pub fn resize_active_pane(
+    pub fn resize_pane_with_id(
         &mut self,
-        client_id: ClientId,
+        pane_id: PaneId,
         strategy: &ResizeStrategy,
     ) -> Result<()> {
-        let err_context =
-            || format!("failed to {strategy} for active tiled pane for client {client_id}");
+        let err_context = || format!("failed to {strategy} for tiled pane with id {pane_id:?}");
 
-        if let Some(active_pane_id) = self.get_active_pane_id(client_id) {
+        if self.panes.contains_key(&pane_id) {
             let mut pane_grid = TiledPaneGrid::new(
                 &mut self.panes,
                 &self.panes_to_hide,
@@ -803,7 +802,7 @@ impl TiledPanes {
             );
 
             match pane_grid
-                .change_pane_size(&active_pane_id, strategy, (RESIZE_PERCENT, RESIZE_PERCENT))
+                .change_pane_size(&pane_id, strategy, (RESIZE_PERCENT, RESIZE_PERCENT))
                 .with_context(err_context)
             {
                 Ok(_) => {},
@@ -812,7 +811,7 @@ impl TiledPanes {
                         // try once more with double the resize percent, but let's keep it at that
                         match pane_grid
                             .change_pane_size(
-                                &active_pane_id,
+                                &pane_id,
                                 strategy,
                                 (RESIZE_PERCENT * 2.0, RESIZE_PERCENT * 2.0),
                             )
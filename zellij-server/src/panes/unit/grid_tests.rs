@@ -1,11 +1,23 @@
 use super::super::Grid;
 use crate::panes::grid::SixelImageStore;
+use crate::panes::hints::{compile_hint_patterns, HintAction, HintTarget};
+use crate::panes::kitty_graphics::{KittyGraphicsCommand, KittyGraphicsGrid};
 use crate::panes::link_handler::LinkHandler;
+use crate::panes::scrollback_spill::ScrollbackSpill;
+use crate::panes::terminal_character::{AnsiCode, NamedColor, UnderlineStyle};
 use ::insta::assert_snapshot;
 use std::cell::RefCell;
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::rc::Rc;
-use zellij_utils::{data::Palette, pane_size::SizeInPixels, position::Position, vte};
+use zellij_utils::{
+    consts::DEFAULT_SCROLL_BUFFER_SIZE,
+    data::Palette,
+    input::actions::{CopyModeMotion, CopyModeSelection},
+    pane_size::SizeInPixels,
+    position::Position,
+    tempfile, vte,
+};
 
 use std::fmt::Write;
 
@@ -1167,6 +1179,34 @@ fn copy_selected_text_from_lines_above() {
     );
 }
 
+#[test]
+fn copy_selected_text_from_the_top_line_above() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = Grid::new(
+        5,
+        20,
+        Rc::new(RefCell::new(Palette::default())),
+        Rc::new(RefCell::new(HashMap::new())),
+        Rc::new(RefCell::new(LinkHandler::new())),
+        Rc::new(RefCell::new(None)),
+        Rc::new(RefCell::new(SixelImageStore::default())),
+    );
+    let mut content = String::new();
+    for i in 0..8 {
+        write!(content, "line {i}\n\r").unwrap();
+    }
+    for byte in content.as_bytes() {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    assert_eq!(grid.lines_above.len(), 4);
+
+    // the top line above the viewport is at -lines_above.len()
+    grid.start_selection(&Position::new(-4, 0));
+    grid.end_selection(&Position::new(-3, 6));
+    let text = grid.get_selected_text();
+    assert_eq!(text.unwrap(), "line 0\nline 1");
+}
+
 #[test]
 fn copy_selected_text_from_lines_below() {
     let mut vte_parser = vte::Parser::new();
@@ -2564,3 +2604,1004 @@ pub fn cursor_hide_persists_through_alternate_screen() {
         "Cursor still shown away from alternate screen"
     );
 }
+
+fn grid_with_semantic_prompts(rows: usize, columns: usize) -> Grid {
+    let mut vte_parser = vte::Parser::new();
+    let sixel_image_store = Rc::new(RefCell::new(SixelImageStore::default()));
+    let terminal_emulator_color_codes = Rc::new(RefCell::new(HashMap::new()));
+    let mut grid = Grid::new(
+        rows,
+        columns,
+        Rc::new(RefCell::new(Palette::default())),
+        terminal_emulator_color_codes,
+        Rc::new(RefCell::new(LinkHandler::new())),
+        Rc::new(RefCell::new(None)),
+        sixel_image_store,
+    );
+    let mut content = String::new();
+    for command in ["seq 10", "seq 11 20"] {
+        write!(
+            content,
+            "\u{1b}]133;A\u{7}$ \u{1b}]133;B\u{7}{}\r\n\u{1b}]133;C\u{7}",
+            command
+        )
+        .unwrap();
+        let range = if command == "seq 10" { 1..=10 } else { 11..=20 };
+        for i in range {
+            write!(content, "{}\r\n", i).unwrap();
+        }
+        write!(content, "\u{1b}]133;D;0\u{7}").unwrap();
+    }
+    write!(content, "\u{1b}]133;A\u{7}$ \u{1b}]133;B\u{7}").unwrap();
+    for byte in content.as_bytes() {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    grid
+}
+
+#[test]
+pub fn dump_last_command_output_with_semantic_prompts() {
+    let mut grid = grid_with_semantic_prompts(5, 50);
+    let expected_output: Vec<String> = (11..=20).map(|i| i.to_string()).collect();
+    assert_eq!(grid.dump_last_command_output(), expected_output.join("\n"));
+    // the output is found regardless of where the viewport is scrolled to
+    grid.move_viewport_up(10);
+    assert_eq!(grid.dump_last_command_output(), expected_output.join("\n"));
+}
+
+#[test]
+pub fn select_last_command_output_with_semantic_prompts() {
+    let mut grid = grid_with_semantic_prompts(5, 50);
+    grid.select_last_command_output();
+    let expected_output: Vec<String> = (11..=20).map(|i| i.to_string()).collect();
+    assert_eq!(grid.get_selected_text(), Some(expected_output.join("\n")));
+}
+
+#[test]
+pub fn last_command_output_is_empty_without_semantic_prompts() {
+    let mut vte_parser = vte::Parser::new();
+    let sixel_image_store = Rc::new(RefCell::new(SixelImageStore::default()));
+    let terminal_emulator_color_codes = Rc::new(RefCell::new(HashMap::new()));
+    let mut grid = Grid::new(
+        5,
+        50,
+        Rc::new(RefCell::new(Palette::default())),
+        terminal_emulator_color_codes,
+        Rc::new(RefCell::new(LinkHandler::new())),
+        Rc::new(RefCell::new(None)),
+        sixel_image_store,
+    );
+    for byte in "$ seq 2\r\n1\r\n2\r\n$ ".as_bytes() {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    assert_eq!(grid.dump_last_command_output(), "");
+    grid.select_last_command_output();
+    assert_eq!(grid.get_selected_text(), None);
+}
+
+#[test]
+pub fn move_viewport_between_semantic_prompts() {
+    let mut grid = grid_with_semantic_prompts(5, 50);
+    grid.move_viewport_to_previous_prompt();
+    assert_eq!(grid.dump_screen(false), "$ seq 11 20\n11\n12\n13\n14");
+    grid.move_viewport_to_previous_prompt();
+    assert_eq!(grid.dump_screen(false), "$ seq 10\n1\n2\n3\n4");
+    // there are no more prompts above, so this should not move the viewport
+    grid.move_viewport_to_previous_prompt();
+    assert_eq!(grid.dump_screen(false), "$ seq 10\n1\n2\n3\n4");
+    grid.move_viewport_to_next_prompt();
+    assert_eq!(grid.dump_screen(false), "$ seq 11 20\n11\n12\n13\n14");
+    // the last prompt cannot be scrolled to the top, so we end up at the bottom
+    grid.move_viewport_to_next_prompt();
+    assert_eq!(grid.dump_screen(false), "17\n18\n19\n20\n$");
+    assert!(!grid.is_scrolled);
+}
+
+#[test]
+pub fn cwd_is_reported_with_osc7() {
+    let mut vte_parser = vte::Parser::new();
+    let sixel_image_store = Rc::new(RefCell::new(SixelImageStore::default()));
+    let terminal_emulator_color_codes = Rc::new(RefCell::new(HashMap::new()));
+    let mut grid = Grid::new(
+        5,
+        50,
+        Rc::new(RefCell::new(Palette::default())),
+        terminal_emulator_color_codes,
+        Rc::new(RefCell::new(LinkHandler::new())),
+        Rc::new(RefCell::new(None)),
+        sixel_image_store,
+    );
+    let osc7 = "\u{1b}]7;file://some-host/home/user/some%20dir;with%20semicolon\u{1b}\\";
+    for byte in osc7.as_bytes() {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    assert_eq!(
+        grid.pending_cwd_update.take(),
+        Some(std::path::PathBuf::from(
+            "/home/user/some dir;with semicolon"
+        ))
+    );
+    let not_a_file_url = "\u{1b}]7;http://some-host/home/user\u{7}";
+    for byte in not_a_file_url.as_bytes() {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    assert_eq!(grid.pending_cwd_update, None);
+}
+
+#[test]
+pub fn kitty_keyboard_protocol_flags_are_pushed_popped_and_queried() {
+    let mut vte_parser = vte::Parser::new();
+    let sixel_image_store = Rc::new(RefCell::new(SixelImageStore::default()));
+    let terminal_emulator_color_codes = Rc::new(RefCell::new(HashMap::new()));
+    let mut grid = Grid::new(
+        5,
+        50,
+        Rc::new(RefCell::new(Palette::default())),
+        terminal_emulator_color_codes,
+        Rc::new(RefCell::new(LinkHandler::new())),
+        Rc::new(RefCell::new(None)),
+        sixel_image_store,
+    );
+    // push 1, push 3, set 16 (unsupported) on top of it with mode 2 (or) and query
+    let content = "\u{1b}[>1u\u{1b}[>3u\u{1b}[=16;2u\u{1b}[?u";
+    for byte in content.as_bytes() {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    assert_eq!(grid.kitty_keyboard_flags(), 19);
+    // the alternate screen has its own stack
+    let content = "\u{1b}[?1049h\u{1b}[?u\u{1b}[>8u";
+    for byte in content.as_bytes() {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    assert_eq!(grid.kitty_keyboard_flags(), 8);
+    let content = "\u{1b}[?1049l\u{1b}[<u\u{1b}[?u";
+    for byte in content.as_bytes() {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    assert_eq!(grid.kitty_keyboard_flags(), 1);
+    assert_eq!(
+        grid.pending_messages_to_pty,
+        vec![
+            "\u{1b}[?3u".as_bytes().to_vec(),
+            "\u{1b}[?0u".as_bytes().to_vec(),
+            "\u{1b}[?1u".as_bytes().to_vec(),
+        ]
+    );
+}
+
+#[test]
+pub fn synchronized_output_mode_is_reported_with_decrqm() {
+    let mut vte_parser = vte::Parser::new();
+    let sixel_image_store = Rc::new(RefCell::new(SixelImageStore::default()));
+    let terminal_emulator_color_codes = Rc::new(RefCell::new(HashMap::new()));
+    let mut grid = Grid::new(
+        5,
+        50,
+        Rc::new(RefCell::new(Palette::default())),
+        terminal_emulator_color_codes,
+        Rc::new(RefCell::new(LinkHandler::new())),
+        Rc::new(RefCell::new(None)),
+        sixel_image_store,
+    );
+    let content = "\u{1b}[?2026$p\u{1b}[?2026h\u{1b}[?2026$p";
+    for byte in content.as_bytes() {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    assert!(grid.is_holding_synchronized_output());
+    // unknown private modes are reported as not recognized, ANSI modes are reported too
+    let content = "\u{1b}[?2026l\u{1b}[?2026$p\u{1b}[?12345$p\u{1b}[4h\u{1b}[4$p";
+    for byte in content.as_bytes() {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    assert!(!grid.is_holding_synchronized_output());
+    assert_eq!(
+        grid.pending_messages_to_pty,
+        vec![
+            "\u{1b}[?2026;2$y".as_bytes().to_vec(),
+            "\u{1b}[?2026;1$y".as_bytes().to_vec(),
+            "\u{1b}[?2026;2$y".as_bytes().to_vec(),
+            "\u{1b}[?12345;0$y".as_bytes().to_vec(),
+            "\u{1b}[4;1$y".as_bytes().to_vec(),
+        ]
+    );
+}
+
+#[test]
+pub fn styled_and_colored_underlines() {
+    let mut vte_parser = vte::Parser::new();
+    let sixel_image_store = Rc::new(RefCell::new(SixelImageStore::default()));
+    let terminal_emulator_color_codes = Rc::new(RefCell::new(HashMap::new()));
+    let mut grid = Grid::new(
+        5,
+        50,
+        Rc::new(RefCell::new(Palette::default())),
+        terminal_emulator_color_codes,
+        Rc::new(RefCell::new(LinkHandler::new())),
+        Rc::new(RefCell::new(None)),
+        sixel_image_store,
+    );
+    let content =
+        "\u{1b}[4:3;58:2::255:0:0ma\u{1b}[4:2;58;5;9mb\u{1b}[4:0;59mc\u{1b}[4;58;2;1;2;3md";
+    for byte in content.as_bytes() {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    let styles: Vec<_> = grid.viewport[0]
+        .columns
+        .iter()
+        .take(4)
+        .map(|terminal_character| {
+            (
+                terminal_character.styles.underline,
+                terminal_character.styles.underline_color,
+            )
+        })
+        .collect();
+    assert_eq!(
+        styles,
+        vec![
+            (
+                Some(AnsiCode::StyledUnderline(UnderlineStyle::Curly)),
+                Some(AnsiCode::RgbCode((255, 0, 0)))
+            ),
+            (
+                Some(AnsiCode::StyledUnderline(UnderlineStyle::Double)),
+                Some(AnsiCode::ColorIndex(9))
+            ),
+            (Some(AnsiCode::Reset), Some(AnsiCode::Reset)),
+            (Some(AnsiCode::On), Some(AnsiCode::RgbCode((1, 2, 3)))),
+        ]
+    );
+    let mut curly_red_underline = grid.viewport[0].columns[0].styles;
+    assert!(format!("{}", curly_red_underline).contains("\u{1b}[4:3m\u{1b}[58:2::255:0:0m"));
+    curly_red_underline.remove_styled_underlines();
+    assert_eq!(curly_red_underline.underline, Some(AnsiCode::On));
+    assert_eq!(curly_red_underline.underline_color, None);
+}
+
+fn kitty_graphics_grid(rows: usize, columns: usize) -> Grid {
+    Grid::new(
+        rows,
+        columns,
+        Rc::new(RefCell::new(Palette::default())),
+        Rc::new(RefCell::new(HashMap::new())),
+        Rc::new(RefCell::new(LinkHandler::new())),
+        Rc::new(RefCell::new(Some(SizeInPixels {
+            height: 20,
+            width: 10,
+        }))),
+        Rc::new(RefCell::new(SixelImageStore::default())),
+    )
+}
+
+fn feed_kitty_graphics_grid(grid: &mut Grid, vte_parser: &mut vte::Parser, content: &str) {
+    for byte in content.as_bytes() {
+        vte_parser.advance(grid, *byte);
+        grid.advance_kitty_graphics_parser(*byte);
+    }
+}
+
+// cell_x, cell_y, columns, rows, image_pixel_y, image_pixel_height
+type KittyImageChunkPosition = (usize, usize, usize, usize, usize, usize);
+
+fn kitty_image_chunk_positions(grid: &Grid) -> Vec<KittyImageChunkPosition> {
+    grid.kitty_image_chunks(0, 0)
+        .iter()
+        .map(|c| {
+            (
+                c.cell_x,
+                c.cell_y,
+                c.columns,
+                c.rows,
+                c.image_pixel_y,
+                c.image_pixel_height,
+            )
+        })
+        .collect()
+}
+
+#[test]
+pub fn kitty_graphics_placements_scroll_with_the_scrollback() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = kitty_graphics_grid(5, 20);
+    // a 20x40 pixel image takes 2x2 cells and moves the cursor after it
+    feed_kitty_graphics_grid(
+        &mut grid,
+        &mut vte_parser,
+        "\u{1b}_Ga=T,i=1,s=20,v=40;AAAA\u{1b}\\",
+    );
+    assert_eq!(
+        grid.pending_messages_to_pty,
+        vec!["\u{1b}_Gi=1;OK\u{1b}\\".as_bytes().to_vec()]
+    );
+    assert_eq!(grid.cursor_coordinates(), Some((2, 1)));
+    assert_eq!(
+        kitty_image_chunk_positions(&grid),
+        vec![(0, 0, 2, 2, 0, 40)]
+    );
+    // the top line of the image scrolls into the scrollback
+    feed_kitty_graphics_grid(&mut grid, &mut vte_parser, "\n\n\n\n");
+    assert_eq!(
+        kitty_image_chunk_positions(&grid),
+        vec![(0, 0, 2, 1, 20, 20)]
+    );
+    grid.scroll_up_one_line();
+    assert_eq!(
+        kitty_image_chunk_positions(&grid),
+        vec![(0, 0, 2, 2, 0, 40)]
+    );
+    grid.reset_viewport();
+    feed_kitty_graphics_grid(&mut grid, &mut vte_parser, "\n");
+    assert_eq!(kitty_image_chunk_positions(&grid), vec![]);
+}
+
+#[test]
+pub fn kitty_graphics_chunked_transmission_queries_and_deletion() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = kitty_graphics_grid(5, 20);
+    let content = "\u{1b}_Ga=q,i=31,s=1,v=1,f=24;AAAA\u{1b}\\\
+        \u{1b}_Ga=q,i=32;AAAA\u{1b}\\\
+        \u{1b}_Ga=t,i=2,s=10,v=10,m=1;AAAA\u{1b}\\\
+        \u{1b}_Gm=0;BBBB\u{1b}\\\
+        \u{1b}_Ga=p,i=2,c=3,r=1,C=1\u{1b}\\";
+    feed_kitty_graphics_grid(&mut grid, &mut vte_parser, content);
+    assert_eq!(
+        grid.pending_messages_to_pty,
+        vec![
+            "\u{1b}_Gi=31;OK\u{1b}\\".as_bytes().to_vec(),
+            "\u{1b}_Gi=32;EINVAL:the image size must be specified\u{1b}\\"
+                .as_bytes()
+                .to_vec(),
+            "\u{1b}_Gi=2;OK\u{1b}\\".as_bytes().to_vec(),
+            "\u{1b}_Gi=2;OK\u{1b}\\".as_bytes().to_vec(),
+        ]
+    );
+    // the image was placed without moving the cursor, stretched to the cells it was given
+    assert_eq!(grid.cursor_coordinates(), Some((0, 0)));
+    let image_chunks = grid.kitty_image_chunks(0, 0);
+    assert_eq!(image_chunks.len(), 1);
+    assert_eq!(image_chunks[0].image.data, "AAAABBBB");
+    assert!(image_chunks[0].is_scaled);
+    assert_eq!(
+        kitty_image_chunk_positions(&grid),
+        vec![(0, 0, 3, 1, 0, 10)]
+    );
+
+    grid.pending_messages_to_pty.clear();
+    let content = "\u{1b}_Ga=d,d=i,i=2\u{1b}\\";
+    feed_kitty_graphics_grid(&mut grid, &mut vte_parser, content);
+    assert_eq!(kitty_image_chunk_positions(&grid), vec![]);
+    // lower case deletions keep the image so it can be placed again
+    let content = "\u{1b}_Ga=p,i=2,c=3,r=1,q=1\u{1b}\\";
+    feed_kitty_graphics_grid(&mut grid, &mut vte_parser, content);
+    assert_eq!(kitty_image_chunk_positions(&grid).len(), 1);
+    let content = "\u{1b}_Ga=d,d=I,i=2\u{1b}\\\u{1b}_Ga=p,i=2\u{1b}\\";
+    feed_kitty_graphics_grid(&mut grid, &mut vte_parser, content);
+    assert_eq!(kitty_image_chunk_positions(&grid), vec![]);
+    assert_eq!(
+        grid.pending_messages_to_pty,
+        vec!["\u{1b}_Gi=2;ENOENT:image not found\u{1b}\\"
+            .as_bytes()
+            .to_vec()]
+    );
+}
+
+#[test]
+pub fn kitty_graphics_files_are_only_read_if_they_are_small_regular_files() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = kitty_graphics_grid(5, 20);
+    let temp_dir = tempfile::tempdir().unwrap();
+    let big_file_path = temp_dir.path().join("big-image");
+    let big_file = std::fs::File::create(&big_file_path).unwrap();
+    big_file.set_len(64 * 1024 * 1024).unwrap();
+    let transmit_file = |image_id: usize, path: &std::path::Path| {
+        format!(
+            "\u{1b}_Ga=t,t=f,i={},s=1,v=1;{}\u{1b}\\",
+            image_id,
+            base64::encode(path.to_string_lossy().as_bytes())
+        )
+    };
+    let content = format!(
+        "{}{}",
+        transmit_file(1, temp_dir.path()),
+        transmit_file(2, &big_file_path)
+    );
+    feed_kitty_graphics_grid(&mut grid, &mut vte_parser, &content);
+    assert_eq!(
+        grid.pending_messages_to_pty,
+        vec![
+            format!(
+                "\u{1b}_Gi=1;EBADF:{} is not a regular file\u{1b}\\",
+                temp_dir.path().display()
+            )
+            .as_bytes()
+            .to_vec(),
+            format!(
+                "\u{1b}_Gi=2;EFBIG:{} is too big\u{1b}\\",
+                big_file_path.display()
+            )
+            .as_bytes()
+            .to_vec(),
+        ]
+    );
+}
+
+#[test]
+pub fn kitty_graphics_temporary_files_are_deleted_once_accepted_and_only_from_temp_dirs() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = kitty_graphics_grid(5, 20);
+    let temp_dir = tempfile::tempdir().unwrap();
+    let not_temp_dir = tempfile::tempdir_in(".").unwrap();
+    let accepted_file = temp_dir.path().join("tty-graphics-protocol-1");
+    let rejected_file = temp_dir.path().join("tty-graphics-protocol-2");
+    let file_outside_temp_dir = not_temp_dir.path().join("tty-graphics-protocol-3");
+    for path in [&accepted_file, &rejected_file, &file_outside_temp_dir] {
+        std::fs::write(path, "AAAA").unwrap();
+    }
+    let transmit_temporary_file = |image_id: usize, size: &str, path: &std::path::Path| {
+        format!(
+            "\u{1b}_Ga=t,t=t,i={},{};{}\u{1b}\\",
+            image_id,
+            size,
+            base64::encode(path.to_string_lossy().as_bytes())
+        )
+    };
+    let content = format!(
+        "{}{}{}",
+        transmit_temporary_file(1, "s=1,v=1", &accepted_file),
+        transmit_temporary_file(2, "s=0,v=0", &rejected_file),
+        transmit_temporary_file(3, "s=1,v=1", &file_outside_temp_dir),
+    );
+    feed_kitty_graphics_grid(&mut grid, &mut vte_parser, &content);
+    assert!(!accepted_file.exists(), "accepted temporary file deleted");
+    assert!(rejected_file.exists(), "rejected temporary file kept");
+    assert!(
+        file_outside_temp_dir.exists(),
+        "file outside of the temporary directories kept"
+    );
+}
+
+#[test]
+pub fn kitty_graphics_files_are_not_read_from_system_directories() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = kitty_graphics_grid(5, 20);
+    let content = format!(
+        "\u{1b}_Ga=t,t=f,i=1,s=1,v=1;{}\u{1b}\\",
+        base64::encode("/proc/self/environ")
+    );
+    feed_kitty_graphics_grid(&mut grid, &mut vte_parser, &content);
+    let environ_path = std::fs::canonicalize("/proc/self/environ").unwrap();
+    assert_eq!(
+        grid.pending_messages_to_pty,
+        vec![format!(
+            "\u{1b}_Gi=1;EPERM:{} cannot be read\u{1b}\\",
+            environ_path.display()
+        )
+        .as_bytes()
+        .to_vec()]
+    );
+}
+
+#[test]
+pub fn kitty_graphics_oversized_chunked_transmissions_are_dropped() {
+    let mut kitty_graphics_grid = KittyGraphicsGrid::default();
+    let chunk = |more_chunks: bool| KittyGraphicsCommand {
+        more_chunks,
+        payload: vec![b'A'; 40 * 1024 * 1024],
+        ..Default::default()
+    };
+    let first_chunk = KittyGraphicsCommand {
+        image_id: 1,
+        pixel_width: 1,
+        pixel_height: 1,
+        ..chunk(true)
+    };
+    for command in [first_chunk, chunk(true), chunk(true)] {
+        let result = kitty_graphics_grid.handle_command(command, 0, 0, 0, None);
+        assert_eq!(result.response, None);
+    }
+    let result = kitty_graphics_grid.handle_command(chunk(false), 0, 0, 0, None);
+    assert_eq!(
+        result.response,
+        Some("\u{1b}_Gi=1;EFBIG:the image is too big\u{1b}\\".to_owned())
+    );
+    // the next transmission starts from scratch
+    let command = KittyGraphicsCommand {
+        image_id: 2,
+        pixel_width: 1,
+        pixel_height: 1,
+        payload: b"AAAA".to_vec(),
+        ..Default::default()
+    };
+    let result = kitty_graphics_grid.handle_command(command, 0, 0, 0, None);
+    assert_eq!(result.response, Some("\u{1b}_Gi=2;OK\u{1b}\\".to_owned()));
+}
+
+#[test]
+pub fn scrollback_spilled_to_disk_is_paged_back_in() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = Grid::new(
+        5,
+        20,
+        Rc::new(RefCell::new(Palette::default())),
+        Rc::new(RefCell::new(HashMap::new())),
+        Rc::new(RefCell::new(LinkHandler::new())),
+        Rc::new(RefCell::new(None)),
+        Rc::new(RefCell::new(SixelImageStore::default())),
+    );
+    let spill_dir = tempfile::tempdir().unwrap();
+    grid.scrollback_spill = Some(ScrollbackSpill::new_in(spill_dir.path().to_path_buf(), 3));
+    let line_count = DEFAULT_SCROLL_BUFFER_SIZE + 20;
+    let mut content = String::new();
+    for i in 0..line_count {
+        write!(content, "\u{1b}[31mline {}\u{1b}[m\n\r", i).unwrap();
+    }
+    for byte in content.as_bytes() {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    // the oldest lines were spilled rather than dropped
+    assert_eq!(grid.lines_above.len(), DEFAULT_SCROLL_BUFFER_SIZE);
+    assert_eq!(grid.scrollback_spill.as_ref().unwrap().len(), 16);
+    assert_eq!(grid.scrollback_position_and_length(), (0, line_count - 4));
+    assert!(grid.dump_screen(true).starts_with("line 0\nline 1\n"));
+
+    while grid.has_lines_above() {
+        grid.scroll_up_one_line();
+    }
+    assert!(grid.scrollback_spill.as_ref().unwrap().is_empty());
+    assert!(grid.dump_screen(false).starts_with("line 0\nline 1\n"));
+    assert_eq!(
+        grid.viewport[0].columns[0].styles.foreground,
+        Some(AnsiCode::NamedColor(NamedColor::Red))
+    );
+    assert_eq!(
+        grid.scrollback_position_and_length(),
+        (line_count - 4, line_count - 4)
+    );
+
+    // scrolling back down spills them again
+    while grid.is_scrolled {
+        grid.scroll_down_one_line();
+    }
+    assert_eq!(grid.lines_above.len(), DEFAULT_SCROLL_BUFFER_SIZE);
+    assert_eq!(grid.scrollback_spill.as_ref().unwrap().len(), 16);
+    assert!(grid
+        .dump_screen(false)
+        .starts_with(&format!("line {}\n", line_count - 4)));
+}
+
+fn grid_with_spilled_scrollback(spill_dir: &tempfile::TempDir, needle_line: usize) -> Grid {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = Grid::new(
+        5,
+        20,
+        Rc::new(RefCell::new(Palette::default())),
+        Rc::new(RefCell::new(HashMap::new())),
+        Rc::new(RefCell::new(LinkHandler::new())),
+        Rc::new(RefCell::new(None)),
+        Rc::new(RefCell::new(SixelImageStore::default())),
+    );
+    grid.scrollback_spill = Some(ScrollbackSpill::new_in(spill_dir.path().to_path_buf(), 3));
+    let mut content = String::new();
+    for i in 0..DEFAULT_SCROLL_BUFFER_SIZE + 20 {
+        if i == needle_line {
+            write!(content, "needle {i}\n\r").unwrap();
+        } else {
+            write!(content, "line {i}\n\r").unwrap();
+        }
+    }
+    for byte in content.as_bytes() {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    // lines 0 to 15 are spilled to disk, in blocks of 3
+    assert_eq!(grid.scrollback_spill.as_ref().unwrap().len(), 16);
+    grid
+}
+
+#[test]
+pub fn scrollback_spill_files_are_only_readable_by_their_user() {
+    use std::os::unix::fs::PermissionsExt;
+    let spill_dir = tempfile::tempdir().unwrap();
+    let _grid = grid_with_spilled_scrollback(&spill_dir, 0);
+    let spill_files: Vec<_> = std::fs::read_dir(spill_dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().metadata().unwrap())
+        .collect();
+    assert_eq!(spill_files.len(), 1);
+    assert_eq!(spill_files[0].permissions().mode() & 0o777, 0o600);
+}
+
+#[test]
+pub fn searching_up_only_pages_in_the_spilled_lines_up_to_the_result() {
+    let spill_dir = tempfile::tempdir().unwrap();
+    let mut grid = grid_with_spilled_scrollback(&spill_dir, 5);
+    grid.set_search_string("needle");
+    grid.search_up();
+    assert!(grid.dump_screen(false).contains("needle 5"));
+    // the block with lines 0 to 2 was never paged in
+    assert_eq!(grid.scrollback_spill.as_ref().unwrap().len(), 3);
+
+    let mut grid = grid_with_spilled_scrollback(&spill_dir, 5);
+    grid.set_search_string("nothing");
+    grid.search_up();
+    assert_eq!(grid.scrollback_spill.as_ref().unwrap().len(), 16);
+}
+
+#[test]
+pub fn wrapping_search_only_pages_in_the_spilled_lines_up_to_the_result() {
+    let spill_dir = tempfile::tempdir().unwrap();
+    let mut grid = grid_with_spilled_scrollback(&spill_dir, 5);
+    grid.toggle_search_wrap();
+    grid.set_search_string("needle");
+    grid.search_down();
+    assert!(grid.dump_screen(false).contains("needle 5"));
+    assert_eq!(grid.scrollback_spill.as_ref().unwrap().len(), 3);
+
+    let mut grid = grid_with_spilled_scrollback(&spill_dir, DEFAULT_SCROLL_BUFFER_SIZE);
+    grid.toggle_search_wrap();
+    grid.set_search_string("needle");
+    grid.search_down();
+    assert!(grid
+        .dump_screen(false)
+        .contains(&format!("needle {DEFAULT_SCROLL_BUFFER_SIZE}")));
+    assert_eq!(grid.scrollback_spill.as_ref().unwrap().len(), 16);
+}
+
+#[test]
+pub fn copy_mode_top_pages_in_one_spilled_block_at_a_time() {
+    let spill_dir = tempfile::tempdir().unwrap();
+    let mut grid = grid_with_spilled_scrollback(&spill_dir, 0);
+    grid.enter_copy_mode();
+    grid.copy_mode_move(CopyModeMotion::Top);
+    assert_eq!(grid.scrollback_spill.as_ref().unwrap().len(), 16);
+    assert!(grid.dump_screen(false).starts_with("line 16\n"));
+    grid.copy_mode_move(CopyModeMotion::Top);
+    assert_eq!(grid.scrollback_spill.as_ref().unwrap().len(), 15);
+    assert!(grid.dump_screen(false).starts_with("line 15\n"));
+    grid.copy_mode_move(CopyModeMotion::Top);
+    assert_eq!(grid.scrollback_spill.as_ref().unwrap().len(), 12);
+    assert!(grid.dump_screen(false).starts_with("line 12\n"));
+}
+
+#[test]
+pub fn copy_mode_motions_and_selection() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = Grid::new(
+        5,
+        40,
+        Rc::new(RefCell::new(Palette::default())),
+        Rc::new(RefCell::new(HashMap::new())),
+        Rc::new(RefCell::new(LinkHandler::new())),
+        Rc::new(RefCell::new(None)),
+        Rc::new(RefCell::new(SixelImageStore::default())),
+    );
+    let content = "hello world foo\n\rsecond line here\n\r";
+    for byte in content.as_bytes() {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    grid.enter_copy_mode();
+    assert_eq!(grid.cursor_coordinates(), Some((0, 2)));
+
+    grid.copy_mode_move(CopyModeMotion::Up);
+    grid.copy_mode_select(CopyModeSelection::Character);
+    grid.copy_mode_move(CopyModeMotion::NextWordStart);
+    assert_eq!(grid.cursor_coordinates(), Some((7, 1)));
+    grid.copy_mode_move(CopyModeMotion::NextWordEnd);
+    assert_eq!(grid.cursor_coordinates(), Some((10, 1)));
+    assert_eq!(grid.get_selected_text(), Some("second line".into()));
+
+    // selecting the same way again stops selecting
+    grid.copy_mode_select(CopyModeSelection::Character);
+    assert_eq!(grid.get_selected_text(), None);
+
+    grid.copy_mode_move(CopyModeMotion::PreviousWordStart);
+    assert_eq!(grid.cursor_coordinates(), Some((7, 1)));
+    grid.copy_mode_move(CopyModeMotion::PreviousWordStart);
+    assert_eq!(grid.cursor_coordinates(), Some((0, 1)));
+    grid.copy_mode_move(CopyModeMotion::PreviousWordStart);
+    assert_eq!(grid.cursor_coordinates(), Some((12, 0)));
+
+    grid.copy_mode_select(CopyModeSelection::Word);
+    assert_eq!(grid.get_selected_text(), Some("foo".into()));
+    grid.copy_mode_move(CopyModeMotion::PreviousWordStart);
+    assert_eq!(grid.get_selected_text(), Some("world foo".into()));
+    grid.copy_mode_select(CopyModeSelection::Line);
+    grid.copy_mode_move(CopyModeMotion::Down);
+    assert_eq!(
+        grid.get_selected_text(),
+        Some("hello world foo\nsecond line here".into())
+    );
+    grid.copy_mode_move(CopyModeMotion::LineEnd);
+    assert_eq!(grid.cursor_coordinates(), Some((15, 1)));
+
+    // search jumps move the cursor to the match
+    grid.set_search_string("hello");
+    grid.search_up();
+    assert_eq!(grid.cursor_coordinates(), Some((0, 0)));
+
+    grid.exit_copy_mode();
+    assert_eq!(grid.get_selected_text(), None);
+    assert_eq!(grid.cursor_coordinates(), Some((0, 2)));
+}
+
+#[test]
+pub fn copy_mode_scrolls_with_the_cursor() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = Grid::new(
+        5,
+        20,
+        Rc::new(RefCell::new(Palette::default())),
+        Rc::new(RefCell::new(HashMap::new())),
+        Rc::new(RefCell::new(LinkHandler::new())),
+        Rc::new(RefCell::new(None)),
+        Rc::new(RefCell::new(SixelImageStore::default())),
+    );
+    let mut content = String::new();
+    for i in 0..20 {
+        write!(content, "line {}\n\r", i).unwrap();
+    }
+    for byte in content.as_bytes() {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    grid.enter_copy_mode();
+    assert_eq!(grid.cursor_coordinates(), Some((0, 4)));
+
+    grid.copy_mode_move(CopyModeMotion::Top);
+    assert!(grid.is_scrolled);
+    assert_eq!(grid.cursor_coordinates(), Some((0, 0)));
+    assert!(grid.dump_screen(false).starts_with("line 0\n"));
+
+    grid.copy_mode_select(CopyModeSelection::Line);
+    grid.copy_mode_move(CopyModeMotion::Down);
+    grid.copy_mode_move(CopyModeMotion::Down);
+    assert_eq!(
+        grid.get_selected_text(),
+        Some("line 0\nline 1\nline 2".into())
+    );
+
+    // the start of the selection stays in place while the cursor scrolls the viewport
+    grid.copy_mode_move(CopyModeMotion::Bottom);
+    assert!(!grid.is_scrolled);
+    grid.copy_mode_move(CopyModeMotion::Up);
+    assert_eq!(grid.cursor_coordinates(), Some((0, 3)));
+    let expected_text: Vec<String> = (0..20).map(|i| format!("line {}", i)).collect();
+    assert_eq!(grid.get_selected_text(), Some(expected_text.join("\n")));
+
+    // moving up past the top of the viewport scrolls it up one line
+    for _ in 0..4 {
+        grid.copy_mode_move(CopyModeMotion::Up);
+    }
+    assert_eq!(grid.cursor_coordinates(), Some((0, 0)));
+    assert!(grid.dump_screen(false).starts_with("line 15\n"));
+}
+
+#[test]
+pub fn block_selection_copies_a_rectangle() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = Grid::new(
+        5,
+        20,
+        Rc::new(RefCell::new(Palette::default())),
+        Rc::new(RefCell::new(HashMap::new())),
+        Rc::new(RefCell::new(LinkHandler::new())),
+        Rc::new(RefCell::new(None)),
+        Rc::new(RefCell::new(SixelImageStore::default())),
+    );
+    let content = "abcdef\n\rghijkl\n\rmnopqr\n\r";
+    for byte in content.as_bytes() {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    grid.start_block_selection(&Position::new(2, 4));
+    grid.end_selection(&Position::new(0, 1));
+    assert_eq!(grid.get_selected_text(), Some("bcd\nhij\nnop".into()));
+
+    // a regular selection between the same points follows the text instead
+    grid.start_selection(&Position::new(2, 4));
+    grid.end_selection(&Position::new(0, 1));
+    assert_eq!(grid.get_selected_text(), Some("bcdef\nghijkl\nmnop".into()));
+}
+
+#[test]
+pub fn copy_mode_block_selection() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = Grid::new(
+        5,
+        20,
+        Rc::new(RefCell::new(Palette::default())),
+        Rc::new(RefCell::new(HashMap::new())),
+        Rc::new(RefCell::new(LinkHandler::new())),
+        Rc::new(RefCell::new(None)),
+        Rc::new(RefCell::new(SixelImageStore::default())),
+    );
+    let content = "abcdef\n\rghijkl\n\rmnopqr\n\r";
+    for byte in content.as_bytes() {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    grid.enter_copy_mode();
+    grid.copy_mode_move(CopyModeMotion::Top);
+    grid.copy_mode_move(CopyModeMotion::Right);
+    grid.copy_mode_select(CopyModeSelection::Block);
+    grid.copy_mode_move(CopyModeMotion::Down);
+    grid.copy_mode_move(CopyModeMotion::Down);
+    grid.copy_mode_move(CopyModeMotion::Right);
+    grid.copy_mode_move(CopyModeMotion::Right);
+    assert_eq!(grid.cursor_coordinates(), Some((3, 2)));
+    assert_eq!(grid.get_selected_text(), Some("bcd\nhij\nnop".into()));
+
+    // switching to a line selection keeps the anchor
+    grid.copy_mode_select(CopyModeSelection::Line);
+    assert_eq!(
+        grid.get_selected_text(),
+        Some("abcdef\nghijkl\nmnopqr".into())
+    );
+}
+
+#[test]
+pub fn hint_mode_opens_urls_and_paths() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = Grid::new(
+        5,
+        60,
+        Rc::new(RefCell::new(Palette::default())),
+        Rc::new(RefCell::new(HashMap::new())),
+        Rc::new(RefCell::new(LinkHandler::new())),
+        Rc::new(RefCell::new(None)),
+        Rc::new(RefCell::new(SixelImageStore::default())),
+    );
+    let content = "see (https://example.com/a_(b)). and\n\rerror at src/main.rs:10:4\n\r";
+    for byte in content.as_bytes() {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    grid.enter_hint_mode(&[]);
+    assert!(grid.is_in_hint_mode());
+    assert_eq!(
+        grid.hint_input(b"a"),
+        Some(HintAction::Activate(HintTarget::Url(
+            "https://example.com/a_(b)".into()
+        )))
+    );
+    assert_eq!(
+        grid.hint_input(b"s"),
+        Some(HintAction::Activate(HintTarget::Path {
+            path: PathBuf::from("src/main.rs"),
+            line_number: Some(10),
+        }))
+    );
+    grid.exit_hint_mode();
+    assert!(!grid.is_in_hint_mode());
+    assert_eq!(grid.hint_input(b"a"), None);
+}
+
+#[test]
+pub fn hint_mode_only_opens_explicit_links_we_would_detect() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = Grid::new(
+        5,
+        60,
+        Rc::new(RefCell::new(Palette::default())),
+        Rc::new(RefCell::new(HashMap::new())),
+        Rc::new(RefCell::new(LinkHandler::new())),
+        Rc::new(RefCell::new(None)),
+        Rc::new(RefCell::new(SixelImageStore::default())),
+    );
+    let content = "\u{1b}]8;;--help\u{1b}\\help\u{1b}]8;;\u{1b}\\ \
+        \u{1b}]8;;javascript:alert(1)\u{1b}\\js\u{1b}]8;;\u{1b}\\ \
+        \u{1b}]8;;https://example.com\u{1b}\\site\u{1b}]8;;\u{1b}\\\n\r";
+    for byte in content.as_bytes() {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    grid.enter_hint_mode(&[]);
+    assert_eq!(
+        grid.hint_input(b"a"),
+        Some(HintAction::Activate(HintTarget::Url(
+            "https://example.com".into()
+        )))
+    );
+    assert_eq!(grid.hint_input(b"s"), None);
+}
+
+#[test]
+pub fn hint_mode_with_more_links_than_letters() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = Grid::new(
+        30,
+        40,
+        Rc::new(RefCell::new(Palette::default())),
+        Rc::new(RefCell::new(HashMap::new())),
+        Rc::new(RefCell::new(LinkHandler::new())),
+        Rc::new(RefCell::new(None)),
+        Rc::new(RefCell::new(SixelImageStore::default())),
+    );
+    let mut content = String::new();
+    for i in 0..27 {
+        write!(content, "https://example.com/{}\n\r", i).unwrap();
+    }
+    for byte in content.as_bytes() {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    grid.enter_hint_mode(&[]);
+    // the labels are two letters long, so a single one doesn't open anything
+    assert_eq!(grid.hint_input(b"s"), None);
+    // a typo ("ss" is not a label) starts the label over
+    assert_eq!(grid.hint_input(b"s"), None);
+    assert_eq!(
+        grid.hint_input(b"a"),
+        Some(HintAction::Activate(HintTarget::Url(
+            "https://example.com/26".into()
+        )))
+    );
+    assert_eq!(
+        grid.hint_input(b"as"),
+        Some(HintAction::Activate(HintTarget::Url(
+            "https://example.com/1".into()
+        )))
+    );
+}
+
+#[test]
+pub fn hint_mode_copies_and_writes_tokens() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = Grid::new(
+        5,
+        60,
+        Rc::new(RefCell::new(Palette::default())),
+        Rc::new(RefCell::new(HashMap::new())),
+        Rc::new(RefCell::new(LinkHandler::new())),
+        Rc::new(RefCell::new(None)),
+        Rc::new(RefCell::new(SixelImageStore::default())),
+    );
+    let content = "commit 3f2a9c1d0e merged from 10.0.0.12\n\r\
+        id 123e4567-e89b-12d3-a456-426614174000 at fe80::1ff:fe23:4567:890a\n\r";
+    for byte in content.as_bytes() {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    grid.enter_hint_mode(&compile_hint_patterns(None));
+    assert_eq!(
+        grid.hint_input(b"a"),
+        Some(HintAction::Activate(HintTarget::Text("3f2a9c1d0e".into())))
+    );
+    // typing the label in upper case writes the token rather than copying it
+    assert_eq!(
+        grid.hint_input(b"S"),
+        Some(HintAction::Write("10.0.0.12".into()))
+    );
+    assert_eq!(
+        grid.hint_input(b"d"),
+        Some(HintAction::Activate(HintTarget::Text(
+            "123e4567-e89b-12d3-a456-426614174000".into()
+        )))
+    );
+    assert_eq!(
+        grid.hint_input(b"f"),
+        Some(HintAction::Activate(HintTarget::Text(
+            "fe80::1ff:fe23:4567:890a".into()
+        )))
+    );
+    assert_eq!(grid.hint_input(b"g"), None);
+}
+
+#[test]
+pub fn hint_mode_with_configured_patterns() {
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = Grid::new(
+        5,
+        60,
+        Rc::new(RefCell::new(Palette::default())),
+        Rc::new(RefCell::new(HashMap::new())),
+        Rc::new(RefCell::new(LinkHandler::new())),
+        Rc::new(RefCell::new(None)),
+        Rc::new(RefCell::new(SixelImageStore::default())),
+    );
+    let content = "fixes ZJ-1234 in 3f2a9c1d0e\n\r";
+    for byte in content.as_bytes() {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    // the configured patterns replace the default ones, and invalid ones are skipped
+    let hint_patterns = compile_hint_patterns(Some(vec![r"[A-Z]+-\d+".into(), "(".into()]));
+    assert_eq!(hint_patterns.len(), 1);
+    grid.enter_hint_mode(&hint_patterns);
+    assert_eq!(
+        grid.hint_input(b"a"),
+        Some(HintAction::Activate(HintTarget::Text("ZJ-1234".into())))
+    );
+    assert_eq!(grid.hint_input(b"s"), None);
+}
@@ -413,3 +413,41 @@ pub fn searching_whole_word_case_insensitive() {
         format!("{:?}", terminal_pane.grid)
     );
 }
+
+#[test]
+pub fn searching_with_regex() {
+    let mut terminal_pane = create_pane();
+    terminal_pane.update_search_term("tort[o]r");
+    assert!(terminal_pane.grid.search_results.selections.is_empty());
+    terminal_pane.toggle_search_regex();
+    assert_snapshot!(
+        "grid_copy_tortor_highlighted",
+        format!("{:?}", terminal_pane.grid)
+    );
+
+    // An invalid regex (eg. while it is still being typed) matches nothing
+    let mut terminal_pane = create_pane();
+    terminal_pane.update_search_term("tort[o");
+    terminal_pane.toggle_search_regex();
+    assert!(terminal_pane.grid.search_results.selections.is_empty());
+}
+
+#[test]
+pub fn searching_with_regex_across_line_wrap() {
+    let mut terminal_pane = create_pane();
+    terminal_pane.update_search_term(r"aliquam\s+sem\s+fringilla");
+    terminal_pane.toggle_search_regex();
+    // Spread across two lines
+    terminal_pane.grid.change_size(30, 60);
+    assert_snapshot!(
+        "grid_copy_multiline_highlighted",
+        format!("{:?}", terminal_pane.grid)
+    );
+
+    // Spread across 4 lines
+    terminal_pane.grid.change_size(40, 4);
+    assert_snapshot!(
+        "grid_copy_multiline_highlighted_narrow",
+        format!("{:?}", terminal_pane.grid)
+    );
+}
@@ -43,6 +43,7 @@ fn contains() {
         start: Position::new(10, 5),
         end: Position::new(40, 20),
         active: false,
+        block: false,
     };
 
     let test_cases = vec![
@@ -93,6 +94,7 @@ fn sorted() {
         start: Position::new(1, 1),
         end: Position::new(10, 2),
         active: false,
+        block: false,
     };
     let sorted_selection = selection.sorted();
     assert_eq!(selection.start, sorted_selection.start);
@@ -102,18 +104,46 @@ fn sorted() {
         start: Position::new(10, 2),
         end: Position::new(1, 1),
         active: false,
+        block: false,
     };
     let sorted_selection = selection.sorted();
     assert_eq!(selection.end, sorted_selection.start);
     assert_eq!(selection.start, sorted_selection.end);
 }
 
+#[test]
+fn block_sorted() {
+    let mut selection = Selection::default();
+    selection.start_block(Position::new(10, 2));
+    selection.end(Position::new(1, 8));
+    let sorted_selection = selection.sorted();
+    assert!(sorted_selection.is_block());
+    assert_eq!(sorted_selection.start, Position::new(1, 2));
+    assert_eq!(sorted_selection.end, Position::new(10, 8));
+}
+
+#[test]
+fn block_contains() {
+    let mut selection = Selection::default();
+    selection.start_block(Position::new(10, 20));
+    selection.end(Position::new(5, 10));
+
+    assert!(selection.contains(5, 10));
+    assert!(selection.contains(7, 15));
+    assert!(selection.contains(10, 19));
+    assert!(!selection.contains(7, 9));
+    assert!(!selection.contains(7, 20));
+    assert!(!selection.contains(4, 15));
+    assert!(!selection.contains(11, 15));
+}
+
 #[test]
 fn line_indices() {
     let selection = Selection {
         start: Position::new(1, 1),
         end: Position::new(10, 2),
         active: false,
+        block: false,
     };
 
     assert_eq!(selection.line_indices(), (1..=10))
@@ -127,6 +157,7 @@ fn move_up_inactive() {
         start,
         end,
         active: false,
+        block: false,
     };
 
     inactive_selection.move_up(2);
@@ -145,6 +176,7 @@ fn move_up_active() {
         start,
         end,
         active: true,
+        block: false,
     };
 
     inactive_selection.move_up(2);
@@ -160,6 +192,7 @@ fn move_down_inactive() {
         start,
         end,
         active: false,
+        block: false,
     };
 
     inactive_selection.move_down(2);
@@ -178,6 +211,7 @@ fn move_down_active() {
         start,
         end,
         active: true,
+        block: false,
     };
 
     inactive_selection.move_down(2);
@@ -668,3 +668,92 @@ pub fn frameless_pane_position_is_on_frame() {
     assert!(!terminal_pane.position_is_on_frame(&Position::new(30, 130)));
     assert!(!terminal_pane.position_is_on_frame(&Position::new(30, 131)));
 }
+
+#[test]
+pub fn pane_output_is_held_during_synchronized_update() {
+    let mut fake_win_size = PaneGeom::default();
+    fake_win_size.cols.set_inner(121);
+    fake_win_size.rows.set_inner(20);
+
+    let pid = 1;
+    let style = Style::default();
+    let sixel_image_store = Rc::new(RefCell::new(SixelImageStore::default()));
+    let terminal_emulator_colors = Rc::new(RefCell::new(Palette::default()));
+    let terminal_emulator_color_codes = Rc::new(RefCell::new(HashMap::new()));
+    let mut terminal_pane = TerminalPane::new(
+        pid,
+        fake_win_size,
+        style,
+        0,
+        String::new(),
+        Rc::new(RefCell::new(LinkHandler::new())),
+        Rc::new(RefCell::new(None)),
+        sixel_image_store,
+        terminal_emulator_colors,
+        terminal_emulator_color_codes,
+        None,
+        None,
+    ); // 0 is the pane index
+    terminal_pane.render(None).unwrap(); // initial render
+    terminal_pane.handle_pty_bytes("\u{1b}[?2026hhalf a frame".as_bytes().to_vec());
+    assert!(
+        terminal_pane.render(None).unwrap().is_none(),
+        "output held back during synchronized update"
+    );
+    terminal_pane.handle_pty_bytes(" and the rest of it\u{1b}[?2026l".as_bytes().to_vec());
+    let (character_chunks, _, _) = terminal_pane.render(None).unwrap().unwrap();
+    let rendered_line: String = character_chunks[0]
+        .terminal_characters
+        .iter()
+        .map(|terminal_character| terminal_character.character)
+        .collect();
+    assert!(
+        rendered_line.starts_with("half a frame and the rest of it"),
+        "whole frame rendered once synchronized update ends"
+    );
+}
+
+#[test]
+pub fn forced_full_render_is_not_held_during_synchronized_update() {
+    let mut fake_win_size = PaneGeom::default();
+    fake_win_size.cols.set_inner(121);
+    fake_win_size.rows.set_inner(20);
+
+    let pid = 1;
+    let style = Style::default();
+    let sixel_image_store = Rc::new(RefCell::new(SixelImageStore::default()));
+    let terminal_emulator_colors = Rc::new(RefCell::new(Palette::default()));
+    let terminal_emulator_color_codes = Rc::new(RefCell::new(HashMap::new()));
+    let mut terminal_pane = TerminalPane::new(
+        pid,
+        fake_win_size,
+        style,
+        0,
+        String::new(),
+        Rc::new(RefCell::new(LinkHandler::new())),
+        Rc::new(RefCell::new(None)),
+        sixel_image_store,
+        terminal_emulator_colors,
+        terminal_emulator_color_codes,
+        None,
+        None,
+    ); // 0 is the pane index
+    terminal_pane.render(None).unwrap(); // initial render
+    terminal_pane.handle_pty_bytes("\u{1b}[?2026hhalf a frame".as_bytes().to_vec());
+    assert!(
+        terminal_pane.render(None).unwrap().is_none(),
+        "output held back during synchronized update"
+    );
+    // eg. the pane was resized or a new client attached
+    terminal_pane.set_should_render(true);
+    terminal_pane.render_full_viewport();
+    assert!(
+        terminal_pane.render(None).unwrap().is_some(),
+        "whole pane rendered when forced to, even during synchronized update"
+    );
+    terminal_pane.handle_pty_bytes(" and the rest".as_bytes().to_vec());
+    assert!(
+        terminal_pane.render(None).unwrap().is_none(),
+        "output held back again after the forced render"
+    );
+}
@@ -18,11 +18,14 @@ use crate::{pty::PtyInstruction, thread_bus::Bus, ClientId, ServerInstruction};
 use wasm_bridge::WasmBridge;
 
 use zellij_utils::{
-    data::{Event, EventType, PluginCapabilities},
+    data::{Event, EventType, PermissionStatus, PermissionType, PipeSource, PluginCapabilities},
     errors::{prelude::*, ContextType, PluginContext},
     input::{
         command::TerminalAction,
-        layout::{FloatingPaneLayout, Layout, Run, RunPlugin, RunPluginLocation, TiledPaneLayout},
+        layout::{
+            FloatingPaneLayout, Layout, PluginUserConfiguration, Run, RunPlugin, RunPluginLocation,
+            TiledPaneLayout,
+        },
         plugins::PluginsConfig,
     },
     ipc::ClientAttributes,
@@ -79,6 +82,24 @@ pub enum PluginInstruction {
         String, // serialized payload
     ),
     PluginSubscribedToEvents(PluginId, ClientId, HashSet<EventType>),
+    PluginUnsubscribedFromEvents(PluginId, ClientId, HashSet<EventType>),
+    PermissionRequestResult(PluginId, ClientId, Vec<PermissionType>, PermissionStatus),
+    PipeMessage(
+        Option<RunPluginLocation>, // destination plugin, all plugins if None
+        PipeSource,
+        String,         // name
+        Option<String>, // payload
+    ),
+    CliPipe(
+        String,                    // pipe id
+        Option<RunPluginLocation>, // destination plugin, all plugins if None
+        String,                    // name
+        Option<String>,            // payload
+        ClientId,                  // the cli client
+    ),
+    CliPipeOutput(PluginId, String, String), // the plugin writing the output, pipe id, output
+    CloseCliPipe(PluginId, String),          // the plugin closing the pipe, pipe id
+    CliPipeTimedOut(String),                 // pipe id
     Exit,
 }
 
@@ -105,6 +126,17 @@ impl From<&PluginInstruction> for PluginContext {
             PluginInstruction::PluginSubscribedToEvents(..) => {
                 PluginContext::PluginSubscribedToEvents
             },
+            PluginInstruction::PluginUnsubscribedFromEvents(..) => {
+                PluginContext::PluginUnsubscribedFromEvents
+            },
+            PluginInstruction::PermissionRequestResult(..) => {
+                PluginContext::PermissionRequestResult
+            },
+            PluginInstruction::PipeMessage(..) => PluginContext::PipeMessage,
+            PluginInstruction::CliPipe(..) => PluginContext::CliPipe,
+            PluginInstruction::CliPipeOutput(..) => PluginContext::CliPipeOutput,
+            PluginInstruction::CloseCliPipe(..) => PluginContext::CloseCliPipe,
+            PluginInstruction::CliPipeTimedOut(..) => PluginContext::CliPipeTimedOut,
         }
     }
 }
@@ -164,6 +196,7 @@ pub(crate) fn plugin_thread_main(
             },
             PluginInstruction::Unload(pid) => {
                 wasm_bridge.unload_plugin(pid)?;
+                wasm_bridge.update_session_info_polling();
             },
             PluginInstruction::Reload(should_float, pane_title, run, tab_index, size) => {
                 match wasm_bridge.reload_plugin(&run) {
@@ -206,6 +239,7 @@ pub(crate) fn plugin_thread_main(
             },
             PluginInstruction::RemoveClient(client_id) => {
                 wasm_bridge.remove_client(client_id);
+                wasm_bridge.update_session_info_polling();
             },
             PluginInstruction::NewTab(
                 cwd,
@@ -215,7 +249,10 @@ pub(crate) fn plugin_thread_main(
                 tab_index,
                 client_id,
             ) => {
-                let mut plugin_ids: HashMap<RunPluginLocation, Vec<PluginId>> = HashMap::new();
+                let mut plugin_ids: HashMap<
+                    (RunPluginLocation, PluginUserConfiguration),
+                    Vec<PluginId>,
+                > = HashMap::new();
                 let mut extracted_run_instructions = tab_layout
                     .clone()
                     .unwrap_or_else(|| layout.new_tab().0)
@@ -235,7 +272,10 @@ pub(crate) fn plugin_thread_main(
                     if let Some(Run::Plugin(run)) = run_instruction {
                         let plugin_id =
                             wasm_bridge.load_plugin(&run, tab_index, size, Some(client_id))?;
-                        plugin_ids.entry(run.location).or_default().push(plugin_id);
+                        plugin_ids
+                            .entry((run.location, run.configuration))
+                            .or_default()
+                            .push(plugin_id);
                     }
                 }
                 drop(bus.senders.send_to_pty(PtyInstruction::NewTab(
@@ -285,6 +325,38 @@ pub(crate) fn plugin_thread_main(
                         wasm_bridge.start_fs_watcher_if_not_started();
                     }
                 }
+                wasm_bridge.update_session_info_polling();
+            },
+            PluginInstruction::PluginUnsubscribedFromEvents(_plugin_id, _client_id, _events) => {
+                wasm_bridge.update_session_info_polling();
+            },
+            PluginInstruction::PermissionRequestResult(
+                plugin_id,
+                client_id,
+                permissions,
+                status,
+            ) => {
+                wasm_bridge.handle_permission_request_result(
+                    plugin_id,
+                    client_id,
+                    permissions,
+                    status,
+                )?;
+            },
+            PluginInstruction::PipeMessage(destination, source, name, payload) => {
+                wasm_bridge.pipe_message(destination, source, name, payload)?;
+            },
+            PluginInstruction::CliPipe(pipe_id, destination, name, payload, client_id) => {
+                wasm_bridge.cli_pipe(pipe_id, destination, name, payload, client_id)?;
+            },
+            PluginInstruction::CliPipeOutput(plugin_id, pipe_id, output) => {
+                wasm_bridge.cli_pipe_output(plugin_id, &pipe_id, output)?;
+            },
+            PluginInstruction::CloseCliPipe(plugin_id, pipe_id) => {
+                wasm_bridge.close_cli_pipe_from_plugin(plugin_id, &pipe_id)?;
+            },
+            PluginInstruction::CliPipeTimedOut(pipe_id) => {
+                wasm_bridge.cli_pipe_timed_out(&pipe_id)?;
             },
             PluginInstruction::Exit => {
                 wasm_bridge.cleanup();
@@ -1,6 +1,6 @@
 use crate::plugins::plugin_map::{PluginEnv, PluginMap, RunningPlugin, Subscriptions};
 use crate::plugins::plugin_worker::{plugin_worker, RunningWorker};
-use crate::plugins::zellij_exports::{wasi_read_string, zellij_exports};
+use crate::plugins::zellij_exports::{wasi_read_string, wasi_write_object, zellij_exports};
 use crate::plugins::PluginId;
 use highway::{HighwayHash, PortableHash};
 use log::info;
@@ -20,17 +20,22 @@ use crate::{
     ui::loading_indication::LoadingIndication, ClientId,
 };
 
+use zellij_utils::strum::IntoEnumIterator;
 use zellij_utils::{
     consts::{VERSION, ZELLIJ_CACHE_DIR, ZELLIJ_SESSION_CACHE_DIR, ZELLIJ_TMP_DIR},
-    data::PluginCapabilities,
+    data::{PermissionType, PluginCapabilities},
     errors::prelude::*,
     input::command::TerminalAction,
     input::layout::Layout,
+    input::permission::PermissionCache,
     input::plugins::PluginConfig,
     ipc::ClientAttributes,
     pane_size::Size,
 };
 
+/// The file (relative to the plugin dir) in which permissions granted to plugins are cached
+pub const PLUGIN_PERMISSIONS_CACHE_FILE: &str = "permissions.kdl";
+
 macro_rules! display_loading_stage {
     ($loading_stage:ident, $loading_indication:expr, $senders:expr, $plugin_id:expr) => {{
         $loading_indication.$loading_stage();
@@ -723,6 +728,11 @@ impl<'a> PluginLoader<'a> {
             }
         }
         start_function.call(&[]).with_context(err_context)?;
+        wasi_write_object(
+            &plugin_env.wasi_env,
+            plugin_env.plugin.userspace_configuration.inner(),
+        )
+        .with_context(err_context)?;
         load_function.call(&[]).with_context(err_context)?;
         display_loading_stage!(
             indicate_starting_plugin_success,
@@ -834,6 +844,21 @@ impl<'a> PluginLoader<'a> {
             },
         }
     }
+    fn granted_permissions(&self) -> HashSet<PermissionType> {
+        if self.plugin.is_builtin() {
+            // plugins that ship with zellij are trusted
+            return PermissionType::iter().collect();
+        }
+        let mut granted_permissions: HashSet<PermissionType> =
+            PermissionCache::from_path(self.plugin_dir.join(PLUGIN_PERMISSIONS_CACHE_FILE))
+                .get_permissions(&Url::from(&self.plugin.location).to_string())
+                .map(|permissions| permissions.iter().copied().collect())
+                .unwrap_or_default();
+        if self.plugin._allow_exec_host_cmd {
+            granted_permissions.insert(PermissionType::RunCommands);
+        }
+        granted_permissions
+    }
     fn create_plugin_instance_env_and_subscriptions(
         &self,
         module: &Module,
@@ -876,6 +901,8 @@ impl<'a> PluginLoader<'a> {
             client_attributes: self.client_attributes.clone(),
             default_shell: self.default_shell.clone(),
             default_layout: self.default_layout.clone(),
+            permissions: Arc::new(Mutex::new(self.granted_permissions())),
+            plugin_cwd: self.zellij_cwd.clone(),
         };
 
         let subscriptions = Arc::new(Mutex::new(HashSet::new()));
@@ -14,9 +14,10 @@ use zellij_utils::async_channel::Sender;
 use zellij_utils::errors::prelude::*;
 use zellij_utils::{
     data::EventType,
+    data::PermissionType,
     data::PluginCapabilities,
     input::command::TerminalAction,
-    input::layout::{Layout, RunPluginLocation},
+    input::layout::{Layout, PluginUserConfiguration, RunPluginLocation},
     input::plugins::PluginConfig,
     ipc::ClientAttributes,
 };
@@ -157,13 +158,16 @@ impl PluginMap {
     pub fn all_plugin_ids_for_plugin_location(
         &self,
         plugin_location: &RunPluginLocation,
+        plugin_configuration: &PluginUserConfiguration,
     ) -> Result<Vec<PluginId>> {
         let err_context = || format!("Failed to get plugin ids for location {plugin_location}");
         let plugin_ids: Vec<PluginId> = self
             .plugin_assets
             .iter()
             .filter(|(_, (running_plugin, _subscriptions, _workers))| {
-                &running_plugin.lock().unwrap().plugin_env.plugin.location == plugin_location
+                let plugin = &running_plugin.lock().unwrap().plugin_env.plugin;
+                &plugin.location == plugin_location
+                    && &plugin.userspace_configuration == plugin_configuration
             })
             .map(|((plugin_id, _client_id), _)| *plugin_id)
             .collect();
@@ -204,6 +208,8 @@ pub struct PluginEnv {
     pub client_attributes: ClientAttributes,
     pub default_shell: Option<TerminalAction>,
     pub default_layout: Box<Layout>,
+    pub permissions: Arc<Mutex<HashSet<PermissionType>>>,
+    pub plugin_cwd: PathBuf,
 }
 
 impl PluginEnv {
@@ -215,6 +221,12 @@ impl PluginEnv {
             self.plugin_id
         )
     }
+    pub fn has_permission(&self, permission: PermissionType) -> bool {
+        self.permissions
+            .lock()
+            .map(|permissions| permissions.contains(&permission))
+            .unwrap_or(false)
+    }
 }
 
 #[derive(Eq, PartialEq, Hash)]
@@ -1,17 +1,21 @@
+use super::plugin_loader::PLUGIN_PERMISSIONS_CACHE_FILE;
 use super::plugin_thread_main;
 use crate::screen::ScreenInstruction;
 use crate::{channels::SenderWithContext, thread_bus::Bus, ServerInstruction};
 use insta::assert_snapshot;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tempfile::tempdir;
+use url::Url;
 use wasmer::Store;
-use zellij_utils::data::{Event, Key, PluginCapabilities};
+use zellij_utils::data::{Event, Key, PermissionType, PluginCapabilities};
 use zellij_utils::errors::ErrorContext;
 use zellij_utils::input::layout::{Layout, RunPlugin, RunPluginLocation};
+use zellij_utils::input::permission::PermissionCache;
 use zellij_utils::input::plugins::PluginsConfig;
 use zellij_utils::ipc::ClientAttributes;
 use zellij_utils::lazy_static::lazy_static;
 use zellij_utils::pane_size::Size;
+use zellij_utils::strum::IntoEnumIterator;
 
 use crate::background_jobs::BackgroundJob;
 use crate::pty_writer::PtyWriteInstruction;
@@ -80,6 +84,17 @@ macro_rules! log_actions_in_thread_naked_variant {
     };
 }
 
+// the fixture plugin does not request any permissions, so we grant it all of them in advance
+fn grant_all_permissions_to_fixture_plugin(data_dir: &Path) {
+    let fixture_location = RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE));
+    PermissionCache::from_path(data_dir.join("plugins").join(PLUGIN_PERMISSIONS_CACHE_FILE))
+        .cache(
+            Url::from(&fixture_location).to_string(),
+            PermissionType::iter(),
+        )
+        .unwrap();
+}
+
 fn create_plugin_thread(
     zellij_cwd: Option<PathBuf>,
 ) -> (
@@ -121,6 +136,7 @@ fn create_plugin_thread(
     .should_silently_fail();
     let store = Store::new(&wasmer::Universal::new(wasmer::Singlepass::default()).engine());
     let data_dir = PathBuf::from(tempdir().unwrap().path());
+    grant_all_permissions_to_fixture_plugin(&data_dir);
     let default_shell = PathBuf::from(".");
     let plugin_capabilities = PluginCapabilities::default();
     let client_attributes = ClientAttributes::default();
@@ -202,6 +218,7 @@ fn create_plugin_thread_with_server_receiver(
     .should_silently_fail();
     let store = Store::new(&wasmer::Universal::new(wasmer::Singlepass::default()).engine());
     let data_dir = PathBuf::from(tempdir().unwrap().path());
+    grant_all_permissions_to_fixture_plugin(&data_dir);
     let default_shell = PathBuf::from(".");
     let plugin_capabilities = PluginCapabilities::default();
     let client_attributes = ClientAttributes::default();
@@ -284,6 +301,7 @@ fn create_plugin_thread_with_pty_receiver(
     .should_silently_fail();
     let store = Store::new(&wasmer::Universal::new(wasmer::Singlepass::default()).engine());
     let data_dir = PathBuf::from(tempdir().unwrap().path());
+    grant_all_permissions_to_fixture_plugin(&data_dir);
     let default_shell = PathBuf::from(".");
     let plugin_capabilities = PluginCapabilities::default();
     let client_attributes = ClientAttributes::default();
@@ -348,6 +366,7 @@ pub fn load_new_plugin_from_hd() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -406,6 +425,7 @@ pub fn plugin_workers() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -467,6 +487,7 @@ pub fn plugin_workers_persist_state() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -540,6 +561,7 @@ pub fn can_subscribe_to_hd_events() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -604,6 +626,7 @@ pub fn switch_to_mode_plugin_command() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -665,6 +688,7 @@ pub fn new_tabs_with_layout_plugin_command() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -740,6 +764,7 @@ pub fn new_tab_plugin_command() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -801,6 +826,7 @@ pub fn go_to_next_tab_plugin_command() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -861,6 +887,7 @@ pub fn go_to_previous_tab_plugin_command() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -921,6 +948,7 @@ pub fn resize_focused_pane_plugin_command() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -981,6 +1009,7 @@ pub fn resize_focused_pane_with_direction_plugin_command() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -1041,6 +1070,7 @@ pub fn focus_next_pane_plugin_command() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -1101,6 +1131,7 @@ pub fn focus_previous_pane_plugin_command() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -1161,6 +1192,7 @@ pub fn move_focus_plugin_command() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -1221,6 +1253,7 @@ pub fn move_focus_or_tab_plugin_command() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -1281,6 +1314,7 @@ pub fn edit_scrollback_plugin_command() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -1341,6 +1375,7 @@ pub fn write_plugin_command() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -1401,6 +1436,7 @@ pub fn write_chars_plugin_command() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -1461,6 +1497,7 @@ pub fn toggle_tab_plugin_command() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -1521,6 +1558,7 @@ pub fn move_pane_plugin_command() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -1581,6 +1619,7 @@ pub fn move_pane_with_direction_plugin_command() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -1641,6 +1680,7 @@ pub fn clear_screen_plugin_command() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -1701,6 +1741,7 @@ pub fn scroll_up_plugin_command() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -1761,6 +1802,7 @@ pub fn scroll_down_plugin_command() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -1821,6 +1863,7 @@ pub fn scroll_to_top_plugin_command() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -1881,6 +1924,7 @@ pub fn scroll_to_bottom_plugin_command() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -1941,6 +1985,7 @@ pub fn page_scroll_up_plugin_command() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -2001,6 +2046,7 @@ pub fn page_scroll_down_plugin_command() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -2061,6 +2107,7 @@ pub fn toggle_focus_fullscreen_plugin_command() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -2121,6 +2168,7 @@ pub fn toggle_pane_frames_plugin_command() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -2181,6 +2229,7 @@ pub fn toggle_pane_embed_or_eject_plugin_command() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -2241,6 +2290,7 @@ pub fn undo_rename_pane_plugin_command() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -2301,6 +2351,7 @@ pub fn close_focus_plugin_command() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -2361,6 +2412,7 @@ pub fn toggle_active_tab_sync_plugin_command() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -2421,6 +2473,7 @@ pub fn close_focused_tab_plugin_command() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -2481,6 +2534,7 @@ pub fn undo_rename_tab_plugin_command() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -2541,6 +2595,7 @@ pub fn previous_swap_layout_plugin_command() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -2601,6 +2656,7 @@ pub fn next_swap_layout_plugin_command() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -2661,6 +2717,7 @@ pub fn go_to_tab_name_plugin_command() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -2721,6 +2778,7 @@ pub fn focus_or_create_tab_plugin_command() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -2781,6 +2839,7 @@ pub fn go_to_tab() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -2841,6 +2900,7 @@ pub fn start_or_reload_plugin() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -2901,6 +2961,7 @@ pub fn quit_zellij_plugin_command() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -2961,6 +3022,7 @@ pub fn detach_plugin_command() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -3021,6 +3083,7 @@ pub fn open_file_floating_plugin_command() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -3081,6 +3144,7 @@ pub fn open_file_plugin_command() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -3141,6 +3205,7 @@ pub fn open_file_with_line_plugin_command() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -3201,6 +3266,7 @@ pub fn open_file_with_line_floating_plugin_command() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -3261,6 +3327,7 @@ pub fn open_terminal_plugin_command() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -3321,6 +3388,7 @@ pub fn open_terminal_floating_plugin_command() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -3381,6 +3449,7 @@ pub fn open_command_pane_plugin_command() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -3441,6 +3510,7 @@ pub fn open_command_pane_floating_plugin_command() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -3501,6 +3571,7 @@ pub fn switch_to_tab_plugin_command() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -3561,6 +3632,7 @@ pub fn hide_self_plugin_command() {
     let plugin_title = Some("test_plugin".to_owned());
     let run_plugin = RunPlugin {
         _allow_exec_host_cmd: false,
+        configuration: Default::default(),
         location: RunPluginLocation::File(PathBuf::from(&*PLUGIN_FIXTURE)),
     };
     let tab_index = 1;
@@ -1,5 +1,7 @@
 use super::{PluginId, PluginInstruction};
-use crate::plugins::plugin_loader::{PluginLoader, VersionMismatchError};
+use crate::plugins::plugin_loader::{
+    PluginLoader, VersionMismatchError, PLUGIN_PERMISSIONS_CACHE_FILE,
+};
 use crate::plugins::plugin_map::{AtomicEvent, PluginEnv, PluginMap, RunningPlugin, Subscriptions};
 use crate::plugins::plugin_worker::MessageToWorker;
 use crate::plugins::watch_filesystem::watch_filesystem;
@@ -10,28 +12,34 @@ use std::{
     path::PathBuf,
     str::FromStr,
     sync::{Arc, Mutex},
+    time::Duration,
 };
+use url::Url;
 use wasmer::{Instance, Module, Store, Value};
 use zellij_utils::async_std::task::{self, JoinHandle};
 use zellij_utils::notify::{RecommendedWatcher, Watcher};
 
 use crate::{
     background_jobs::BackgroundJob, screen::ScreenInstruction, thread_bus::ThreadSenders,
-    ui::loading_indication::LoadingIndication, ClientId,
+    ui::loading_indication::LoadingIndication, ClientId, ServerInstruction,
 };
 use zellij_utils::{
     consts::VERSION,
-    data::{Event, EventType, PluginCapabilities},
+    data::{Event, EventType, PermissionStatus, PermissionType, PipeSource, PluginCapabilities},
     errors::prelude::*,
     input::{
         command::TerminalAction,
         layout::{Layout, RunPlugin, RunPluginLocation},
+        permission::PermissionCache,
         plugins::PluginsConfig,
     },
-    ipc::ClientAttributes,
+    ipc::{ClientAttributes, ExitReason},
     pane_size::Size,
 };
 
+// how long a cli pipe is kept open for the plugins it was sent to to close it
+const CLI_PIPE_TIMEOUT: Duration = Duration::from_secs(30);
+
 pub struct WasmBridge {
     connected_clients: Arc<Mutex<Vec<ClientId>>>,
     plugins: PluginsConfig,
@@ -56,6 +64,9 @@ pub struct WasmBridge {
     client_attributes: ClientAttributes,
     default_shell: Option<TerminalAction>,
     default_layout: Box<Layout>,
+    // pipe id to the cli client waiting on it and the plugins the pipe was delivered to
+    cli_pipes: HashMap<String, (ClientId, HashSet<PluginId>)>,
+    polling_session_infos: bool,
 }
 
 impl WasmBridge {
@@ -97,6 +108,8 @@ impl WasmBridge {
             client_attributes,
             default_shell,
             default_layout,
+            cli_pipes: HashMap::new(),
+            polling_session_infos: false,
         }
     }
     pub fn load_plugin(
@@ -206,12 +219,12 @@ impl WasmBridge {
         Ok(())
     }
     pub fn reload_plugin(&mut self, run_plugin: &RunPlugin) -> Result<()> {
-        if self.plugin_is_currently_being_loaded(&run_plugin.location) {
+        if self.plugin_is_currently_being_loaded(&run_plugin) {
             self.pending_plugin_reloads.insert(run_plugin.clone());
             return Ok(());
         }
 
-        let plugin_ids = self.all_plugin_ids_for_plugin_location(&run_plugin.location)?;
+        let plugin_ids = self.all_plugin_ids_for_plugin(&run_plugin)?;
         for plugin_id in &plugin_ids {
             let (rows, columns) = self.size_of_plugin_id(*plugin_id).unwrap_or((0, 0));
             self.cached_events_for_pending_plugins
@@ -499,6 +512,195 @@ impl WasmBridge {
         }
         Ok(())
     }
+    pub fn pipe_message(
+        &mut self,
+        destination: Option<RunPluginLocation>,
+        source: PipeSource,
+        name: String,
+        payload: Option<String>,
+    ) -> Result<()> {
+        let plugins_to_update: Vec<(PluginId, ClientId, Arc<Mutex<RunningPlugin>>)> = self
+            .plugin_map
+            .lock()
+            .unwrap()
+            .running_plugins_and_subscriptions()
+            .drain(..)
+            .filter(|(plugin_id, _client_id, running_plugin, subscriptions)| {
+                let is_source = source == PipeSource::Plugin(*plugin_id);
+                let is_pending = self
+                    .cached_events_for_pending_plugins
+                    .contains_key(plugin_id);
+                let is_subscribed = subscriptions
+                    .lock()
+                    .unwrap()
+                    .contains(&EventType::PipeMessage);
+                let is_destination = destination.as_ref().map_or(true, |destination| {
+                    &running_plugin.lock().unwrap().plugin_env.plugin.location == destination
+                });
+                !is_source && !is_pending && is_subscribed && is_destination
+            })
+            .map(|(plugin_id, client_id, running_plugin, _subscriptions)| {
+                (plugin_id, client_id, running_plugin)
+            })
+            .collect();
+        if plugins_to_update.is_empty() {
+            if let PipeSource::Cli(pipe_id) = &source {
+                let error = match &destination {
+                    Some(destination) => format!(
+                        "No running plugin at {} is listening to pipe messages",
+                        destination
+                    ),
+                    None => "No running plugin is listening to pipe messages".to_owned(),
+                };
+                self.close_cli_pipe(pipe_id, ExitReason::Error(error));
+            }
+            return Ok(());
+        }
+        if let PipeSource::Cli(pipe_id) = &source {
+            if let Some((_client_id, recipients)) = self.cli_pipes.get_mut(pipe_id) {
+                recipients.extend(plugins_to_update.iter().map(|(plugin_id, ..)| *plugin_id));
+            }
+        }
+        let event = Event::PipeMessage(source.clone(), name, payload);
+        task::spawn({
+            let senders = self.senders.clone();
+            async move {
+                for (plugin_id, client_id, running_plugin) in plugins_to_update {
+                    let running_plugin = running_plugin.lock().unwrap();
+                    let mut plugin_bytes = vec![];
+                    match apply_event_to_plugin(
+                        plugin_id,
+                        client_id,
+                        &running_plugin.instance,
+                        &running_plugin.plugin_env,
+                        &event,
+                        running_plugin.rows,
+                        running_plugin.columns,
+                        &mut plugin_bytes,
+                    ) {
+                        Ok(()) => {
+                            let _ = senders
+                                .send_to_screen(ScreenInstruction::PluginBytes(plugin_bytes));
+                        },
+                        Err(e) => {
+                            log::error!("{:?}", e);
+                            let stringified_error = format!("{:?}", e).replace("\n", "\n\r");
+                            handle_plugin_crash(plugin_id, stringified_error, senders.clone());
+                        },
+                    }
+                }
+            }
+        });
+        Ok(())
+    }
+    pub fn cli_pipe(
+        &mut self,
+        pipe_id: String,
+        destination: Option<RunPluginLocation>,
+        name: String,
+        payload: Option<String>,
+        client_id: ClientId,
+    ) -> Result<()> {
+        self.cli_pipes
+            .insert(pipe_id.clone(), (client_id, HashSet::new()));
+        // the pipe stays open until one of the plugins it was sent to closes it, plugins that
+        // never do (eg. because they crashed) do not leave the cli client hanging
+        task::spawn({
+            let senders = self.senders.clone();
+            let pipe_id = pipe_id.clone();
+            async move {
+                task::sleep(CLI_PIPE_TIMEOUT).await;
+                let _ = senders.send_to_plugin(PluginInstruction::CliPipeTimedOut(pipe_id));
+            }
+        });
+        self.pipe_message(destination, PipeSource::Cli(pipe_id), name, payload)
+    }
+    pub fn cli_pipe_output(
+        &mut self,
+        plugin_id: PluginId,
+        pipe_id: &str,
+        output: String,
+    ) -> Result<()> {
+        // only the plugins a pipe was sent to can write to it
+        match self.cli_pipes.get(pipe_id) {
+            Some((client_id, recipients)) if recipients.contains(&plugin_id) => self
+                .senders
+                .send_to_server(ServerInstruction::CliPipeOutput(output, *client_id))
+                .with_context(|| format!("failed to send output to cli pipe {pipe_id}")),
+            _ => {
+                log::error!("Cli pipe {} not found for plugin {}", pipe_id, plugin_id);
+                Ok(())
+            },
+        }
+    }
+    pub fn close_cli_pipe_from_plugin(&mut self, plugin_id: PluginId, pipe_id: &str) -> Result<()> {
+        // only the plugins a pipe was sent to can close it
+        match self.cli_pipes.get(pipe_id) {
+            Some((_client_id, recipients)) if recipients.contains(&plugin_id) => {
+                self.close_cli_pipe(pipe_id, ExitReason::Normal);
+            },
+            _ => {
+                log::error!("Cli pipe {} not found for plugin {}", pipe_id, plugin_id);
+            },
+        }
+        Ok(())
+    }
+    pub fn cli_pipe_timed_out(&mut self, pipe_id: &str) -> Result<()> {
+        // pipes closed in time are gone by now
+        self.close_cli_pipe(
+            pipe_id,
+            ExitReason::Error("Timed out waiting for the plugins to close the pipe".to_owned()),
+        );
+        Ok(())
+    }
+    fn close_cli_pipe(&mut self, pipe_id: &str, exit_reason: ExitReason) {
+        if let Some((client_id, _recipients)) = self.cli_pipes.remove(pipe_id) {
+            let _ = self
+                .senders
+                .send_to_server(ServerInstruction::CloseCliPipe(client_id, exit_reason));
+        }
+    }
+    pub fn handle_permission_request_result(
+        &mut self,
+        plugin_id: PluginId,
+        client_id: ClientId,
+        permissions: Vec<PermissionType>,
+        status: PermissionStatus,
+    ) -> Result<()> {
+        let err_context = || format!("failed to grant permissions to plugin {plugin_id}");
+        if status == PermissionStatus::Granted {
+            let running_plugins = self.plugin_map.lock().unwrap().running_plugins();
+            let mut plugin_url = None;
+            for (p_id, _client_id, running_plugin) in running_plugins {
+                if p_id == plugin_id {
+                    let running_plugin = running_plugin.lock().unwrap();
+                    running_plugin
+                        .plugin_env
+                        .permissions
+                        .lock()
+                        .to_anyhow()
+                        .with_context(err_context)?
+                        .extend(permissions.iter().copied());
+                    plugin_url = Some(Url::from(&running_plugin.plugin_env.plugin.location));
+                }
+            }
+            if let Some(plugin_url) = plugin_url {
+                PermissionCache::from_path(self.plugin_dir.join(PLUGIN_PERMISSIONS_CACHE_FILE))
+                    .cache(plugin_url.to_string(), permissions)
+                    .with_context(err_context)
+                    .non_fatal();
+            }
+            // the plugin might have missed state updates while it was not permitted to read them
+            let _ = self
+                .senders
+                .send_to_screen(ScreenInstruction::RequestStateUpdateForPlugins);
+        }
+        self.update_plugins(vec![(
+            Some(plugin_id),
+            Some(client_id),
+            Event::PermissionRequestResult(status),
+        )])
+    }
     pub fn apply_cached_events(&mut self, plugin_ids: Vec<PluginId>) -> Result<()> {
         let mut applied_plugin_paths = HashSet::new();
         for plugin_id in plugin_ids {
@@ -616,20 +818,17 @@ impl WasmBridge {
         }
         Ok(())
     }
-    fn plugin_is_currently_being_loaded(&self, plugin_location: &RunPluginLocation) -> bool {
+    fn plugin_is_currently_being_loaded(&self, run_plugin: &RunPlugin) -> bool {
         self.loading_plugins
             .iter()
-            .find(|((_plugin_id, run_plugin), _)| &run_plugin.location == plugin_location)
+            .find(|((_plugin_id, loading_run_plugin), _)| loading_run_plugin == run_plugin)
             .is_some()
     }
-    fn all_plugin_ids_for_plugin_location(
-        &self,
-        plugin_location: &RunPluginLocation,
-    ) -> Result<Vec<PluginId>> {
+    fn all_plugin_ids_for_plugin(&self, run_plugin: &RunPlugin) -> Result<Vec<PluginId>> {
         self.plugin_map
             .lock()
             .unwrap()
-            .all_plugin_ids_for_plugin_location(plugin_location)
+            .all_plugin_ids_for_plugin_location(&run_plugin.location, &run_plugin.configuration)
     }
     fn size_of_plugin_id(&self, plugin_id: PluginId) -> Option<(usize, usize)> {
         // (rows/colums)
@@ -691,6 +890,30 @@ impl WasmBridge {
         }
         Ok(())
     }
+    /// Starts or stops polling the sessions on the machine, depending on whether a plugin is
+    /// currently subscribed to their updates
+    pub fn update_session_info_polling(&mut self) {
+        let has_subscribers = self
+            .plugin_map
+            .lock()
+            .unwrap()
+            .running_plugins_and_subscriptions()
+            .iter()
+            .any(|(_plugin_id, _client_id, _running_plugin, subscriptions)| {
+                subscriptions
+                    .lock()
+                    .unwrap()
+                    .contains(&EventType::SessionUpdate)
+            });
+        if has_subscribers != self.polling_session_infos {
+            self.polling_session_infos = has_subscribers;
+            let _ = self.senders.send_to_background_jobs(if has_subscribers {
+                BackgroundJob::StartPollingSessionInfos
+            } else {
+                BackgroundJob::StopPollingSessionInfos
+            });
+        }
+    }
     pub fn start_fs_watcher_if_not_started(&mut self) {
         if self.watcher.is_none() {
             self.watcher = match watch_filesystem(self.senders.clone(), &self.zellij_cwd) {
@@ -735,6 +958,9 @@ pub fn apply_event_to_plugin(
     plugin_bytes: &mut Vec<(PluginId, ClientId, Vec<u8>)>,
 ) -> Result<()> {
     let err_context = || format!("Failed to apply event to plugin {plugin_id}");
+    if !event_is_permitted(plugin_env, event) {
+        return Ok(());
+    }
     let update = instance
         .exports
         .get_function("update")
@@ -777,6 +1003,19 @@ pub fn apply_event_to_plugin(
     Ok(())
 }
 
+fn event_is_permitted(plugin_env: &PluginEnv, event: &Event) -> bool {
+    match event {
+        Event::ModeUpdate(..)
+        | Event::TabUpdate(..)
+        | Event::PaneUpdate(..)
+        | Event::SessionUpdate(..)
+        | Event::CopyToClipboard(..)
+        | Event::SystemClipboardFailure
+        | Event::InputReceived => plugin_env.has_permission(PermissionType::ReadApplicationState),
+        _ => true,
+    }
+}
+
 pub fn handle_plugin_crash(plugin_id: PluginId, message: String, senders: ThreadSenders) {
     let mut loading_indication = LoadingIndication::new("Panic!".to_owned());
     loading_indication.indicate_loading_error(message);
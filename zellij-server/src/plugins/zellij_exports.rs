@@ -5,7 +5,7 @@ use crate::route::route_action;
 use log::{debug, warn};
 use serde::{de::DeserializeOwned, Serialize};
 use std::{
-    collections::HashSet,
+    collections::{BTreeMap, HashSet},
     path::PathBuf,
     process,
     sync::{Arc, Mutex},
@@ -17,33 +17,40 @@ use wasmer_wasi::WasiEnv;
 
 use url::Url;
 
-use crate::{panes::PaneId, screen::ScreenInstruction};
+use crate::{
+    background_jobs::BackgroundJob, panes::PaneId, pty::PtyInstruction, screen::ScreenInstruction,
+};
 
 use zellij_utils::{
     consts::VERSION,
-    data::{Direction, Event, EventType, InputMode, PluginIds, Resize},
+    data::{
+        self, Direction, Event, EventType, HttpVerb, InputMode, PermissionStatus, PermissionType,
+        PipeSource, PluginIds, Resize, ResizeStrategy,
+    },
     errors::prelude::*,
     input::{
         actions::Action,
         command::{RunCommand, RunCommandAction, TerminalAction},
-        layout::Layout,
+        layout::{Layout, RunPluginLocation},
         plugins::PluginType,
     },
-    serde,
+    serde, sessions,
 };
 
 macro_rules! apply_action {
-    ($action:ident, $error_message:ident, $env: ident) => {
-        if let Err(e) = route_action(
-            $action,
-            $env.plugin_env.client_id,
-            $env.plugin_env.senders.clone(),
-            $env.plugin_env.capabilities.clone(),
-            $env.plugin_env.client_attributes.clone(),
-            $env.plugin_env.default_shell.clone(),
-            $env.plugin_env.default_layout.clone(),
-        ) {
-            log::error!("{}: {:?}", $error_message(), e);
+    ($action:ident, $error_message:ident, $env: ident, $permission: expr) => {
+        if has_permission($env, $permission) {
+            if let Err(e) = route_action(
+                $action,
+                $env.plugin_env.client_id,
+                $env.plugin_env.senders.clone(),
+                $env.plugin_env.capabilities.clone(),
+                $env.plugin_env.client_attributes.clone(),
+                $env.plugin_env.default_shell.clone(),
+                $env.plugin_env.default_layout.clone(),
+            ) {
+                log::error!("{}: {:?}", $error_message(), e);
+            }
         }
     };
 }
@@ -68,6 +75,7 @@ pub fn zellij_exports(
         host_subscribe,
         host_unsubscribe,
         host_set_selectable,
+        host_request_permission,
         host_get_plugin_ids,
         host_get_zellij_version,
         host_open_file,
@@ -125,6 +133,22 @@ pub fn zellij_exports(
         host_focus_or_create_tab,
         host_go_to_tab,
         host_start_or_reload_plugin,
+        host_focus_pane_with_id,
+        host_close_pane_with_id,
+        host_write_to_pane_with_id,
+        host_write_chars_to_pane_with_id,
+        host_rename_pane_with_id,
+        host_resize_pane_with_id,
+        host_toggle_pane_embed_or_eject_with_id,
+        host_rerun_command_pane,
+        host_web_request,
+        host_run_command,
+        host_pipe_message_to_plugins,
+        host_cli_pipe_output,
+        host_close_cli_pipe,
+        host_switch_session,
+        host_rename_session,
+        host_kill_sessions,
     }
 }
 
@@ -169,7 +193,16 @@ fn host_unsubscribe(env: &ForeignFunctionEnv) {
                 .lock()
                 .to_anyhow()?
                 .retain(|k| !old.contains(k));
-            Ok(())
+            Ok(old)
+        })
+        .and_then(|old| {
+            env.plugin_env
+                .senders
+                .send_to_plugin(PluginInstruction::PluginUnsubscribedFromEvents(
+                    env.plugin_env.plugin_id,
+                    env.plugin_env.client_id,
+                    old,
+                ))
         })
         .with_context(|| format!("failed to unsubscribe for plugin {}", env.plugin_env.name()))
         .fatal();
@@ -204,6 +237,42 @@ fn host_set_selectable(env: &ForeignFunctionEnv, selectable: i32) {
     }
 }
 
+fn host_request_permission(env: &ForeignFunctionEnv) {
+    wasi_read_object::<Vec<PermissionType>>(&env.plugin_env.wasi_env)
+        .and_then(|permissions| {
+            if permissions
+                .iter()
+                .all(|p| env.plugin_env.has_permission(*p))
+            {
+                // everything was already granted (eg. from the permission cache), no need to
+                // bother the user
+                return env
+                    .plugin_env
+                    .senders
+                    .send_to_plugin(PluginInstruction::Update(vec![(
+                        Some(env.plugin_env.plugin_id),
+                        Some(env.plugin_env.client_id),
+                        Event::PermissionRequestResult(PermissionStatus::Granted),
+                    )]));
+            }
+            env.plugin_env
+                .senders
+                .send_to_screen(ScreenInstruction::RequestPluginPermissions(
+                    env.plugin_env.plugin_id,
+                    env.plugin_env.client_id,
+                    env.plugin_env.plugin.location.to_string(),
+                    permissions,
+                ))
+        })
+        .with_context(|| {
+            format!(
+                "failed to request permissions for plugin {}",
+                env.plugin_env.name()
+            )
+        })
+        .non_fatal();
+}
+
 fn host_get_plugin_ids(env: &ForeignFunctionEnv) {
     let ids = PluginIds {
         plugin_id: env.plugin_env.plugin_id,
@@ -241,7 +310,7 @@ fn host_open_file(env: &ForeignFunctionEnv) {
             };
             let floating = false;
             let action = Action::EditFile(path, None, None, None, floating); // TODO: add cwd
-            apply_action!(action, error_msg, env);
+            apply_action!(action, error_msg, env, PermissionType::OpenFiles);
             Ok(())
         })
         .with_context(|| {
@@ -259,7 +328,7 @@ fn host_open_file_floating(env: &ForeignFunctionEnv) {
             let error_msg = || format!("failed to open file in plugin {}", env.plugin_env.name());
             let floating = true;
             let action = Action::EditFile(path, None, None, None, floating); // TODO: add cwd
-            apply_action!(action, error_msg, env);
+            apply_action!(action, error_msg, env, PermissionType::OpenFiles);
             Ok(())
         })
         .with_context(|| {
@@ -277,7 +346,7 @@ fn host_open_file_with_line(env: &ForeignFunctionEnv) {
             let error_msg = || format!("failed to open file in plugin {}", env.plugin_env.name());
             let floating = false;
             let action = Action::EditFile(path, Some(line), None, None, floating); // TODO: add cwd
-            apply_action!(action, error_msg, env);
+            apply_action!(action, error_msg, env, PermissionType::OpenFiles);
             Ok(())
         })
         .with_context(|| {
@@ -295,7 +364,7 @@ fn host_open_file_with_line_floating(env: &ForeignFunctionEnv) {
             let error_msg = || format!("failed to open file in plugin {}", env.plugin_env.name());
             let floating = true;
             let action = Action::EditFile(path, Some(line), None, None, floating); // TODO: add cwd
-            apply_action!(action, error_msg, env);
+            apply_action!(action, error_msg, env, PermissionType::OpenFiles);
             Ok(())
         })
         .with_context(|| {
@@ -322,7 +391,12 @@ fn host_open_terminal(env: &ForeignFunctionEnv) {
                 _ => None,
             };
             let action = Action::NewTiledPane(None, run_command_action, None);
-            apply_action!(action, error_msg, env);
+            apply_action!(
+                action,
+                error_msg,
+                env,
+                PermissionType::OpenTerminalsOrPlugins
+            );
             Ok(())
         })
         .with_context(|| {
@@ -349,7 +423,12 @@ fn host_open_terminal_floating(env: &ForeignFunctionEnv) {
                 _ => None,
             };
             let action = Action::NewFloatingPane(run_command_action, None);
-            apply_action!(action, error_msg, env);
+            apply_action!(
+                action,
+                error_msg,
+                env,
+                PermissionType::OpenTerminalsOrPlugins
+            );
             Ok(())
         })
         .with_context(|| {
@@ -379,7 +458,7 @@ fn host_open_command_pane(env: &ForeignFunctionEnv) {
                 hold_on_start,
             };
             let action = Action::NewTiledPane(direction, Some(run_command_action), name);
-            apply_action!(action, error_msg, env);
+            apply_action!(action, error_msg, env, PermissionType::RunCommands);
             Ok(())
         })
         .with_context(error_msg)
@@ -404,7 +483,7 @@ fn host_open_command_pane_floating(env: &ForeignFunctionEnv) {
                 hold_on_start,
             };
             let action = Action::NewFloatingPane(Some(run_command_action), name);
-            apply_action!(action, error_msg, env);
+            apply_action!(action, error_msg, env, PermissionType::RunCommands);
             Ok(())
         })
         .with_context(error_msg)
@@ -412,6 +491,9 @@ fn host_open_command_pane_floating(env: &ForeignFunctionEnv) {
 }
 
 fn host_switch_tab_to(env: &ForeignFunctionEnv, tab_idx: u32) {
+    if !has_permission(env, PermissionType::ChangeApplicationState) {
+        return;
+    }
     env.plugin_env
         .senders
         .send_to_screen(ScreenInstruction::GoToTab(
@@ -484,7 +566,7 @@ fn host_exec_cmd(env: &ForeignFunctionEnv) {
     let command = cmdline.remove(0);
 
     // Bail out if we're forbidden to run command
-    if !env.plugin_env.plugin._allow_exec_host_cmd {
+    if !has_permission(env, PermissionType::RunCommands) {
         warn!("This plugin isn't allow to run command in host side, skip running this command: '{cmd} {args}'.",
         	cmd = command, args = cmdline.join(" "));
         return;
@@ -551,7 +633,12 @@ fn host_switch_to_mode(env: &ForeignFunctionEnv) {
                     env.plugin_env.name()
                 )
             };
-            apply_action!(action, error_msg, env);
+            apply_action!(
+                action,
+                error_msg,
+                env,
+                PermissionType::ChangeApplicationState
+            );
             Ok(())
         })
         .with_context(|| format!("failed to subscribe for plugin {}", env.plugin_env.name()))
@@ -595,7 +682,12 @@ fn host_new_tabs_with_layout(env: &ForeignFunctionEnv) {
             }
             for action in tabs_to_open {
                 let error_msg = || format!("Failed to create layout tab");
-                apply_action!(action, error_msg, env);
+                apply_action!(
+                    action,
+                    error_msg,
+                    env,
+                    PermissionType::ChangeApplicationState
+                );
             }
             Ok(())
         })
@@ -605,19 +697,34 @@ fn host_new_tabs_with_layout(env: &ForeignFunctionEnv) {
 fn host_new_tab(env: &ForeignFunctionEnv) {
     let action = Action::NewTab(None, vec![], None, None, None);
     let error_msg = || format!("Failed to open new tab");
-    apply_action!(action, error_msg, env);
+    apply_action!(
+        action,
+        error_msg,
+        env,
+        PermissionType::ChangeApplicationState
+    );
 }
 
 fn host_go_to_next_tab(env: &ForeignFunctionEnv) {
     let action = Action::GoToNextTab;
     let error_msg = || format!("Failed to go to next tab");
-    apply_action!(action, error_msg, env);
+    apply_action!(
+        action,
+        error_msg,
+        env,
+        PermissionType::ChangeApplicationState
+    );
 }
 
 fn host_go_to_previous_tab(env: &ForeignFunctionEnv) {
     let action = Action::GoToPreviousTab;
     let error_msg = || format!("Failed to go to previous tab");
-    apply_action!(action, error_msg, env);
+    apply_action!(
+        action,
+        error_msg,
+        env,
+        PermissionType::ChangeApplicationState
+    );
 }
 
 fn host_resize(env: &ForeignFunctionEnv) {
@@ -625,7 +732,12 @@ fn host_resize(env: &ForeignFunctionEnv) {
     wasi_read_object::<Resize>(&env.plugin_env.wasi_env)
         .and_then(|resize| {
             let action = Action::Resize(resize, None);
-            apply_action!(action, error_msg, env);
+            apply_action!(
+                action,
+                error_msg,
+                env,
+                PermissionType::ChangeApplicationState
+            );
             Ok(())
         })
         .with_context(error_msg)
@@ -637,7 +749,12 @@ fn host_resize_with_direction(env: &ForeignFunctionEnv) {
     wasi_read_object::<(Resize, Direction)>(&env.plugin_env.wasi_env)
         .and_then(|(resize, direction)| {
             let action = Action::Resize(resize, Some(direction));
-            apply_action!(action, error_msg, env);
+            apply_action!(
+                action,
+                error_msg,
+                env,
+                PermissionType::ChangeApplicationState
+            );
             Ok(())
         })
         .with_context(error_msg)
@@ -647,13 +764,23 @@ fn host_resize_with_direction(env: &ForeignFunctionEnv) {
 fn host_focus_next_pane(env: &ForeignFunctionEnv) {
     let action = Action::FocusNextPane;
     let error_msg = || format!("Failed to focus next pane");
-    apply_action!(action, error_msg, env);
+    apply_action!(
+        action,
+        error_msg,
+        env,
+        PermissionType::ChangeApplicationState
+    );
 }
 
 fn host_focus_previous_pane(env: &ForeignFunctionEnv) {
     let action = Action::FocusPreviousPane;
     let error_msg = || format!("Failed to focus previous pane");
-    apply_action!(action, error_msg, env);
+    apply_action!(
+        action,
+        error_msg,
+        env,
+        PermissionType::ChangeApplicationState
+    );
 }
 
 fn host_move_focus(env: &ForeignFunctionEnv) {
@@ -661,7 +788,12 @@ fn host_move_focus(env: &ForeignFunctionEnv) {
     wasi_read_object::<Direction>(&env.plugin_env.wasi_env)
         .and_then(|direction| {
             let action = Action::MoveFocus(direction);
-            apply_action!(action, error_msg, env);
+            apply_action!(
+                action,
+                error_msg,
+                env,
+                PermissionType::ChangeApplicationState
+            );
             Ok(())
         })
         .with_context(error_msg)
@@ -673,7 +805,12 @@ fn host_move_focus_or_tab(env: &ForeignFunctionEnv) {
     wasi_read_object::<Direction>(&env.plugin_env.wasi_env)
         .and_then(|direction| {
             let action = Action::MoveFocusOrTab(direction);
-            apply_action!(action, error_msg, env);
+            apply_action!(
+                action,
+                error_msg,
+                env,
+                PermissionType::ChangeApplicationState
+            );
             Ok(())
         })
         .with_context(error_msg)
@@ -683,13 +820,23 @@ fn host_move_focus_or_tab(env: &ForeignFunctionEnv) {
 fn host_detach(env: &ForeignFunctionEnv) {
     let action = Action::Detach;
     let error_msg = || format!("Failed to detach");
-    apply_action!(action, error_msg, env);
+    apply_action!(
+        action,
+        error_msg,
+        env,
+        PermissionType::ChangeApplicationState
+    );
 }
 
 fn host_edit_scrollback(env: &ForeignFunctionEnv) {
     let action = Action::EditScrollback;
     let error_msg = || format!("Failed to edit scrollback");
-    apply_action!(action, error_msg, env);
+    apply_action!(
+        action,
+        error_msg,
+        env,
+        PermissionType::ChangeApplicationState
+    );
 }
 
 fn host_write(env: &ForeignFunctionEnv) {
@@ -697,7 +844,7 @@ fn host_write(env: &ForeignFunctionEnv) {
     wasi_read_object::<Vec<u8>>(&env.plugin_env.wasi_env)
         .and_then(|bytes| {
             let action = Action::Write(bytes);
-            apply_action!(action, error_msg, env);
+            apply_action!(action, error_msg, env, PermissionType::WriteToStdin);
             Ok(())
         })
         .with_context(error_msg)
@@ -709,7 +856,7 @@ fn host_write_chars(env: &ForeignFunctionEnv) {
     wasi_read_string(&env.plugin_env.wasi_env)
         .and_then(|chars_to_write| {
             let action = Action::WriteChars(chars_to_write);
-            apply_action!(action, error_msg, env);
+            apply_action!(action, error_msg, env, PermissionType::WriteToStdin);
             Ok(())
         })
         .with_context(error_msg)
@@ -719,13 +866,23 @@ fn host_write_chars(env: &ForeignFunctionEnv) {
 fn host_toggle_tab(env: &ForeignFunctionEnv) {
     let action = Action::ToggleTab;
     let error_msg = || format!("Failed to toggle tab");
-    apply_action!(action, error_msg, env);
+    apply_action!(
+        action,
+        error_msg,
+        env,
+        PermissionType::ChangeApplicationState
+    );
 }
 
 fn host_move_pane(env: &ForeignFunctionEnv) {
     let error_msg = || format!("failed to move pane in plugin {}", env.plugin_env.name());
     let action = Action::MovePane(None);
-    apply_action!(action, error_msg, env);
+    apply_action!(
+        action,
+        error_msg,
+        env,
+        PermissionType::ChangeApplicationState
+    );
 }
 
 fn host_move_pane_with_direction(env: &ForeignFunctionEnv) {
@@ -733,7 +890,12 @@ fn host_move_pane_with_direction(env: &ForeignFunctionEnv) {
     wasi_read_object::<Direction>(&env.plugin_env.wasi_env)
         .and_then(|direction| {
             let action = Action::MovePane(Some(direction));
-            apply_action!(action, error_msg, env);
+            apply_action!(
+                action,
+                error_msg,
+                env,
+                PermissionType::ChangeApplicationState
+            );
             Ok(())
         })
         .with_context(error_msg)
@@ -743,42 +905,77 @@ fn host_move_pane_with_direction(env: &ForeignFunctionEnv) {
 fn host_clear_screen(env: &ForeignFunctionEnv) {
     let error_msg = || format!("failed to clear screen in plugin {}", env.plugin_env.name());
     let action = Action::ClearScreen;
-    apply_action!(action, error_msg, env);
+    apply_action!(
+        action,
+        error_msg,
+        env,
+        PermissionType::ChangeApplicationState
+    );
 }
 fn host_scroll_up(env: &ForeignFunctionEnv) {
     let error_msg = || format!("failed to scroll up in plugin {}", env.plugin_env.name());
     let action = Action::ScrollUp;
-    apply_action!(action, error_msg, env);
+    apply_action!(
+        action,
+        error_msg,
+        env,
+        PermissionType::ChangeApplicationState
+    );
 }
 
 fn host_scroll_down(env: &ForeignFunctionEnv) {
     let error_msg = || format!("failed to scroll down in plugin {}", env.plugin_env.name());
     let action = Action::ScrollDown;
-    apply_action!(action, error_msg, env);
+    apply_action!(
+        action,
+        error_msg,
+        env,
+        PermissionType::ChangeApplicationState
+    );
 }
 
 fn host_scroll_to_top(env: &ForeignFunctionEnv) {
     let error_msg = || format!("failed to scroll in plugin {}", env.plugin_env.name());
     let action = Action::ScrollToTop;
-    apply_action!(action, error_msg, env);
+    apply_action!(
+        action,
+        error_msg,
+        env,
+        PermissionType::ChangeApplicationState
+    );
 }
 
 fn host_scroll_to_bottom(env: &ForeignFunctionEnv) {
     let error_msg = || format!("failed to scroll in plugin {}", env.plugin_env.name());
     let action = Action::ScrollToBottom;
-    apply_action!(action, error_msg, env);
+    apply_action!(
+        action,
+        error_msg,
+        env,
+        PermissionType::ChangeApplicationState
+    );
 }
 
 fn host_page_scroll_up(env: &ForeignFunctionEnv) {
     let error_msg = || format!("failed to scroll in plugin {}", env.plugin_env.name());
     let action = Action::PageScrollUp;
-    apply_action!(action, error_msg, env);
+    apply_action!(
+        action,
+        error_msg,
+        env,
+        PermissionType::ChangeApplicationState
+    );
 }
 
 fn host_page_scroll_down(env: &ForeignFunctionEnv) {
     let error_msg = || format!("failed to scroll in plugin {}", env.plugin_env.name());
     let action = Action::PageScrollDown;
-    apply_action!(action, error_msg, env);
+    apply_action!(
+        action,
+        error_msg,
+        env,
+        PermissionType::ChangeApplicationState
+    );
 }
 
 fn host_toggle_focus_fullscreen(env: &ForeignFunctionEnv) {
@@ -789,7 +986,12 @@ fn host_toggle_focus_fullscreen(env: &ForeignFunctionEnv) {
         )
     };
     let action = Action::ToggleFocusFullscreen;
-    apply_action!(action, error_msg, env);
+    apply_action!(
+        action,
+        error_msg,
+        env,
+        PermissionType::ChangeApplicationState
+    );
 }
 
 fn host_toggle_pane_frames(env: &ForeignFunctionEnv) {
@@ -800,7 +1002,12 @@ fn host_toggle_pane_frames(env: &ForeignFunctionEnv) {
         )
     };
     let action = Action::TogglePaneFrames;
-    apply_action!(action, error_msg, env);
+    apply_action!(
+        action,
+        error_msg,
+        env,
+        PermissionType::ChangeApplicationState
+    );
 }
 
 fn host_toggle_pane_embed_or_eject(env: &ForeignFunctionEnv) {
@@ -811,7 +1018,12 @@ fn host_toggle_pane_embed_or_eject(env: &ForeignFunctionEnv) {
         )
     };
     let action = Action::TogglePaneEmbedOrFloating;
-    apply_action!(action, error_msg, env);
+    apply_action!(
+        action,
+        error_msg,
+        env,
+        PermissionType::ChangeApplicationState
+    );
 }
 
 fn host_undo_rename_pane(env: &ForeignFunctionEnv) {
@@ -822,7 +1034,12 @@ fn host_undo_rename_pane(env: &ForeignFunctionEnv) {
         )
     };
     let action = Action::UndoRenamePane;
-    apply_action!(action, error_msg, env);
+    apply_action!(
+        action,
+        error_msg,
+        env,
+        PermissionType::ChangeApplicationState
+    );
 }
 
 fn host_close_focus(env: &ForeignFunctionEnv) {
@@ -833,7 +1050,12 @@ fn host_close_focus(env: &ForeignFunctionEnv) {
         )
     };
     let action = Action::CloseFocus;
-    apply_action!(action, error_msg, env);
+    apply_action!(
+        action,
+        error_msg,
+        env,
+        PermissionType::ChangeApplicationState
+    );
 }
 
 fn host_toggle_active_tab_sync(env: &ForeignFunctionEnv) {
@@ -844,7 +1066,12 @@ fn host_toggle_active_tab_sync(env: &ForeignFunctionEnv) {
         )
     };
     let action = Action::ToggleActiveSyncTab;
-    apply_action!(action, error_msg, env);
+    apply_action!(
+        action,
+        error_msg,
+        env,
+        PermissionType::ChangeApplicationState
+    );
 }
 
 fn host_close_focused_tab(env: &ForeignFunctionEnv) {
@@ -855,7 +1082,12 @@ fn host_close_focused_tab(env: &ForeignFunctionEnv) {
         )
     };
     let action = Action::CloseTab;
-    apply_action!(action, error_msg, env);
+    apply_action!(
+        action,
+        error_msg,
+        env,
+        PermissionType::ChangeApplicationState
+    );
 }
 
 fn host_undo_rename_tab(env: &ForeignFunctionEnv) {
@@ -866,13 +1098,23 @@ fn host_undo_rename_tab(env: &ForeignFunctionEnv) {
         )
     };
     let action = Action::UndoRenameTab;
-    apply_action!(action, error_msg, env);
+    apply_action!(
+        action,
+        error_msg,
+        env,
+        PermissionType::ChangeApplicationState
+    );
 }
 
 fn host_quit_zellij(env: &ForeignFunctionEnv) {
     let error_msg = || format!("failed to quit zellij in plugin {}", env.plugin_env.name());
     let action = Action::Quit;
-    apply_action!(action, error_msg, env);
+    apply_action!(
+        action,
+        error_msg,
+        env,
+        PermissionType::ChangeApplicationState
+    );
 }
 
 fn host_previous_swap_layout(env: &ForeignFunctionEnv) {
@@ -883,7 +1125,12 @@ fn host_previous_swap_layout(env: &ForeignFunctionEnv) {
         )
     };
     let action = Action::PreviousSwapLayout;
-    apply_action!(action, error_msg, env);
+    apply_action!(
+        action,
+        error_msg,
+        env,
+        PermissionType::ChangeApplicationState
+    );
 }
 
 fn host_next_swap_layout(env: &ForeignFunctionEnv) {
@@ -894,7 +1141,12 @@ fn host_next_swap_layout(env: &ForeignFunctionEnv) {
         )
     };
     let action = Action::NextSwapLayout;
-    apply_action!(action, error_msg, env);
+    apply_action!(
+        action,
+        error_msg,
+        env,
+        PermissionType::ChangeApplicationState
+    );
 }
 
 fn host_go_to_tab_name(env: &ForeignFunctionEnv) {
@@ -903,7 +1155,12 @@ fn host_go_to_tab_name(env: &ForeignFunctionEnv) {
         .and_then(|tab_name| {
             let create = false;
             let action = Action::GoToTabName(tab_name, create);
-            apply_action!(action, error_msg, env);
+            apply_action!(
+                action,
+                error_msg,
+                env,
+                PermissionType::ChangeApplicationState
+            );
             Ok(())
         })
         .with_context(error_msg)
@@ -921,7 +1178,12 @@ fn host_focus_or_create_tab(env: &ForeignFunctionEnv) {
         .and_then(|tab_name| {
             let create = true;
             let action = Action::GoToTabName(tab_name, create);
-            apply_action!(action, error_msg, env);
+            apply_action!(
+                action,
+                error_msg,
+                env,
+                PermissionType::ChangeApplicationState
+            );
             Ok(())
         })
         .with_context(error_msg)
@@ -936,7 +1198,12 @@ fn host_go_to_tab(env: &ForeignFunctionEnv, tab_index: i32) {
         )
     };
     let action = Action::GoToTab(tab_index as u32);
-    apply_action!(action, error_msg, env);
+    apply_action!(
+        action,
+        error_msg,
+        env,
+        PermissionType::ChangeApplicationState
+    );
 }
 
 fn host_start_or_reload_plugin(env: &ForeignFunctionEnv) {
@@ -950,13 +1217,380 @@ fn host_start_or_reload_plugin(env: &ForeignFunctionEnv) {
         .and_then(|url| Url::parse(&url).map_err(|e| anyhow!("Failed to parse url: {}", e)))
         .and_then(|url| {
             let action = Action::StartOrReloadPlugin(url);
-            apply_action!(action, error_msg, env);
+            apply_action!(
+                action,
+                error_msg,
+                env,
+                PermissionType::OpenTerminalsOrPlugins
+            );
             Ok(())
         })
         .with_context(error_msg)
         .fatal();
 }
 
+fn host_focus_pane_with_id(env: &ForeignFunctionEnv) {
+    let error_msg = || format!("failed to focus pane in plugin {}", env.plugin_env.name());
+    wasi_read_object::<(data::PaneId, bool)>(&env.plugin_env.wasi_env)
+        .and_then(|(pane_id, should_float_if_hidden)| {
+            if !has_permission(env, PermissionType::ChangeApplicationState) {
+                return Ok(());
+            }
+            env.plugin_env
+                .senders
+                .send_to_screen(ScreenInstruction::FocusPaneWithId(
+                    pane_id.into(),
+                    should_float_if_hidden,
+                    env.plugin_env.client_id,
+                ))
+        })
+        .with_context(error_msg)
+        .non_fatal();
+}
+
+fn host_close_pane_with_id(env: &ForeignFunctionEnv) {
+    let error_msg = || format!("failed to close pane in plugin {}", env.plugin_env.name());
+    wasi_read_object::<data::PaneId>(&env.plugin_env.wasi_env)
+        .and_then(|pane_id| {
+            if !has_permission(env, PermissionType::ChangeApplicationState) {
+                return Ok(());
+            }
+            let pane_id = PaneId::from(pane_id);
+            env.plugin_env
+                .senders
+                .send_to_screen(ScreenInstruction::ClosePane(pane_id, None))?;
+            env.plugin_env
+                .senders
+                .send_to_pty(PtyInstruction::ClosePane(pane_id))
+        })
+        .with_context(error_msg)
+        .non_fatal();
+}
+
+fn host_write_to_pane_with_id(env: &ForeignFunctionEnv) {
+    let error_msg = || {
+        format!(
+            "failed to write to pane in plugin {}",
+            env.plugin_env.name()
+        )
+    };
+    wasi_read_object::<(Vec<u8>, data::PaneId)>(&env.plugin_env.wasi_env)
+        .and_then(|(bytes, pane_id)| {
+            if !has_permission(env, PermissionType::WriteToStdin) {
+                return Ok(());
+            }
+            env.plugin_env
+                .senders
+                .send_to_screen(ScreenInstruction::WriteToPaneWithId(bytes, pane_id.into()))
+        })
+        .with_context(error_msg)
+        .non_fatal();
+}
+
+fn host_write_chars_to_pane_with_id(env: &ForeignFunctionEnv) {
+    let error_msg = || {
+        format!(
+            "failed to write to pane in plugin {}",
+            env.plugin_env.name()
+        )
+    };
+    wasi_read_object::<(String, data::PaneId)>(&env.plugin_env.wasi_env)
+        .and_then(|(chars_to_write, pane_id)| {
+            if !has_permission(env, PermissionType::WriteToStdin) {
+                return Ok(());
+            }
+            env.plugin_env
+                .senders
+                .send_to_screen(ScreenInstruction::WriteToPaneWithId(
+                    chars_to_write.into_bytes(),
+                    pane_id.into(),
+                ))
+        })
+        .with_context(error_msg)
+        .non_fatal();
+}
+
+fn host_rename_pane_with_id(env: &ForeignFunctionEnv) {
+    let error_msg = || format!("failed to rename pane in plugin {}", env.plugin_env.name());
+    wasi_read_object::<(data::PaneId, String)>(&env.plugin_env.wasi_env)
+        .and_then(|(pane_id, new_name)| {
+            if !has_permission(env, PermissionType::ChangeApplicationState) {
+                return Ok(());
+            }
+            env.plugin_env
+                .senders
+                .send_to_screen(ScreenInstruction::RenamePaneWithId(
+                    pane_id.into(),
+                    new_name,
+                ))
+        })
+        .with_context(error_msg)
+        .non_fatal();
+}
+
+fn host_resize_pane_with_id(env: &ForeignFunctionEnv) {
+    let error_msg = || format!("failed to resize pane in plugin {}", env.plugin_env.name());
+    wasi_read_object::<(ResizeStrategy, data::PaneId)>(&env.plugin_env.wasi_env)
+        .and_then(|(strategy, pane_id)| {
+            if !has_permission(env, PermissionType::ChangeApplicationState) {
+                return Ok(());
+            }
+            env.plugin_env
+                .senders
+                .send_to_screen(ScreenInstruction::ResizePaneWithId(
+                    strategy,
+                    pane_id.into(),
+                ))
+        })
+        .with_context(error_msg)
+        .non_fatal();
+}
+
+fn host_toggle_pane_embed_or_eject_with_id(env: &ForeignFunctionEnv) {
+    let error_msg = || {
+        format!(
+            "failed to toggle pane embed or eject in plugin {}",
+            env.plugin_env.name()
+        )
+    };
+    wasi_read_object::<data::PaneId>(&env.plugin_env.wasi_env)
+        .and_then(|pane_id| {
+            if !has_permission(env, PermissionType::ChangeApplicationState) {
+                return Ok(());
+            }
+            env.plugin_env.senders.send_to_screen(
+                ScreenInstruction::TogglePaneEmbedOrFloatingWithId(pane_id.into()),
+            )
+        })
+        .with_context(error_msg)
+        .non_fatal();
+}
+
+fn host_rerun_command_pane(env: &ForeignFunctionEnv, terminal_pane_id: u32) {
+    if !has_permission(env, PermissionType::RunCommands) {
+        return;
+    }
+    env.plugin_env
+        .senders
+        .send_to_screen(ScreenInstruction::RerunCommandPaneWithId(terminal_pane_id))
+        .with_context(|| {
+            format!(
+                "failed to rerun command of pane {terminal_pane_id} from plugin {}",
+                env.plugin_env.name()
+            )
+        })
+        .non_fatal();
+}
+
+fn host_web_request(env: &ForeignFunctionEnv) {
+    let error_msg = || {
+        format!(
+            "failed to send web request from plugin {}",
+            env.plugin_env.name()
+        )
+    };
+    wasi_read_object::<(
+        String,
+        HttpVerb,
+        BTreeMap<String, String>,
+        Vec<u8>,
+        BTreeMap<String, String>,
+    )>(&env.plugin_env.wasi_env)
+    .and_then(|(url, verb, headers, body, context)| {
+        if !has_permission(env, PermissionType::WebAccess) {
+            return Ok(());
+        }
+        env.plugin_env
+            .senders
+            .send_to_background_jobs(BackgroundJob::WebRequest(
+                env.plugin_env.plugin_id,
+                env.plugin_env.client_id,
+                url,
+                verb,
+                headers,
+                body,
+                context,
+            ))
+    })
+    .with_context(error_msg)
+    .non_fatal();
+}
+
+fn host_run_command(env: &ForeignFunctionEnv) {
+    let error_msg = || format!("failed to run command in plugin {}", env.plugin_env.name());
+    wasi_read_object::<(
+        Vec<String>,
+        BTreeMap<String, String>,
+        PathBuf,
+        BTreeMap<String, String>,
+    )>(&env.plugin_env.wasi_env)
+    .and_then(|(mut command_line, env_variables, cwd, context)| {
+        if !has_permission(env, PermissionType::RunCommands) {
+            return Ok(());
+        }
+        if command_line.is_empty() {
+            return Err(anyhow!("no command to run"));
+        }
+        let command = command_line.remove(0);
+        // relative paths are relative to the folder zellij was started in, which is the one the
+        // plugin sees as /host
+        let cwd = env.plugin_env.plugin_cwd.join(cwd);
+        env.plugin_env
+            .senders
+            .send_to_background_jobs(BackgroundJob::RunCommand(
+                env.plugin_env.plugin_id,
+                env.plugin_env.client_id,
+                command,
+                command_line,
+                env_variables,
+                cwd,
+                context,
+            ))
+    })
+    .with_context(error_msg)
+    .non_fatal();
+}
+
+fn host_pipe_message_to_plugins(env: &ForeignFunctionEnv) {
+    let error_msg = || {
+        format!(
+            "failed to pipe message from plugin {}",
+            env.plugin_env.name()
+        )
+    };
+    wasi_read_object::<(Option<String>, String, Option<String>)>(&env.plugin_env.wasi_env)
+        .and_then(|(plugin_url, name, payload)| {
+            if !has_permission(env, PermissionType::MessageOtherPlugins) {
+                return Ok(());
+            }
+            let destination = plugin_url
+                .map(|url| RunPluginLocation::parse(&url))
+                .transpose()?;
+            env.plugin_env
+                .senders
+                .send_to_plugin(PluginInstruction::PipeMessage(
+                    destination,
+                    PipeSource::Plugin(env.plugin_env.plugin_id),
+                    name,
+                    payload,
+                ))
+        })
+        .with_context(error_msg)
+        .non_fatal();
+}
+
+fn host_cli_pipe_output(env: &ForeignFunctionEnv) {
+    let error_msg = || {
+        format!(
+            "failed to write to cli pipe from plugin {}",
+            env.plugin_env.name()
+        )
+    };
+    wasi_read_object::<(String, String)>(&env.plugin_env.wasi_env)
+        .and_then(|(pipe_id, output)| {
+            if !has_permission(env, PermissionType::WriteToCliPipes) {
+                return Ok(());
+            }
+            env.plugin_env
+                .senders
+                .send_to_plugin(PluginInstruction::CliPipeOutput(
+                    env.plugin_env.plugin_id,
+                    pipe_id,
+                    output,
+                ))
+        })
+        .with_context(error_msg)
+        .non_fatal();
+}
+
+fn host_close_cli_pipe(env: &ForeignFunctionEnv) {
+    let error_msg = || {
+        format!(
+            "failed to close cli pipe from plugin {}",
+            env.plugin_env.name()
+        )
+    };
+    wasi_read_object::<String>(&env.plugin_env.wasi_env)
+        .and_then(|pipe_id| {
+            if !has_permission(env, PermissionType::WriteToCliPipes) {
+                return Ok(());
+            }
+            env.plugin_env
+                .senders
+                .send_to_plugin(PluginInstruction::CloseCliPipe(
+                    env.plugin_env.plugin_id,
+                    pipe_id,
+                ))
+        })
+        .with_context(error_msg)
+        .non_fatal();
+}
+
+fn host_switch_session(env: &ForeignFunctionEnv) {
+    let error_msg = || {
+        format!(
+            "failed to switch session in plugin {}",
+            env.plugin_env.name()
+        )
+    };
+    wasi_read_object::<data::ConnectToSession>(&env.plugin_env.wasi_env)
+        .and_then(|connect_to_session| {
+            let action = Action::SwitchSession(connect_to_session);
+            apply_action!(
+                action,
+                error_msg,
+                env,
+                PermissionType::ChangeApplicationState
+            );
+            Ok(())
+        })
+        .with_context(error_msg)
+        .non_fatal();
+}
+
+fn host_rename_session(env: &ForeignFunctionEnv) {
+    let error_msg = || {
+        format!(
+            "failed to rename session in plugin {}",
+            env.plugin_env.name()
+        )
+    };
+    wasi_read_object::<String>(&env.plugin_env.wasi_env)
+        .and_then(|new_name| {
+            let action = Action::RenameSession(new_name);
+            apply_action!(
+                action,
+                error_msg,
+                env,
+                PermissionType::ChangeApplicationState
+            );
+            Ok(())
+        })
+        .with_context(error_msg)
+        .non_fatal();
+}
+
+fn host_kill_sessions(env: &ForeignFunctionEnv) {
+    wasi_read_object::<Vec<String>>(&env.plugin_env.wasi_env)
+        .map(|session_names| {
+            if !has_permission(env, PermissionType::ChangeApplicationState) {
+                return;
+            }
+            for session_name in session_names {
+                sessions::kill_session(&session_name)
+                    .with_context(|| format!("failed to kill session {}", session_name))
+                    .non_fatal();
+            }
+        })
+        .with_context(|| {
+            format!(
+                "failed to kill sessions in plugin {}",
+                env.plugin_env.name()
+            )
+        })
+        .non_fatal();
+}
+
 // Custom panic handler for plugins.
 //
 // This is called when a panic occurs in a plugin. Since most panics will likely originate in the
@@ -981,6 +1615,18 @@ fn host_report_panic(env: &ForeignFunctionEnv) {
 
 // Helper Functions ---------------------------------------------------------------------------------------------------
 
+fn has_permission(env: &ForeignFunctionEnv, permission: PermissionType) -> bool {
+    let has_permission = env.plugin_env.has_permission(permission);
+    if !has_permission {
+        warn!(
+            "Plugin {} was not granted the {:?} permission, ignoring request",
+            env.plugin_env.name(),
+            permission
+        );
+    }
+    has_permission
+}
+
 pub fn wasi_read_string(wasi_env: &WasiEnv) -> Result<String> {
     let err_context = || format!("failed to read string from WASI env '{wasi_env:?}'");
 
@@ -1,8 +1,10 @@
 use crate::terminal_bytes::TerminalBytes;
 use crate::{
+    background_jobs::BackgroundJob,
     panes::PaneId,
     plugins::PluginInstruction,
     screen::ScreenInstruction,
+    session_layout_metadata::SessionLayoutMetadata,
     thread_bus::{Bus, ThreadSenders},
     ClientId, ServerInstruction,
 };
@@ -10,13 +12,17 @@ use async_std::task::{self, JoinHandle};
 use std::{collections::HashMap, os::unix::io::RawFd, path::PathBuf};
 use zellij_utils::nix::unistd::Pid;
 use zellij_utils::{
-    async_std,
+    async_std, envs,
     errors::prelude::*,
     errors::{ContextType, PtyContext},
     input::{
         command::{RunCommand, TerminalAction},
-        layout::{FloatingPaneLayout, Layout, Run, RunPluginLocation, TiledPaneLayout},
+        layout::{
+            FloatingPaneLayout, Layout, PluginUserConfiguration, Run, RunPluginLocation,
+            TiledPaneLayout,
+        },
     },
+    session_serialization::serialize_session_layout,
 };
 
 pub type VteBytes = Vec<u8>;
@@ -52,13 +58,16 @@ pub enum PtyInstruction {
         Option<TerminalAction>,
         Option<TiledPaneLayout>,
         Vec<FloatingPaneLayout>,
-        usize,                                // tab_index
-        HashMap<RunPluginLocation, Vec<u32>>, // plugin_ids
+        usize,                                                           // tab_index
+        HashMap<(RunPluginLocation, PluginUserConfiguration), Vec<u32>>, // plugin_ids
         ClientId,
     ), // the String is the tab name
     ClosePane(PaneId),
     CloseTab(Vec<PaneId>),
     ReRunCommandInPane(PaneId, RunCommand),
+    DumpLayoutToHd(SessionLayoutMetadata),
+    UpdateTerminalCwd(u32, PathBuf), // the cwd reported by the terminal with OSC 7
+    QueryTerminalCwds(Vec<u32>),     // terminal_ids
     Exit,
 }
 
@@ -75,6 +84,9 @@ impl From<&PtyInstruction> for PtyContext {
             PtyInstruction::CloseTab(_) => PtyContext::CloseTab,
             PtyInstruction::NewTab(..) => PtyContext::NewTab,
             PtyInstruction::ReRunCommandInPane(..) => PtyContext::ReRunCommandInPane,
+            PtyInstruction::DumpLayoutToHd(..) => PtyContext::DumpLayoutToHd,
+            PtyInstruction::UpdateTerminalCwd(..) => PtyContext::UpdateTerminalCwd,
+            PtyInstruction::QueryTerminalCwds(..) => PtyContext::QueryTerminalCwds,
             PtyInstruction::Exit => PtyContext::Exit,
         }
     }
@@ -84,6 +96,7 @@ pub(crate) struct Pty {
     pub active_panes: HashMap<ClientId, PaneId>,
     pub bus: Bus<PtyInstruction>,
     pub id_to_child_pid: HashMap<u32, RawFd>, // terminal_id => child raw fd
+    id_to_reported_cwd: HashMap<u32, PathBuf>, // terminal_id => cwd reported with OSC 7
     debug_to_file: bool,
     task_handles: HashMap<u32, JoinHandle<()>>, // terminal_id to join-handle
     default_editor: Option<PathBuf>,
@@ -420,6 +433,40 @@ pub(crate) fn pty_thread_main(mut pty: Pty, layout: Box<Layout>) -> Result<()> {
                     },
                 }
             },
+            PtyInstruction::DumpLayoutToHd(mut session_layout_metadata) => {
+                let err_context = || "failed to dump layout to hd".to_string();
+                pty.populate_session_layout_metadata(&mut session_layout_metadata);
+                let session_name = envs::get_session_name().with_context(err_context)?;
+                match serialize_session_layout((&session_layout_metadata).into()) {
+                    Ok(kdl_layout) => {
+                        pty.bus
+                            .senders
+                            .send_to_background_jobs(BackgroundJob::WriteSessionLayout(
+                                session_name,
+                                kdl_layout,
+                            ))
+                            .with_context(err_context)?;
+                    },
+                    Err(e) => {
+                        log::error!("Failed to serialize session layout: {}", e);
+                    },
+                }
+            },
+            PtyInstruction::UpdateTerminalCwd(terminal_id, cwd) => {
+                pty.id_to_reported_cwd.insert(terminal_id, cwd);
+            },
+            PtyInstruction::QueryTerminalCwds(terminal_ids) => {
+                let terminal_ids_to_cwds: HashMap<u32, PathBuf> = terminal_ids
+                    .into_iter()
+                    .filter_map(|terminal_id| {
+                        pty.process_cwd(terminal_id).map(|cwd| (terminal_id, cwd))
+                    })
+                    .collect();
+                pty.bus
+                    .senders
+                    .send_to_screen(ScreenInstruction::UpdateTerminalCwds(terminal_ids_to_cwds))
+                    .context("failed to query terminal cwds")?;
+            },
             PtyInstruction::Exit => break,
         }
     }
@@ -436,6 +483,7 @@ impl Pty {
             active_panes: HashMap::new(),
             bus,
             id_to_child_pid: HashMap::new(),
+            id_to_reported_cwd: HashMap::new(),
             debug_to_file,
             task_handles: HashMap::new(),
             default_editor,
@@ -480,23 +528,51 @@ impl Pty {
         }
     }
     fn fill_cwd(&self, terminal_action: &mut TerminalAction, client_id: ClientId) {
-        if let TerminalAction::RunCommand(run_command) = terminal_action {
-            if run_command.cwd.is_none() {
-                run_command.cwd = self
-                    .active_panes
-                    .get(&client_id)
-                    .and_then(|pane| match pane {
-                        PaneId::Plugin(..) => None,
-                        PaneId::Terminal(id) => self.id_to_child_pid.get(id),
-                    })
-                    .and_then(|&id| {
-                        self.bus
-                            .os_input
-                            .as_ref()
-                            .and_then(|input| input.get_cwd(Pid::from_raw(id)))
-                    });
-            };
+        let cwd = match terminal_action {
+            TerminalAction::RunCommand(RunCommand { cwd, .. }) => cwd,
+            TerminalAction::OpenFile(_, _, cwd) => cwd,
         };
+        if cwd.is_none() {
+            *cwd = self
+                .active_panes
+                .get(&client_id)
+                .and_then(|pane| match pane {
+                    PaneId::Plugin(..) => None,
+                    PaneId::Terminal(id) => self.terminal_cwd(*id),
+                });
+        }
+    }
+    fn terminal_cwd(&self, terminal_id: u32) -> Option<PathBuf> {
+        // the cwd reported by the shell is preferred, since the cwd of the process we spawned
+        // is meaningless eg. inside ssh or nested shells - as long as it exists on this machine
+        self.id_to_reported_cwd
+            .get(&terminal_id)
+            .filter(|cwd| cwd.is_dir())
+            .cloned()
+            .or_else(|| self.process_cwd(terminal_id))
+    }
+    fn process_cwd(&self, terminal_id: u32) -> Option<PathBuf> {
+        self.id_to_child_pid.get(&terminal_id).and_then(|&pid| {
+            self.bus
+                .os_input
+                .as_ref()
+                .and_then(|input| input.get_cwd(Pid::from_raw(pid)))
+        })
+    }
+    pub fn populate_session_layout_metadata(
+        &self,
+        session_layout_metadata: &mut SessionLayoutMetadata,
+    ) {
+        let terminal_ids_to_cwds: HashMap<u32, PathBuf> = session_layout_metadata
+            .all_terminal_ids()
+            .iter()
+            .filter_map(|terminal_id| {
+                self.terminal_cwd(*terminal_id)
+                    .map(|cwd| (*terminal_id, cwd))
+            })
+            .collect();
+        session_layout_metadata.update_terminal_cwds(terminal_ids_to_cwds);
+        session_layout_metadata.set_default_shell(get_default_shell());
     }
     pub fn spawn_terminal(
         &mut self,
@@ -595,7 +671,7 @@ impl Pty {
         layout: TiledPaneLayout,
         floating_panes_layout: Vec<FloatingPaneLayout>,
         default_shell: Option<TerminalAction>,
-        plugin_ids: HashMap<RunPluginLocation, Vec<u32>>,
+        plugin_ids: HashMap<(RunPluginLocation, PluginUserConfiguration), Vec<u32>>,
         tab_index: usize,
         client_id: ClientId,
     ) -> Result<()> {
@@ -899,6 +975,7 @@ impl Pty {
         match id {
             PaneId::Terminal(id) => {
                 self.task_handles.remove(&id);
+                self.id_to_reported_cwd.remove(&id);
                 if let Some(child_fd) = self.id_to_child_pid.remove(&id) {
                     task::block_on(async {
                         let err_context = || format!("failed to run async task for pane {id}");
@@ -11,17 +11,19 @@ use crate::{
 };
 use zellij_utils::{
     channels::SenderWithContext,
-    data::{Direction, Event, PluginCapabilities, ResizeStrategy},
+    data::{Direction, Event, InputMode, KeyEventType, PluginCapabilities, ResizeStrategy},
     errors::prelude::*,
     input::{
         actions::{Action, SearchDirection, SearchOption},
         command::TerminalAction,
-        get_mode_info,
+        get_mode_info, kitty_keyboard,
         layout::{Layout, RunPluginLocation},
     },
     ipc::{
-        ClientAttributes, ClientToServerMsg, ExitReason, IpcReceiverWithContext, ServerToClientMsg,
+        ClientAttributes, ClientToServerMsg, ClientType, ExitReason, IpcReceiverWithContext,
+        ServerToClientMsg,
     },
+    sessions::is_valid_session_name,
 };
 
 use crate::ClientId;
@@ -68,6 +70,16 @@ pub(crate) fn route_action(
                 .send_to_screen(ScreenInstruction::WriteCharacter(val, client_id))
                 .with_context(err_context)?;
         },
+        Action::WriteKey(key, event_type) => {
+            if event_type != KeyEventType::Release {
+                senders
+                    .send_to_screen(ScreenInstruction::ClearScroll(client_id))
+                    .with_context(err_context)?;
+            }
+            senders
+                .send_to_screen(ScreenInstruction::WriteKey(key, event_type, client_id))
+                .with_context(err_context)?;
+        },
         Action::WriteChars(val) => {
             senders
                 .send_to_screen(ScreenInstruction::ClearScroll(client_id))
@@ -166,9 +178,14 @@ pub(crate) fn route_action(
                 .send_to_screen(ScreenInstruction::ClearScreen(client_id))
                 .with_context(err_context)?;
         },
-        Action::DumpScreen(val, full) => {
+        Action::DumpScreen(val, full, last_output) => {
             senders
-                .send_to_screen(ScreenInstruction::DumpScreen(val, client_id, full))
+                .send_to_screen(ScreenInstruction::DumpScreen(
+                    val,
+                    client_id,
+                    full,
+                    last_output,
+                ))
                 .with_context(err_context)?;
         },
         Action::EditScrollback => {
@@ -206,6 +223,21 @@ pub(crate) fn route_action(
                 .send_to_screen(ScreenInstruction::ScrollToTop(client_id))
                 .with_context(err_context)?;
         },
+        Action::ScrollToPreviousPrompt => {
+            senders
+                .send_to_screen(ScreenInstruction::ScrollToPreviousPrompt(client_id))
+                .with_context(err_context)?;
+        },
+        Action::ScrollToNextPrompt => {
+            senders
+                .send_to_screen(ScreenInstruction::ScrollToNextPrompt(client_id))
+                .with_context(err_context)?;
+        },
+        Action::SelectLastCommandOutput => {
+            senders
+                .send_to_screen(ScreenInstruction::SelectLastCommandOutput(client_id))
+                .with_context(err_context)?;
+        },
         Action::PageScrollUp => {
             senders
                 .send_to_screen(ScreenInstruction::PageScrollUp(client_id))
@@ -488,11 +520,47 @@ pub(crate) fn route_action(
                 .with_context(err_context)?;
             should_break = true;
         },
+        Action::SwitchSession(connect_to_session) => {
+            let name = &connect_to_session.name;
+            if !is_valid_session_name(name) {
+                log::error!("Cannot switch to session {:?}: invalid session name", name);
+                senders
+                    .send_to_server(ServerInstruction::UnblockInputThread)
+                    .with_context(err_context)?;
+            } else {
+                senders
+                    .send_to_server(ServerInstruction::SwitchSession(
+                        connect_to_session,
+                        client_id,
+                    ))
+                    .with_context(err_context)?;
+                should_break = true;
+            }
+        },
+        Action::RenameSession(name) => {
+            if !is_valid_session_name(&name) {
+                log::error!("Cannot rename session to {:?}: invalid session name", name);
+                senders
+                    .send_to_server(ServerInstruction::UnblockInputThread)
+                    .with_context(err_context)?;
+            } else {
+                senders
+                    .send_to_server(ServerInstruction::RenameSession(name))
+                    .with_context(err_context)?;
+            }
+        },
         Action::LeftClick(point) => {
             senders
                 .send_to_screen(ScreenInstruction::LeftClick(point, client_id))
                 .with_context(err_context)?;
         },
+        Action::LeftClickWithBlockSelection(point) => {
+            senders
+                .send_to_screen(ScreenInstruction::LeftClickWithBlockSelection(
+                    point, client_id,
+                ))
+                .with_context(err_context)?;
+        },
         Action::RightClick(point) => {
             senders
                 .send_to_screen(ScreenInstruction::RightClick(point, client_id))
@@ -580,11 +648,27 @@ pub(crate) fn route_action(
                 },
                 SearchOption::WholeWord => ScreenInstruction::SearchToggleWholeWord(client_id),
                 SearchOption::Wrap => ScreenInstruction::SearchToggleWrap(client_id),
+                SearchOption::Regex => ScreenInstruction::SearchToggleRegex(client_id),
             };
             senders
                 .send_to_screen(instruction)
                 .with_context(err_context)?;
         },
+        Action::CopyModeMove(motion) => {
+            senders
+                .send_to_screen(ScreenInstruction::CopyModeMove(motion, client_id))
+                .with_context(err_context)?;
+        },
+        Action::CopyModeSelect(selection) => {
+            senders
+                .send_to_screen(ScreenInstruction::CopyModeSelect(selection, client_id))
+                .with_context(err_context)?;
+        },
+        Action::HintInput(input) => {
+            senders
+                .send_to_screen(ScreenInstruction::HintInput(input, client_id))
+                .with_context(err_context)?;
+        },
         Action::ToggleMouseMode => {}, // Handled client side
         Action::PreviousSwapLayout => {
             senders
@@ -634,10 +718,70 @@ pub(crate) fn route_action(
                 ))
                 .with_context(err_context)?;
         },
+        Action::CliPipe {
+            pipe_id,
+            name,
+            payload,
+            plugin,
+        } => {
+            let destination = plugin
+                .map(|url| RunPluginLocation::parse(&url))
+                .transpose()
+                .with_context(err_context)?;
+            senders
+                .send_to_plugin(PluginInstruction::CliPipe(
+                    pipe_id,
+                    destination,
+                    name,
+                    payload,
+                    client_id,
+                ))
+                .with_context(err_context)?;
+        },
     }
     Ok(should_break)
 }
 
+// read-only clients can watch the session, but nothing that would change it or the way other
+// clients see it - this includes scrolling and searching, since the viewport is shared between
+// clients, so they are not let into these modes either
+fn is_allowed_for_read_only_client(action: &Action) -> bool {
+    match action {
+        Action::SwitchToMode(input_mode) => !matches!(
+            input_mode,
+            InputMode::Scroll
+                | InputMode::EnterSearch
+                | InputMode::Search
+                | InputMode::Copy
+                | InputMode::Hint
+        ),
+        Action::Detach | Action::NoOp | Action::ToggleMouseMode => true,
+        _ => false,
+    }
+}
+
+// returns the action that should be routed on behalf of this client, or None if it should be
+// dropped altogether
+fn filter_action_for_client(
+    action: Action,
+    client_id: ClientId,
+    session_state: &SessionState,
+) -> Option<Action> {
+    if !session_state.client_is_read_only(client_id) || is_allowed_for_read_only_client(&action) {
+        return Some(action);
+    }
+    log::warn!(
+        "Rejecting action {:?} of read-only client {}",
+        action,
+        client_id
+    );
+    match action {
+        // the client already switched, bring it back
+        Action::SwitchToMode(_) => Some(Action::SwitchToMode(InputMode::Normal)),
+        _ => None,
+    }
+}
+
 // this should only be used for one-off startup instructions
 macro_rules! send_to_screen_or_retry_queue {
     ($rlocked_sessions:expr, $message:expr, $instruction: expr, $retry_queue:expr) => {{
@@ -654,6 +798,14 @@ macro_rules! send_to_screen_or_retry_queue {
     }};
 }
 
+fn prompt_answer_from_action(action: &Action) -> Option<Vec<u8>> {
+    match action {
+        Action::Write(bytes) => Some(bytes.clone()),
+        Action::WriteKey(key, event_type) => kitty_keyboard::legacy_encoding(key, *event_type),
+        _ => None,
+    }
+}
+
 pub(crate) fn route_thread_main(
     session_data: Arc<RwLock<Option<SessionMetaData>>>,
     session_state: Arc<RwLock<SessionState>>,
@@ -677,6 +829,26 @@ pub(crate) fn route_thread_main(
                     let mut should_break = false;
                     match instruction {
                         ClientToServerMsg::Action(action, maybe_client_id) => {
+                            let action = match filter_action_for_client(
+                                action,
+                                client_id,
+                                &*session_state.read().to_anyhow().with_context(err_context)?,
+                            ) {
+                                Some(action) => action,
+                                None => {
+                                    let _ = os_input.send_to_client(
+                                        client_id,
+                                        ServerToClientMsg::UnblockInputThread,
+                                    );
+                                    return Ok(false);
+                                },
+                            };
+                            // only keys this client typed itself can answer the prompts
+                            // shown to it, never writes made on its behalf (eg. by plugins)
+                            let prompt_answer = match maybe_client_id {
+                                Some(maybe_client_id) if maybe_client_id != client_id => None,
+                                _ => prompt_answer_from_action(&action),
+                            };
                             let client_id = maybe_client_id.unwrap_or(client_id);
                             if let Some(rlocked_sessions) = rlocked_sessions.as_ref() {
                                 if let Action::SwitchToMode(input_mode) = action {
@@ -701,6 +873,14 @@ pub(crate) fn route_thread_main(
                                 )? {
                                     should_break = true;
                                 }
+                                if let Some(bytes) = prompt_answer {
+                                    rlocked_sessions
+                                        .senders
+                                        .send_to_screen(ScreenInstruction::AnswerPrompt(
+                                            bytes, client_id,
+                                        ))
+                                        .with_context(err_context)?;
+                                }
                             }
                         },
                         ClientToServerMsg::TerminalResize(new_size) => {
@@ -785,9 +965,27 @@ pub(crate) fn route_thread_main(
                                 .send(new_client_instruction)
                                 .with_context(err_context)?;
                         },
-                        ClientToServerMsg::AttachClient(client_attributes, opts) => {
-                            let attach_client_instruction =
-                                ServerInstruction::AttachClient(client_attributes, opts, client_id);
+                        ClientToServerMsg::AttachClient(
+                            client_attributes,
+                            opts,
+                            client_type,
+                            tab_position,
+                            pane_id,
+                        ) => {
+                            if client_type == ClientType::Reader {
+                                session_state
+                                    .write()
+                                    .to_anyhow()
+                                    .with_context(err_context)?
+                                    .set_client_read_only(client_id);
+                            }
+                            let attach_client_instruction = ServerInstruction::AttachClient(
+                                client_attributes,
+                                opts,
+                                tab_position,
+                                pane_id,
+                                client_id,
+                            );
                             to_server
                                 .send(attach_client_instruction)
                                 .with_context(err_context)?;
@@ -814,6 +1012,10 @@ pub(crate) fn route_thread_main(
                         ClientToServerMsg::ListClients => {
                             let _ = to_server.send(ServerInstruction::ActiveClients(client_id));
                         },
+                        ClientToServerMsg::QuerySessionInfo => {
+                            let _ = to_server.send(ServerInstruction::QuerySessionInfo(client_id));
+                            should_break = true;
+                        },
                     }
                     Ok(should_break)
                 };
@@ -844,3 +1046,7 @@ pub(crate) fn route_thread_main(
     }
     Ok(())
 }
+
+#[path = "./unit/route_tests.rs"]
+#[cfg(test)]
+mod route_tests;
@@ -3,10 +3,12 @@
 use std::cell::RefCell;
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::PathBuf;
+use std::process::{Command, Stdio};
 use std::rc::Rc;
 use std::str;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-use zellij_utils::data::{Direction, Resize, ResizeStrategy};
+use zellij_utils::data::{Direction, PermissionType, Resize, ResizeStrategy};
 use zellij_utils::errors::prelude::*;
 use zellij_utils::input::command::RunCommand;
 use zellij_utils::input::options::Clipboard;
@@ -14,34 +16,50 @@ use zellij_utils::pane_size::{Size, SizeInPixels};
 use zellij_utils::{
     input::command::TerminalAction,
     input::layout::{
-        FloatingPaneLayout, Run, RunPlugin, RunPluginLocation, SwapFloatingLayout, SwapTiledLayout,
-        TiledPaneLayout,
+        FloatingPaneLayout, PluginUserConfiguration, Run, RunPlugin, RunPluginLocation,
+        SwapFloatingLayout, SwapTiledLayout, TiledPaneLayout,
     },
     position::Position,
+    regex::Regex,
 };
 
 use crate::panes::alacritty_functions::xparse_color;
+use crate::panes::grid::SYNCHRONIZED_OUTPUT_TIMEOUT;
 use crate::panes::terminal_character::AnsiCode;
 
 use crate::{
+    background_jobs::BackgroundJob,
     output::Output,
+    panes::kitty_graphics::KittyImageStore,
     panes::sixel::SixelImageStore,
-    panes::PaneId,
+    panes::{compile_hint_patterns, is_openable_url, HintAction, HintTarget, PaneId},
     plugins::PluginInstruction,
     pty::{ClientOrTabIndex, PtyInstruction, VteBytes},
+    session_layout_metadata::SessionLayoutMetadata,
     tab::Tab,
     thread_bus::Bus,
     ui::{
         loading_indication::LoadingIndication,
-        overlay::{Overlay, OverlayWindow},
+        overlay::{
+            prompt::{generate_permission_prompt, PromptCallback},
+            Overlay, OverlayWindow, Overlayable,
+        },
     },
     ClientId, ServerInstruction,
 };
 use zellij_utils::{
-    data::{Event, InputMode, ModeInfo, Palette, PaletteColor, PluginCapabilities, Style, TabInfo},
+    data::{
+        Event, InputMode, Key, KeyEventType, ModeInfo, Palette, PaletteColor, PaneManifest,
+        PluginCapabilities, SessionInfo, Style, TabInfo,
+    },
+    envs,
     errors::{ContextType, ScreenContext},
-    input::{get_mode_info, options::Options},
-    ipc::{ClientAttributes, PixelDimensions, ServerToClientMsg},
+    input::{
+        actions::{CopyModeMotion, CopyModeSelection},
+        get_mode_info,
+        options::Options,
+    },
+    ipc::{ClientAttributes, ClientType, PixelDimensions, ServerToClientMsg},
 };
 
 /// Get the active tab and call a closure on it
@@ -145,6 +163,9 @@ pub enum ScreenInstruction {
     HorizontalSplit(PaneId, Option<InitialTitle>, HoldForCommand, ClientId),
     VerticalSplit(PaneId, Option<InitialTitle>, HoldForCommand, ClientId),
     WriteCharacter(Vec<u8>, ClientId),
+    WriteKey(Key, KeyEventType, ClientId),
+    /// Keys pressed by the client itself, which answer a prompt shown to it
+    AnswerPrompt(Vec<u8>, ClientId),
     Resize(ClientId, ResizeStrategy),
     SwitchFocus(ClientId),
     FocusNextPane(ClientId),
@@ -163,7 +184,9 @@ pub enum ScreenInstruction {
     MovePaneLeft(ClientId),
     Exit,
     ClearScreen(ClientId),
-    DumpScreen(String, ClientId, bool),
+    DumpScreen(String, ClientId, bool, bool), // bools are full and last_command_output
+    DumpLayoutToHd,
+    UpdateTerminalCwds(HashMap<u32, PathBuf>), // the cwds of the processes running in the terminals
     EditScrollback(ClientId),
     ScrollUp(ClientId),
     ScrollUpAt(Position, ClientId),
@@ -171,6 +194,9 @@ pub enum ScreenInstruction {
     ScrollDownAt(Position, ClientId),
     ScrollToBottom(ClientId),
     ScrollToTop(ClientId),
+    ScrollToPreviousPrompt(ClientId),
+    ScrollToNextPrompt(ClientId),
+    SelectLastCommandOutput(ClientId),
     PageScrollUp(ClientId),
     PageScrollDown(ClientId),
     HalfPageScrollUp(ClientId),
@@ -204,7 +230,7 @@ pub enum ScreenInstruction {
         Vec<FloatingPaneLayout>,
         Vec<(u32, HoldForCommand)>, // new pane pids
         Vec<(u32, HoldForCommand)>, // new floating pane pids
-        HashMap<RunPluginLocation, Vec<u32>>,
+        HashMap<(RunPluginLocation, PluginUserConfiguration), Vec<u32>>,
         usize, // tab_index
         ClientId,
     ),
@@ -231,6 +257,7 @@ pub enum ScreenInstruction {
     ChangeMode(ModeInfo, ClientId),
     ChangeModeForAllClients(ModeInfo),
     LeftClick(Position, ClientId),
+    LeftClickWithBlockSelection(Position, ClientId),
     RightClick(Position, ClientId),
     MiddleClick(Position, ClientId),
     LeftMouseRelease(Position, ClientId),
@@ -240,7 +267,7 @@ pub enum ScreenInstruction {
     MouseHoldRight(Position, ClientId),
     MouseHoldMiddle(Position, ClientId),
     Copy(ClientId),
-    AddClient(ClientId),
+    AddClient(ClientId, ClientType),
     RemoveClient(ClientId),
     AddOverlay(Overlay, ClientId),
     RemoveOverlay(ClientId),
@@ -251,12 +278,18 @@ pub enum ScreenInstruction {
     SearchUp(ClientId),
     SearchToggleCaseSensitivity(ClientId),
     SearchToggleWholeWord(ClientId),
+    SearchToggleRegex(ClientId),
     SearchToggleWrap(ClientId),
+    CopyModeMove(CopyModeMotion, ClientId),
+    CopyModeSelect(CopyModeSelection, ClientId),
+    HintInput(Vec<u8>, ClientId),
     AddRedPaneFrameColorOverride(Vec<PaneId>, Option<String>), // Option<String> => optional error text
     ClearPaneFrameColorOverride(Vec<PaneId>),
     PreviousSwapLayout(ClientId),
     NextSwapLayout(ClientId),
     QueryTabNames(ClientId),
+    QuerySessionInfo(ClientId),
+    RenameSession(String), // String - the new session name
     NewTiledPluginPane(RunPluginLocation, Option<String>, ClientId), // Option<String> is
     // optional pane title
     NewFloatingPluginPane(RunPluginLocation, Option<String>, ClientId), // Option<String> is an
@@ -275,6 +308,18 @@ pub enum ScreenInstruction {
     RequestStateUpdateForPlugins,
     LaunchOrFocusPlugin(RunPlugin, bool, ClientId), // bool is should_float
     SuppressPane(PaneId, ClientId),
+    FocusPaneWithId(PaneId, bool, ClientId), // bool is should_float_if_hidden
+    WriteToPaneWithId(Vec<u8>, PaneId),
+    RenamePaneWithId(PaneId, String),
+    ResizePaneWithId(ResizeStrategy, PaneId),
+    TogglePaneEmbedOrFloatingWithId(PaneId),
+    RerunCommandPaneWithId(u32), // u32 is the terminal pane id
+    RequestPluginPermissions(
+        u32, // u32 - plugin_id
+        ClientId,
+        String, // plugin name
+        Vec<PermissionType>,
+    ),
 }
 
 impl From<&ScreenInstruction> for ScreenContext {
@@ -292,6 +337,8 @@ impl From<&ScreenInstruction> for ScreenContext {
             ScreenInstruction::HorizontalSplit(..) => ScreenContext::HorizontalSplit,
             ScreenInstruction::VerticalSplit(..) => ScreenContext::VerticalSplit,
             ScreenInstruction::WriteCharacter(..) => ScreenContext::WriteCharacter,
+            ScreenInstruction::WriteKey(..) => ScreenContext::WriteKey,
+            ScreenInstruction::AnswerPrompt(..) => ScreenContext::AnswerPrompt,
             ScreenInstruction::Resize(.., strategy) => match strategy {
                 ResizeStrategy {
                     resize: Resize::Increase,
@@ -338,11 +385,18 @@ impl From<&ScreenInstruction> for ScreenContext {
             ScreenInstruction::Exit => ScreenContext::Exit,
             ScreenInstruction::ClearScreen(..) => ScreenContext::ClearScreen,
             ScreenInstruction::DumpScreen(..) => ScreenContext::DumpScreen,
+            ScreenInstruction::DumpLayoutToHd => ScreenContext::DumpLayoutToHd,
+            ScreenInstruction::UpdateTerminalCwds(..) => ScreenContext::UpdateTerminalCwds,
             ScreenInstruction::EditScrollback(..) => ScreenContext::EditScrollback,
             ScreenInstruction::ScrollUp(..) => ScreenContext::ScrollUp,
             ScreenInstruction::ScrollDown(..) => ScreenContext::ScrollDown,
             ScreenInstruction::ScrollToBottom(..) => ScreenContext::ScrollToBottom,
             ScreenInstruction::ScrollToTop(..) => ScreenContext::ScrollToTop,
+            ScreenInstruction::ScrollToPreviousPrompt(..) => ScreenContext::ScrollToPreviousPrompt,
+            ScreenInstruction::ScrollToNextPrompt(..) => ScreenContext::ScrollToNextPrompt,
+            ScreenInstruction::SelectLastCommandOutput(..) => {
+                ScreenContext::SelectLastCommandOutput
+            },
             ScreenInstruction::PageScrollUp(..) => ScreenContext::PageScrollUp,
             ScreenInstruction::PageScrollDown(..) => ScreenContext::PageScrollDown,
             ScreenInstruction::HalfPageScrollUp(..) => ScreenContext::HalfPageScrollUp,
@@ -386,6 +440,9 @@ impl From<&ScreenInstruction> for ScreenContext {
             ScreenInstruction::ScrollUpAt(..) => ScreenContext::ScrollUpAt,
             ScreenInstruction::ScrollDownAt(..) => ScreenContext::ScrollDownAt,
             ScreenInstruction::LeftClick(..) => ScreenContext::LeftClick,
+            ScreenInstruction::LeftClickWithBlockSelection(..) => {
+                ScreenContext::LeftClickWithBlockSelection
+            },
             ScreenInstruction::RightClick(..) => ScreenContext::RightClick,
             ScreenInstruction::MiddleClick(..) => ScreenContext::MiddleClick,
             ScreenInstruction::LeftMouseRelease(..) => ScreenContext::LeftMouseRelease,
@@ -409,7 +466,11 @@ impl From<&ScreenInstruction> for ScreenContext {
                 ScreenContext::SearchToggleCaseSensitivity
             },
             ScreenInstruction::SearchToggleWholeWord(..) => ScreenContext::SearchToggleWholeWord,
+            ScreenInstruction::SearchToggleRegex(..) => ScreenContext::SearchToggleRegex,
             ScreenInstruction::SearchToggleWrap(..) => ScreenContext::SearchToggleWrap,
+            ScreenInstruction::CopyModeMove(..) => ScreenContext::CopyModeMove,
+            ScreenInstruction::CopyModeSelect(..) => ScreenContext::CopyModeSelect,
+            ScreenInstruction::HintInput(..) => ScreenContext::HintInput,
             ScreenInstruction::AddRedPaneFrameColorOverride(..) => {
                 ScreenContext::AddRedPaneFrameColorOverride
             },
@@ -419,6 +480,8 @@ impl From<&ScreenInstruction> for ScreenContext {
             ScreenInstruction::PreviousSwapLayout(..) => ScreenContext::PreviousSwapLayout,
             ScreenInstruction::NextSwapLayout(..) => ScreenContext::NextSwapLayout,
             ScreenInstruction::QueryTabNames(..) => ScreenContext::QueryTabNames,
+            ScreenInstruction::QuerySessionInfo(..) => ScreenContext::QuerySessionInfo,
+            ScreenInstruction::RenameSession(..) => ScreenContext::RenameSession,
             ScreenInstruction::NewTiledPluginPane(..) => ScreenContext::NewTiledPluginPane,
             ScreenInstruction::NewFloatingPluginPane(..) => ScreenContext::NewFloatingPluginPane,
             ScreenInstruction::StartOrReloadPluginPane(..) => {
@@ -439,10 +502,28 @@ impl From<&ScreenInstruction> for ScreenContext {
             },
             ScreenInstruction::LaunchOrFocusPlugin(..) => ScreenContext::LaunchOrFocusPlugin,
             ScreenInstruction::SuppressPane(..) => ScreenContext::SuppressPane,
+            ScreenInstruction::FocusPaneWithId(..) => ScreenContext::FocusPaneWithId,
+            ScreenInstruction::WriteToPaneWithId(..) => ScreenContext::WriteToPaneWithId,
+            ScreenInstruction::RenamePaneWithId(..) => ScreenContext::RenamePaneWithId,
+            ScreenInstruction::ResizePaneWithId(..) => ScreenContext::ResizePaneWithId,
+            ScreenInstruction::TogglePaneEmbedOrFloatingWithId(..) => {
+                ScreenContext::TogglePaneEmbedOrFloatingWithId
+            },
+            ScreenInstruction::RerunCommandPaneWithId(..) => ScreenContext::RerunCommandPaneWithId,
+            ScreenInstruction::RequestPluginPermissions(..) => {
+                ScreenContext::RequestPluginPermissions
+            },
         }
     }
 }
 
+#[cfg(target_os = "macos")]
+const DEFAULT_LINK_OPENER: &str = "open";
+#[cfg(not(target_os = "macos"))]
+const DEFAULT_LINK_OPENER: &str = "xdg-open";
+
+const PANE_MANIFEST_UPDATE_INTERVAL: Duration = Duration::from_millis(250);
+
 #[derive(Debug, Clone)]
 pub(crate) struct CopyOptions {
     pub command: Option<String>,
@@ -487,11 +568,14 @@ pub(crate) struct Screen {
     pixel_dimensions: PixelDimensions,
     character_cell_size: Rc<RefCell<Option<SizeInPixels>>>,
     sixel_image_store: Rc<RefCell<SixelImageStore>>,
+    kitty_image_store: Rc<RefCell<KittyImageStore>>,
     /// The overlay that is drawn on top of [`Pane`]'s', [`Tab`]'s and the [`Screen`]
     overlay: OverlayWindow,
     terminal_emulator_colors: Rc<RefCell<Palette>>,
     terminal_emulator_color_codes: Rc<RefCell<HashMap<usize, String>>>,
     connected_clients: Rc<RefCell<HashSet<ClientId>>>,
+    /// The clients that only watch the session, shown apart from the others in the tab bar.
+    read_only_clients: HashSet<ClientId>,
     /// The indices of this [`Screen`]'s active [`Tab`]s.
     active_tab_indices: BTreeMap<ClientId, usize>,
     tab_history: BTreeMap<ClientId, Vec<usize>>,
@@ -502,10 +586,31 @@ pub(crate) struct Screen {
     auto_layout: bool,
     session_is_mirrored: bool,
     copy_options: CopyOptions,
+    styled_underlines: bool,
+    /// The command the links found in hint mode are opened with.
+    link_opener: Option<String>,
+    /// The patterns of the tokens that hint mode labels alongside links and paths.
+    hint_patterns: Vec<Regex>,
+    /// The last [`PaneManifest`] sent to plugins, so that they are only updated on changes.
+    pane_manifest: PaneManifest,
+    /// Whether something other than the output of the panes happened since the last
+    /// [`PaneManifest`] was built, which means it has to be rebuilt on the next render.
+    pane_manifest_is_outdated: bool,
+    /// When the [`PaneManifest`] may be rebuilt again if only the output of the panes changed.
+    next_pane_manifest_update_at: Option<Instant>,
+    pane_manifest_update_is_scheduled: bool,
+    /// Tells the time when throttling the [`PaneManifest`] updates, replaced in the tests.
+    clock: Box<dyn Fn() -> Instant>,
+    /// When we will render again for panes whose synchronized update times out.
+    synchronized_output_render_at: Option<Instant>,
+    created_at: SystemTime,
+    /// The last time the screen changed, reported as the last activity of the session.
+    last_activity: SystemTime,
 }
 
 impl Screen {
     /// Creates and returns a new [`Screen`].
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         bus: Bus<ScreenInstruction>,
         client_attributes: &ClientAttributes,
@@ -515,6 +620,9 @@ impl Screen {
         auto_layout: bool,
         session_is_mirrored: bool,
         copy_options: CopyOptions,
+        styled_underlines: bool,
+        link_opener: Option<String>,
+        hint_patterns: Vec<Regex>,
     ) -> Self {
         Screen {
             bus,
@@ -523,8 +631,10 @@ impl Screen {
             pixel_dimensions: Default::default(),
             character_cell_size: Rc::new(RefCell::new(None)),
             sixel_image_store: Rc::new(RefCell::new(SixelImageStore::default())),
+            kitty_image_store: Rc::new(RefCell::new(KittyImageStore::default())),
             style: client_attributes.style,
             connected_clients: Rc::new(RefCell::new(HashSet::new())),
+            read_only_clients: HashSet::new(),
             active_tab_indices: BTreeMap::new(),
             tabs: BTreeMap::new(),
             overlay: OverlayWindow::default(),
@@ -537,6 +647,17 @@ impl Screen {
             auto_layout,
             session_is_mirrored,
             copy_options,
+            styled_underlines,
+            link_opener,
+            hint_patterns,
+            pane_manifest: PaneManifest::default(),
+            pane_manifest_is_outdated: true,
+            next_pane_manifest_update_at: None,
+            pane_manifest_update_is_scheduled: false,
+            clock: Box::new(Instant::now),
+            synchronized_output_render_at: None,
+            created_at: SystemTime::now(),
+            last_activity: SystemTime::now(),
         }
     }
 
@@ -926,7 +1047,9 @@ impl Screen {
 
         let mut output = Output::new(
             self.sixel_image_store.clone(),
+            self.kitty_image_store.clone(),
             self.character_cell_size.clone(),
+            self.styled_underlines,
         );
         let mut tabs_to_close = vec![];
         for (tab_index, tab) in &mut self.tabs {
@@ -939,7 +1062,27 @@ impl Screen {
         for tab_index in tabs_to_close {
             self.close_tab_at_index(tab_index).context(err_context)?;
         }
+        self.update_panes().context(err_context)?;
+        if self
+            .tabs
+            .values()
+            .any(|tab| tab.is_holding_synchronized_output())
+        {
+            self.render_after_synchronized_output_timeout()
+                .context(err_context)?;
+        }
+        if !self.overlay.overlay_stack.is_empty() {
+            let overlay_output = self
+                .overlay
+                .generate_overlay(self.size)
+                .context(err_context)?;
+            output.add_post_vte_instruction_to_multiple_clients(
+                self.connected_clients.borrow().iter().copied(),
+                &overlay_output,
+            );
+        }
         if output.is_dirty() {
+            self.last_activity = SystemTime::now();
             let serialized_output = output.serialize().context(err_context)?;
             self.bus
                 .senders
@@ -950,6 +1093,57 @@ impl Screen {
         }
     }
 
+    /// Describes this session to `zellij list-sessions`.
+    pub fn session_info(&self) -> SessionInfo {
+        let seconds_since_epoch = |time: SystemTime| {
+            time.duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or(0)
+        };
+        let mut tabs: Vec<&Tab> = self.tabs.values().collect();
+        tabs.sort_by_key(|tab| tab.position);
+        SessionInfo {
+            name: envs::get_session_name().unwrap_or_default(),
+            connected_clients: self.connected_clients.borrow().len(),
+            tabs: tabs.iter().map(|tab| tab.name.clone()).collect(),
+            panes: tabs
+                .iter()
+                .map(|tab| {
+                    tab.get_all_pane_ids()
+                        .iter()
+                        .filter(|pane_id| matches!(pane_id, PaneId::Terminal(_)))
+                        .count()
+                })
+                .sum(),
+            created_at: seconds_since_epoch(self.created_at),
+            last_activity: seconds_since_epoch(self.last_activity),
+            pane_manifest: PaneManifest {
+                panes: tabs
+                    .iter()
+                    .map(|tab| (tab.position, tab.pane_infos()))
+                    .collect(),
+            },
+        }
+    }
+
+    fn render_after_synchronized_output_timeout(&mut self) -> Result<()> {
+        // a pane whose synchronized update timed out is rendered even if its application does
+        // not send anything else, so we need to make sure a render happens at that point
+        let now = Instant::now();
+        if self
+            .synchronized_output_render_at
+            .map(|render_at| render_at > now)
+            .unwrap_or(false)
+        {
+            return Ok(());
+        }
+        self.synchronized_output_render_at = Some(now + SYNCHRONIZED_OUTPUT_TIMEOUT);
+        self.bus
+            .senders
+            .send_to_background_jobs(BackgroundJob::RenderAfter(SYNCHRONIZED_OUTPUT_TIMEOUT))
+            .context("failed to schedule render after synchronized output timeout")
+    }
+
     /// Returns a mutable reference to this [`Screen`]'s tabs.
     pub fn get_tabs_mut(&mut self) -> &mut BTreeMap<usize, Tab> {
         &mut self.tabs
@@ -1004,6 +1198,66 @@ impl Screen {
         &mut self.overlay.overlay_stack
     }
 
+    /// Whether the most recent prompt is shown to this client, in which case its input goes to
+    /// the prompt rather than to its terminals
+    pub fn has_prompt_for(&self, client_id: ClientId) -> bool {
+        self.overlay
+            .overlay_stack
+            .last()
+            .map(|o| o.client_id() == client_id)
+            .unwrap_or(false)
+    }
+
+    /// Answers the most recent prompt according to the key the user pressed: y/Y confirms it,
+    /// n/N/Esc denies it and anything else is ignored. Only the client the prompt is shown to
+    /// can answer it.
+    pub fn answer_prompt(&mut self, bytes: &[u8], client_id: ClientId) -> Result<()> {
+        if !self.has_prompt_for(client_id) {
+            return Ok(());
+        }
+        match bytes {
+            [b'y'] | [b'Y'] => self.confirm_prompt(),
+            [b'n'] | [b'N'] | [27] => self.deny_prompt(),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn confirm_prompt(&mut self) -> Result<()> {
+        let callback = self
+            .overlay
+            .overlay_stack
+            .pop()
+            .and_then(|o| o.prompt_confirm());
+        self.close_prompt(callback)
+            .context("failed to confirm prompt")
+    }
+
+    pub fn deny_prompt(&mut self) -> Result<()> {
+        let callback = self
+            .overlay
+            .overlay_stack
+            .pop()
+            .and_then(|o| o.prompt_deny());
+        self.close_prompt(callback).context("failed to deny prompt")
+    }
+
+    fn close_prompt(&mut self, callback: Option<Box<PromptCallback>>) -> Result<()> {
+        match callback.map(|c| *c) {
+            Some(PromptCallback::Server(instruction)) => {
+                self.bus.senders.send_to_server(instruction)?;
+            },
+            Some(PromptCallback::Plugin(instruction)) => {
+                self.bus.senders.send_to_plugin(instruction)?;
+            },
+            None => {},
+        }
+        // re-render everything to remove the prompt from the screen
+        for tab in self.tabs.values_mut() {
+            tab.set_force_render();
+        }
+        self.render()
+    }
+
     /// Returns a mutable reference to this [`Screen`]'s indexed [`Tab`].
     pub fn get_indexed_tab_mut(&mut self, tab_index: usize) -> Option<&mut Tab> {
         self.get_tabs_mut().get_mut(&tab_index)
@@ -1065,7 +1319,7 @@ impl Screen {
         floating_panes_layout: Vec<FloatingPaneLayout>,
         new_terminal_ids: Vec<(u32, HoldForCommand)>,
         new_floating_terminal_ids: Vec<(u32, HoldForCommand)>,
-        new_plugin_ids: HashMap<RunPluginLocation, Vec<u32>>,
+        new_plugin_ids: HashMap<(RunPluginLocation, PluginUserConfiguration), Vec<u32>>,
         tab_index: usize,
         client_id: ClientId,
     ) -> Result<()> {
@@ -1138,7 +1392,8 @@ impl Screen {
 
         if !self.active_tab_indices.contains_key(&client_id) {
             // this means this is a new client and we need to add it to our state properly
-            self.add_client(client_id).with_context(err_context)?;
+            self.add_client(client_id, ClientType::Writer)
+                .with_context(err_context)?;
         }
 
         self.update_tabs()
@@ -1146,7 +1401,7 @@ impl Screen {
             .with_context(err_context)
     }
 
-    pub fn add_client(&mut self, client_id: ClientId) -> Result<()> {
+    pub fn add_client(&mut self, client_id: ClientId, client_type: ClientType) -> Result<()> {
         let err_context = |tab_index| {
             format!("failed to attach client {client_id} to tab with index {tab_index}")
         };
@@ -1170,6 +1425,9 @@ impl Screen {
 
         self.active_tab_indices.insert(client_id, tab_index);
         self.connected_clients.borrow_mut().insert(client_id);
+        if client_type == ClientType::Reader {
+            self.read_only_clients.insert(client_id);
+        }
         self.tab_history.insert(client_id, tab_history);
         self.tabs
             .get_mut(&tab_index)
@@ -1194,6 +1452,8 @@ impl Screen {
             self.tab_history.remove(&client_id);
         }
         self.connected_clients.borrow_mut().remove(&client_id);
+        self.read_only_clients.remove(&client_id);
+        self.kitty_image_store.borrow_mut().remove_client(client_id);
         self.update_tabs().with_context(err_context)
     }
 
@@ -1214,6 +1474,11 @@ impl Screen {
                         .copied()
                         .collect()
                 };
+                let read_only_clients: Vec<ClientId> = other_focused_clients
+                    .iter()
+                    .filter(|c_id| self.read_only_clients.contains(c_id))
+                    .copied()
+                    .collect();
                 let (active_swap_layout_name, is_swap_layout_dirty) = tab.swap_layout_info();
                 tab_data.push(TabInfo {
                     position: tab.position,
@@ -1224,8 +1489,10 @@ impl Screen {
                     is_sync_panes_active: tab.is_sync_panes_active(),
                     are_floating_panes_visible: tab.are_floating_panes_visible(),
                     other_focused_clients,
+                    read_only_clients,
                     active_swap_layout_name,
                     is_swap_layout_dirty,
+                    active_pane_cwd: tab.active_pane_cwd(*client_id),
                 });
             }
             plugin_updates.push((None, Some(*client_id), Event::TabUpdate(tab_data)));
@@ -1237,6 +1504,81 @@ impl Screen {
         Ok(())
     }
 
+    pub fn update_panes(&mut self) -> Result<()> {
+        // building the manifest means querying the cwd of every terminal pane, so renders caused
+        // only by pane output (which can change titles and cwds) rebuild it at most once per
+        // interval, with a render scheduled to catch up on whatever happened in between
+        let now = (self.clock)();
+        if !self.pane_manifest_is_outdated {
+            if let Some(update_at) = self.next_pane_manifest_update_at.filter(|at| *at > now) {
+                if !self.pane_manifest_update_is_scheduled {
+                    self.pane_manifest_update_is_scheduled = true;
+                    self.bus
+                        .senders
+                        .send_to_background_jobs(BackgroundJob::RenderAfter(update_at - now))
+                        .context("failed to schedule pane update")?;
+                }
+                return Ok(());
+            }
+        }
+        self.pane_manifest_is_outdated = false;
+        self.pane_manifest_update_is_scheduled = false;
+        self.next_pane_manifest_update_at = Some(now + PANE_MANIFEST_UPDATE_INTERVAL);
+        let mut pane_manifest = PaneManifest::default();
+        for tab in self.tabs.values() {
+            pane_manifest.panes.insert(tab.position, tab.pane_infos());
+        }
+        // the cwds of the processes are queried by the pty thread, which knows their pids, and
+        // are cached in the panes until it replies
+        let terminal_ids: Vec<u32> = pane_manifest
+            .panes
+            .values()
+            .flatten()
+            .filter(|pane_info| !pane_info.is_plugin)
+            .map(|pane_info| pane_info.id)
+            .collect();
+        self.bus
+            .senders
+            .send_to_pty(PtyInstruction::QueryTerminalCwds(terminal_ids))
+            .context("failed to query terminal cwds")?;
+        if pane_manifest == self.pane_manifest {
+            return Ok(());
+        }
+        self.pane_manifest = pane_manifest.clone();
+        self.bus
+            .senders
+            .send_to_plugin(PluginInstruction::Update(vec![(
+                None,
+                None,
+                Event::PaneUpdate(pane_manifest),
+            )]))
+            .context("failed to update panes")
+    }
+
+    pub fn dump_layout_to_hd(&self) -> Result<()> {
+        let err_context = || "failed to dump layout to hd".to_string();
+        let focused_tab_index = self
+            .get_first_client_id()
+            .and_then(|client_id| self.active_tab_indices.get(&client_id))
+            .copied();
+        let mut session_layout_metadata = SessionLayoutMetadata::default();
+        let mut tabs: Vec<&Tab> = self.tabs.values().collect();
+        tabs.sort_by_key(|tab| tab.position);
+        for tab in tabs {
+            let (tiled_panes, floating_panes) = tab.pane_layout_metadata();
+            session_layout_metadata.add_tab(
+                tab.name.clone(),
+                Some(tab.index) == focused_tab_index,
+                tiled_panes,
+                floating_panes,
+            );
+        }
+        self.bus
+            .senders
+            .send_to_pty(PtyInstruction::DumpLayoutToHd(session_layout_metadata))
+            .with_context(err_context)
+    }
+
     pub fn update_active_tab_name(&mut self, buf: Vec<u8>, client_id: ClientId) -> Result<()> {
         let err_context =
             || format!("failed to update active tabs name for client id: {client_id:?}");
@@ -1321,14 +1663,39 @@ impl Screen {
         };
 
         // If we leave the Search-related modes, we need to clear all previous searches
-        let search_related_modes = [InputMode::EnterSearch, InputMode::Search, InputMode::Scroll];
+        let search_related_modes = [
+            InputMode::EnterSearch,
+            InputMode::Search,
+            InputMode::Scroll,
+            InputMode::Copy,
+            InputMode::Hint,
+        ];
         if search_related_modes.contains(&previous_mode)
             && !search_related_modes.contains(&mode_info.mode)
         {
             active_tab!(self, client_id, |tab: &mut Tab| tab.clear_search(client_id));
         }
 
-        if previous_mode == InputMode::Scroll
+        if previous_mode == InputMode::Copy && mode_info.mode != InputMode::Copy {
+            active_tab!(self, client_id, |tab: &mut Tab| tab
+                .exit_copy_mode(client_id));
+        } else if previous_mode != InputMode::Copy && mode_info.mode == InputMode::Copy {
+            active_tab!(self, client_id, |tab: &mut Tab| tab
+                .enter_copy_mode(client_id));
+        }
+
+        if previous_mode == InputMode::Hint && mode_info.mode != InputMode::Hint {
+            active_tab!(self, client_id, |tab: &mut Tab| tab
+                .exit_hint_mode(client_id));
+        } else if previous_mode != InputMode::Hint && mode_info.mode == InputMode::Hint {
+            let hint_patterns = self.hint_patterns.clone();
+            active_tab!(self, client_id, |tab: &mut Tab| tab
+                .enter_hint_mode(&hint_patterns, client_id));
+        }
+
+        if (previous_mode == InputMode::Scroll
+            || previous_mode == InputMode::Copy
+            || previous_mode == InputMode::Hint)
             && (mode_info.mode == InputMode::Normal || mode_info.mode == InputMode::Locked)
         {
             if let Ok(active_tab) = self.get_active_tab_mut(client_id) {
@@ -1368,6 +1735,105 @@ impl Screen {
 
         Ok(())
     }
+    /// Opens the target of an activated hint (urls with the link opener, paths in the editor) and
+    /// then returns the client to its default mode. Tokens have already been copied or written by
+    /// the tab.
+    pub fn handle_hint_action(
+        &mut self,
+        hint_action: HintAction,
+        client_id: ClientId,
+    ) -> Result<()> {
+        let err_context = || format!("failed to handle hint action for client {client_id}");
+
+        match hint_action {
+            HintAction::Activate(HintTarget::Url(url)) if !is_openable_url(&url) => {
+                log::error!("Refusing to open {:?}, it is not a url we detect", url);
+            },
+            HintAction::Activate(HintTarget::Url(url)) => {
+                let link_opener = self
+                    .link_opener
+                    .clone()
+                    .unwrap_or_else(|| DEFAULT_LINK_OPENER.to_owned());
+                let mut command_with_args = link_opener.split(' ');
+                let command = command_with_args.next().unwrap_or(DEFAULT_LINK_OPENER);
+                match Command::new(command)
+                    .args(command_with_args)
+                    .arg(&url)
+                    .stdin(Stdio::null())
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::null())
+                    .spawn()
+                {
+                    Ok(mut child) => {
+                        // reap the opener once it exits so that it doesn't linger as a zombie
+                        std::thread::spawn(move || child.wait());
+                    },
+                    Err(e) => {
+                        Err::<(), _>(e)
+                            .with_context(|| format!("couldn't spawn {command} to open {url}"))
+                            .non_fatal();
+                    },
+                }
+            },
+            HintAction::Activate(HintTarget::Path { path, line_number }) => {
+                self.bus
+                    .senders
+                    .send_to_pty(PtyInstruction::OpenInPlaceEditor(
+                        path,
+                        line_number,
+                        client_id,
+                    ))
+                    .with_context(err_context)?;
+            },
+            HintAction::Activate(HintTarget::Text(_)) | HintAction::Write(_) => {},
+        }
+
+        let mut mode_info = self
+            .mode_info
+            .get(&client_id)
+            .unwrap_or(&self.default_mode_info)
+            .clone();
+        mode_info.mode = self.default_mode_info.mode;
+        self.bus
+            .senders
+            .send_to_plugin(PluginInstruction::Update(vec![(
+                None,
+                Some(client_id),
+                Event::ModeUpdate(mode_info.clone()),
+            )]))
+            .with_context(err_context)?;
+        self.change_mode(mode_info, client_id)
+            .with_context(err_context)
+    }
+    /// Updates the session name in the mode info of all clients and tells the plugins about it,
+    /// the server has already moved the session socket.
+    pub fn rename_session(&mut self, session_name: String) -> Result<()> {
+        let err_context = || format!("failed to rename session to {session_name}");
+
+        self.default_mode_info.session_name = Some(session_name.clone());
+        for mode_info in self.mode_info.values_mut() {
+            mode_info.session_name = Some(session_name.clone());
+        }
+        for tab in self.tabs.values_mut() {
+            tab.update_session_name(&session_name);
+        }
+        let plugin_updates = self
+            .connected_clients
+            .borrow()
+            .iter()
+            .map(|client_id| {
+                let mode_info = self
+                    .mode_info
+                    .get(client_id)
+                    .unwrap_or(&self.default_mode_info);
+                (None, Some(*client_id), Event::ModeUpdate(mode_info.clone()))
+            })
+            .collect();
+        self.bus
+            .senders
+            .send_to_plugin(PluginInstruction::Update(plugin_updates))
+            .with_context(err_context)
+    }
     pub fn change_mode_for_all_clients(&mut self, mode_info: ModeInfo) -> Result<()> {
         let err_context = || {
             format!(
@@ -1471,29 +1937,52 @@ impl Screen {
         client_id: ClientId,
     ) -> Result<bool> {
         // true => found and focused, false => not
-        let err_context = || format!("failed to focus_plugin_pane");
-        let mut tab_index_and_plugin_pane_id = None;
-        let all_tabs = self.get_tabs_mut();
-        for (tab_index, tab) in all_tabs.iter_mut() {
-            if let Some(plugin_pane_id) = tab.find_plugin(&run_plugin) {
-                tab_index_and_plugin_pane_id = Some((*tab_index, plugin_pane_id));
-                break;
-            }
+        let plugin_pane_id = self
+            .tabs
+            .values()
+            .find_map(|tab| tab.find_plugin(run_plugin));
+        match plugin_pane_id {
+            Some(plugin_pane_id) => self
+                .focus_pane_with_id(plugin_pane_id, should_float, client_id)
+                .context("failed to focus plugin pane"),
+            None => Ok(false),
         }
-        match tab_index_and_plugin_pane_id {
-            Some((tab_index, plugin_pane_id)) => {
-                self.go_to_tab(tab_index + 1, client_id)?;
+    }
+
+    pub fn focus_pane_with_id(
+        &mut self,
+        pane_id: PaneId,
+        should_float_if_hidden: bool,
+        client_id: ClientId,
+    ) -> Result<bool> {
+        // true => found and focused, false => not
+        let err_context = || format!("failed to focus pane with id {pane_id:?}");
+        let tab_index_and_position = self
+            .tabs
+            .iter()
+            .find(|(_tab_index, tab)| tab.has_pane_with_pid(&pane_id))
+            .map(|(tab_index, tab)| (*tab_index, tab.position));
+        match tab_index_and_position {
+            Some((tab_index, tab_position)) => {
+                self.go_to_tab(tab_position + 1, client_id)
+                    .with_context(err_context)?;
                 self.tabs
                     .get_mut(&tab_index)
                     .with_context(err_context)?
-                    .focus_pane_with_id(plugin_pane_id, should_float, client_id)
-                    .context("failed to focus plugin pane")?;
+                    .focus_pane_with_id(pane_id, should_float_if_hidden, client_id)
+                    .with_context(err_context)?;
                 Ok(true)
             },
             None => Ok(false),
         }
     }
 
+    fn get_tab_with_pane_id_mut(&mut self, pane_id: &PaneId) -> Option<&mut Tab> {
+        self.tabs
+            .values_mut()
+            .find(|tab| tab.has_pane_with_pid(pane_id))
+    }
+
     fn unblock_input(&self) -> Result<()> {
         self.bus
             .senders
@@ -1520,6 +2009,9 @@ pub(crate) fn screen_thread_main(
         config_options.copy_clipboard.unwrap_or_default(),
         config_options.copy_on_select.unwrap_or(true),
     );
+    let styled_underlines = config_options.styled_underlines.unwrap_or(true);
+    let link_opener = config_options.link_opener;
+    let hint_patterns = compile_hint_patterns(config_options.hint_patterns);
 
     let mut screen = Screen::new(
         bus,
@@ -1536,6 +2028,9 @@ pub(crate) fn screen_thread_main(
         auto_layout,
         session_is_mirrored,
         copy_options,
+        styled_underlines,
+        link_opener,
+        hint_patterns,
     );
 
     let mut pending_tab_ids: HashSet<usize> = HashSet::new();
@@ -1548,17 +2043,33 @@ pub(crate) fn screen_thread_main(
             .recv()
             .context("failed to receive event on channel")?;
         err_ctx.add_call(ContextType::Screen((&event).into()));
+        if !matches!(
+            event,
+            ScreenInstruction::PtyBytes(..)
+                | ScreenInstruction::PluginBytes(..)
+                | ScreenInstruction::Render
+                | ScreenInstruction::UpdateTerminalCwds(..)
+        ) {
+            // anything other than the output of the panes can change their layout, focus, etc.
+            screen.pane_manifest_is_outdated = true;
+        }
 
         match event {
             ScreenInstruction::PtyBytes(pid, vte_bytes) => {
+                let mut has_new_reported_cwd = false;
                 let all_tabs = screen.get_tabs_mut();
                 for tab in all_tabs.values_mut() {
                     if tab.has_terminal_pid(pid) {
                         tab.handle_pty_bytes(pid, vte_bytes)
                             .context("failed to process pty bytes")?;
+                        has_new_reported_cwd = tab.take_new_reported_cwd();
                         break;
                     }
                 }
+                if has_new_reported_cwd {
+                    // plugins are informed of the cwd of the active pane of each tab
+                    screen.update_tabs()?;
+                }
             },
             ScreenInstruction::PluginBytes(mut plugin_bytes) => {
                 for (pid, client_id, vte_bytes) in plugin_bytes.drain(..) {
@@ -1711,6 +2222,11 @@ pub(crate) fn screen_thread_main(
                 screen.update_tabs()?;
                 screen.render()?;
             },
+            ScreenInstruction::WriteCharacter(_bytes, client_id)
+                if screen.has_prompt_for(client_id) =>
+            {
+                // the prompt is answered through AnswerPrompt, only by the client's own keys
+            },
             ScreenInstruction::WriteCharacter(bytes, client_id) => {
                 let mut should_update_tabs = false;
                 active_tab_and_connected_client_id!(
@@ -1732,6 +2248,34 @@ pub(crate) fn screen_thread_main(
                     screen.update_tabs()?;
                 }
             },
+            ScreenInstruction::WriteKey(_key, _event_type, client_id)
+                if screen.has_prompt_for(client_id) => {},
+            ScreenInstruction::AnswerPrompt(bytes, client_id) => {
+                screen.answer_prompt(&bytes, client_id)?;
+            },
+            ScreenInstruction::WriteKey(key, event_type, client_id) => {
+                let mut should_update_tabs = false;
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| {
+                        let write_result = match tab.is_sync_panes_active() {
+                            true => tab.write_key_to_terminals_on_current_tab(
+                                key, event_type, client_id,
+                            ),
+                            false => tab.write_key_to_active_terminal(key, event_type, client_id),
+                        };
+                        if let Ok(true) = write_result {
+                            should_update_tabs = true;
+                        }
+                        write_result
+                    },
+                    ?
+                );
+                if should_update_tabs {
+                    screen.update_tabs()?;
+                }
+            },
             ScreenInstruction::Resize(client_id, strategy) => {
                 active_tab_and_connected_client_id!(
                     screen,
@@ -1832,20 +2376,35 @@ pub(crate) fn screen_thread_main(
                 screen.render()?;
                 screen.unblock_input()?;
             },
-            ScreenInstruction::DumpScreen(file, client_id, full) => {
+            ScreenInstruction::DumpScreen(file, client_id, full, last_command_output) => {
                 active_tab_and_connected_client_id!(
                     screen,
                     client_id,
                     |tab: &mut Tab, client_id: ClientId| tab.dump_active_terminal_screen(
                         Some(file.to_string()),
                         client_id,
-                        full
+                        full,
+                        last_command_output
                     ),
                     ?
                 );
                 screen.render()?;
                 screen.unblock_input()?;
             },
+            ScreenInstruction::DumpLayoutToHd => {
+                screen.dump_layout_to_hd()?;
+            },
+            ScreenInstruction::UpdateTerminalCwds(terminal_ids_to_cwds) => {
+                let mut cwd_changed = false;
+                for tab in screen.get_tabs_mut().values_mut() {
+                    cwd_changed |= tab.update_process_cwds(&terminal_ids_to_cwds);
+                }
+                if cwd_changed {
+                    screen.pane_manifest_is_outdated = true;
+                    screen.update_tabs()?;
+                    screen.render()?;
+                }
+            },
             ScreenInstruction::EditScrollback(client_id) => {
                 active_tab_and_connected_client_id!(
                     screen,
@@ -1973,6 +2532,36 @@ pub(crate) fn screen_thread_main(
                 screen.render()?;
                 screen.unblock_input()?;
             },
+            ScreenInstruction::ScrollToPreviousPrompt(client_id) => {
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| tab
+                        .scroll_active_terminal_to_previous_prompt(client_id), ?
+                );
+                screen.render()?;
+                screen.unblock_input()?;
+            },
+            ScreenInstruction::ScrollToNextPrompt(client_id) => {
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| tab
+                        .scroll_active_terminal_to_next_prompt(client_id), ?
+                );
+                screen.render()?;
+                screen.unblock_input()?;
+            },
+            ScreenInstruction::SelectLastCommandOutput(client_id) => {
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| tab
+                        .select_last_command_output(client_id), ?
+                );
+                screen.render()?;
+                screen.unblock_input()?;
+            },
             ScreenInstruction::PageScrollUp(client_id) => {
                 active_tab_and_connected_client_id!(
                     screen,
@@ -2323,6 +2912,13 @@ pub(crate) fn screen_thread_main(
                 screen.render()?;
                 screen.unblock_input()?;
             },
+            ScreenInstruction::LeftClickWithBlockSelection(point, client_id) => {
+                active_tab!(screen, client_id, |tab: &mut Tab| tab
+                    .handle_left_click_with_block_selection(&point, client_id), ?);
+                screen.update_tabs()?;
+                screen.render()?;
+                screen.unblock_input()?;
+            },
             ScreenInstruction::RightClick(point, client_id) => {
                 active_tab!(screen, client_id, |tab: &mut Tab| tab
                     .handle_right_click(&point, client_id), ?);
@@ -2381,8 +2977,8 @@ pub(crate) fn screen_thread_main(
                 screen.unblock_input()?;
                 screen.render()?;
             },
-            ScreenInstruction::AddClient(client_id) => {
-                screen.add_client(client_id)?;
+            ScreenInstruction::AddClient(client_id, client_type) => {
+                screen.add_client(client_id, client_type)?;
                 screen.update_tabs()?;
                 screen.render()?;
             },
@@ -2401,20 +2997,11 @@ pub(crate) fn screen_thread_main(
                 screen.unblock_input()?;
             },
             ScreenInstruction::ConfirmPrompt(_client_id) => {
-                let overlay = screen.get_active_overlays_mut().pop();
-                let instruction = overlay.and_then(|o| o.prompt_confirm());
-                if let Some(instruction) = instruction {
-                    screen
-                        .bus
-                        .senders
-                        .send_to_server(*instruction)
-                        .context("failed to confirm prompt")?;
-                }
+                screen.confirm_prompt()?;
                 screen.unblock_input()?;
             },
             ScreenInstruction::DenyPrompt(_client_id) => {
-                screen.get_active_overlays_mut().pop();
-                screen.render()?;
+                screen.deny_prompt()?;
                 screen.unblock_input()?;
             },
             ScreenInstruction::UpdateSearch(c, client_id) => {
@@ -2461,6 +3048,43 @@ pub(crate) fn screen_thread_main(
                 screen.render()?;
                 screen.unblock_input()?;
             },
+            ScreenInstruction::CopyModeMove(motion, client_id) => {
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| tab.copy_mode_move(motion, client_id)
+                );
+                screen.render()?;
+                screen.unblock_input()?;
+            },
+            ScreenInstruction::CopyModeSelect(selection, client_id) => {
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| tab.copy_mode_select(selection, client_id)
+                );
+                screen.render()?;
+                screen.unblock_input()?;
+            },
+            ScreenInstruction::HintInput(input, client_id) => {
+                let mut hint_action_and_client_id = None;
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| -> Result<()> {
+                        hint_action_and_client_id = tab
+                            .hint_input(input, client_id)?
+                            .map(|hint_action| (hint_action, client_id));
+                        Ok(())
+                    },
+                    ?
+                );
+                if let Some((hint_action, client_id)) = hint_action_and_client_id {
+                    screen.handle_hint_action(hint_action, client_id)?;
+                }
+                screen.render()?;
+                screen.unblock_input()?;
+            },
             ScreenInstruction::SearchToggleWholeWord(client_id) => {
                 active_tab_and_connected_client_id!(
                     screen,
@@ -2470,6 +3094,15 @@ pub(crate) fn screen_thread_main(
                 screen.render()?;
                 screen.unblock_input()?;
             },
+            ScreenInstruction::SearchToggleRegex(client_id) => {
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| tab.toggle_search_regex(client_id)
+                );
+                screen.render()?;
+                screen.unblock_input()?;
+            },
             ScreenInstruction::AddRedPaneFrameColorOverride(pane_ids, error_text) => {
                 let all_tabs = screen.get_tabs_mut();
                 for pane_id in pane_ids {
@@ -2527,6 +3160,20 @@ pub(crate) fn screen_thread_main(
                     .senders
                     .send_to_server(ServerInstruction::Log(tab_names, client_id))?;
             },
+            ScreenInstruction::QuerySessionInfo(client_id) => {
+                screen
+                    .bus
+                    .senders
+                    .send_to_server(ServerInstruction::SessionInfo(
+                        screen.session_info(),
+                        client_id,
+                    ))?;
+            },
+            ScreenInstruction::RenameSession(session_name) => {
+                screen.rename_session(session_name)?;
+                screen.render()?;
+                screen.unblock_input()?;
+            },
             ScreenInstruction::NewTiledPluginPane(run_plugin_location, pane_title, client_id) => {
                 let tab_index = screen.active_tab_indices.values().next().unwrap_or(&1);
                 let size = Size::default();
@@ -2534,6 +3181,7 @@ pub(crate) fn screen_thread_main(
                 let run_plugin = RunPlugin {
                     _allow_exec_host_cmd: false,
                     location: run_plugin_location,
+                    configuration: PluginUserConfiguration::default(),
                 };
                 screen.bus.senders.send_to_plugin(PluginInstruction::Load(
                     should_float,
@@ -2557,6 +3205,7 @@ pub(crate) fn screen_thread_main(
                 let run_plugin = RunPlugin {
                     _allow_exec_host_cmd: false,
                     location: run_plugin_location,
+                    configuration: PluginUserConfiguration::default(),
                 };
                 screen.bus.senders.send_to_plugin(PluginInstruction::Load(
                     should_float,
@@ -2574,6 +3223,7 @@ pub(crate) fn screen_thread_main(
                 let run_plugin = RunPlugin {
                     _allow_exec_host_cmd: false,
                     location: run_plugin_location,
+                    configuration: PluginUserConfiguration::default(),
                 };
                 screen
                     .bus
@@ -2640,6 +3290,9 @@ pub(crate) fn screen_thread_main(
                 screen.render()?;
             },
             ScreenInstruction::RequestStateUpdateForPlugins => {
+                // make sure the pane manifest is sent again even if it did not change
+                screen.pane_manifest = PaneManifest::default();
+                screen.pane_manifest_is_outdated = true;
                 let all_tabs = screen.get_tabs_mut();
                 for tab in all_tabs.values_mut() {
                     tab.update_input_modes()?;
@@ -2687,6 +3340,73 @@ pub(crate) fn screen_thread_main(
                     }
                 }
             },
+            ScreenInstruction::FocusPaneWithId(pane_id, should_float_if_hidden, client_id) => {
+                let client_id = if screen.active_tab_indices.contains_key(&client_id) {
+                    Some(client_id)
+                } else {
+                    screen.get_first_client_id()
+                };
+                match client_id {
+                    Some(client_id) => {
+                        if screen.focus_pane_with_id(pane_id, should_float_if_hidden, client_id)? {
+                            screen.render()?;
+                            screen.update_tabs()?;
+                        } else {
+                            log::error!("Could not find pane with id {:?} to focus", pane_id);
+                        }
+                    },
+                    None => log::error!("No connected clients found - cannot focus pane"),
+                }
+            },
+            ScreenInstruction::WriteToPaneWithId(bytes, pane_id) => {
+                let mut should_update_tabs = false;
+                if let Some(tab) = screen.get_tab_with_pane_id_mut(&pane_id) {
+                    should_update_tabs = tab.write_to_pane_id(bytes, pane_id, None)?;
+                }
+                if should_update_tabs {
+                    screen.update_tabs()?;
+                }
+            },
+            ScreenInstruction::RenamePaneWithId(pane_id, new_name) => {
+                if let Some(tab) = screen.get_tab_with_pane_id_mut(&pane_id) {
+                    tab.rename_pane_with_id(pane_id, new_name)?;
+                }
+                screen.render()?;
+            },
+            ScreenInstruction::ResizePaneWithId(strategy, pane_id) => {
+                if let Some(tab) = screen.get_tab_with_pane_id_mut(&pane_id) {
+                    tab.resize_pane_with_id(strategy, pane_id)?;
+                }
+                screen.render()?;
+                screen.update_tabs()?;
+            },
+            ScreenInstruction::TogglePaneEmbedOrFloatingWithId(pane_id) => {
+                if let Some(tab) = screen.get_tab_with_pane_id_mut(&pane_id) {
+                    tab.toggle_pane_embed_or_floating_for_pane_id(pane_id, None)?;
+                }
+                screen.update_tabs()?; // update tabs so that the ui indication will be send to the plugins
+                screen.render()?;
+            },
+            ScreenInstruction::RerunCommandPaneWithId(terminal_pane_id) => {
+                let pane_id = PaneId::Terminal(terminal_pane_id);
+                if let Some(tab) = screen.get_tab_with_pane_id_mut(&pane_id) {
+                    tab.rerun_terminal_pane_with_id(terminal_pane_id)?;
+                }
+                screen.render()?;
+            },
+            ScreenInstruction::RequestPluginPermissions(
+                plugin_id,
+                client_id,
+                plugin_name,
+                permissions,
+            ) => {
+                // unlike other overlays, permission prompts are stacked so that requests from
+                // several plugins are all answered
+                let prompt =
+                    generate_permission_prompt(plugin_id, client_id, &plugin_name, permissions);
+                screen.get_active_overlays_mut().push(prompt);
+                screen.render()?;
+            },
         }
     }
     Ok(())
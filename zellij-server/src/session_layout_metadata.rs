@@ -0,0 +1,146 @@
+use crate::panes::PaneId;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use zellij_utils::input::layout::Run;
+use zellij_utils::pane_size::PaneGeom;
+use zellij_utils::session_serialization::{
+    GlobalLayoutManifest, PaneLayoutManifest, TabLayoutManifest,
+};
+
+/// A snapshot of the layout of the session, collected by the screen thread and completed (eg.
+/// with the cwd of each terminal) by the pty thread before being serialized.
+#[derive(Default, Debug, Clone)]
+pub struct SessionLayoutMetadata {
+    default_shell: Option<PathBuf>,
+    tabs: Vec<TabLayoutMetadata>,
+}
+
+impl SessionLayoutMetadata {
+    pub fn add_tab(
+        &mut self,
+        name: String,
+        is_focused: bool,
+        tiled_panes: Vec<PaneLayoutMetadata>,
+        floating_panes: Vec<PaneLayoutMetadata>,
+    ) {
+        self.tabs.push(TabLayoutMetadata {
+            name,
+            is_focused,
+            tiled_panes,
+            floating_panes,
+        })
+    }
+    pub fn set_default_shell(&mut self, default_shell: PathBuf) {
+        self.default_shell = Some(default_shell);
+    }
+    pub fn all_terminal_ids(&self) -> Vec<u32> {
+        let mut terminal_ids = vec![];
+        for tab in &self.tabs {
+            for pane in tab.tiled_panes.iter().chain(tab.floating_panes.iter()) {
+                if let PaneId::Terminal(id) = pane.id {
+                    terminal_ids.push(id);
+                }
+            }
+        }
+        terminal_ids
+    }
+    pub fn update_terminal_cwds(&mut self, mut terminal_ids_to_cwds: HashMap<u32, PathBuf>) {
+        for tab in self.tabs.iter_mut() {
+            for pane in tab
+                .tiled_panes
+                .iter_mut()
+                .chain(tab.floating_panes.iter_mut())
+            {
+                if let PaneId::Terminal(id) = pane.id {
+                    if let Some(cwd) = terminal_ids_to_cwds.remove(&id) {
+                        pane.cwd = Some(cwd);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl From<&SessionLayoutMetadata> for GlobalLayoutManifest {
+    fn from(session_layout_metadata: &SessionLayoutMetadata) -> Self {
+        GlobalLayoutManifest {
+            default_shell: session_layout_metadata.default_shell.clone(),
+            tabs: session_layout_metadata
+                .tabs
+                .iter()
+                .map(|t| (t.name.clone(), t.into()))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct TabLayoutMetadata {
+    name: String,
+    is_focused: bool,
+    tiled_panes: Vec<PaneLayoutMetadata>,
+    floating_panes: Vec<PaneLayoutMetadata>,
+}
+
+impl From<&TabLayoutMetadata> for TabLayoutManifest {
+    fn from(tab_layout_metadata: &TabLayoutMetadata) -> Self {
+        TabLayoutManifest {
+            tiled_panes: tab_layout_metadata
+                .tiled_panes
+                .iter()
+                .map(|p| p.into())
+                .collect(),
+            floating_panes: tab_layout_metadata
+                .floating_panes
+                .iter()
+                .map(|p| p.into())
+                .collect(),
+            is_focused: tab_layout_metadata.is_focused,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PaneLayoutMetadata {
+    id: PaneId,
+    geom: PaneGeom,
+    run: Option<Run>,
+    cwd: Option<PathBuf>,
+    is_borderless: bool,
+    title: Option<String>,
+    is_focused: bool,
+}
+
+impl PaneLayoutMetadata {
+    pub fn new(
+        id: PaneId,
+        geom: PaneGeom,
+        run: Option<Run>,
+        is_borderless: bool,
+        title: Option<String>,
+        is_focused: bool,
+    ) -> Self {
+        PaneLayoutMetadata {
+            id,
+            geom,
+            run,
+            cwd: None,
+            is_borderless,
+            title,
+            is_focused,
+        }
+    }
+}
+
+impl From<&PaneLayoutMetadata> for PaneLayoutManifest {
+    fn from(pane_layout_metadata: &PaneLayoutMetadata) -> Self {
+        PaneLayoutManifest {
+            geom: pane_layout_metadata.geom,
+            run: pane_layout_metadata.run.clone(),
+            cwd: pane_layout_metadata.cwd.clone(),
+            is_borderless: pane_layout_metadata.is_borderless,
+            title: pane_layout_metadata.title.clone(),
+            is_focused: pane_layout_metadata.is_focused,
+        }
+    }
+}
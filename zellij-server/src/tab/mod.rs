@@ -25,12 +25,13 @@ use swap_layouts::SwapLayouts;
 use self::clipboard::ClipboardProvider;
 use crate::{
     os_input_output::ServerOsApi,
-    output::{CharacterChunk, Output, SixelImageChunk},
+    output::{CharacterChunk, KittyImageChunk, Output, SixelImageChunk},
     panes::sixel::SixelImageStore,
     panes::{FloatingPanes, TiledPanes},
-    panes::{LinkHandler, PaneId, PluginPane, TerminalPane},
+    panes::{HintAction, HintTarget, LinkHandler, PaneId, PluginPane, TerminalPane},
     plugins::PluginInstruction,
     pty::{ClientOrTabIndex, PtyInstruction, VteBytes},
+    session_layout_metadata::PaneLayoutMetadata,
     thread_bus::ThreadSenders,
     ClientId, ServerInstruction,
 };
@@ -40,19 +41,23 @@ use std::rc::Rc;
 use std::time::Instant;
 use std::{
     collections::{HashMap, HashSet},
+    path::PathBuf,
     str,
 };
 use zellij_utils::{
-    data::{Event, InputMode, ModeInfo, Palette, PaletteColor, Style},
+    data::{Event, InputMode, Key, KeyEventType, ModeInfo, Palette, PaletteColor, PaneInfo, Style},
     input::{
+        actions::{CopyModeMotion, CopyModeSelection},
         command::TerminalAction,
+        kitty_keyboard,
         layout::{
-            FloatingPaneLayout, Run, RunPlugin, RunPluginLocation, SwapFloatingLayout,
-            SwapTiledLayout, TiledPaneLayout,
+            FloatingPaneLayout, PluginUserConfiguration, Run, RunPlugin, RunPluginLocation,
+            SwapFloatingLayout, SwapTiledLayout, TiledPaneLayout,
         },
         parse_keys,
     },
     pane_size::{Offset, PaneGeom, Size, SizeInPixels, Viewport},
+    regex::Regex,
 };
 
 #[macro_export]
@@ -170,6 +175,7 @@ pub(crate) struct Tab {
     pending_instructions: Vec<BufferedTabInstruction>, // instructions that came while the tab was
     // pending and need to be re-applied
     swap_layouts: SwapLayouts,
+    has_new_reported_cwd: bool,
 }
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
@@ -201,6 +207,9 @@ pub trait Pane {
     fn adjust_input_to_terminal(&mut self, _input_bytes: Vec<u8>) -> Option<AdjustedInput> {
         None
     }
+    fn encode_key(&self, key: &Key, event_type: KeyEventType) -> Option<Vec<u8>> {
+        kitty_keyboard::legacy_encoding(key, event_type)
+    }
     fn position_and_size(&self) -> PaneGeom;
     fn current_geom(&self) -> PaneGeom;
     fn geom_override(&self) -> Option<PaneGeom>;
@@ -239,6 +248,12 @@ pub trait Pane {
     fn dump_screen(&mut self, _client_id: ClientId, _full: bool) -> String {
         "".to_owned()
     }
+    fn dump_last_command_output(&self) -> String {
+        "".to_owned()
+    }
+    fn scroll_to_previous_prompt(&mut self) {}
+    fn scroll_to_next_prompt(&mut self) {}
+    fn select_last_command_output(&mut self) {}
     fn scroll_up(&mut self, count: usize, client_id: ClientId);
     fn scroll_down(&mut self, count: usize, client_id: ClientId);
     fn clear_scroll(&mut self);
@@ -257,6 +272,10 @@ pub trait Pane {
         }
     }
     fn start_selection(&mut self, _start: &Position, _client_id: ClientId) {}
+    fn start_block_selection(&mut self, start: &Position, client_id: ClientId) {
+        // panes without block selection fall back to a regular one
+        self.start_selection(start, client_id)
+    }
     fn update_selection(&mut self, _position: &Position, _client_id: ClientId) {}
     fn end_selection(&mut self, _end: &Position, _client_id: ClientId) {}
     fn reset_selection(&mut self) {}
@@ -336,6 +355,18 @@ pub trait Pane {
     fn drain_clipboard_update(&mut self) -> Option<String> {
         None
     }
+    fn drain_cwd_update(&mut self) -> Option<PathBuf> {
+        // returns the cwd most recently reported by the pane, if it changed
+        None
+    }
+    fn cwd(&self) -> Option<PathBuf> {
+        // the cwd reported by the pane itself (OSC 7) or otherwise the last known cwd of its process
+        None
+    }
+    fn update_process_cwd(&mut self, _cwd: PathBuf) -> bool {
+        // returns true if the cwd of the process changed
+        false
+    }
     fn render_full_viewport(&mut self) {}
     fn relative_position(&self, position_on_screen: &Position) -> Position {
         position_on_screen.relative_to(self.get_content_y(), self.get_content_x())
@@ -427,21 +458,69 @@ pub trait Pane {
     fn toggle_search_wrap(&mut self) {
         // No-op by default (only terminal-panes currently have search capability)
     }
+    fn toggle_search_regex(&mut self) {
+        // No-op by default (only terminal-panes currently have search capability)
+    }
     fn clear_search(&mut self) {
         // No-op by default (only terminal-panes currently have search capability)
     }
+    fn enter_copy_mode(&mut self) {
+        // No-op by default (only terminal-panes currently have copy mode)
+    }
+    fn exit_copy_mode(&mut self) {
+        // No-op by default (only terminal-panes currently have copy mode)
+    }
+    fn copy_mode_move(&mut self, _motion: CopyModeMotion) {
+        // No-op by default (only terminal-panes currently have copy mode)
+    }
+    fn copy_mode_select(&mut self, _selection: CopyModeSelection) {
+        // No-op by default (only terminal-panes currently have copy mode)
+    }
+    fn enter_hint_mode(&mut self, _hint_patterns: &[Regex]) {
+        // No-op by default (only terminal-panes currently have hint mode)
+    }
+    fn exit_hint_mode(&mut self) {
+        // No-op by default (only terminal-panes currently have hint mode)
+    }
+    fn hint_input(&mut self, _input: &[u8]) -> Option<HintAction> {
+        // None by default (only terminal-panes currently have hint mode)
+        None
+    }
     fn is_alternate_mode_active(&self) -> bool {
         // False by default (only terminal-panes support alternate mode)
         false
     }
+    fn is_holding_synchronized_output(&self) -> bool {
+        // False by default (only terminal-panes support synchronized output)
+        false
+    }
+    fn kitty_image_chunks(&self) -> Vec<KittyImageChunk> {
+        // Empty by default (only terminal-panes support the kitty graphics protocol)
+        vec![]
+    }
     fn hold(&mut self, _exit_status: Option<i32>, _is_first_run: bool, _run_command: RunCommand) {
         // No-op by default, only terminal panes support holding
     }
+    fn is_held(&self) -> bool {
+        // False by default, only terminal panes support holding
+        false
+    }
+    fn exit_status(&self) -> Option<i32> {
+        // None by default, only terminal panes run commands that can exit
+        None
+    }
+    fn rerun(&mut self) -> Option<RunCommand> {
+        // None by default, only terminal panes can be held and rerun
+        None
+    }
     fn add_red_pane_frame_color_override(&mut self, _error_text: Option<String>);
     fn clear_pane_frame_color_override(&mut self);
     fn frame_color_override(&self) -> Option<PaletteColor>;
     fn invoked_with(&self) -> &Option<Run>;
     fn set_title(&mut self, title: String);
+    fn rename(&mut self, name: String); // explicitly give this pane a name
+    fn custom_title(&self) -> Option<String>; // the name explicitly given to this pane, if any
+    fn current_title(&self) -> String; // the title as it is displayed in the pane frame
     fn update_loading_indication(&mut self, _loading_indication: LoadingIndication) {} // only relevant for plugins
     fn start_loading_indication(&mut self, _loading_indication: LoadingIndication) {} // only relevant for plugins
     fn progress_animation_offset(&mut self) {} // only relevant for plugins
@@ -584,6 +663,7 @@ impl Tab {
             is_pending: true, // will be switched to false once the layout is applied
             pending_instructions: vec![],
             swap_layouts,
+            has_new_reported_cwd: false,
         }
     }
 
@@ -593,7 +673,7 @@ impl Tab {
         floating_panes_layout: Vec<FloatingPaneLayout>,
         new_terminal_ids: Vec<(u32, HoldForCommand)>,
         new_floating_terminal_ids: Vec<(u32, HoldForCommand)>,
-        new_plugin_ids: HashMap<RunPluginLocation, Vec<u32>>,
+        new_plugin_ids: HashMap<(RunPluginLocation, PluginUserConfiguration), Vec<u32>>,
         client_id: ClientId,
     ) -> Result<()> {
         self.swap_layouts
@@ -855,6 +935,13 @@ impl Tab {
         self.mode_info.borrow_mut().insert(client_id, mode_info);
     }
 
+    pub fn update_session_name(&mut self, session_name: &str) {
+        self.default_mode_info.session_name = Some(session_name.to_owned());
+        for mode_info in self.mode_info.borrow_mut().values_mut() {
+            mode_info.session_name = Some(session_name.to_owned());
+        }
+    }
+
     pub fn add_multiple_clients(
         &mut self,
         client_ids_to_mode_infos: Vec<(ClientId, ModeInfo)>,
@@ -899,38 +986,46 @@ impl Tab {
         self.connected_clients.borrow().is_empty()
     }
     pub fn toggle_pane_embed_or_floating(&mut self, client_id: ClientId) -> Result<()> {
-        let err_context =
-            || format!("failed to toggle embedded/floating pane for client {client_id}");
+        let focused_pane_id = if self.floating_panes.panes_are_visible() {
+            self.floating_panes.active_pane_id(client_id)
+        } else {
+            self.tiled_panes.focused_pane_id(client_id)
+        };
+        match focused_pane_id {
+            Some(focused_pane_id) => {
+                self.toggle_pane_embed_or_floating_for_pane_id(focused_pane_id, Some(client_id))
+            },
+            None => Ok(()),
+        }
+    }
+    pub fn toggle_pane_embed_or_floating_for_pane_id(
+        &mut self,
+        pane_id: PaneId,
+        client_id: Option<ClientId>,
+    ) -> Result<()> {
+        let err_context = || format!("failed to toggle embedded/floating pane with id {pane_id:?}");
         if self.tiled_panes.fullscreen_is_active() {
             self.tiled_panes.unset_fullscreen();
         }
-        if self.floating_panes.panes_are_visible() {
-            if let Some(focused_floating_pane_id) = self.floating_panes.active_pane_id(client_id) {
-                if self.tiled_panes.has_room_for_new_pane() {
-                    let floating_pane_to_embed = self
-                        .close_pane(focused_floating_pane_id, true, Some(client_id))
-                        .with_context(|| format!(
-                        "failed to find floating pane (ID: {focused_floating_pane_id:?}) to embed for client {client_id}",
-                    ))
-                        .with_context(err_context)?;
-                    self.hide_floating_panes();
-                    self.add_tiled_pane(
-                        floating_pane_to_embed,
-                        focused_floating_pane_id,
-                        Some(client_id),
-                    )?;
-                }
+        if self.floating_panes.panes_contain(&pane_id) {
+            if self.tiled_panes.has_room_for_new_pane() {
+                let floating_pane_to_embed = self
+                    .close_pane(pane_id, true, client_id)
+                    .with_context(|| {
+                        format!("failed to find floating pane (ID: {pane_id:?}) to embed")
+                    })
+                    .with_context(err_context)?;
+                self.hide_floating_panes();
+                self.add_tiled_pane(floating_pane_to_embed, pane_id, client_id)?;
             }
-        } else if let Some(focused_pane_id) = self.tiled_panes.focused_pane_id(client_id) {
+        } else if self.tiled_panes.panes_contain(&pane_id) {
             if self.get_selectable_tiled_panes().count() <= 1 {
                 // don't close the only pane on screen...
                 return Ok(());
             }
-            if let Some(embedded_pane_to_float) =
-                self.close_pane(focused_pane_id, true, Some(client_id))
-            {
+            if let Some(embedded_pane_to_float) = self.close_pane(pane_id, true, client_id) {
                 self.show_floating_panes();
-                self.add_floating_pane(embedded_pane_to_float, focused_pane_id, Some(client_id))?;
+                self.add_floating_pane(embedded_pane_to_float, pane_id, client_id)?;
             }
         }
         Ok(())
@@ -1374,6 +1469,7 @@ impl Tab {
             terminal_output.handle_pty_bytes(bytes);
             let messages_to_pty = terminal_output.drain_messages_to_pty();
             let clipboard_update = terminal_output.drain_clipboard_update();
+            let cwd_update = terminal_output.drain_cwd_update();
             for message in messages_to_pty {
                 self.write_to_pane_id(message, PaneId::Terminal(pid), None)
                     .with_context(err_context)?;
@@ -1382,9 +1478,42 @@ impl Tab {
                 self.write_selection_to_clipboard(&string)
                     .with_context(err_context)?;
             }
+            if let Some(cwd) = cwd_update {
+                self.has_new_reported_cwd = true;
+                self.senders
+                    .send_to_pty(PtyInstruction::UpdateTerminalCwd(pid, cwd))
+                    .with_context(err_context)?;
+            }
         }
         Ok(())
     }
+    pub fn take_new_reported_cwd(&mut self) -> bool {
+        // whether one of the panes in this tab reported a new cwd since this was last called
+        std::mem::replace(&mut self.has_new_reported_cwd, false)
+    }
+    pub fn update_process_cwds(&mut self, terminal_ids_to_cwds: &HashMap<u32, PathBuf>) -> bool {
+        // returns true if the cwd of one of the panes in this tab changed
+        let mut cwd_changed = false;
+        for (terminal_id, cwd) in terminal_ids_to_cwds {
+            let pane_id = PaneId::Terminal(*terminal_id);
+            if let Some(pane) = self
+                .tiled_panes
+                .get_pane_mut(pane_id)
+                .or_else(|| self.floating_panes.get_pane_mut(pane_id))
+                .or_else(|| {
+                    self.suppressed_panes
+                        .values_mut()
+                        .find(|s_p| s_p.pid() == pane_id)
+                })
+            {
+                cwd_changed |= pane.update_process_cwd(cwd.clone());
+            }
+        }
+        cwd_changed
+    }
+    pub fn active_pane_cwd(&self, client_id: ClientId) -> Option<PathBuf> {
+        self.get_active_pane(client_id)?.cwd()
+    }
 
     pub fn write_to_terminals_on_current_tab(
         &mut self,
@@ -1420,24 +1549,114 @@ impl Tab {
         };
 
         self.clear_search(client_id); // this is an inexpensive operation if empty, if we need more such cleanups we should consider moving this and the rest to some sort of cleanup method
-        let pane_id = if self.floating_panes.panes_are_visible() {
+        let pane_id = self
+            .get_active_pane_id_for_input(client_id)
+            .with_context(err_context)?;
+        // Can't use 'err_context' here since it borrows 'input_bytes'
+        self.write_to_pane_id(input_bytes, pane_id, Some(client_id))
+            .with_context(|| format!("failed to write to active terminal for client {client_id}"))
+    }
+
+    pub fn write_key_to_terminals_on_current_tab(
+        &mut self,
+        key: Key,
+        event_type: KeyEventType,
+        client_id: ClientId,
+    ) -> Result<bool> {
+        // returns true if a UI update should be triggered (eg. when closing a command pane with
+        // ctrl-c)
+        let mut should_trigger_ui_change = false;
+        let pane_ids = self.get_static_and_floating_pane_ids();
+        for pane_id in pane_ids {
+            let ui_change_triggered = self
+                .write_key_to_pane_id(key, event_type, pane_id, Some(client_id))
+                .context("failed to write key to terminals on current tab")?;
+            if ui_change_triggered {
+                should_trigger_ui_change = true;
+            }
+        }
+        Ok(should_trigger_ui_change)
+    }
+
+    pub fn write_key_to_active_terminal(
+        &mut self,
+        key: Key,
+        event_type: KeyEventType,
+        client_id: ClientId,
+    ) -> Result<bool> {
+        // returns true if a UI update should be triggered (eg. if a command pane
+        // was closed with ctrl-c)
+        let err_context =
+            || format!("failed to write key {key:?} to active terminal for client {client_id}");
+
+        self.clear_search(client_id);
+        let pane_id = self
+            .get_active_pane_id_for_input(client_id)
+            .with_context(err_context)?;
+        self.write_key_to_pane_id(key, event_type, pane_id, Some(client_id))
+            .with_context(err_context)
+    }
+
+    pub fn write_key_to_pane_id(
+        &mut self,
+        key: Key,
+        event_type: KeyEventType,
+        pane_id: PaneId,
+        client_id: Option<ClientId>,
+    ) -> Result<bool> {
+        // returns true if we need to update the UI (eg. when a command pane is closed with ctrl-c)
+        let err_context = || format!("failed to write key {key:?} to pane with id {pane_id:?}");
+
+        match pane_id {
+            PaneId::Terminal(_) => {
+                // each pane gets the key encoded according to what its application asked for
+                let encoded_key = self
+                    .floating_panes
+                    .get(&pane_id)
+                    .or_else(|| self.tiled_panes.get_pane(pane_id))
+                    .or_else(|| self.suppressed_panes.get(&pane_id))
+                    .ok_or_else(|| anyhow!(format!("failed to find pane with id {pane_id:?}")))
+                    .with_context(err_context)?
+                    .encode_key(&key, event_type);
+                match encoded_key {
+                    Some(input_bytes) => self
+                        .write_to_pane_id(input_bytes, pane_id, client_id)
+                        .with_context(err_context),
+                    None => Ok(false),
+                }
+            },
+            PaneId::Plugin(pid) => {
+                let should_not_write_to_plugin = self.is_sync_panes_active()
+                    && self
+                        .floating_panes
+                        .get(&pane_id)
+                        .or_else(|| self.tiled_panes.get_pane(pane_id))
+                        .map(|pane| pane.exclude_from_sync())
+                        .unwrap_or(false);
+                if event_type != KeyEventType::Release && !should_not_write_to_plugin {
+                    self.senders
+                        .send_to_plugin(PluginInstruction::Update(vec![(
+                            Some(pid),
+                            client_id,
+                            Event::Key(key),
+                        )]))
+                        .with_context(err_context)?;
+                }
+                Ok(false)
+            },
+        }
+    }
+
+    fn get_active_pane_id_for_input(&self, client_id: ClientId) -> Result<PaneId> {
+        let active_pane_id = if self.floating_panes.panes_are_visible() {
             self.floating_panes
                 .get_active_pane_id(client_id)
                 .or_else(|| self.tiled_panes.get_active_pane_id(client_id))
-                .ok_or_else(|| {
-                    anyhow!(format!(
-                        "failed to find active pane id for client {client_id}"
-                    ))
-                })
-                .with_context(err_context)?
         } else {
-            self.tiled_panes
-                .get_active_pane_id(client_id)
-                .with_context(err_context)?
+            self.tiled_panes.get_active_pane_id(client_id)
         };
-        // Can't use 'err_context' here since it borrows 'input_bytes'
-        self.write_to_pane_id(input_bytes, pane_id, Some(client_id))
-            .with_context(|| format!("failed to write to active terminal for client {client_id}"))
+        active_pane_id
+            .ok_or_else(|| anyhow!("failed to find active pane id for client {client_id}"))
     }
 
     pub fn write_to_terminal_at(
@@ -1577,6 +1796,111 @@ impl Tab {
     pub fn are_floating_panes_visible(&self) -> bool {
         self.floating_panes.panes_are_visible()
     }
+    pub fn pane_layout_metadata(&self) -> (Vec<PaneLayoutMetadata>, Vec<PaneLayoutMetadata>) {
+        // (tiled_panes, floating_panes)
+        let floating_panes_are_focused =
+            self.floating_panes.panes_are_visible() && self.floating_panes.has_active_panes();
+        let focused_tiled_pane_id = self.tiled_panes.first_active_pane_id();
+        let focused_floating_pane_id = self.floating_panes.first_active_floating_pane_id();
+        let tiled_panes = self
+            .tiled_panes
+            .get_panes()
+            .map(|(pane_id, pane)| {
+                PaneLayoutMetadata::new(
+                    *pane_id,
+                    pane.position_and_size(), // not the fullscreen geom override
+                    pane.invoked_with().clone(),
+                    pane.borderless(),
+                    pane.custom_title(),
+                    !floating_panes_are_focused && focused_tiled_pane_id == Some(*pane_id),
+                )
+            })
+            .collect();
+        let viewport = *self.viewport.borrow();
+        let floating_panes = self
+            .floating_panes
+            .get_panes()
+            .map(|(pane_id, pane)| {
+                let mut geom = pane.position_and_size();
+                geom.x = geom.x.saturating_sub(viewport.x);
+                geom.y = geom.y.saturating_sub(viewport.y);
+                PaneLayoutMetadata::new(
+                    *pane_id,
+                    geom,
+                    pane.invoked_with().clone(),
+                    pane.borderless(),
+                    pane.custom_title(),
+                    floating_panes_are_focused && focused_floating_pane_id == Some(*pane_id),
+                )
+            })
+            .collect();
+        (tiled_panes, floating_panes)
+    }
+    pub fn pane_infos(&self) -> Vec<PaneInfo> {
+        let mut focused_clients: HashMap<PaneId, Vec<ClientId>> = HashMap::new();
+        for client_id in self.connected_clients.borrow().iter() {
+            if let Some(pane_id) = self.get_active_pane_id(*client_id) {
+                focused_clients.entry(pane_id).or_default().push(*client_id);
+            }
+        }
+        for client_ids in focused_clients.values_mut() {
+            client_ids.sort_unstable();
+        }
+        let fullscreen_pane_id = if self.is_fullscreen_active() {
+            self.tiled_panes.first_active_pane_id()
+        } else {
+            None
+        };
+        let pane_info = |pane: &dyn Pane, is_floating: bool, is_suppressed: bool| {
+            let pane_id = pane.pid();
+            let (id, is_plugin, cwd) = match pane_id {
+                PaneId::Terminal(terminal_id) => (terminal_id, false, pane.cwd()),
+                PaneId::Plugin(plugin_id) => (plugin_id, true, None),
+            };
+            PaneInfo {
+                id,
+                is_plugin,
+                title: pane.current_title(),
+                focused_clients: focused_clients.get(&pane_id).cloned().unwrap_or_default(),
+                is_floating,
+                is_stacked: pane.current_geom().is_stacked,
+                is_fullscreen: fullscreen_pane_id == Some(pane_id),
+                is_suppressed,
+                is_selectable: pane.selectable(),
+                pane_x: pane.x(),
+                pane_y: pane.y(),
+                pane_rows: pane.rows(),
+                pane_columns: pane.cols(),
+                pane_content_x: pane.get_content_x(),
+                pane_content_y: pane.get_content_y(),
+                pane_content_rows: pane.get_content_rows(),
+                pane_content_columns: pane.get_content_columns(),
+                is_held: pane.is_held(),
+                exit_status: pane.exit_status(),
+                cwd,
+            }
+        };
+        let mut suppressed_panes: Vec<&dyn Pane> = self
+            .suppressed_panes
+            .values()
+            .map(|pane| pane.as_ref())
+            .collect();
+        suppressed_panes.sort_by_key(|pane| pane.pid());
+        self.tiled_panes
+            .get_panes()
+            .map(|(_pane_id, pane)| pane_info(pane.as_ref(), false, false))
+            .chain(
+                self.floating_panes
+                    .get_panes()
+                    .map(|(_pane_id, pane)| pane_info(pane.as_ref(), true, false)),
+            )
+            .chain(
+                suppressed_panes
+                    .into_iter()
+                    .map(|pane| pane_info(pane, false, true)),
+            )
+            .collect()
+    }
     pub fn focus_pane_left_fullscreen(&mut self, client_id: ClientId) {
         if !self.is_fullscreen_active() {
             return;
@@ -1776,6 +2100,12 @@ impl Tab {
             .filter(|(_, p)| p.selectable());
         selectable_tiled_panes.count() > 0 || selectable_floating_panes.count() > 0
     }
+    pub fn is_holding_synchronized_output(&self) -> bool {
+        self.tiled_panes
+            .get_panes()
+            .chain(self.floating_panes.get_panes())
+            .any(|(_, p)| p.is_holding_synchronized_output())
+    }
     pub fn has_selectable_tiled_panes(&self) -> bool {
         let selectable_tiled_panes = self.tiled_panes.get_panes().filter(|(_, p)| p.selectable());
         selectable_tiled_panes.count() > 0
@@ -1807,19 +2137,30 @@ impl Tab {
         Ok(())
     }
     pub fn resize(&mut self, client_id: ClientId, strategy: ResizeStrategy) -> Result<()> {
+        let active_pane_id = if self.floating_panes.panes_are_visible() {
+            self.floating_panes.active_pane_id(client_id)
+        } else {
+            self.tiled_panes.get_active_pane_id(client_id)
+        };
+        match active_pane_id {
+            Some(active_pane_id) => self.resize_pane_with_id(strategy, active_pane_id),
+            None => Ok(()),
+        }
+    }
+    pub fn resize_pane_with_id(&mut self, strategy: ResizeStrategy, pane_id: PaneId) -> Result<()> {
         let err_context = || format!("unable to resize pane");
         self.swap_layouts.set_is_floating_damaged();
         self.swap_layouts.set_is_tiled_damaged();
-        if self.floating_panes.panes_are_visible() {
+        if self.floating_panes.panes_contain(&pane_id) {
             let successfully_resized = self
                 .floating_panes
-                .resize_active_pane(client_id, &mut self.os_api, &strategy)
+                .resize_pane_with_id(pane_id, &mut self.os_api, &strategy)
                 .with_context(err_context)?;
             if successfully_resized {
                 self.set_force_render(); // we force render here to make sure the panes under the floating pane render and don't leave "garbage" in case of a decrease
             }
         } else {
-            match self.tiled_panes.resize_active_pane(client_id, &strategy) {
+            match self.tiled_panes.resize_pane_with_id(pane_id, &strategy) {
                 Ok(_) => {},
                 Err(err) => match err.downcast_ref::<ZellijError>() {
                     Some(ZellijError::CantResizeFixedPanes { pane_ids }) => {
@@ -2273,12 +2614,17 @@ impl Tab {
         file: Option<String>,
         client_id: ClientId,
         full: bool,
+        last_command_output: bool,
     ) -> Result<()> {
         let err_context =
             || format!("failed to dump active terminal screen for client {client_id}");
 
         if let Some(active_pane) = self.get_active_pane_or_floating_pane_mut(client_id) {
-            let dump = active_pane.dump_screen(client_id, full);
+            let dump = if last_command_output {
+                active_pane.dump_last_command_output()
+            } else {
+                active_pane.dump_screen(client_id, full)
+            };
             self.os_api
                 .write_to_file(dump, file)
                 .with_context(err_context)?;
@@ -2294,6 +2640,7 @@ impl Tab {
             Some(String::from(file.to_string_lossy())),
             client_id,
             true,
+            false,
         )
         .with_context(err_context)?;
         let line_number = self
@@ -2404,6 +2751,47 @@ impl Tab {
         Ok(())
     }
 
+    pub fn scroll_active_terminal_to_previous_prompt(&mut self, client_id: ClientId) -> Result<()> {
+        if let Some(active_pane) = self.get_active_pane_or_floating_pane_mut(client_id) {
+            active_pane.scroll_to_previous_prompt();
+        }
+        Ok(())
+    }
+
+    pub fn scroll_active_terminal_to_next_prompt(&mut self, client_id: ClientId) -> Result<()> {
+        let err_context =
+            || format!("failed to scroll to next prompt in active pane for client {client_id}");
+
+        if let Some(active_pane) = self.get_active_pane_or_floating_pane_mut(client_id) {
+            active_pane.scroll_to_next_prompt();
+            if !active_pane.is_scrolled() {
+                if let PaneId::Terminal(raw_fd) = active_pane.pid() {
+                    self.process_pending_vte_events(raw_fd)
+                        .with_context(err_context)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn select_last_command_output(&mut self, client_id: ClientId) -> Result<()> {
+        let err_context = || format!("failed to select last command output for client {client_id}");
+
+        let copy_on_select = self.copy_on_select;
+        if let Some(active_pane) = self.get_active_pane_or_floating_pane_mut(client_id) {
+            active_pane.select_last_command_output();
+            if copy_on_select {
+                let selected_text = active_pane.get_selected_text();
+                active_pane.reset_selection();
+                if let Some(selected_text) = selected_text {
+                    self.write_selection_to_clipboard(&selected_text)
+                        .with_context(err_context)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
     pub fn clear_active_terminal_scroll(&mut self, client_id: ClientId) -> Result<()> {
         // TODO: is this a thing?
         let err_context =
@@ -2545,6 +2933,25 @@ impl Tab {
     }
 
     pub fn handle_left_click(&mut self, position: &Position, client_id: ClientId) -> Result<()> {
+        let block_selection = false;
+        self.left_click(position, client_id, block_selection)
+    }
+
+    pub fn handle_left_click_with_block_selection(
+        &mut self,
+        position: &Position,
+        client_id: ClientId,
+    ) -> Result<()> {
+        let block_selection = true;
+        self.left_click(position, client_id, block_selection)
+    }
+
+    fn left_click(
+        &mut self,
+        position: &Position,
+        client_id: ClientId,
+        block_selection: bool,
+    ) -> Result<()> {
         let err_context = || {
             format!(
                 "failed to handle mouse left click at position {position:?} for client {client_id}"
@@ -2576,7 +2983,11 @@ impl Tab {
                         .with_context(err_context)?;
                 }
             } else {
-                pane.start_selection(&relative_position, client_id);
+                if block_selection {
+                    pane.start_block_selection(&relative_position, client_id);
+                } else {
+                    pane.start_selection(&relative_position, client_id);
+                }
                 if let PaneId::Terminal(_) = pane.pid() {
                     self.selecting_with_mouse = true;
                 }
@@ -3069,6 +3480,36 @@ impl Tab {
         Ok(())
     }
 
+    pub fn rename_pane_with_id(&mut self, pane_id: PaneId, new_name: String) -> Result<()> {
+        let pane = self
+            .tiled_panes
+            .get_pane_mut(pane_id)
+            .or_else(|| self.floating_panes.get_pane_mut(pane_id))
+            .or_else(|| self.suppressed_panes.get_mut(&pane_id))
+            .with_context(|| format!("failed to find pane with id {pane_id:?} to rename"))?;
+        pane.rename(new_name);
+        Ok(())
+    }
+
+    pub fn rerun_terminal_pane_with_id(&mut self, terminal_pane_id: u32) -> Result<()> {
+        let pane_id = PaneId::Terminal(terminal_pane_id);
+        let err_context = || format!("failed to rerun command of pane with id {pane_id:?}");
+        let pane = self
+            .tiled_panes
+            .get_pane_mut(pane_id)
+            .or_else(|| self.floating_panes.get_pane_mut(pane_id))
+            .or_else(|| self.suppressed_panes.get_mut(&pane_id))
+            .with_context(err_context)?;
+        // only held command panes can be rerun, for all others this does nothing
+        if let Some(run_command) = pane.rerun() {
+            self.pids_waiting_resize.insert(terminal_pane_id);
+            self.senders
+                .send_to_pty(PtyInstruction::ReRunCommandInPane(pane_id, run_command))
+                .with_context(err_context)?;
+        }
+        Ok(())
+    }
+
     pub fn is_position_inside_viewport(&self, point: &Position) -> Result<bool> {
         let Position {
             line: Line(line),
@@ -3140,12 +3581,105 @@ impl Tab {
         }
     }
 
+    pub fn toggle_search_regex(&mut self, client_id: ClientId) {
+        if let Some(active_pane) = self.get_active_pane_or_floating_pane_mut(client_id) {
+            active_pane.toggle_search_regex();
+        }
+    }
+
     pub fn clear_search(&mut self, client_id: ClientId) {
         if let Some(active_pane) = self.get_active_pane_or_floating_pane_mut(client_id) {
             active_pane.clear_search();
         }
     }
 
+    pub fn enter_copy_mode(&mut self, client_id: ClientId) {
+        if let Some(active_pane) = self.get_active_pane_or_floating_pane_mut(client_id) {
+            active_pane.enter_copy_mode();
+        }
+    }
+
+    pub fn exit_copy_mode(&mut self, client_id: ClientId) {
+        if let Some(active_pane) = self.get_active_pane_or_floating_pane_mut(client_id) {
+            active_pane.exit_copy_mode();
+        }
+    }
+
+    pub fn copy_mode_move(&mut self, motion: CopyModeMotion, client_id: ClientId) {
+        if let Some(active_pane) = self.get_active_pane_or_floating_pane_mut(client_id) {
+            active_pane.copy_mode_move(motion);
+        }
+    }
+
+    pub fn copy_mode_select(&mut self, selection: CopyModeSelection, client_id: ClientId) {
+        if let Some(active_pane) = self.get_active_pane_or_floating_pane_mut(client_id) {
+            active_pane.copy_mode_select(selection);
+        }
+    }
+
+    pub fn enter_hint_mode(&mut self, hint_patterns: &[Regex], client_id: ClientId) {
+        if let Some(active_pane) = self.get_active_pane_or_floating_pane_mut(client_id) {
+            active_pane.enter_hint_mode(hint_patterns);
+        }
+    }
+
+    pub fn exit_hint_mode(&mut self, client_id: ClientId) {
+        if let Some(active_pane) = self.get_active_pane_or_floating_pane_mut(client_id) {
+            active_pane.exit_hint_mode();
+        }
+    }
+
+    /// Copies or writes the token of the hint whose label was completed by this input. Returns
+    /// what was done with the hint, with relative paths resolved against the cwd of the pane it
+    /// was found in so that they can be opened.
+    pub fn hint_input(
+        &mut self,
+        input: Vec<u8>,
+        client_id: ClientId,
+    ) -> Result<Option<HintAction>> {
+        let err_context = || format!("failed to handle hint input for client {client_id}");
+
+        let hint_action = match self
+            .get_active_pane_or_floating_pane_mut(client_id)
+            .and_then(|active_pane| active_pane.hint_input(&input))
+        {
+            Some(hint_action) => hint_action,
+            None => return Ok(None),
+        };
+        match hint_action {
+            HintAction::Activate(HintTarget::Path { path, line_number }) => {
+                let path = if let Ok(path_in_home) = path.strip_prefix("~") {
+                    match std::env::var_os("HOME") {
+                        Some(home) => PathBuf::from(home).join(path_in_home),
+                        None => path,
+                    }
+                } else if path.is_relative() {
+                    match self.active_pane_cwd(client_id) {
+                        Some(cwd) => cwd.join(path),
+                        None => path,
+                    }
+                } else {
+                    path
+                };
+                Ok(Some(HintAction::Activate(HintTarget::Path {
+                    path,
+                    line_number,
+                })))
+            },
+            HintAction::Activate(HintTarget::Text(text)) => {
+                self.write_selection_to_clipboard(&text)
+                    .with_context(err_context)?;
+                Ok(Some(HintAction::Activate(HintTarget::Text(text))))
+            },
+            HintAction::Write(text) => {
+                self.write_to_active_terminal(text.clone().into_bytes(), client_id)
+                    .with_context(err_context)?;
+                Ok(Some(HintAction::Write(text)))
+            },
+            hint_action => Ok(Some(hint_action)),
+        }
+    }
+
     pub fn is_pending(&self) -> bool {
         self.is_pending
     }
@@ -1,4 +1,5 @@
 use super::{Output, Tab};
+use crate::panes::kitty_graphics::KittyImageStore;
 use crate::panes::sixel::SixelImageStore;
 use crate::screen::CopyOptions;
 use crate::Arc;
@@ -20,8 +21,8 @@ use zellij_utils::data::ResizeStrategy;
 use zellij_utils::envs::set_session_name;
 use zellij_utils::errors::{prelude::*, ErrorContext};
 use zellij_utils::input::layout::{
-    FloatingPaneLayout, Layout, RunPluginLocation, SwapFloatingLayout, SwapTiledLayout,
-    TiledPaneLayout,
+    FloatingPaneLayout, Layout, PluginUserConfiguration, RunPluginLocation, SwapFloatingLayout,
+    SwapTiledLayout, TiledPaneLayout,
 };
 use zellij_utils::input::plugins::PluginTag;
 use zellij_utils::ipc::IpcReceiverWithContext;
@@ -265,7 +266,7 @@ fn create_new_tab_with_swap_layouts(
         Vec<FloatingPaneLayout>,
         Vec<(u32, Option<RunCommand>)>,
         Vec<(u32, Option<RunCommand>)>,
-        HashMap<RunPluginLocation, Vec<u32>>,
+        HashMap<(RunPluginLocation, PluginUserConfiguration), Vec<u32>>,
     )>,
     draw_pane_frames: bool,
 ) -> Tab {
@@ -701,7 +702,7 @@ fn dump_screen() {
     tab.handle_pty_bytes(2, Vec::from("scratch".as_bytes()))
         .unwrap();
     let file = "/tmp/log.sh";
-    tab.dump_active_terminal_screen(Some(file.to_string()), client_id, false)
+    tab.dump_active_terminal_screen(Some(file.to_string()), client_id, false, false)
         .unwrap();
     assert_eq!(
         map.lock().unwrap().get(file).unwrap(),
@@ -730,7 +731,7 @@ fn clear_screen() {
         .unwrap();
     let file = "/tmp/log-clear-screen.sh";
     tab.clear_active_terminal_screen(client_id).unwrap();
-    tab.dump_active_terminal_screen(Some(file.to_string()), client_id, false)
+    tab.dump_active_terminal_screen(Some(file.to_string()), client_id, false, false)
         .unwrap();
     assert_eq!(
         map.lock().unwrap().get(file).unwrap(),
@@ -2003,7 +2004,12 @@ fn move_floating_pane_with_sixel_image() {
         width: 8,
         height: 21,
     })));
-    let mut output = Output::new(sixel_image_store.clone(), character_cell_size);
+    let mut output = Output::new(
+        sixel_image_store.clone(),
+        Rc::new(RefCell::new(KittyImageStore::default())),
+        character_cell_size,
+        true,
+    );
 
     tab.toggle_floating_panes(Some(client_id), None).unwrap();
     tab.new_pane(new_pane_id, None, None, None, Some(client_id))
@@ -2041,7 +2047,12 @@ fn floating_pane_above_sixel_image() {
         width: 8,
         height: 21,
     })));
-    let mut output = Output::new(sixel_image_store.clone(), character_cell_size);
+    let mut output = Output::new(
+        sixel_image_store.clone(),
+        Rc::new(RefCell::new(KittyImageStore::default())),
+        character_cell_size,
+        true,
+    );
 
     tab.toggle_floating_panes(Some(client_id), None).unwrap();
     tab.new_pane(new_pane_id, None, None, None, Some(client_id))
@@ -2065,6 +2076,82 @@ fn floating_pane_above_sixel_image() {
     assert_snapshot!(snapshot);
 }
 
+// (x, y) of the cells covered by the kitty image placements in the output, each placement
+// directly follows the instruction moving the cursor to its position
+fn cells_of_kitty_placements(output: &str) -> HashSet<(usize, usize)> {
+    let mut cells = HashSet::new();
+    let parts: Vec<&str> = output.split("H\u{1b}_Ga=p,").collect();
+    for part in parts.windows(2) {
+        let goto = part[0].rsplit("\u{1b}[").next().unwrap();
+        let coordinates: Vec<usize> = goto.split(';').map(|c| c.parse().unwrap()).collect();
+        let control_data = part[1].split('\u{1b}').next().unwrap();
+        let key = |key: &str| -> usize {
+            control_data
+                .split(',')
+                .find_map(|key_value| key_value.strip_prefix(key))
+                .unwrap()
+                .parse()
+                .unwrap()
+        };
+        for y in 0..key("r=") {
+            for x in 0..key("c=") {
+                cells.insert((coordinates[1] - 1 + x, coordinates[0] - 1 + y));
+            }
+        }
+    }
+    cells
+}
+
+#[test]
+fn floating_pane_above_kitty_image() {
+    let new_pane_id = PaneId::Terminal(2);
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let client_id = 1;
+    let sixel_image_store = Rc::new(RefCell::new(SixelImageStore::default()));
+    let mut tab = create_new_tab_with_sixel_support(size, sixel_image_store.clone());
+    let character_cell_size = Rc::new(RefCell::new(Some(SizeInPixels {
+        width: 8,
+        height: 21,
+    })));
+    let mut output = Output::new(
+        sixel_image_store,
+        Rc::new(RefCell::new(KittyImageStore::default())),
+        character_cell_size,
+        true,
+    );
+
+    tab.toggle_floating_panes(Some(client_id), None).unwrap();
+    tab.new_pane(new_pane_id, None, None, None, Some(client_id))
+        .unwrap();
+    let image = "\u{1b}_Ga=T,s=100,v=100,c=100,r=15;AAAA\u{1b}\\";
+    tab.handle_pty_bytes(1, image.as_bytes().to_vec()).unwrap();
+
+    tab.render(&mut output).unwrap();
+    let serialized_output = output.serialize().unwrap();
+    let serialized_output = serialized_output.get(&client_id).unwrap();
+    assert_eq!(serialized_output.matches("\u{1b}_Ga=t,").count(), 1);
+    let floating_pane_geom = tab
+        .floating_panes
+        .get_pane(new_pane_id)
+        .unwrap()
+        .position_and_size();
+    // the image is placed inside the frame of the tiled pane, minus the parts the floating
+    // pane covers
+    let mut expected_cells = HashSet::new();
+    for y in 1..16 {
+        for x in 1..101 {
+            if !floating_pane_geom.contains(&Position::new(y as i32, x as u16)) {
+                expected_cells.insert((x, y));
+            }
+        }
+    }
+    assert!(expected_cells.len() < 100 * 15);
+    assert_eq!(cells_of_kitty_placements(serialized_output), expected_cells);
+}
+
 #[test]
 fn suppress_tiled_pane() {
     let size = Size {
@@ -4936,11 +5023,17 @@ fn layout_with_plugins_and_commands_swaped_properly() {
     let new_floating_terminal_ids = vec![];
     let mut new_plugin_ids = HashMap::new();
     new_plugin_ids.insert(
-        RunPluginLocation::Zellij(PluginTag::new("tab-bar")),
+        (
+            RunPluginLocation::Zellij(PluginTag::new("tab-bar")),
+            PluginUserConfiguration::default(),
+        ),
         vec![1],
     );
     new_plugin_ids.insert(
-        RunPluginLocation::Zellij(PluginTag::new("status-bar")),
+        (
+            RunPluginLocation::Zellij(PluginTag::new("status-bar")),
+            PluginUserConfiguration::default(),
+        ),
         vec![2],
     );
 
@@ -5031,11 +5124,17 @@ fn base_layout_is_included_in_swap_layouts() {
     let new_floating_terminal_ids = vec![];
     let mut new_plugin_ids = HashMap::new();
     new_plugin_ids.insert(
-        RunPluginLocation::Zellij(PluginTag::new("tab-bar")),
+        (
+            RunPluginLocation::Zellij(PluginTag::new("tab-bar")),
+            PluginUserConfiguration::default(),
+        ),
         vec![1],
     );
     new_plugin_ids.insert(
-        RunPluginLocation::Zellij(PluginTag::new("status-bar")),
+        (
+            RunPluginLocation::Zellij(PluginTag::new("status-bar")),
+            PluginUserConfiguration::default(),
+        ),
         vec![2],
     );
 
@@ -5123,11 +5222,17 @@ fn swap_layouts_including_command_panes_absent_from_existing_layout() {
     let new_floating_terminal_ids = vec![];
     let mut new_plugin_ids = HashMap::new();
     new_plugin_ids.insert(
-        RunPluginLocation::Zellij(PluginTag::new("tab-bar")),
+        (
+            RunPluginLocation::Zellij(PluginTag::new("tab-bar")),
+            PluginUserConfiguration::default(),
+        ),
         vec![1],
     );
     new_plugin_ids.insert(
-        RunPluginLocation::Zellij(PluginTag::new("status-bar")),
+        (
+            RunPluginLocation::Zellij(PluginTag::new("status-bar")),
+            PluginUserConfiguration::default(),
+        ),
         vec![2],
     );
 
@@ -5218,11 +5323,17 @@ fn swap_layouts_not_including_command_panes_present_in_existing_layout() {
     let new_floating_terminal_ids = vec![];
     let mut new_plugin_ids = HashMap::new();
     new_plugin_ids.insert(
-        RunPluginLocation::Zellij(PluginTag::new("tab-bar")),
+        (
+            RunPluginLocation::Zellij(PluginTag::new("tab-bar")),
+            PluginUserConfiguration::default(),
+        ),
         vec![1],
     );
     new_plugin_ids.insert(
-        RunPluginLocation::Zellij(PluginTag::new("status-bar")),
+        (
+            RunPluginLocation::Zellij(PluginTag::new("status-bar")),
+            PluginUserConfiguration::default(),
+        ),
         vec![2],
     );
 
@@ -5386,11 +5497,17 @@ fn swap_layouts_not_including_plugin_panes_present_in_existing_layout() {
     let new_floating_terminal_ids = vec![];
     let mut new_plugin_ids = HashMap::new();
     new_plugin_ids.insert(
-        RunPluginLocation::Zellij(PluginTag::new("tab-bar")),
+        (
+            RunPluginLocation::Zellij(PluginTag::new("tab-bar")),
+            PluginUserConfiguration::default(),
+        ),
         vec![1],
     );
     new_plugin_ids.insert(
-        RunPluginLocation::Zellij(PluginTag::new("status-bar")),
+        (
+            RunPluginLocation::Zellij(PluginTag::new("status-bar")),
+            PluginUserConfiguration::default(),
+        ),
         vec![2],
     );
 
@@ -5870,11 +5987,17 @@ fn floating_layout_with_plugins_and_commands_swaped_properly() {
     let new_terminal_ids = vec![(4, None)];
     let mut new_plugin_ids = HashMap::new();
     new_plugin_ids.insert(
-        RunPluginLocation::Zellij(PluginTag::new("tab-bar")),
+        (
+            RunPluginLocation::Zellij(PluginTag::new("tab-bar")),
+            PluginUserConfiguration::default(),
+        ),
         vec![1],
     );
     new_plugin_ids.insert(
-        RunPluginLocation::Zellij(PluginTag::new("status-bar")),
+        (
+            RunPluginLocation::Zellij(PluginTag::new("status-bar")),
+            PluginUserConfiguration::default(),
+        ),
         vec![2],
     );
 
@@ -5963,11 +6086,17 @@ fn base_floating_layout_is_included_in_swap_layouts() {
     let new_terminal_ids = vec![(4, None)];
     let mut new_plugin_ids = HashMap::new();
     new_plugin_ids.insert(
-        RunPluginLocation::Zellij(PluginTag::new("tab-bar")),
+        (
+            RunPluginLocation::Zellij(PluginTag::new("tab-bar")),
+            PluginUserConfiguration::default(),
+        ),
         vec![1],
     );
     new_plugin_ids.insert(
-        RunPluginLocation::Zellij(PluginTag::new("status-bar")),
+        (
+            RunPluginLocation::Zellij(PluginTag::new("status-bar")),
+            PluginUserConfiguration::default(),
+        ),
         vec![2],
     );
 
@@ -6055,11 +6184,17 @@ fn swap_floating_layouts_including_command_panes_absent_from_existing_layout() {
     let new_terminal_ids = vec![(4, None)];
     let mut new_plugin_ids = HashMap::new();
     new_plugin_ids.insert(
-        RunPluginLocation::Zellij(PluginTag::new("tab-bar")),
+        (
+            RunPluginLocation::Zellij(PluginTag::new("tab-bar")),
+            PluginUserConfiguration::default(),
+        ),
         vec![1],
     );
     new_plugin_ids.insert(
-        RunPluginLocation::Zellij(PluginTag::new("status-bar")),
+        (
+            RunPluginLocation::Zellij(PluginTag::new("status-bar")),
+            PluginUserConfiguration::default(),
+        ),
         vec![2],
     );
 
@@ -6150,11 +6285,17 @@ fn swap_floating_layouts_not_including_command_panes_present_in_existing_layout(
     let new_terminal_ids = vec![(4, None)];
     let mut new_plugin_ids = HashMap::new();
     new_plugin_ids.insert(
-        RunPluginLocation::Zellij(PluginTag::new("tab-bar")),
+        (
+            RunPluginLocation::Zellij(PluginTag::new("tab-bar")),
+            PluginUserConfiguration::default(),
+        ),
         vec![1],
     );
     new_plugin_ids.insert(
-        RunPluginLocation::Zellij(PluginTag::new("status-bar")),
+        (
+            RunPluginLocation::Zellij(PluginTag::new("status-bar")),
+            PluginUserConfiguration::default(),
+        ),
         vec![2],
     );
 
@@ -6307,11 +6448,17 @@ fn swap_floating_layouts_not_including_plugin_panes_present_in_existing_layout()
     let new_terminal_ids = vec![(4, None)];
     let mut new_plugin_ids = HashMap::new();
     new_plugin_ids.insert(
-        RunPluginLocation::Zellij(PluginTag::new("tab-bar")),
+        (
+            RunPluginLocation::Zellij(PluginTag::new("tab-bar")),
+            PluginUserConfiguration::default(),
+        ),
         vec![1],
     );
     new_plugin_ids.insert(
-        RunPluginLocation::Zellij(PluginTag::new("status-bar")),
+        (
+            RunPluginLocation::Zellij(PluginTag::new("status-bar")),
+            PluginUserConfiguration::default(),
+        ),
         vec![2],
     );
 
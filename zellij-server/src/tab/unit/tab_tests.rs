@@ -14402,3 +14402,153 @@ fn correctly_resize_frameless_panes_on_pane_close() {
     let content_size = (pane.get_content_columns(), pane.get_content_rows());
     assert_eq!(content_size, (cols, rows));
 }
+
+#[test]
+pub fn resize_pane_with_id_that_is_not_focused() {
+    // ┌───────────┐                  ┌───────────┐
+    // │           │                  │           │
+    // │           │                  │           │
+    // ├───────────┤ ==resize=down==> │           │
+    // │███████████│                  ├───────────┤
+    // │███████████│                  │███████████│
+    // │███████████│                  │███████████│
+    // └───────────┘                  └───────────┘
+    // █ == focused pane, the resize is applied to the pane above it by its id
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut tab = create_new_tab(size);
+    let new_pane_id = PaneId::Terminal(2);
+    tab.horizontal_split(new_pane_id, None, 1).unwrap();
+    tab.resize_pane_with_id(
+        ResizeStrategy::new(Resize::Increase, Some(Direction::Down)),
+        PaneId::Terminal(1),
+    )
+    .unwrap();
+
+    assert_eq!(
+        tab.tiled_panes
+            .panes
+            .get(&PaneId::Terminal(1))
+            .unwrap()
+            .position_and_size()
+            .rows
+            .as_usize(),
+        11,
+        "resized pane row count"
+    );
+    assert_eq!(
+        tab.tiled_panes
+            .panes
+            .get(&new_pane_id)
+            .unwrap()
+            .position_and_size()
+            .rows
+            .as_usize(),
+        9,
+        "focused pane row count"
+    );
+    assert_eq!(
+        tab.get_active_pane_id(1),
+        Some(new_pane_id),
+        "focus did not change"
+    );
+}
+
+#[test]
+pub fn toggle_pane_with_id_that_is_not_focused_embed_or_floating() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut tab = create_new_tab(size);
+    let new_pane_id = PaneId::Terminal(2);
+    tab.vertical_split(new_pane_id, None, 1).unwrap();
+    tab.toggle_pane_embed_or_floating_for_pane_id(PaneId::Terminal(1), Some(1))
+        .unwrap();
+    assert!(
+        tab.floating_panes.panes_contain(&PaneId::Terminal(1)),
+        "pane was floated"
+    );
+    assert!(
+        !tab.tiled_panes.panes_contain(&PaneId::Terminal(1)),
+        "pane is no longer tiled"
+    );
+    tab.toggle_pane_embed_or_floating_for_pane_id(PaneId::Terminal(1), Some(1))
+        .unwrap();
+    assert!(
+        tab.tiled_panes.panes_contain(&PaneId::Terminal(1)),
+        "pane was embedded again"
+    );
+    assert!(
+        !tab.floating_panes.panes_contain(&PaneId::Terminal(1)),
+        "pane is no longer floating"
+    );
+}
+
+#[test]
+pub fn rename_pane_with_id_that_is_not_focused() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut tab = create_new_tab(size);
+    let new_pane_id = PaneId::Terminal(2);
+    tab.vertical_split(new_pane_id, None, 1).unwrap();
+    tab.rename_pane_with_id(PaneId::Terminal(1), "my awesome pane".into())
+        .unwrap();
+    assert_eq!(
+        tab.tiled_panes
+            .panes
+            .get(&PaneId::Terminal(1))
+            .unwrap()
+            .custom_title(),
+        Some("my awesome pane".to_owned()),
+        "unfocused pane was renamed"
+    );
+    assert_eq!(
+        tab.tiled_panes
+            .panes
+            .get(&new_pane_id)
+            .unwrap()
+            .custom_title(),
+        None,
+        "focused pane was not renamed"
+    );
+}
+
+#[test]
+fn cwd_reported_by_pane_is_preferred_over_the_cwd_of_its_process() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut tab = create_new_tab(size);
+    let client_id = 1;
+    let mut terminal_ids_to_cwds = HashMap::new();
+    terminal_ids_to_cwds.insert(1, PathBuf::from("/process/cwd"));
+    assert!(
+        tab.update_process_cwds(&terminal_ids_to_cwds),
+        "cwd of the process cached in the pane"
+    );
+    assert!(
+        !tab.update_process_cwds(&terminal_ids_to_cwds),
+        "cwd of the process did not change"
+    );
+    assert_eq!(
+        tab.active_pane_cwd(client_id),
+        Some(PathBuf::from("/process/cwd"))
+    );
+    tab.handle_pty_bytes(
+        1,
+        "\u{1b}]7;file://some-host/reported/cwd\u{1b}\\"
+            .as_bytes()
+            .to_vec(),
+    )
+    .unwrap();
+    assert_eq!(
+        tab.active_pane_cwd(client_id),
+        Some(PathBuf::from("/reported/cwd"))
+    );
+}
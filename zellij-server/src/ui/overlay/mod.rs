@@ -8,7 +8,8 @@
 
 pub mod prompt;
 
-use crate::ServerInstruction;
+use crate::ClientId;
+use prompt::PromptCallback;
 use zellij_utils::errors::prelude::*;
 use zellij_utils::pane_size::Size;
 
@@ -62,12 +63,17 @@ impl Overlayable for OverlayWindow {
 }
 
 impl Overlay {
-    pub fn prompt_confirm(self) -> Option<Box<ServerInstruction>> {
+    pub fn client_id(&self) -> ClientId {
+        match &self.overlay_type {
+            OverlayType::Prompt(p) => p.client_id,
+        }
+    }
+    pub fn prompt_confirm(self) -> Option<Box<PromptCallback>> {
         match self.overlay_type {
             OverlayType::Prompt(p) => p.confirm(),
         }
     }
-    pub fn prompt_deny(self) -> Option<Box<ServerInstruction>> {
+    pub fn prompt_deny(self) -> Option<Box<PromptCallback>> {
         match self.overlay_type {
             OverlayType::Prompt(p) => p.deny(),
         }
@@ -1,34 +1,49 @@
 use zellij_utils::pane_size::Size;
 
 use super::{Overlay, OverlayType, Overlayable};
-use crate::{ClientId, ServerInstruction};
+use crate::{
+    plugins::{PluginId, PluginInstruction},
+    ClientId, ServerInstruction,
+};
+use zellij_utils::data::{PermissionStatus, PermissionType};
 use zellij_utils::errors::prelude::*;
 
 use std::fmt::Write;
 
+/// The instruction sent once the user answers a [`Prompt`], and the thread it is sent to
+#[derive(Clone, Debug)]
+pub enum PromptCallback {
+    Server(ServerInstruction),
+    Plugin(PluginInstruction),
+}
+
 #[derive(Clone, Debug)]
 pub struct Prompt {
     pub message: String,
-    on_confirm: Option<Box<ServerInstruction>>,
-    on_deny: Option<Box<ServerInstruction>>,
+    /// The client this prompt is shown to, the only one whose keys can answer it
+    pub client_id: ClientId,
+    on_confirm: Option<Box<PromptCallback>>,
+    on_deny: Option<Box<PromptCallback>>,
 }
 
 impl Prompt {
     pub fn new(
         message: String,
-        on_confirm: Option<Box<ServerInstruction>>,
-        on_deny: Option<Box<ServerInstruction>>,
+        client_id: ClientId,
+        on_confirm: Option<Box<PromptCallback>>,
+        on_deny: Option<Box<PromptCallback>>,
     ) -> Self {
         Self {
             message,
+            client_id,
             on_confirm,
             on_deny,
         }
     }
-    pub fn confirm(self) -> Option<Box<ServerInstruction>> {
+    pub fn confirm(self) -> Option<Box<PromptCallback>> {
         self.on_confirm
     }
-    pub fn deny(self) -> Option<Box<ServerInstruction>> {
+    pub fn deny(self) -> Option<Box<PromptCallback>> {
         self.on_deny
     }
 }
@@ -39,7 +54,7 @@ impl Overlayable for Prompt {
         let rows = size.rows;
         let mut vte_output = self.message.clone();
         Overlay::pad_cols(&mut vte_output, size.cols);
-        for (x, h) in vte_output.chars().enumerate() {
+        for (x, h) in vte_output.chars().take(size.cols).enumerate() {
             write!(
                 &mut output,
                 "\u{1b}[{};{}H\u{1b}[48;5;238m{}",
@@ -49,6 +64,7 @@ impl Overlayable for Prompt {
             )
             .context("failed to generate VTE output from prompt")?;
         }
+        output.push_str("\u{1b}[m");
         Ok(output)
     }
 }
@@ -56,10 +72,49 @@ impl Overlayable for Prompt {
 pub fn _generate_quit_prompt(client_id: ClientId) -> Overlay {
     let prompt = Prompt::new(
         (" Do you want to quit zellij? [Y]es / [N]o").to_string(),
-        Some(Box::new(ServerInstruction::ClientExit(client_id))),
+        client_id,
+        Some(Box::new(PromptCallback::Server(
+            ServerInstruction::ClientExit(client_id),
+        ))),
         None,
     );
     Overlay {
         overlay_type: OverlayType::Prompt(prompt),
     }
 }
+
+pub fn generate_permission_prompt(
+    plugin_id: PluginId,
+    client_id: ClientId,
+    plugin_name: &str,
+    permissions: Vec<PermissionType>,
+) -> Overlay {
+    let requested_permissions: Vec<String> = permissions.iter().map(|p| p.display_name()).collect();
+    let prompt = Prompt::new(
+        format!(
+            " Allow plugin {} to: {}? [Y]es / [N]o",
+            plugin_name,
+            requested_permissions.join(", ")
+        ),
+        client_id,
+        Some(Box::new(PromptCallback::Plugin(
+            PluginInstruction::PermissionRequestResult(
+                plugin_id,
+                client_id,
+                permissions.clone(),
+                PermissionStatus::Granted,
+            ),
+        ))),
+        Some(Box::new(PromptCallback::Plugin(
+            PluginInstruction::PermissionRequestResult(
+                plugin_id,
+                client_id,
+                permissions,
+                PermissionStatus::Denied,
+            ),
+        ))),
+    );
+    Overlay {
+        overlay_type: OverlayType::Prompt(prompt),
+    }
+}
@@ -57,10 +57,10 @@ impl<'a> PaneContentsAndUi<'a> {
     ) -> Result<()> {
         let err_context = "failed to render pane contents to multiple clients";
 
+        let clients: Vec<ClientId> = clients.collect();
         if let Some((character_chunks, raw_vte_output, sixel_image_chunks)) =
             self.pane.render(None).context(err_context)?
         {
-            let clients: Vec<ClientId> = clients.collect();
             self.output
                 .add_character_chunks_to_multiple_clients(
                     character_chunks,
@@ -87,6 +87,13 @@ impl<'a> PaneContentsAndUi<'a> {
                 }
             }
         }
+        // kitty image placements are collected on every render, even if the pane did not change,
+        // so that we know all the placements each client should display
+        self.output.add_kitty_image_chunks_to_multiple_clients(
+            self.pane.kitty_image_chunks(),
+            clients.iter().copied(),
+            self.z_index,
+        );
         Ok(())
     }
     pub fn render_pane_contents_for_client(&mut self, client_id: ClientId) -> Result<()> {
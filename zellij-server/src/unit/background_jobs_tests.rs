@@ -0,0 +1,214 @@
+use super::*;
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::thread;
+
+// serves a single http request with `response`, returning the raw request it received
+fn local_http_server(response: &'static str) -> (String, thread::JoinHandle<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let server_thread = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = vec![];
+        let mut buf = [0; 1024];
+        loop {
+            let read_bytes = stream.read(&mut buf).unwrap();
+            request.extend_from_slice(&buf[..read_bytes]);
+            let raw_request = String::from_utf8_lossy(&request).to_string();
+            if let Some(headers_end) = raw_request.find("\r\n\r\n") {
+                let content_length = raw_request[..headers_end]
+                    .lines()
+                    .find_map(|line| {
+                        line.to_lowercase()
+                            .strip_prefix("content-length:")
+                            .and_then(|length| length.trim().parse::<usize>().ok())
+                    })
+                    .unwrap_or(0);
+                if raw_request.len() >= headers_end + 4 + content_length {
+                    break;
+                }
+            }
+            if read_bytes == 0 {
+                break;
+            }
+        }
+        stream.write_all(response.as_bytes()).unwrap();
+        String::from_utf8_lossy(&request).to_string()
+    });
+    (url, server_thread)
+}
+
+#[test]
+fn web_request_returns_status_headers_and_body() {
+    let (url, server_thread) = local_http_server(
+        "HTTP/1.1 200 OK\r\nContent-Length: 5\r\nX-Build-Status: passing\r\nConnection: close\r\n\r\nhello",
+    );
+    let mut headers = BTreeMap::new();
+    headers.insert("X-Token".to_owned(), "secret".to_owned());
+    let (status, response_headers, body) = task::block_on(web_request(
+        &format!("{}/status", url),
+        HttpVerb::Post,
+        headers,
+        "ping".as_bytes().to_vec(),
+    ))
+    .unwrap();
+    let request = server_thread.join().unwrap();
+    assert_eq!(status, 200, "status code returned");
+    assert_eq!(
+        response_headers.get("x-build-status"),
+        Some(&"passing".to_owned()),
+        "response headers returned"
+    );
+    assert_eq!(body, "hello".as_bytes().to_vec(), "response body returned");
+    assert!(
+        request.starts_with("POST /status HTTP/1.1\r\n"),
+        "request sent with the right verb and path"
+    );
+    assert!(
+        request.to_lowercase().contains("x-token: secret"),
+        "request headers sent"
+    );
+    assert!(request.ends_with("ping"), "request body sent");
+}
+
+#[test]
+fn web_request_to_unreachable_server_is_err() {
+    // bind and immediately drop a listener to get a local port nothing listens on
+    let port = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let result = task::block_on(web_request(
+        &format!("http://127.0.0.1:{}", port),
+        HttpVerb::Get,
+        BTreeMap::new(),
+        vec![],
+    ));
+    assert!(result.is_err(), "unreachable server is an error");
+}
+
+#[test]
+fn run_command_captures_exit_code_and_output() {
+    let cwd = zellij_utils::tempfile::tempdir().unwrap();
+    let mut env = BTreeMap::new();
+    env.insert("GREETING".to_owned(), "hello".to_owned());
+    let (exit_code, stdout, stderr) = run_command(
+        "sh",
+        &[
+            "-c".to_owned(),
+            "echo $GREETING; pwd; echo oops >&2; exit 3".to_owned(),
+        ],
+        env,
+        cwd.path(),
+    );
+    let expected_stdout = format!("hello\n{}\n", cwd.path().canonicalize().unwrap().display());
+    assert_eq!(exit_code, Some(3), "exit code captured");
+    assert_eq!(
+        String::from_utf8_lossy(&stdout),
+        expected_stdout,
+        "stdout captured with env variables and cwd applied"
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&stderr),
+        "oops\n",
+        "stderr captured"
+    );
+}
+
+#[test]
+fn run_command_that_fails_to_start_has_no_exit_code() {
+    let (exit_code, stdout, stderr) = run_command(
+        "/path/to/a/command/that/does/not/exist",
+        &[],
+        BTreeMap::new(),
+        Path::new("/"),
+    );
+    assert_eq!(exit_code, None, "no exit code");
+    assert!(stdout.is_empty(), "no stdout");
+    assert!(!stderr.is_empty(), "error reported in stderr");
+}
+
+#[test]
+fn move_session_layout_renames_the_layout_file() {
+    let data_dir = zellij_utils::tempfile::tempdir().unwrap();
+    let old_layout_file_path = data_dir.path().join("old-name.kdl");
+    let new_layout_file_path = data_dir.path().join("new-name.kdl");
+    fs::write(&old_layout_file_path, "layout {\n}").unwrap();
+    move_session_layout(&old_layout_file_path, &new_layout_file_path).unwrap();
+    assert!(!old_layout_file_path.exists(), "old layout file removed");
+    assert_eq!(
+        fs::read_to_string(&new_layout_file_path).unwrap(),
+        "layout {\n}",
+        "layout moved to the new name"
+    );
+}
+
+#[test]
+fn move_session_layout_keeps_a_newer_layout() {
+    let data_dir = zellij_utils::tempfile::tempdir().unwrap();
+    let old_layout_file_path = data_dir.path().join("old-name.kdl");
+    let new_layout_file_path = data_dir.path().join("new-name.kdl");
+    fs::write(&old_layout_file_path, "layout {\n}").unwrap();
+    fs::write(&new_layout_file_path, "layout {\n    pane\n}").unwrap();
+    move_session_layout(&old_layout_file_path, &new_layout_file_path).unwrap();
+    assert!(!old_layout_file_path.exists(), "old layout file removed");
+    assert_eq!(
+        fs::read_to_string(&new_layout_file_path).unwrap(),
+        "layout {\n    pane\n}",
+        "layout written under the new name kept"
+    );
+}
+
+#[test]
+fn move_session_layout_without_a_layout_file_is_ok() {
+    let data_dir = zellij_utils::tempfile::tempdir().unwrap();
+    let old_layout_file_path = data_dir.path().join("old-name.kdl");
+    let new_layout_file_path = data_dir.path().join("new-name.kdl");
+    move_session_layout(&old_layout_file_path, &new_layout_file_path).unwrap();
+    assert!(!new_layout_file_path.exists(), "no layout file created");
+}
+
+#[test]
+fn remove_session_layout_removes_the_layout_file() {
+    let data_dir = zellij_utils::tempfile::tempdir().unwrap();
+    let layout_file_path = data_dir.path().join("session-name.kdl");
+    fs::write(&layout_file_path, "layout {\n}").unwrap();
+    remove_session_layout(&layout_file_path).unwrap();
+    assert!(!layout_file_path.exists(), "layout file removed");
+    remove_session_layout(&layout_file_path).unwrap();
+}
+
+#[test]
+fn session_layout_is_not_written_after_it_was_removed() {
+    use zellij_utils::channels::{self, ChannelWithContext, SenderWithContext};
+    let data_dir = zellij_utils::tempfile::tempdir().unwrap();
+    let (to_background_jobs, background_jobs_receiver): ChannelWithContext<BackgroundJob> =
+        channels::unbounded();
+    let to_background_jobs = SenderWithContext::new(to_background_jobs);
+    let bus = Bus::new(
+        vec![background_jobs_receiver],
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+    )
+    .should_silently_fail();
+    let background_jobs_thread = thread::spawn({
+        let data_dir = data_dir.path().to_path_buf();
+        move || background_jobs_main(bus, data_dir, None).unwrap()
+    });
+    let layout_file_path = session_layout_file_path(data_dir.path(), "zellij-test");
+    let write_session_layout =
+        || BackgroundJob::WriteSessionLayout("zellij-test".into(), "layout {\n}".into());
+    let _ = to_background_jobs.send(write_session_layout());
+    let _ = to_background_jobs.send(BackgroundJob::RemoveSessionLayout("zellij-test".into()));
+    // eg. dumped by the screen thread before it knew the session ended
+    let _ = to_background_jobs.send(write_session_layout());
+    let _ = to_background_jobs.send(BackgroundJob::Exit);
+    background_jobs_thread.join().unwrap();
+    assert!(!layout_file_path.exists(), "layout file removed");
+}
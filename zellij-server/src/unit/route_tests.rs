@@ -0,0 +1,83 @@
+use super::*;
+use zellij_utils::{data::Direction, position::Position};
+
+fn session_state_with_reader_and_writer() -> (SessionState, ClientId, ClientId) {
+    let mut session_state = SessionState::new();
+    let writer = session_state.new_client();
+    let reader = session_state.new_client();
+    session_state.set_client_read_only(reader);
+    (session_state, writer, reader)
+}
+
+#[test]
+fn read_only_client_mutating_actions_are_rejected() {
+    let (session_state, _writer, reader) = session_state_with_reader_and_writer();
+    let mutating_actions = vec![
+        Action::Write(vec![b'a']),
+        Action::WriteChars("ls".into()),
+        Action::NewTab(None, vec![], None, None, None),
+        Action::CloseFocus,
+        Action::MoveFocus(Direction::Left),
+        Action::ToggleFloatingPanes,
+        Action::Quit,
+        Action::ScrollUp,
+        Action::ScrollDownAt(Position::new(0, 0)),
+        Action::PageScrollUp,
+        Action::HalfPageScrollDown,
+        Action::ScrollToTop,
+        Action::ScrollToBottom,
+    ];
+    for action in mutating_actions {
+        assert_eq!(
+            filter_action_for_client(action.clone(), reader, &session_state),
+            None,
+            "action {action:?} was not rejected"
+        );
+    }
+}
+
+#[test]
+fn read_only_client_is_brought_back_to_normal_mode() {
+    let (session_state, _writer, reader) = session_state_with_reader_and_writer();
+    for input_mode in [
+        InputMode::Scroll,
+        InputMode::EnterSearch,
+        InputMode::Search,
+        InputMode::Copy,
+        InputMode::Hint,
+    ] {
+        assert_eq!(
+            filter_action_for_client(Action::SwitchToMode(input_mode), reader, &session_state),
+            Some(Action::SwitchToMode(InputMode::Normal)),
+            "read-only client was let into {input_mode:?} mode"
+        );
+    }
+    assert_eq!(
+        filter_action_for_client(Action::SwitchToMode(InputMode::Tab), reader, &session_state),
+        Some(Action::SwitchToMode(InputMode::Tab)),
+    );
+}
+
+#[test]
+fn read_only_client_can_detach() {
+    let (session_state, _writer, reader) = session_state_with_reader_and_writer();
+    assert_eq!(
+        filter_action_for_client(Action::Detach, reader, &session_state),
+        Some(Action::Detach),
+    );
+}
+
+#[test]
+fn writer_client_actions_are_not_filtered() {
+    let (session_state, writer, _reader) = session_state_with_reader_and_writer();
+    for action in [
+        Action::WriteChars("ls".into()),
+        Action::ScrollUp,
+        Action::Quit,
+    ] {
+        assert_eq!(
+            filter_action_for_client(action.clone(), writer, &session_state),
+            Some(action),
+        );
+    }
+}
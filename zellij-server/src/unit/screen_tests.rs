@@ -1,4 +1,6 @@
-use super::{screen_thread_main, CopyOptions, Screen, ScreenInstruction};
+use super::{
+    screen_thread_main, CopyOptions, Screen, ScreenInstruction, PANE_MANIFEST_UPDATE_INTERVAL,
+};
 use crate::panes::PaneId;
 use crate::{
     channels::SenderWithContext,
@@ -15,10 +17,11 @@ use zellij_utils::errors::{prelude::*, ErrorContext};
 use zellij_utils::input::actions::Action;
 use zellij_utils::input::command::{RunCommand, TerminalAction};
 use zellij_utils::input::layout::{
-    Layout, Run, RunPlugin, RunPluginLocation, SplitDirection, TiledPaneLayout,
+    Layout, PluginUserConfiguration, Run, RunPlugin, RunPluginLocation, SplitDirection,
+    TiledPaneLayout,
 };
 use zellij_utils::input::options::Options;
-use zellij_utils::ipc::IpcReceiverWithContext;
+use zellij_utils::ipc::{ClientType, IpcReceiverWithContext};
 use zellij_utils::pane_size::{Size, SizeInPixels};
 
 use crate::background_jobs::BackgroundJob;
@@ -26,13 +29,17 @@ use crate::pty_writer::PtyWriteInstruction;
 use std::env::set_var;
 use std::os::unix::io::RawFd;
 use std::sync::{Arc, Mutex};
+use std::time::Instant;
 
 use crate::{plugins::PluginInstruction, pty::PtyInstruction};
 use zellij_utils::ipc::PixelDimensions;
 
 use zellij_utils::{
     channels::{self, ChannelWithContext, Receiver},
-    data::{Direction, InputMode, ModeInfo, Palette, PluginCapabilities},
+    data::{
+        Direction, Event, InputMode, ModeInfo, Palette, PaneManifest, PermissionStatus,
+        PermissionType, PluginCapabilities,
+    },
     interprocess::local_socket::LocalSocketStream,
     ipc::{ClientAttributes, ClientToServerMsg, ServerToClientMsg},
 };
@@ -40,7 +47,7 @@ use zellij_utils::{
 use crate::panes::grid::Grid;
 use crate::panes::link_handler::LinkHandler;
 use crate::panes::sixel::SixelImageStore;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
 use std::rc::Rc;
 use zellij_utils::vte;
@@ -231,6 +238,9 @@ fn create_new_screen(size: Size) -> Screen {
     let auto_layout = true;
     let session_is_mirrored = true;
     let copy_options = CopyOptions::default();
+    let styled_underlines = true;
+    let link_opener = None;
+    let hint_patterns = vec![];
 
     let screen = Screen::new(
         bus,
@@ -241,6 +251,9 @@ fn create_new_screen(size: Size) -> Screen {
         auto_layout,
         session_is_mirrored,
         copy_options,
+        styled_underlines,
+        link_opener,
+        hint_patterns,
     );
     screen
 }
@@ -299,7 +312,10 @@ impl MockScreen {
         let mut pane_ids = vec![];
         let mut plugin_ids = HashMap::new();
         plugin_ids.insert(
-            RunPluginLocation::File(PathBuf::from("/path/to/fake/plugin")),
+            (
+                RunPluginLocation::File(PathBuf::from("/path/to/fake/plugin")),
+                PluginUserConfiguration::default(),
+            ),
             vec![1],
         );
         for i in 0..pane_count {
@@ -365,6 +381,7 @@ impl MockScreen {
         let _ = self.to_screen.send(ScreenInstruction::Exit);
         let _ = self.to_server.send(ServerInstruction::KillSession);
         let _ = self.to_plugin.send(PluginInstruction::Exit);
+        let _ = self.to_background_jobs.send(BackgroundJob::Exit);
         for thread in threads {
             let _ = thread.join();
         }
@@ -492,6 +509,27 @@ macro_rules! log_actions_in_thread {
     };
 }
 
+fn without_pane_updates(plugin_instructions: &[PluginInstruction]) -> Vec<&PluginInstruction> {
+    // pane updates are tested separately, they would otherwise clutter every log of plugin updates
+    plugin_instructions
+        .iter()
+        .filter(|instruction| match instruction {
+            PluginInstruction::Update(updates) => !updates
+                .iter()
+                .any(|(_, _, event)| matches!(event, Event::PaneUpdate(..))),
+            _ => true,
+        })
+        .collect()
+}
+
+fn without_cwd_queries(pty_instructions: &[PtyInstruction]) -> Vec<&PtyInstruction> {
+    // the cwds of the terminals are queried whenever the pane updates are, which depends on timing
+    pty_instructions
+        .iter()
+        .filter(|instruction| !matches!(instruction, PtyInstruction::QueryTerminalCwds(..)))
+        .collect()
+}
+
 fn new_tab(screen: &mut Screen, pid: u32, tab_index: usize) {
     let client_id = 1;
     let new_terminal_ids = vec![(pid, None)];
@@ -646,6 +684,30 @@ pub fn close_the_middle_tab() {
     );
 }
 
+#[test]
+fn session_info_describes_the_tabs_and_panes() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut screen = create_new_screen(size);
+
+    new_tab(&mut screen, 1, 1);
+    new_tab(&mut screen, 2, 2);
+    let session_info = screen.session_info();
+
+    assert_eq!(
+        session_info.tabs,
+        vec!["Tab #2".to_owned(), "Tab #3".to_owned()]
+    );
+    assert_eq!(session_info.panes, 2, "one terminal pane in each tab");
+    assert!(session_info.created_at > 0, "creation time is set");
+    assert!(
+        session_info.last_activity >= session_info.created_at,
+        "last activity is not before the session was created"
+    );
+}
+
 #[test]
 fn move_focus_left_at_left_screen_edge_changes_tab() {
     let size = Size {
@@ -997,7 +1059,7 @@ fn attach_after_first_tab_closed() {
 
     screen.close_tab_at_index(0).expect("TEST");
     screen.remove_client(1).expect("TEST");
-    screen.add_client(1).expect("TEST");
+    screen.add_client(1, ClientType::Writer).expect("TEST");
 }
 
 // Following are tests for sending CLI actions
@@ -1283,6 +1345,7 @@ pub fn send_cli_dump_screen_action() {
     let cli_action = CliAction::DumpScreen {
         path: PathBuf::from("/tmp/foo"),
         full: true,
+        last_output: false,
     };
     let _ = mock_screen.to_screen.send(ScreenInstruction::PtyBytes(
         0,
@@ -1852,7 +1915,10 @@ pub fn send_cli_new_pane_action_with_default_parameters() {
     send_cli_action_to_server(&session_metadata, cli_new_pane_action, client_id);
     std::thread::sleep(std::time::Duration::from_millis(100)); // give time for actions to be
     mock_screen.teardown(vec![pty_thread, screen_thread]);
-    assert_snapshot!(format!("{:?}", *received_pty_instructions.lock().unwrap()));
+    assert_snapshot!(format!(
+        "{:?}",
+        without_cwd_queries(&received_pty_instructions.lock().unwrap())
+    ));
 }
 
 #[test]
@@ -1888,7 +1954,10 @@ pub fn send_cli_new_pane_action_with_split_direction() {
     send_cli_action_to_server(&session_metadata, cli_new_pane_action, client_id);
     std::thread::sleep(std::time::Duration::from_millis(100)); // give time for actions to be
     mock_screen.teardown(vec![pty_thread, screen_thread]);
-    assert_snapshot!(format!("{:?}", *received_pty_instructions.lock().unwrap()));
+    assert_snapshot!(format!(
+        "{:?}",
+        without_cwd_queries(&received_pty_instructions.lock().unwrap())
+    ));
 }
 
 #[test]
@@ -1924,7 +1993,10 @@ pub fn send_cli_new_pane_action_with_command_and_cwd() {
     send_cli_action_to_server(&session_metadata, cli_new_pane_action, client_id);
     std::thread::sleep(std::time::Duration::from_millis(100)); // give time for actions to be
     mock_screen.teardown(vec![pty_thread, screen_thread]);
-    assert_snapshot!(format!("{:?}", *received_pty_instructions.lock().unwrap()));
+    assert_snapshot!(format!(
+        "{:?}",
+        without_cwd_queries(&received_pty_instructions.lock().unwrap())
+    ));
 }
 
 #[test]
@@ -1957,7 +2029,10 @@ pub fn send_cli_edit_action_with_default_parameters() {
     send_cli_action_to_server(&session_metadata, cli_edit_action, client_id);
     std::thread::sleep(std::time::Duration::from_millis(100)); // give time for actions to be
     mock_screen.teardown(vec![pty_thread, screen_thread]);
-    assert_snapshot!(format!("{:?}", *received_pty_instructions.lock().unwrap()));
+    assert_snapshot!(format!(
+        "{:?}",
+        without_cwd_queries(&received_pty_instructions.lock().unwrap())
+    ));
 }
 
 #[test]
@@ -1990,7 +2065,10 @@ pub fn send_cli_edit_action_with_line_number() {
     send_cli_action_to_server(&session_metadata, cli_edit_action, client_id);
     std::thread::sleep(std::time::Duration::from_millis(100)); // give time for actions to be
     mock_screen.teardown(vec![pty_thread, screen_thread]);
-    assert_snapshot!(format!("{:?}", *received_pty_instructions.lock().unwrap()));
+    assert_snapshot!(format!(
+        "{:?}",
+        without_cwd_queries(&received_pty_instructions.lock().unwrap())
+    ));
 }
 
 #[test]
@@ -2023,7 +2101,10 @@ pub fn send_cli_edit_action_with_split_direction() {
     send_cli_action_to_server(&session_metadata, cli_edit_action, client_id);
     std::thread::sleep(std::time::Duration::from_millis(100)); // give time for actions to be
     mock_screen.teardown(vec![pty_thread, screen_thread]);
-    assert_snapshot!(format!("{:?}", *received_pty_instructions.lock().unwrap()));
+    assert_snapshot!(format!(
+        "{:?}",
+        without_cwd_queries(&received_pty_instructions.lock().unwrap())
+    ));
 }
 
 #[test]
@@ -2427,7 +2508,7 @@ pub fn send_cli_rename_tab() {
     mock_screen.teardown(vec![plugin_thread, screen_thread]);
     assert_snapshot!(format!(
         "{:#?}",
-        *received_plugin_instructions.lock().unwrap()
+        without_pane_updates(&received_plugin_instructions.lock().unwrap())
     ))
 }
 
@@ -2465,10 +2546,329 @@ pub fn send_cli_undo_rename_tab() {
     mock_screen.teardown(vec![plugin_thread, screen_thread]);
     assert_snapshot!(format!(
         "{:#?}",
-        *received_plugin_instructions.lock().unwrap()
+        without_pane_updates(&received_plugin_instructions.lock().unwrap())
     ))
 }
 
+#[test]
+pub fn pane_update_is_sent_to_plugins_when_focus_changes() {
+    let size = Size { cols: 80, rows: 10 };
+    let mut initial_layout = TiledPaneLayout::default();
+    initial_layout.children_split_direction = SplitDirection::Vertical;
+    initial_layout.children = vec![TiledPaneLayout::default(), TiledPaneLayout::default()];
+    let mut mock_screen = MockScreen::new(size);
+    let screen_thread = mock_screen.run(Some(initial_layout));
+    let received_plugin_instructions = Arc::new(Mutex::new(vec![]));
+    let plugin_receiver = mock_screen.plugin_receiver.take().unwrap();
+    let plugin_thread = log_actions_in_thread!(
+        received_plugin_instructions,
+        PluginInstruction::Exit,
+        plugin_receiver
+    );
+    let _ = mock_screen
+        .to_screen
+        .send(ScreenInstruction::MoveFocusRight(
+            mock_screen.main_client_id,
+        ));
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    mock_screen.teardown(vec![plugin_thread, screen_thread]);
+    let pane_manifests: Vec<PaneManifest> = received_plugin_instructions
+        .lock()
+        .unwrap()
+        .iter()
+        .filter_map(|instruction| match instruction {
+            PluginInstruction::Update(updates) => {
+                updates.iter().find_map(|(_, _, event)| match event {
+                    Event::PaneUpdate(pane_manifest) => Some(pane_manifest.clone()),
+                    _ => None,
+                })
+            },
+            _ => None,
+        })
+        .collect();
+    let focused_pane_ids = |pane_manifest: &PaneManifest| -> Vec<u32> {
+        pane_manifest
+            .panes
+            .get(&0)
+            .map(|pane_infos| {
+                pane_infos
+                    .iter()
+                    .filter(|pane_info| !pane_info.focused_clients.is_empty())
+                    .map(|pane_info| pane_info.id)
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    let last_pane_manifest = pane_manifests.last().expect("no pane update was sent");
+    assert_eq!(last_pane_manifest.panes.len(), 1, "one tab in the manifest");
+    assert_eq!(
+        last_pane_manifest.panes.get(&0).unwrap().len(),
+        2,
+        "both panes in the manifest"
+    );
+    assert!(
+        pane_manifests
+            .iter()
+            .any(|pane_manifest| focused_pane_ids(pane_manifest) == vec![0]),
+        "first pane focused initially"
+    );
+    assert_eq!(
+        focused_pane_ids(last_pane_manifest),
+        vec![1],
+        "focus change reported"
+    );
+}
+
+#[test]
+pub fn pane_update_caused_by_pane_output_is_throttled() {
+    let size = Size { cols: 80, rows: 10 };
+    let mut screen = create_new_screen(size);
+    let (to_plugin, plugin_receiver): ChannelWithContext<PluginInstruction> = channels::unbounded();
+    let (to_background_jobs, background_jobs_receiver): ChannelWithContext<BackgroundJob> =
+        channels::unbounded();
+    screen.bus = Bus::new(
+        vec![],
+        None,
+        None,
+        Some(&SenderWithContext::new(to_plugin)),
+        None,
+        None,
+        Some(&SenderWithContext::new(to_background_jobs)),
+        Some(Box::new(FakeInputOutput::default())),
+    )
+    .should_silently_fail();
+    let now = Rc::new(Cell::new(Instant::now()));
+    let clock = now.clone();
+    screen.clock = Box::new(move || clock.get());
+    let pane_titles = |plugin_receiver: &Receiver<(PluginInstruction, ErrorContext)>| {
+        plugin_receiver
+            .try_iter()
+            .filter_map(|(instruction, _)| match instruction {
+                PluginInstruction::Update(updates) => {
+                    updates.into_iter().find_map(|(_, _, event)| match event {
+                        Event::PaneUpdate(pane_manifest) => pane_manifest
+                            .panes
+                            .get(&0)
+                            .map(|pane_infos| pane_infos.iter().map(|p| p.title.clone()).collect()),
+                        _ => None,
+                    })
+                },
+                _ => None,
+            })
+            .collect::<Vec<Vec<String>>>()
+    };
+
+    new_tab(&mut screen, 1, 0);
+    assert_eq!(
+        pane_titles(&plugin_receiver).len(),
+        1,
+        "initial pane update"
+    );
+    for title in ["first title", "second title"] {
+        screen
+            .get_active_tab_mut(1)
+            .unwrap()
+            .handle_pty_bytes(1, format!("\u{1b}]0;{title}\u{7}").as_bytes().to_vec())
+            .unwrap();
+        screen.update_panes().unwrap();
+    }
+    let scheduled_renders: Vec<BackgroundJob> = background_jobs_receiver
+        .try_iter()
+        .map(|(job, _)| job)
+        .collect();
+    assert!(
+        pane_titles(&plugin_receiver).is_empty(),
+        "pane updates caused by pane output are throttled"
+    );
+    assert_eq!(
+        scheduled_renders.len(),
+        1,
+        "one render scheduled to catch up"
+    );
+    assert!(matches!(
+        scheduled_renders[0],
+        BackgroundJob::RenderAfter(after) if after == PANE_MANIFEST_UPDATE_INTERVAL
+    ));
+
+    // this is when the render the background jobs thread scheduled arrives
+    now.set(now.get() + PANE_MANIFEST_UPDATE_INTERVAL);
+    screen.update_panes().unwrap();
+    let pane_titles = pane_titles(&plugin_receiver);
+    assert_eq!(pane_titles.len(), 1, "pane update once the interval passed");
+    assert!(
+        pane_titles[0].contains(&"second title".to_owned()),
+        "final title sent to plugins"
+    );
+}
+
+#[test]
+pub fn renaming_the_session_updates_the_mode_info_of_plugins() {
+    let size = Size { cols: 80, rows: 10 };
+    let mut mock_screen = MockScreen::new(size);
+    let screen_thread = mock_screen.run(None);
+    let received_plugin_instructions = Arc::new(Mutex::new(vec![]));
+    let plugin_receiver = mock_screen.plugin_receiver.take().unwrap();
+    let plugin_thread = log_actions_in_thread!(
+        received_plugin_instructions,
+        PluginInstruction::Exit,
+        plugin_receiver
+    );
+    let _ = mock_screen
+        .to_screen
+        .send(ScreenInstruction::RenameSession("renamed-session".into()));
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    mock_screen.teardown(vec![plugin_thread, screen_thread]);
+    let session_names: Vec<(Option<ClientId>, Option<String>)> = received_plugin_instructions
+        .lock()
+        .unwrap()
+        .iter()
+        .filter_map(|instruction| match instruction {
+            PluginInstruction::Update(updates) => Some(updates.clone()),
+            _ => None,
+        })
+        .flatten()
+        .filter_map(|(_, client_id, event)| match event {
+            Event::ModeUpdate(mode_info) => Some((client_id, mode_info.session_name)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        session_names.last(),
+        Some(&(
+            Some(mock_screen.main_client_id),
+            Some("renamed-session".to_owned())
+        )),
+        "new session name sent to the plugins"
+    );
+}
+
+#[test]
+pub fn plugin_permission_prompts_are_answered_with_the_keyboard() {
+    let size = Size { cols: 80, rows: 10 };
+    let mut mock_screen = MockScreen::new(size);
+    let screen_thread = mock_screen.run(None);
+    let received_plugin_instructions = Arc::new(Mutex::new(vec![]));
+    let plugin_receiver = mock_screen.plugin_receiver.take().unwrap();
+    let plugin_thread = log_actions_in_thread!(
+        received_plugin_instructions,
+        PluginInstruction::Exit,
+        plugin_receiver
+    );
+    let client_id = mock_screen.main_client_id;
+    let _ = mock_screen
+        .to_screen
+        .send(ScreenInstruction::RequestPluginPermissions(
+            1,
+            client_id,
+            "file:/path/to/first/plugin.wasm".to_owned(),
+            vec![PermissionType::RunCommands],
+        ));
+    let _ = mock_screen
+        .to_screen
+        .send(ScreenInstruction::RequestPluginPermissions(
+            2,
+            client_id,
+            "file:/path/to/second/plugin.wasm".to_owned(),
+            vec![PermissionType::ReadApplicationState],
+        ));
+    let _ = mock_screen.to_screen.send(ScreenInstruction::AnswerPrompt(
+        "x".as_bytes().to_vec(),
+        client_id,
+    ));
+    // the prompt is not shown to this client
+    let _ = mock_screen.to_screen.send(ScreenInstruction::AnswerPrompt(
+        "y".as_bytes().to_vec(),
+        client_id + 1,
+    ));
+    let _ = mock_screen.to_screen.send(ScreenInstruction::AnswerPrompt(
+        "n".as_bytes().to_vec(),
+        client_id,
+    ));
+    let _ = mock_screen.to_screen.send(ScreenInstruction::AnswerPrompt(
+        "Y".as_bytes().to_vec(),
+        client_id,
+    ));
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    mock_screen.teardown(vec![plugin_thread, screen_thread]);
+    let permission_results: Vec<(u32, Vec<PermissionType>, PermissionStatus)> =
+        received_plugin_instructions
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|instruction| match instruction {
+                PluginInstruction::PermissionRequestResult(
+                    plugin_id,
+                    _client_id,
+                    permissions,
+                    status,
+                ) => Some((*plugin_id, permissions.clone(), *status)),
+                _ => None,
+            })
+            .collect();
+    assert_eq!(
+        permission_results,
+        vec![
+            (
+                2,
+                vec![PermissionType::ReadApplicationState],
+                PermissionStatus::Denied
+            ),
+            (
+                1,
+                vec![PermissionType::RunCommands],
+                PermissionStatus::Granted
+            ),
+        ],
+        "most recent prompt answered first, unrelated keys ignored"
+    );
+}
+
+#[test]
+pub fn plugin_permission_prompts_are_not_answered_by_written_characters() {
+    let size = Size { cols: 80, rows: 10 };
+    let mut mock_screen = MockScreen::new(size);
+    let session_metadata = mock_screen.clone_session_metadata();
+    let screen_thread = mock_screen.run(None);
+    let received_plugin_instructions = Arc::new(Mutex::new(vec![]));
+    let plugin_receiver = mock_screen.plugin_receiver.take().unwrap();
+    let plugin_thread = log_actions_in_thread!(
+        received_plugin_instructions,
+        PluginInstruction::Exit,
+        plugin_receiver
+    );
+    let client_id = mock_screen.main_client_id;
+    let _ = mock_screen
+        .to_screen
+        .send(ScreenInstruction::RequestPluginPermissions(
+            1,
+            client_id,
+            "file:/path/to/plugin.wasm".to_owned(),
+            vec![PermissionType::RunCommands],
+        ));
+    // this is the route a plugin's write_chars("y") takes on behalf of its client
+    send_cli_action_to_server(
+        &session_metadata,
+        CliAction::WriteChars { chars: "y".into() },
+        client_id,
+    );
+    send_cli_action_to_server(
+        &session_metadata,
+        CliAction::Write {
+            bytes: "y".as_bytes().to_vec(),
+        },
+        client_id,
+    );
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    mock_screen.teardown(vec![plugin_thread, screen_thread]);
+    let permission_results = received_plugin_instructions
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|instruction| matches!(instruction, PluginInstruction::PermissionRequestResult(..)))
+        .count();
+    assert_eq!(permission_results, 0, "no permissions granted or denied");
+}
+
 #[test]
 pub fn send_cli_query_tab_names_action() {
     let size = Size { cols: 80, rows: 10 };
@@ -2552,6 +2952,7 @@ pub fn send_cli_launch_or_focus_plugin_action_when_plugin_is_already_loaded() {
     let existing_plugin_pane = TiledPaneLayout {
         run: Some(Run::Plugin(RunPlugin {
             _allow_exec_host_cmd: false,
+            configuration: Default::default(),
             location: RunPluginLocation::File(PathBuf::from("/path/to/fake/plugin")),
         })),
         ..Default::default()
@@ -2641,3 +3042,57 @@ pub fn screen_can_suppress_pane() {
     }
     assert_snapshot!(format!("{}", snapshot_count));
 }
+
+#[test]
+pub fn send_cli_pipe_action() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let client_id = 10; // fake client id should not appear in the screen's state
+    let mut mock_screen = MockScreen::new(size);
+    let plugin_receiver = mock_screen.plugin_receiver.take().unwrap();
+    let session_metadata = mock_screen.clone_session_metadata();
+    let screen_thread = mock_screen.run(None);
+    let received_plugin_instructions = Arc::new(Mutex::new(vec![]));
+    let plugin_thread = log_actions_in_thread!(
+        received_plugin_instructions,
+        PluginInstruction::Exit,
+        plugin_receiver
+    );
+    let cli_action = CliAction::Pipe {
+        plugin: Some(url::Url::parse("file:/path/to/fake/plugin").unwrap()),
+        name: Some("my_message".to_owned()),
+        payload: vec!["hello".to_owned(), "plugin".to_owned()],
+    };
+    send_cli_action_to_server(&session_metadata, cli_action, client_id);
+    std::thread::sleep(std::time::Duration::from_millis(100)); // give time for actions to be
+    mock_screen.teardown(vec![plugin_thread, screen_thread]);
+
+    let cli_pipe_instruction = received_plugin_instructions
+        .lock()
+        .unwrap()
+        .iter()
+        .find_map(|instruction| match instruction {
+            PluginInstruction::CliPipe(_pipe_id, destination, name, payload, client_id) => Some((
+                destination.clone(),
+                name.clone(),
+                payload.clone(),
+                *client_id,
+            )),
+            _ => None,
+        });
+
+    assert_eq!(
+        cli_pipe_instruction,
+        Some((
+            Some(RunPluginLocation::File(PathBuf::from(
+                "/path/to/fake/plugin"
+            ))),
+            "my_message".to_owned(),
+            Some("hello plugin".to_owned()),
+            client_id
+        )),
+        "pipe sent to the plugin thread"
+    );
+}
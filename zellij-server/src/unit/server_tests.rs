@@ -0,0 +1,65 @@
+use super::*;
+use std::os::unix::fs::FileTypeExt;
+use zellij_utils::interprocess::local_socket::{LocalSocketListener, LocalSocketStream};
+
+fn rename(socket_path: &Path, new_name: &str, aliases: &mut Vec<PathBuf>) -> PathBuf {
+    let new_socket_path = socket_path.with_file_name(new_name);
+    rename_socket(socket_path, &new_socket_path, aliases).unwrap();
+    new_socket_path
+}
+
+fn leads_to_session(path: &Path, listener: &LocalSocketListener) -> bool {
+    let connection = LocalSocketStream::connect(path);
+    connection.is_ok() && listener.accept().is_ok()
+}
+
+#[test]
+fn renamed_sessions_can_still_be_reached_through_their_old_names() {
+    let sock_dir = tempfile::tempdir().unwrap();
+    let first_path = sock_dir.path().join("first");
+    let listener = LocalSocketListener::bind(&*first_path).unwrap();
+    let mut aliases = vec![];
+    let second_path = rename(&first_path, "second", &mut aliases);
+    let third_path = rename(&second_path, "third", &mut aliases);
+    for path in [&first_path, &second_path, &third_path] {
+        assert!(
+            leads_to_session(path, &listener),
+            "{path:?} leads to the session"
+        );
+    }
+    let first_path = rename(&third_path, "first", &mut aliases);
+    assert!(first_path
+        .symlink_metadata()
+        .unwrap()
+        .file_type()
+        .is_socket());
+    for path in [&first_path, &second_path, &third_path] {
+        assert!(
+            leads_to_session(path, &listener),
+            "{path:?} leads to the session"
+        );
+    }
+    remove_socket_aliases(&aliases, &first_path);
+    assert!(!second_path.exists() && !third_path.exists());
+    assert!(first_path.exists());
+}
+
+#[test]
+fn old_session_names_taken_by_other_sessions_are_left_alone() {
+    let sock_dir = tempfile::tempdir().unwrap();
+    let first_path = sock_dir.path().join("first");
+    let _listener = LocalSocketListener::bind(&*first_path).unwrap();
+    let mut aliases = vec![];
+    let second_path = rename(&first_path, "second", &mut aliases);
+    // a new session binds its socket in place of the alias
+    std::fs::remove_file(&first_path).unwrap();
+    let _other_listener = LocalSocketListener::bind(&*first_path).unwrap();
+    let third_path = rename(&second_path, "third", &mut aliases);
+    assert_eq!(aliases, vec![second_path.clone()]);
+    remove_socket_aliases(&aliases, &third_path);
+    assert!(first_path
+        .symlink_metadata()
+        .unwrap()
+        .file_type()
+        .is_socket());
+}
@@ -2,11 +2,14 @@ pub mod prelude;
 pub mod shim;
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use zellij_utils::data::Event;
 
 #[allow(unused_variables)]
 pub trait ZellijPlugin {
-    fn load(&mut self) {}
+    /// Called once when the plugin is loaded, with the user configuration given to it in the
+    /// layout or in the `plugins` block of the config.
+    fn load(&mut self, configuration: BTreeMap<String, String>) {}
     fn update(&mut self, event: Event) -> bool {
         false
     } // return true if it should render
@@ -47,7 +50,12 @@ macro_rules! register_plugin {
         #[no_mangle]
         fn load() {
             STATE.with(|state| {
-                state.borrow_mut().load();
+                let configuration: std::collections::BTreeMap<String, String> =
+                    $crate::shim::object_from_stdin()
+                        .context($crate::PLUGIN_MISMATCH)
+                        .to_stdout()
+                        .unwrap();
+                state.borrow_mut().load(configuration);
             });
         }
 
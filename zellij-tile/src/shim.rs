@@ -1,5 +1,5 @@
 use serde::{de::DeserializeOwned, Serialize};
-use std::{io, path::Path};
+use std::{collections::BTreeMap, io, path::Path};
 use zellij_utils::data::*;
 use zellij_utils::errors::prelude::*;
 
@@ -21,6 +21,11 @@ pub fn set_selectable(selectable: bool) {
     unsafe { host_set_selectable(selectable as i32) };
 }
 
+pub fn request_permission(permissions: &[PermissionType]) {
+    object_to_stdout(&permissions);
+    unsafe { host_request_permission() };
+}
+
 // Query Functions
 pub fn get_plugin_ids() -> PluginIds {
     unsafe { host_get_plugin_ids() };
@@ -269,6 +274,119 @@ pub fn start_or_reload_plugin(url: &str) {
     unsafe { host_start_or_reload_plugin() };
 }
 
+pub fn focus_pane_with_id(pane_id: PaneId, should_float_if_hidden: bool) {
+    object_to_stdout(&(pane_id, should_float_if_hidden));
+    unsafe { host_focus_pane_with_id() };
+}
+
+pub fn close_pane_with_id(pane_id: PaneId) {
+    object_to_stdout(&pane_id);
+    unsafe { host_close_pane_with_id() };
+}
+
+pub fn write_to_pane_with_id(bytes: Vec<u8>, pane_id: PaneId) {
+    object_to_stdout(&(bytes, pane_id));
+    unsafe { host_write_to_pane_with_id() };
+}
+
+pub fn write_chars_to_pane_with_id(chars: &str, pane_id: PaneId) {
+    object_to_stdout(&(chars, pane_id));
+    unsafe { host_write_chars_to_pane_with_id() };
+}
+
+pub fn rename_pane_with_id(pane_id: PaneId, new_name: &str) {
+    object_to_stdout(&(pane_id, new_name));
+    unsafe { host_rename_pane_with_id() };
+}
+
+pub fn resize_pane_with_id(resize_strategy: ResizeStrategy, pane_id: PaneId) {
+    object_to_stdout(&(resize_strategy, pane_id));
+    unsafe { host_resize_pane_with_id() };
+}
+
+pub fn toggle_pane_embed_or_eject_with_id(pane_id: PaneId) {
+    object_to_stdout(&pane_id);
+    unsafe { host_toggle_pane_embed_or_eject_with_id() };
+}
+
+pub fn rerun_command_pane(terminal_pane_id: u32) {
+    unsafe { host_rerun_command_pane(terminal_pane_id) };
+}
+
+/// The response arrives as an `Event::WebRequestResult` with `context`, requests that got no
+/// response at all (eg. the server could not be reached) have a status of 0 and the error as body
+pub fn web_request(
+    url: &str,
+    verb: HttpVerb,
+    headers: BTreeMap<String, String>,
+    body: Vec<u8>,
+    context: BTreeMap<String, String>,
+) {
+    object_to_stdout(&(url, verb, headers, body, context));
+    unsafe { host_web_request() };
+}
+
+pub fn run_command(
+    command_line: &[&str],
+    env_variables: BTreeMap<String, String>,
+    cwd: &Path,
+    context: BTreeMap<String, String>,
+) {
+    object_to_stdout(&(command_line, env_variables, cwd, context));
+    unsafe { host_run_command() };
+}
+
+pub fn pipe_message_to_plugins(plugin_url: Option<&str>, name: &str, payload: Option<&str>) {
+    object_to_stdout(&(plugin_url, name, payload));
+    unsafe { host_pipe_message_to_plugins() };
+}
+
+pub fn cli_pipe_output(pipe_id: &str, output: &str) {
+    object_to_stdout(&(pipe_id, output));
+    unsafe { host_cli_pipe_output() };
+}
+
+/// Closes the cli pipe, ending the `zellij action pipe` command that is waiting on it
+pub fn close_cli_pipe(pipe_id: &str) {
+    object_to_stdout(&pipe_id);
+    unsafe { host_close_cli_pipe() };
+}
+
+pub fn switch_session(name: &str) {
+    object_to_stdout(&ConnectToSession::new(name));
+    unsafe { host_switch_session() };
+}
+
+pub fn switch_session_with_focus(name: &str, tab_position: Option<usize>, pane_id: Option<PaneId>) {
+    object_to_stdout(&ConnectToSession {
+        name: name.to_owned(),
+        tab_position,
+        pane_id,
+        layout: None,
+    });
+    unsafe { host_switch_session() };
+}
+
+pub fn switch_session_with_layout(name: &str, layout: &str) {
+    object_to_stdout(&ConnectToSession {
+        name: name.to_owned(),
+        layout: Some(layout.to_owned()),
+        ..Default::default()
+    });
+    unsafe { host_switch_session() };
+}
+
+pub fn rename_session(name: &str) {
+    object_to_stdout(&name);
+    unsafe { host_rename_session() };
+}
+
+pub fn kill_sessions<S: AsRef<str>>(session_names: &[S]) {
+    let session_names: Vec<&str> = session_names.iter().map(|name| name.as_ref()).collect();
+    object_to_stdout(&session_names);
+    unsafe { host_kill_sessions() };
+}
+
 // Internal Functions
 
 #[doc(hidden)]
@@ -308,6 +426,7 @@ extern "C" {
     fn host_subscribe();
     fn host_unsubscribe();
     fn host_set_selectable(selectable: i32);
+    fn host_request_permission();
     fn host_get_plugin_ids();
     fn host_get_zellij_version();
     fn host_open_file();
@@ -365,4 +484,20 @@ extern "C" {
     fn host_focus_or_create_tab();
     fn host_go_to_tab(tab_index: i32);
     fn host_start_or_reload_plugin();
+    fn host_focus_pane_with_id();
+    fn host_close_pane_with_id();
+    fn host_write_to_pane_with_id();
+    fn host_write_chars_to_pane_with_id();
+    fn host_rename_pane_with_id();
+    fn host_resize_pane_with_id();
+    fn host_toggle_pane_embed_or_eject_with_id();
+    fn host_rerun_command_pane(terminal_pane_id: u32);
+    fn host_web_request();
+    fn host_run_command();
+    fn host_pipe_message_to_plugins();
+    fn host_cli_pipe_output();
+    fn host_close_cli_pipe();
+    fn host_switch_session();
+    fn host_rename_session();
+    fn host_kill_sessions();
 }
@@ -74,7 +74,15 @@ pub enum SessionCommand {
 pub enum Sessions {
     /// List active sessions
     #[clap(visible_alias = "ls")]
-    ListSessions,
+    ListSessions {
+        /// Show the clients, tabs, panes, age and last activity of each session
+        #[clap(short, long, value_parser)]
+        long: bool,
+
+        /// Print the sessions and their details as JSON
+        #[clap(short, long, value_parser, conflicts_with("long"))]
+        json: bool,
+    },
 
     /// Attach to a session
     #[clap(visible_alias = "a")]
@@ -91,6 +99,15 @@ pub enum Sessions {
         #[clap(long, value_parser)]
         index: Option<usize>,
 
+        /// Attach as a spectator, that can watch but not type into, scroll or change the session
+        #[clap(long, value_parser, conflicts_with("create"))]
+        read_only: bool,
+
+        /// Resurrect the session without asking if it is no longer running, running its commands
+        /// right away rather than waiting for them to be started
+        #[clap(long, value_parser, conflicts_with("read-only"))]
+        force_run_commands: bool,
+
         /// Change the behaviour of zellij
         #[clap(subcommand, name = "options")]
         options: Option<Box<SessionCommand>>,
@@ -222,6 +239,17 @@ pub enum CliAction {
         /// Dump the pane with full scrollback
         #[clap(short, long, value_parser, default_value("false"), takes_value(false))]
         full: bool,
+
+        /// Dump only the output of the last command (requires shell integration)
+        #[clap(
+            short,
+            long,
+            value_parser,
+            default_value("false"),
+            takes_value(false),
+            conflicts_with("full")
+        )]
+        last_output: bool,
     },
     /// Open the pane scrollback in your default editor
     EditScrollback,
@@ -233,6 +261,12 @@ pub enum CliAction {
     ScrollToBottom,
     /// Scroll up to top in focus pane.
     ScrollToTop,
+    /// Scroll up to the previous shell prompt in focus pane (requires shell integration)
+    ScrollToPreviousPrompt,
+    /// Scroll down to the next shell prompt in focus pane (requires shell integration)
+    ScrollToNextPrompt,
+    /// Select the output of the last command in focus pane (requires shell integration)
+    SelectLastCommandOutput,
     /// Scroll up one page in focus pane.
     PageScrollUp,
     /// Scroll down one page in focus pane.
@@ -346,6 +380,22 @@ pub enum CliAction {
         #[clap(short, long, value_parser)]
         create: bool,
     },
+    /// Switch to session [name], creating it if it does not exist
+    SwitchSession {
+        name: String,
+
+        /// Focus the tab at this position (starting from 0) in the session
+        #[clap(short, long, value_parser)]
+        tab_position: Option<usize>,
+
+        /// Focus the terminal pane with this id in the session
+        #[clap(short, long, value_parser)]
+        pane_id: Option<u32>,
+    },
+    /// Rename the current session, the attached clients stay connected
+    RenameSession {
+        name: String,
+    },
     /// Renames the focused pane
     RenameTab {
         name: String,
@@ -382,4 +432,19 @@ pub enum CliAction {
         floating: bool,
         url: Url,
     },
+    /// Send a message to plugins, writing whatever they respond with to STDOUT until one of them
+    /// closes the pipe
+    Pipe {
+        /// The url of the plugin to send the message to, all plugins if not specified
+        #[clap(short, long, value_parser)]
+        plugin: Option<Url>,
+
+        /// The name of the message
+        #[clap(short, long, value_parser)]
+        name: Option<String>,
+
+        /// The payload of the message, read from STDIN if not specified
+        #[clap(last(true))]
+        payload: Vec<String>,
+    },
 }
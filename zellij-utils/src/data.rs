@@ -2,6 +2,7 @@ use crate::input::actions::Action;
 use crate::input::config::ConversionError;
 use clap::ArgEnum;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 use std::path::PathBuf;
 use std::str::FromStr;
@@ -58,20 +59,254 @@ pub enum Key {
     BackTab,
     Null,
     Esc,
+    // a combination of modifiers only reported by terminals supporting the kitty keyboard
+    // protocol (eg. Shift Enter, Ctrl Alt a or Super a), see `Key::from_bare_key_and_modifiers`
+    Modified(BareKey, KeyModifiers),
+}
+
+/// A key without its modifiers, see [`Key::Modified`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, PartialOrd, Ord)]
+pub enum BareKey {
+    PageDown,
+    PageUp,
+    Left,
+    Down,
+    Up,
+    Right,
+    Home,
+    End,
+    Backspace,
+    Delete,
+    Insert,
+    F(u8),
+    Char(char),
+    Tab,
+    Enter,
+    Esc,
+}
+
+impl FromStr for BareKey {
+    type Err = Box<dyn std::error::Error>;
+    fn from_str(key_str: &str) -> Result<Self, Self::Err> {
+        match key_str {
+            "Backspace" => Ok(BareKey::Backspace),
+            "Left" => Ok(BareKey::Left),
+            "Right" => Ok(BareKey::Right),
+            "Up" => Ok(BareKey::Up),
+            "Down" => Ok(BareKey::Down),
+            "Home" => Ok(BareKey::Home),
+            "End" => Ok(BareKey::End),
+            "PageUp" => Ok(BareKey::PageUp),
+            "PageDown" => Ok(BareKey::PageDown),
+            "Tab" => Ok(BareKey::Tab),
+            "Delete" => Ok(BareKey::Delete),
+            "Insert" => Ok(BareKey::Insert),
+            "Space" => Ok(BareKey::Char(' ')),
+            "Enter" => Ok(BareKey::Enter),
+            "Esc" => Ok(BareKey::Esc),
+            _ => {
+                let mut key_chars = key_str.chars();
+                match (key_chars.next(), key_chars.next()) {
+                    (Some(key_char), None) => Ok(BareKey::Char(key_char)),
+                    (Some('F'), Some(_)) => {
+                        let f_index: u8 = key_str[1..]
+                            .parse()
+                            .map_err(|e| format!("Failed to parse F index: {}", e))?;
+                        if f_index >= 1 && f_index <= 12 {
+                            Ok(BareKey::F(f_index))
+                        } else {
+                            Err(format!("Failed to parse key: {}", key_str).into())
+                        }
+                    },
+                    _ => Err(format!("Failed to parse key: {}", key_str).into()),
+                }
+            },
+        }
+    }
+}
+
+/// The modifiers held down together with a [`BareKey`]
+#[derive(
+    Debug, Default, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, PartialOrd, Ord,
+)]
+pub struct KeyModifiers {
+    pub shift: bool,
+    pub alt: bool,
+    pub ctrl: bool,
+    pub super_key: bool,
+}
+
+impl KeyModifiers {
+    pub fn is_empty(&self) -> bool {
+        !self.shift && !self.alt && !self.ctrl && !self.super_key
+    }
+}
+
+impl fmt::Display for KeyModifiers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut modifiers = vec![];
+        if self.ctrl {
+            modifiers.push("Ctrl");
+        }
+        if self.alt {
+            modifiers.push("Alt");
+        }
+        if self.shift {
+            modifiers.push("Shift");
+        }
+        if self.super_key {
+            modifiers.push("Super");
+        }
+        write!(f, "{}", modifiers.join("+"))
+    }
+}
+
+/// Whether a key was pressed, is being held down or was released - terminals only report the
+/// latter two when using the kitty keyboard protocol
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum KeyEventType {
+    Press,
+    Repeat,
+    Release,
+}
+
+impl Default for KeyEventType {
+    fn default() -> Self {
+        KeyEventType::Press
+    }
+}
+
+impl Key {
+    /// Prefers the legacy variants over [`Key::Modified`] whenever they can represent the key,
+    /// so that the same keybinds work whether or not the terminal supports the kitty keyboard
+    /// protocol
+    pub fn from_bare_key_and_modifiers(bare_key: BareKey, modifiers: KeyModifiers) -> Self {
+        let KeyModifiers {
+            shift,
+            alt,
+            ctrl,
+            super_key,
+        } = modifiers;
+        if super_key {
+            return Key::Modified(bare_key, modifiers);
+        }
+        match (bare_key, shift, alt, ctrl) {
+            (bare_key, false, false, false) => match bare_key {
+                BareKey::PageDown => Key::PageDown,
+                BareKey::PageUp => Key::PageUp,
+                BareKey::Left => Key::Left,
+                BareKey::Down => Key::Down,
+                BareKey::Up => Key::Up,
+                BareKey::Right => Key::Right,
+                BareKey::Home => Key::Home,
+                BareKey::End => Key::End,
+                BareKey::Backspace => Key::Backspace,
+                BareKey::Delete => Key::Delete,
+                BareKey::Insert => Key::Insert,
+                BareKey::F(n) => Key::F(n),
+                BareKey::Char(c) => Key::Char(c),
+                BareKey::Tab => Key::Char('\t'),
+                BareKey::Enter => Key::Char('\n'),
+                BareKey::Esc => Key::Esc,
+            },
+            (BareKey::Tab, true, false, false) => Key::BackTab,
+            (BareKey::Char(c), true, false, false) if c.is_ascii_alphabetic() => {
+                Key::Char(c.to_ascii_uppercase())
+            },
+            (BareKey::Char(c), false, true, false) => Key::Alt(CharOrArrow::Char(c)),
+            (BareKey::Char(c), true, true, false) if c.is_ascii_alphabetic() => {
+                Key::Alt(CharOrArrow::Char(c.to_ascii_uppercase()))
+            },
+            (BareKey::Left, false, true, false) => {
+                Key::Alt(CharOrArrow::Direction(Direction::Left))
+            },
+            (BareKey::Right, false, true, false) => {
+                Key::Alt(CharOrArrow::Direction(Direction::Right))
+            },
+            (BareKey::Up, false, true, false) => Key::Alt(CharOrArrow::Direction(Direction::Up)),
+            (BareKey::Down, false, true, false) => {
+                Key::Alt(CharOrArrow::Direction(Direction::Down))
+            },
+            (BareKey::Char(c), false, false, true) if c.is_ascii_graphic() || c == ' ' => {
+                Key::Ctrl(c.to_ascii_lowercase())
+            },
+            _ => Key::Modified(bare_key, modifiers),
+        }
+    }
+    /// The inverse of [`Key::from_bare_key_and_modifiers`]
+    pub fn bare_key_and_modifiers(&self) -> (BareKey, KeyModifiers) {
+        let no_modifiers = KeyModifiers::default();
+        let alt = KeyModifiers {
+            alt: true,
+            ..Default::default()
+        };
+        let ctrl = KeyModifiers {
+            ctrl: true,
+            ..Default::default()
+        };
+        let shift = KeyModifiers {
+            shift: true,
+            ..Default::default()
+        };
+        match *self {
+            Key::PageDown => (BareKey::PageDown, no_modifiers),
+            Key::PageUp => (BareKey::PageUp, no_modifiers),
+            Key::Left => (BareKey::Left, no_modifiers),
+            Key::Down => (BareKey::Down, no_modifiers),
+            Key::Up => (BareKey::Up, no_modifiers),
+            Key::Right => (BareKey::Right, no_modifiers),
+            Key::Home => (BareKey::Home, no_modifiers),
+            Key::End => (BareKey::End, no_modifiers),
+            Key::Backspace => (BareKey::Backspace, no_modifiers),
+            Key::Delete => (BareKey::Delete, no_modifiers),
+            Key::Insert => (BareKey::Insert, no_modifiers),
+            Key::F(n) => (BareKey::F(n), no_modifiers),
+            Key::Char('\n') => (BareKey::Enter, no_modifiers),
+            Key::Char('\t') => (BareKey::Tab, no_modifiers),
+            Key::BackTab => (BareKey::Tab, shift),
+            Key::Char(c) => (BareKey::Char(c), no_modifiers),
+            Key::Alt(CharOrArrow::Char(c)) => (BareKey::Char(c), alt),
+            Key::Alt(CharOrArrow::Direction(Direction::Left)) => (BareKey::Left, alt),
+            Key::Alt(CharOrArrow::Direction(Direction::Right)) => (BareKey::Right, alt),
+            Key::Alt(CharOrArrow::Direction(Direction::Up)) => (BareKey::Up, alt),
+            Key::Alt(CharOrArrow::Direction(Direction::Down)) => (BareKey::Down, alt),
+            Key::Ctrl(c) => (BareKey::Char(c), ctrl),
+            Key::Null => (BareKey::Char(' '), ctrl),
+            Key::Esc => (BareKey::Esc, no_modifiers),
+            Key::Modified(bare_key, modifiers) => (bare_key, modifiers),
+        }
+    }
 }
 
 impl FromStr for Key {
     type Err = Box<dyn std::error::Error>;
     fn from_str(key_str: &str) -> Result<Self, Self::Err> {
+        let mut modifiers = KeyModifiers::default();
+        let mut modifier_count = 0;
         let mut modifier: Option<&str> = None;
         let mut main_key: Option<&str> = None;
-        for (index, part) in key_str.split_ascii_whitespace().enumerate() {
-            if index == 0 && (part == "Ctrl" || part == "Alt") {
+        for part in key_str.split_ascii_whitespace() {
+            let is_modifier =
+                main_key.is_none() && matches!(part, "Ctrl" | "Alt" | "Shift" | "Super");
+            if is_modifier {
+                match part {
+                    "Ctrl" => modifiers.ctrl = true,
+                    "Alt" => modifiers.alt = true,
+                    "Shift" => modifiers.shift = true,
+                    _ => modifiers.super_key = true,
+                }
+                modifier_count += 1;
                 modifier = Some(part);
             } else if main_key.is_none() {
                 main_key = Some(part)
             }
         }
+        if modifier_count > 1 || modifiers.shift || modifiers.super_key {
+            // combinations only the kitty keyboard protocol can tell apart
+            let main_key = main_key.ok_or_else(|| format!("Failed to parse key: {}", key_str))?;
+            let bare_key = BareKey::from_str(main_key)?;
+            return Ok(Key::from_bare_key_and_modifiers(bare_key, modifiers));
+        }
         match (modifier, main_key) {
             (Some("Ctrl"), Some(main_key)) => {
                 let mut key_chars = main_key.chars();
@@ -80,7 +315,9 @@ impl FromStr for Key {
                     let key_char = key_chars.next().unwrap();
                     Ok(Key::Ctrl(key_char))
                 } else {
-                    Err(format!("Failed to parse key: {}", key_str).into())
+                    // eg. Ctrl Enter, which only the kitty keyboard protocol can tell apart
+                    let bare_key = BareKey::from_str(main_key)?;
+                    Ok(Key::from_bare_key_and_modifiers(bare_key, modifiers))
                 }
             },
             (Some("Alt"), Some(main_key)) => {
@@ -99,7 +336,8 @@ impl FromStr for Key {
                             let key_char = key_chars.next().unwrap();
                             Ok(Key::Alt(CharOrArrow::Char(key_char)))
                         } else {
-                            Err(format!("Failed to parse key: {}", key_str).into())
+                            let bare_key = BareKey::from_str(main_key)?;
+                            Ok(Key::from_bare_key_and_modifiers(bare_key, modifiers))
                         }
                     },
                 }
@@ -175,6 +413,12 @@ impl fmt::Display for Key {
             Key::Ctrl(c) => write!(f, "Ctrl+{}", Key::Char(*c)),
             Key::Null => write!(f, "NULL"),
             Key::Esc => write!(f, "ESC"),
+            Key::Modified(bare_key, modifiers) => write!(
+                f,
+                "{}+{}",
+                modifiers,
+                Key::from_bare_key_and_modifiers(*bare_key, KeyModifiers::default())
+            ),
         }
     }
 }
@@ -478,6 +722,100 @@ pub enum Event {
     FileSystemRead(Vec<PathBuf>),
     FileSystemUpdate(Vec<PathBuf>),
     FileSystemDelete(Vec<PathBuf>),
+    PaneUpdate(PaneManifest),
+    PermissionRequestResult(PermissionStatus),
+    WebRequestResult(
+        u16,                      // status code, 0 if no response was received
+        BTreeMap<String, String>, // headers
+        Vec<u8>,                  // body
+        BTreeMap<String, String>, // context
+    ),
+    PipeMessage(
+        PipeSource,
+        String,         // name
+        Option<String>, // payload
+    ),
+    RunCommandResult(
+        Option<i32>,              // exit code
+        Vec<u8>,                  // stdout
+        Vec<u8>,                  // stderr
+        BTreeMap<String, String>, // context
+    ),
+    SessionUpdate(
+        Vec<SessionInfo>, // all the running sessions, including this one
+        Vec<String>,      // the names of the layouts new sessions can be started with
+    ),
+}
+
+/// A category of host functionality a plugin must be granted before it can use it.
+#[derive(
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    Copy,
+    Clone,
+    EnumIter,
+    EnumString,
+    ToString,
+    Serialize,
+    Deserialize,
+    PartialOrd,
+    Ord,
+)]
+pub enum PermissionType {
+    ReadApplicationState,
+    ChangeApplicationState,
+    OpenFiles,
+    RunCommands,
+    OpenTerminalsOrPlugins,
+    WriteToStdin,
+    WebAccess,
+    MessageOtherPlugins,
+    WriteToCliPipes,
+}
+
+impl PermissionType {
+    pub fn display_name(&self) -> String {
+        match self {
+            PermissionType::ReadApplicationState => {
+                "Access Zellij state (Panes, Tabs and UI)".to_owned()
+            },
+            PermissionType::ChangeApplicationState => {
+                "Change Zellij state (Panes, Tabs and UI)".to_owned()
+            },
+            PermissionType::OpenFiles => "Open files (eg. for editing)".to_owned(),
+            PermissionType::RunCommands => "Run commands".to_owned(),
+            PermissionType::OpenTerminalsOrPlugins => "Start new terminals and plugins".to_owned(),
+            PermissionType::WriteToStdin => "Write to standard input (STDIN)".to_owned(),
+            PermissionType::WebAccess => "Make web requests".to_owned(),
+            PermissionType::MessageOtherPlugins => "Send messages to other plugins".to_owned(),
+            PermissionType::WriteToCliPipes => {
+                "Write output to the command line pipes sent to it".to_owned()
+            },
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone, Serialize, Deserialize)]
+pub enum PermissionStatus {
+    Granted,
+    Denied,
+}
+
+/// Where a message piped to a plugin came from
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Serialize, Deserialize)]
+pub enum PipeSource {
+    Cli(String), // pipe id, to write back to the cli with `cli_pipe_output` and `close_cli_pipe`
+    Plugin(u32), // plugin id
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone, Serialize, Deserialize)]
+pub enum HttpVerb {
+    Get,
+    Post,
+    Put,
+    Delete,
 }
 
 /// Describes the different input modes, which change the way that keystrokes will be interpreted.
@@ -540,6 +878,12 @@ pub enum InputMode {
     /// `Tmux` mode allows for basic tmux keybindings functionality
     #[serde(alias = "tmux")]
     Tmux,
+    /// `Copy` mode allows moving a cursor over a pane's scrollback and selecting text to copy.
+    #[serde(alias = "copy")]
+    Copy,
+    /// `Hint` mode labels the links in a pane so that they can be opened by typing their label.
+    #[serde(alias = "hint")]
+    Hint,
 }
 
 // impl TryFrom<&str> for InputMode {
@@ -609,6 +953,8 @@ impl FromStr for InputMode {
             "move" | "Move" => Ok(InputMode::Move),
             "prompt" | "Prompt" => Ok(InputMode::Prompt),
             "tmux" | "Tmux" => Ok(InputMode::Tmux),
+            "copy" | "Copy" => Ok(InputMode::Copy),
+            "hint" | "Hint" => Ok(InputMode::Hint),
             "entersearch" | "Entersearch" | "EnterSearch" => Ok(InputMode::EnterSearch),
             e => Err(ConversionError::UnknownInputMode(e.into())),
         }
@@ -696,8 +1042,79 @@ pub struct TabInfo {
     pub is_sync_panes_active: bool,
     pub are_floating_panes_visible: bool,
     pub other_focused_clients: Vec<ClientId>,
+    /// The clients among `other_focused_clients` that are attached read-only
+    pub read_only_clients: Vec<ClientId>,
     pub active_swap_layout_name: Option<String>,
     pub is_swap_layout_dirty: bool,
+    pub active_pane_cwd: Option<PathBuf>,
+}
+
+/// Identifies a pane, eg. in order to act on it from a plugin
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
+pub enum PaneId {
+    Terminal(u32),
+    Plugin(u32),
+}
+
+/// What `zellij list-sessions` and the `SessionUpdate` event report about a running session
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SessionInfo {
+    pub name: String,
+    pub connected_clients: usize,
+    pub tabs: Vec<String>,  // the tab names, by position
+    pub panes: usize,       // terminal panes, plugins are not counted
+    pub created_at: u64,    // seconds since the unix epoch
+    pub last_activity: u64, // seconds since the unix epoch, when the screen last changed
+    pub pane_manifest: PaneManifest,
+}
+
+/// The session a client should switch to, and optionally where to focus in it
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ConnectToSession {
+    pub name: String,
+    pub tab_position: Option<usize>,
+    pub pane_id: Option<PaneId>,
+    pub layout: Option<String>, // name or path of the layout to start the session with if it is not running
+}
+
+impl ConnectToSession {
+    pub fn new(name: &str) -> Self {
+        ConnectToSession {
+            name: name.to_owned(),
+            ..Default::default()
+        }
+    }
+}
+
+/// All the panes of the session, keyed by the position of the tab they are in
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct PaneManifest {
+    pub panes: HashMap<usize, Vec<PaneInfo>>, // usize is the tab position
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct PaneInfo {
+    /* subset of fields to publish to plugins */
+    pub id: u32, // the terminal or plugin id, depending on is_plugin
+    pub is_plugin: bool,
+    pub title: String,
+    pub focused_clients: Vec<ClientId>,
+    pub is_floating: bool,
+    pub is_stacked: bool,
+    pub is_fullscreen: bool,
+    pub is_suppressed: bool,
+    pub is_selectable: bool,
+    pub pane_x: usize,
+    pub pane_y: usize,
+    pub pane_rows: usize,
+    pub pane_columns: usize,
+    pub pane_content_x: usize,
+    pub pane_content_y: usize,
+    pub pane_content_rows: usize,
+    pub pane_content_columns: usize,
+    pub is_held: bool, // the pane's command exited and it is waiting for the user to re-run it
+    pub exit_status: Option<i32>,
+    pub cwd: Option<PathBuf>,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
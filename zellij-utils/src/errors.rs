@@ -222,6 +222,8 @@ pub enum ScreenContext {
     HorizontalSplit,
     VerticalSplit,
     WriteCharacter,
+    WriteKey,
+    AnswerPrompt,
     ResizeIncreaseAll,
     ResizeIncreaseLeft,
     ResizeIncreaseDown,
@@ -264,6 +266,9 @@ pub enum ScreenContext {
     ScrollDownAt,
     ScrollToBottom,
     ScrollToTop,
+    ScrollToPreviousPrompt,
+    ScrollToNextPrompt,
+    SelectLastCommandOutput,
     PageScrollUp,
     PageScrollDown,
     HalfPageScrollUp,
@@ -298,6 +303,7 @@ pub enum ScreenContext {
     ChangeMode,
     ChangeModeForAllClients,
     LeftClick,
+    LeftClickWithBlockSelection,
     RightClick,
     MiddleClick,
     LeftMouseRelease,
@@ -319,12 +325,18 @@ pub enum ScreenContext {
     SearchUp,
     SearchToggleCaseSensitivity,
     SearchToggleWholeWord,
+    SearchToggleRegex,
     SearchToggleWrap,
+    CopyModeMove,
+    CopyModeSelect,
+    HintInput,
     AddRedPaneFrameColorOverride,
     ClearPaneFrameColorOverride,
     PreviousSwapLayout,
     NextSwapLayout,
     QueryTabNames,
+    QuerySessionInfo,
+    RenameSession,
     NewTiledPluginPane,
     StartOrReloadPluginPane,
     NewFloatingPluginPane,
@@ -335,6 +347,15 @@ pub enum ScreenContext {
     RequestStateUpdateForPlugins,
     LaunchOrFocusPlugin,
     SuppressPane,
+    FocusPaneWithId,
+    WriteToPaneWithId,
+    RenamePaneWithId,
+    ResizePaneWithId,
+    TogglePaneEmbedOrFloatingWithId,
+    RerunCommandPaneWithId,
+    DumpLayoutToHd,
+    RequestPluginPermissions,
+    UpdateTerminalCwds,
 }
 
 /// Stack call representations corresponding to the different types of [`PtyInstruction`]s.
@@ -350,6 +371,9 @@ pub enum PtyContext {
     ClosePane,
     CloseTab,
     ReRunCommandInPane,
+    DumpLayoutToHd,
+    UpdateTerminalCwd,
+    QueryTerminalCwds,
     Exit,
 }
 
@@ -371,6 +395,13 @@ pub enum PluginContext {
     PostMessageToPluginWorker,
     PostMessageToPlugin,
     PluginSubscribedToEvents,
+    PluginUnsubscribedFromEvents,
+    PermissionRequestResult,
+    PipeMessage,
+    CliPipe,
+    CliPipeOutput,
+    CloseCliPipe,
+    CliPipeTimedOut,
 }
 
 /// Stack call representations corresponding to the different types of [`ClientInstruction`]s.
@@ -388,6 +419,8 @@ pub enum ClientContext {
     OwnClientId,
     StartedParsingStdinQuery,
     DoneParsingStdinQuery,
+    SwitchSession,
+    SessionInfo,
 }
 
 /// Stack call representations corresponding to the different types of [`ServerInstruction`]s.
@@ -401,10 +434,16 @@ pub enum ServerContext {
     Error,
     KillSession,
     DetachSession,
+    SwitchSession,
+    RenameSession,
     AttachClient,
     ConnStatus,
     ActiveClients,
+    QuerySessionInfo,
+    SessionInfo,
     Log,
+    CliPipeOutput,
+    CloseCliPipe,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
@@ -418,6 +457,14 @@ pub enum BackgroundJobContext {
     DisplayPaneError,
     AnimatePluginLoading,
     StopPluginLoadingAnimation,
+    WriteSessionLayout,
+    MoveSessionLayout,
+    RemoveSessionLayout,
+    StartPollingSessionInfos,
+    StopPollingSessionInfos,
+    RenderAfter,
+    WebRequest,
+    RunCommand,
     Exit,
 }
 
@@ -2,12 +2,12 @@
 
 use super::command::RunCommandAction;
 use super::layout::{
-    FloatingPaneLayout, Layout, RunPlugin, RunPluginLocation, SwapFloatingLayout, SwapTiledLayout,
-    TiledPaneLayout,
+    FloatingPaneLayout, Layout, PluginUserConfiguration, RunPlugin, RunPluginLocation,
+    SwapFloatingLayout, SwapTiledLayout, TiledPaneLayout,
 };
 use crate::cli::CliAction;
-use crate::data::InputMode;
-use crate::data::{Direction, Resize};
+use crate::data::{ConnectToSession, Direction, PaneId, Resize};
+use crate::data::{InputMode, Key, KeyEventType};
 use crate::input::config::{Config, ConfigError, KdlError};
 use crate::input::options::OnForceClose;
 use crate::setup::{find_default_config_dir, get_layout_dir};
@@ -17,6 +17,7 @@ use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use std::str::FromStr;
 use url::Url;
+use uuid::Uuid;
 
 use crate::position::Position;
 
@@ -72,6 +73,7 @@ pub enum SearchOption {
     CaseSensitivity,
     WholeWord,
     Wrap,
+    Regex,
 }
 
 impl FromStr for SearchOption {
@@ -83,6 +85,7 @@ impl FromStr for SearchOption {
             },
             "WholeWord" | "wholeword" | "Wholeword" => Ok(SearchOption::WholeWord),
             "Wrap" | "wrap" => Ok(SearchOption::Wrap),
+            "Regex" | "regex" => Ok(SearchOption::Regex),
             _ => Err(format!(
                 "Failed to parse SearchOption. Unknown SearchOption: {}",
                 s
@@ -91,6 +94,68 @@ impl FromStr for SearchOption {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum CopyModeMotion {
+    Left,
+    Right,
+    Up,
+    Down,
+    NextWordStart,
+    PreviousWordStart,
+    NextWordEnd,
+    LineStart,
+    LineEnd,
+    Top,
+    Bottom,
+}
+
+impl FromStr for CopyModeMotion {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Left" | "left" => Ok(CopyModeMotion::Left),
+            "Right" | "right" => Ok(CopyModeMotion::Right),
+            "Up" | "up" => Ok(CopyModeMotion::Up),
+            "Down" | "down" => Ok(CopyModeMotion::Down),
+            "NextWordStart" | "nextwordstart" => Ok(CopyModeMotion::NextWordStart),
+            "PreviousWordStart" | "previouswordstart" => Ok(CopyModeMotion::PreviousWordStart),
+            "NextWordEnd" | "nextwordend" => Ok(CopyModeMotion::NextWordEnd),
+            "LineStart" | "linestart" => Ok(CopyModeMotion::LineStart),
+            "LineEnd" | "lineend" => Ok(CopyModeMotion::LineEnd),
+            "Top" | "top" => Ok(CopyModeMotion::Top),
+            "Bottom" | "bottom" => Ok(CopyModeMotion::Bottom),
+            _ => Err(format!(
+                "Failed to parse CopyModeMotion. Unknown CopyModeMotion: {}",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum CopyModeSelection {
+    Character,
+    Word,
+    Line,
+    Block,
+}
+
+impl FromStr for CopyModeSelection {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Character" | "character" => Ok(CopyModeSelection::Character),
+            "Word" | "word" => Ok(CopyModeSelection::Word),
+            "Line" | "line" => Ok(CopyModeSelection::Line),
+            "Block" | "block" => Ok(CopyModeSelection::Block),
+            _ => Err(format!(
+                "Failed to parse CopyModeSelection. Unknown CopyModeSelection: {}",
+                s
+            )),
+        }
+    }
+}
+
 // As these actions are bound to the default config, please
 // do take care when refactoring - or renaming.
 // They might need to be adjusted in the default config
@@ -104,6 +169,9 @@ pub enum Action {
     Write(Vec<u8>),
     /// Write Characters to the terminal.
     WriteChars(String),
+    /// Write a key reported through the kitty keyboard protocol to the terminal, encoded
+    /// according to what the application running in it requested.
+    WriteKey(Key, KeyEventType),
     /// Switch to the specified input mode.
     SwitchToMode(InputMode),
     /// Switch all connected clients to the specified input mode.
@@ -123,8 +191,8 @@ pub enum Action {
     MovePaneBackwards,
     /// Clear all buffers of a current screen
     ClearScreen,
-    /// Dumps the screen to a file
-    DumpScreen(String, bool),
+    /// Dumps the screen to a file (full scrollback, only the last command output)
+    DumpScreen(String, bool, bool),
     /// Scroll up in focus pane.
     EditScrollback,
     ScrollUp,
@@ -138,6 +206,12 @@ pub enum Action {
     ScrollToBottom,
     /// Scroll up to top in focus pane.
     ScrollToTop,
+    /// Scroll up to the previous shell prompt in focus pane.
+    ScrollToPreviousPrompt,
+    /// Scroll down to the next shell prompt in focus pane.
+    ScrollToNextPrompt,
+    /// Select the output of the last command in focus pane.
+    SelectLastCommandOutput,
     /// Scroll up one page in focus pane.
     PageScrollUp,
     /// Scroll down one page in focus pane.
@@ -202,7 +276,13 @@ pub enum Action {
     Run(RunCommandAction),
     /// Detach session and exit
     Detach,
+    /// Switch the client to another session, creating it if it does not exist
+    SwitchSession(ConnectToSession),
+    /// Rename the current session, keeping its clients attached
+    RenameSession(String),
     LeftClick(Position),
+    /// Left click that starts a rectangular (block) selection
+    LeftClickWithBlockSelection(Position),
     RightClick(Position),
     MiddleClick(Position),
     LaunchOrFocusPlugin(RunPlugin, bool), // bool => should float
@@ -225,6 +305,12 @@ pub enum Action {
     Search(SearchDirection),
     /// Toggle case sensitivity of search
     SearchToggleOption(SearchOption),
+    /// Move the copy mode cursor
+    CopyModeMove(CopyModeMotion),
+    /// Start (or stop) selecting text from the copy mode cursor
+    CopyModeSelect(CopyModeSelection),
+    /// Type part of the label of a hint to act on it
+    HintInput(Vec<u8>),
     ToggleMouseMode,
     PreviousSwapLayout,
     NextSwapLayout,
@@ -234,6 +320,13 @@ pub enum Action {
     NewTiledPluginPane(RunPluginLocation, Option<String>), // String is an optional name
     NewFloatingPluginPane(RunPluginLocation, Option<String>), // String is an optional name
     StartOrReloadPlugin(Url),
+    /// Send a message from the cli to plugins
+    CliPipe {
+        pipe_id: String,
+        name: String,
+        payload: Option<String>,
+        plugin: Option<String>, // plugin url, all plugins if None
+    },
 }
 
 impl Action {
@@ -261,15 +354,23 @@ impl Action {
             CliAction::MovePane { direction } => Ok(vec![Action::MovePane(direction)]),
             CliAction::MovePaneBackwards => Ok(vec![Action::MovePaneBackwards]),
             CliAction::Clear => Ok(vec![Action::ClearScreen]),
-            CliAction::DumpScreen { path, full } => Ok(vec![Action::DumpScreen(
+            CliAction::DumpScreen {
+                path,
+                full,
+                last_output,
+            } => Ok(vec![Action::DumpScreen(
                 path.as_os_str().to_string_lossy().into(),
                 full,
+                last_output,
             )]),
             CliAction::EditScrollback => Ok(vec![Action::EditScrollback]),
             CliAction::ScrollUp => Ok(vec![Action::ScrollUp]),
             CliAction::ScrollDown => Ok(vec![Action::ScrollDown]),
             CliAction::ScrollToBottom => Ok(vec![Action::ScrollToBottom]),
             CliAction::ScrollToTop => Ok(vec![Action::ScrollToTop]),
+            CliAction::ScrollToPreviousPrompt => Ok(vec![Action::ScrollToPreviousPrompt]),
+            CliAction::ScrollToNextPrompt => Ok(vec![Action::ScrollToNextPrompt]),
+            CliAction::SelectLastCommandOutput => Ok(vec![Action::SelectLastCommandOutput]),
             CliAction::PageScrollUp => Ok(vec![Action::PageScrollUp]),
             CliAction::PageScrollDown => Ok(vec![Action::PageScrollDown]),
             CliAction::HalfPageScrollUp => Ok(vec![Action::HalfPageScrollUp]),
@@ -385,6 +486,17 @@ impl Action {
             CliAction::CloseTab => Ok(vec![Action::CloseTab]),
             CliAction::GoToTab { index } => Ok(vec![Action::GoToTab(index)]),
             CliAction::GoToTabName { name, create } => Ok(vec![Action::GoToTabName(name, create)]),
+            CliAction::SwitchSession {
+                name,
+                tab_position,
+                pane_id,
+            } => Ok(vec![Action::SwitchSession(ConnectToSession {
+                name,
+                tab_position,
+                pane_id: pane_id.map(PaneId::Terminal),
+                layout: None,
+            })]),
+            CliAction::RenameSession { name } => Ok(vec![Action::RenameSession(name)]),
             CliAction::RenameTab { name } => Ok(vec![
                 Action::TabNameInput(vec![0]),
                 Action::TabNameInput(name.as_bytes().to_vec()),
@@ -481,9 +593,29 @@ impl Action {
                 let run_plugin = RunPlugin {
                     location: run_plugin_location,
                     _allow_exec_host_cmd: false,
+                    configuration: PluginUserConfiguration::default(),
                 };
                 Ok(vec![Action::LaunchOrFocusPlugin(run_plugin, floating)])
             },
+            CliAction::Pipe {
+                plugin,
+                name,
+                payload,
+            } => {
+                let pipe_id = Uuid::new_v4().to_string();
+                let name = name.unwrap_or_else(|| pipe_id.clone());
+                let payload = if payload.is_empty() {
+                    None
+                } else {
+                    Some(payload.join(" "))
+                };
+                Ok(vec![Action::CliPipe {
+                    pipe_id,
+                    name,
+                    payload,
+                    plugin: plugin.map(|url| url.to_string()),
+                }])
+            },
         }
     }
 }
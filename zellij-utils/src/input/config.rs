@@ -228,11 +228,11 @@ impl Config {
 mod config_test {
     use super::*;
     use crate::data::{InputMode, Palette, PaletteColor, PluginTag};
-    use crate::input::layout::RunPluginLocation;
+    use crate::input::layout::{PluginUserConfiguration, RunPlugin, RunPluginLocation};
     use crate::input::options::{Clipboard, OnForceClose};
     use crate::input::plugins::{PluginConfig, PluginType, PluginsConfig};
     use crate::input::theme::{FrameConfig, Theme, Themes, UiConfig};
-    use std::collections::HashMap;
+    use std::collections::{BTreeMap, HashMap};
     use std::io::Write;
     use tempfile::tempdir;
 
@@ -315,6 +315,7 @@ mod config_test {
             scrollback_editor "/path/to/my/scrollback-editor"
             session_name "my awesome session"
             attach_to_session true
+            hint_patterns r"\b[0-9a-f]{7,40}\b" "[A-Z]+-\\d+"
         "#;
         let config = Config::from_kdl(config_contents, None).unwrap();
         assert_eq!(
@@ -412,6 +413,14 @@ mod config_test {
             Some(true),
             "Option set in config"
         );
+        assert_eq!(
+            config.options.hint_patterns,
+            Some(vec![
+                String::from(r"\b[0-9a-f]{7,40}\b"),
+                String::from(r"[A-Z]+-\d+"),
+            ]),
+            "Option set in config"
+        );
     }
 
     #[test]
@@ -600,6 +609,7 @@ mod config_test {
                 run: PluginType::Pane(None),
                 location: RunPluginLocation::Zellij(PluginTag::new("tab-bar")),
                 _allow_exec_host_cmd: false,
+                userspace_configuration: Default::default(),
             },
         );
         expected_plugin_configuration.insert(
@@ -609,6 +619,7 @@ mod config_test {
                 run: PluginType::Pane(None),
                 location: RunPluginLocation::Zellij(PluginTag::new("status-bar")),
                 _allow_exec_host_cmd: false,
+                userspace_configuration: Default::default(),
             },
         );
         expected_plugin_configuration.insert(
@@ -618,6 +629,7 @@ mod config_test {
                 run: PluginType::Pane(None),
                 location: RunPluginLocation::Zellij(PluginTag::new("strider")),
                 _allow_exec_host_cmd: true,
+                userspace_configuration: Default::default(),
             },
         );
         expected_plugin_configuration.insert(
@@ -627,6 +639,7 @@ mod config_test {
                 run: PluginType::Pane(None),
                 location: RunPluginLocation::Zellij(PluginTag::new("compact-bar")),
                 _allow_exec_host_cmd: false,
+                userspace_configuration: Default::default(),
             },
         );
         assert_eq!(
@@ -636,6 +649,39 @@ mod config_test {
         );
     }
 
+    #[test]
+    fn can_define_plugin_user_configuration_in_configfile() {
+        let config_contents = r#"
+            plugins {
+                strider {
+                    path "strider"
+                    start_path "/tmp"
+                    hide_hidden_files true
+                }
+            }
+        "#;
+        let config = Config::from_kdl(config_contents, None).unwrap();
+        let mut configuration_from_layout = BTreeMap::new();
+        configuration_from_layout.insert("start_path".to_owned(), "/home".to_owned());
+        let run_plugin = RunPlugin {
+            _allow_exec_host_cmd: false,
+            location: RunPluginLocation::Zellij(PluginTag::new("strider")),
+            configuration: PluginUserConfiguration::new(configuration_from_layout),
+        };
+        let mut expected_configuration = BTreeMap::new();
+        expected_configuration.insert("start_path".to_owned(), "/home".to_owned());
+        expected_configuration.insert("hide_hidden_files".to_owned(), "true".to_owned());
+        assert_eq!(
+            config
+                .plugins
+                .get(run_plugin)
+                .unwrap()
+                .userspace_configuration,
+            PluginUserConfiguration::new(expected_configuration),
+            "configuration from the layout overrides the one from the config"
+        );
+    }
+
     #[test]
     fn can_define_ui_configuration_in_configfile() {
         let config_contents = r#"
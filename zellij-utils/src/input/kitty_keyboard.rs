@@ -0,0 +1,219 @@
+//! Encodes keys for the applications running in panes, either in the legacy (xterm) encoding or
+//! according to the progressive enhancements of the kitty keyboard protocol they requested.
+//! See: https://sw.kovidgoyal.net/kitty/keyboard-protocol/
+use crate::data::{BareKey, Key, KeyEventType, KeyModifiers};
+
+pub const DISAMBIGUATE_ESCAPE_CODES: u8 = 0b1;
+pub const REPORT_EVENT_TYPES: u8 = 0b10;
+pub const REPORT_ALTERNATE_KEYS: u8 = 0b100;
+pub const REPORT_ALL_KEYS_AS_ESCAPE_CODES: u8 = 0b1000;
+pub const REPORT_ASSOCIATED_TEXT: u8 = 0b10000;
+
+// the enhancements we know how to encode, others are ignored if requested
+pub const SUPPORTED_FLAGS: u8 =
+    DISAMBIGUATE_ESCAPE_CODES | REPORT_EVENT_TYPES | REPORT_ALL_KEYS_AS_ESCAPE_CODES;
+
+const ESC: &str = "\u{1b}";
+
+/// Encodes `key` for an application that requested the kitty keyboard protocol enhancements in
+/// `flags`, falling back to the legacy encoding if it requested none of the ones we support.
+///
+/// Returns `None` for events the application did not ask for (eg. key releases).
+pub fn encode_key(key: &Key, event_type: KeyEventType, flags: u8) -> Option<Vec<u8>> {
+    let flags = flags & SUPPORTED_FLAGS;
+    if flags == 0 {
+        legacy_encoding(key, event_type)
+    } else {
+        kitty_encoding(key, event_type, flags)
+    }
+}
+
+pub fn legacy_encoding(key: &Key, event_type: KeyEventType) -> Option<Vec<u8>> {
+    if event_type == KeyEventType::Release {
+        return None;
+    }
+    let (bare_key, modifiers) = key.bare_key_and_modifiers();
+    let alt_prefix = if modifiers.alt { ESC } else { "" };
+    let encoded = match bare_key {
+        BareKey::Char(c) if modifiers.ctrl => match ctrl_byte(c) {
+            Some(byte) => format!("{}{}", alt_prefix, byte as char),
+            None => format!("{}{}", alt_prefix, c),
+        },
+        BareKey::Char(c) if modifiers.shift => format!("{}{}", alt_prefix, c.to_ascii_uppercase()),
+        BareKey::Char(c) => format!("{}{}", alt_prefix, c),
+        BareKey::Enter => format!("{}\r", alt_prefix),
+        BareKey::Tab if modifiers.shift => format!("{}[Z", ESC),
+        BareKey::Tab => format!("{}\t", alt_prefix),
+        BareKey::Backspace if modifiers.ctrl => format!("{}\u{8}", alt_prefix),
+        BareKey::Backspace => format!("{}\u{7f}", alt_prefix),
+        BareKey::Esc => format!("{}{}", alt_prefix, ESC),
+        _ => {
+            let modifier_parameter = modifier_parameter(&modifiers);
+            return Some(functional_key(bare_key, modifier_parameter, None, false)?.into_bytes());
+        },
+    };
+    Some(encoded.into_bytes())
+}
+
+fn kitty_encoding(key: &Key, event_type: KeyEventType, flags: u8) -> Option<Vec<u8>> {
+    let report_event_types = flags & REPORT_EVENT_TYPES != 0;
+    let report_all_keys = flags & REPORT_ALL_KEYS_AS_ESCAPE_CODES != 0;
+    if event_type == KeyEventType::Release && !report_event_types {
+        return None;
+    }
+    let (bare_key, mut modifiers) = key.bare_key_and_modifiers();
+    let bare_key = match bare_key {
+        // the protocol always reports the unshifted key
+        BareKey::Char(c) if c.is_ascii_uppercase() => {
+            modifiers.shift = true;
+            BareKey::Char(c.to_ascii_lowercase())
+        },
+        bare_key => bare_key,
+    };
+    let event_type = if report_event_types {
+        Some(event_type)
+    } else {
+        None
+    };
+    let is_press = event_type != Some(KeyEventType::Release);
+    let only_shift = !modifiers.alt && !modifiers.ctrl && !modifiers.super_key;
+    let modifier_parameter = modifier_parameter(&modifiers);
+    let encoded = match bare_key {
+        BareKey::Char(c) => {
+            if !report_all_keys && only_shift && is_press {
+                // keys that produce text are still sent as text
+                if modifiers.shift {
+                    c.to_ascii_uppercase().to_string()
+                } else {
+                    c.to_string()
+                }
+            } else {
+                csi_u(c as u32, modifier_parameter, event_type)
+            }
+        },
+        BareKey::Enter | BareKey::Tab | BareKey::Backspace
+            if !report_all_keys && modifiers.is_empty() && is_press =>
+        {
+            // these are sent as in the legacy encoding so that eg. `reset` can still be typed if
+            // an application exits without resetting the protocol
+            return legacy_encoding(key, KeyEventType::Press);
+        },
+        BareKey::Enter => csi_u(13, modifier_parameter, event_type),
+        BareKey::Tab => csi_u(9, modifier_parameter, event_type),
+        BareKey::Backspace => csi_u(127, modifier_parameter, event_type),
+        BareKey::Esc => csi_u(27, modifier_parameter, event_type),
+        _ => functional_key(bare_key, modifier_parameter, event_type, true)?,
+    };
+    Some(encoded.into_bytes())
+}
+
+// the value of the modifiers parameter, 1 meaning no modifiers
+fn modifier_parameter(modifiers: &KeyModifiers) -> u8 {
+    let mut parameter = 1;
+    if modifiers.shift {
+        parameter += 1;
+    }
+    if modifiers.alt {
+        parameter += 2;
+    }
+    if modifiers.ctrl {
+        parameter += 4;
+    }
+    if modifiers.super_key {
+        parameter += 8;
+    }
+    parameter
+}
+
+fn event_type_suffix(event_type: Option<KeyEventType>) -> &'static str {
+    match event_type {
+        Some(KeyEventType::Repeat) => ":2",
+        Some(KeyEventType::Release) => ":3",
+        _ => "",
+    }
+}
+
+fn csi_u(code: u32, modifier_parameter: u8, event_type: Option<KeyEventType>) -> String {
+    let suffix = event_type_suffix(event_type);
+    if modifier_parameter == 1 && suffix.is_empty() {
+        format!("{}[{}u", ESC, code)
+    } else {
+        format!("{}[{};{}{}u", ESC, code, modifier_parameter, suffix)
+    }
+}
+
+// arrows, home/end, page up/down, insert/delete and the F keys, encoded either as
+// CSI 1;<modifiers> <letter> or CSI <number>;<modifiers> ~
+fn functional_key(
+    bare_key: BareKey,
+    modifier_parameter: u8,
+    event_type: Option<KeyEventType>,
+    is_kitty_protocol: bool,
+) -> Option<String> {
+    let letter = match bare_key {
+        BareKey::Up => Some('A'),
+        BareKey::Down => Some('B'),
+        BareKey::Right => Some('C'),
+        BareKey::Left => Some('D'),
+        BareKey::End => Some('F'),
+        BareKey::Home => Some('H'),
+        BareKey::F(1) => Some('P'),
+        BareKey::F(2) => Some('Q'),
+        BareKey::F(3) if !is_kitty_protocol => Some('R'),
+        BareKey::F(4) => Some('S'),
+        _ => None,
+    };
+    let number = match bare_key {
+        BareKey::Insert => Some(2),
+        BareKey::Delete => Some(3),
+        BareKey::PageUp => Some(5),
+        BareKey::PageDown => Some(6),
+        // CSI R is ambiguous with cursor position reports
+        BareKey::F(3) => Some(13),
+        BareKey::F(5) => Some(15),
+        BareKey::F(6) => Some(17),
+        BareKey::F(7) => Some(18),
+        BareKey::F(8) => Some(19),
+        BareKey::F(9) => Some(20),
+        BareKey::F(10) => Some(21),
+        BareKey::F(11) => Some(23),
+        BareKey::F(12) => Some(24),
+        _ => None,
+    };
+    let suffix = event_type_suffix(event_type);
+    let has_parameters = modifier_parameter != 1 || !suffix.is_empty();
+    match (letter, number) {
+        (Some(letter), _) if has_parameters => Some(format!(
+            "{}[1;{}{}{}",
+            ESC, modifier_parameter, suffix, letter
+        )),
+        // the legacy encoding uses SS3 for unmodified F1-F4
+        (Some(letter @ 'P'..='S'), _) if !is_kitty_protocol => Some(format!("{}O{}", ESC, letter)),
+        (Some(letter), _) => Some(format!("{}[{}", ESC, letter)),
+        (None, Some(number)) if has_parameters => Some(format!(
+            "{}[{};{}{}~",
+            ESC, number, modifier_parameter, suffix
+        )),
+        (None, Some(number)) => Some(format!("{}[{}~", ESC, number)),
+        (None, None) => None,
+    }
+}
+
+fn ctrl_byte(c: char) -> Option<u8> {
+    match c.to_ascii_lowercase() {
+        c @ 'a'..='z' => Some(c as u8 & 0x1f),
+        ' ' | '@' | '2' => Some(0),
+        '[' | '3' => Some(27),
+        '\\' | '4' => Some(28),
+        ']' | '5' => Some(29),
+        '^' | '6' => Some(30),
+        '_' | '7' | '/' => Some(31),
+        '?' | '8' => Some(127),
+        _ => None,
+    }
+}
+
+// The unit test location.
+#[cfg(test)]
+#[path = "./unit/kitty_keyboard_test.rs"]
+mod kitty_keyboard_test;
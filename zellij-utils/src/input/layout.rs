@@ -212,6 +212,30 @@ pub struct RunPlugin {
     #[serde(default)]
     pub _allow_exec_host_cmd: bool,
     pub location: RunPluginLocation,
+    #[serde(default)]
+    pub configuration: PluginUserConfiguration,
+}
+
+/// Arbitrary key/value configuration passed to a plugin on load, specified in layouts or in the
+/// `plugins` block of the config.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PluginUserConfiguration(BTreeMap<String, String>);
+
+impl PluginUserConfiguration {
+    pub fn new(configuration: BTreeMap<String, String>) -> Self {
+        PluginUserConfiguration(configuration)
+    }
+    pub fn inner(&self) -> &BTreeMap<String, String> {
+        &self.0
+    }
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+    /// Merges `other` into this configuration, values in `other` take precedence.
+    pub fn merge(&mut self, other: &PluginUserConfiguration) {
+        self.0
+            .extend(other.0.iter().map(|(k, v)| (k.clone(), v.clone())));
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
@@ -370,6 +394,11 @@ impl FloatingPaneLayout {
             },
         }
     }
+    pub fn add_start_suspended(&mut self, start_suspended: Option<bool>) {
+        if let Some(run) = self.run.as_mut() {
+            run.add_start_suspended(start_suspended);
+        }
+    }
 }
 
 impl From<&TiledPaneLayout> for FloatingPaneLayout {
@@ -535,6 +564,14 @@ impl TiledPaneLayout {
             child.add_cwd_to_layout(cwd);
         }
     }
+    pub fn add_start_suspended(&mut self, start_suspended: Option<bool>) {
+        if let Some(run) = self.run.as_mut() {
+            run.add_start_suspended(start_suspended);
+        }
+        for child in self.children.iter_mut() {
+            child.add_start_suspended(start_suspended);
+        }
+    }
     pub fn deepest_depth(&self) -> usize {
         let mut deepest_child_depth = 0;
         for child in self.children.iter() {
@@ -739,6 +776,34 @@ impl Layout {
             swap_layout_and_path,
         ))
     }
+    /// The names of the built-in layouts followed by those of the layouts in `layout_dir`, as
+    /// they can be given to `from_path_or_default`
+    pub fn available_layout_names(layout_dir: Option<&PathBuf>) -> Vec<String> {
+        let mut layout_names: Vec<String> = ["default", "compact", "strider", "disable-status-bar"]
+            .iter()
+            .map(|layout_name| layout_name.to_string())
+            .collect();
+        let mut layout_names_in_dir: Vec<String> = layout_dir
+            .and_then(|layout_dir| std::fs::read_dir(layout_dir).ok())
+            .map(|files| {
+                files
+                    .filter_map(|file| file.ok())
+                    .map(|file| file.path())
+                    .filter(|path| path.extension().map(|ext| ext == "kdl").unwrap_or(false))
+                    .filter_map(|path| path.file_stem()?.to_str().map(|stem| stem.to_owned()))
+                    .filter(|layout_name| !layout_name.ends_with(".swap"))
+                    .collect()
+            })
+            .unwrap_or_default();
+        layout_names_in_dir.sort();
+        for layout_name in layout_names_in_dir {
+            // a layout in the dir with the name of a built-in one replaces it
+            if !layout_names.contains(&layout_name) {
+                layout_names.push(layout_name);
+            }
+        }
+        layout_names
+    }
     pub fn stringified_from_default_assets(
         path: &Path,
     ) -> Result<(String, String, Option<(String, String)>), ConfigError> {
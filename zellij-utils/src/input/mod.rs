@@ -2,8 +2,11 @@ pub mod actions;
 pub mod command;
 pub mod config;
 pub mod keybinds;
+pub mod kitty_keyboard;
 pub mod layout;
 pub mod options;
+#[cfg(not(target_family = "wasm"))]
+pub mod permission;
 pub mod plugins;
 pub mod theme;
 
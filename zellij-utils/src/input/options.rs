@@ -124,6 +124,37 @@ pub struct Options {
     #[clap(long, value_parser)]
     #[serde(default)]
     pub auto_layout: Option<bool>,
+
+    /// Whether to negotiate the kitty keyboard protocol with the terminal emulator, allowing keys
+    /// like Shift Enter or Ctrl Alt a to be bound and passed through to panes (true or false)
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub support_kitty_keyboard_protocol: Option<bool>,
+
+    /// Whether to send styled underlines (eg. undercurl) and underline colors to the terminal
+    /// emulator, disable this if it does not support them (true or false)
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub styled_underlines: Option<bool>,
+
+    /// Whether to compress scrollback lines beyond the scroll_buffer_size and spill them to a file
+    /// in the cache directory instead of discarding them (true or false)
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub scroll_buffer_spill_to_disk: Option<bool>,
+
+    /// Command used to open the links found in hint mode, the link is passed to it as the last
+    /// argument - end the command with -- if it accepts one (default is xdg-open, or open on
+    /// macOS)
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub link_opener: Option<String>,
+
+    /// Regular expressions matching the tokens (eg. git hashes or ip addresses) that hint mode
+    /// labels alongside links and file paths, these replace the default patterns
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub hint_patterns: Option<Vec<String>>,
 }
 
 #[derive(ArgEnum, Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
@@ -166,6 +197,13 @@ impl Options {
         let mouse_mode = other.mouse_mode.or(self.mouse_mode);
         let pane_frames = other.pane_frames.or(self.pane_frames);
         let auto_layout = other.auto_layout.or(self.auto_layout);
+        let support_kitty_keyboard_protocol = other
+            .support_kitty_keyboard_protocol
+            .or(self.support_kitty_keyboard_protocol);
+        let styled_underlines = other.styled_underlines.or(self.styled_underlines);
+        let scroll_buffer_spill_to_disk = other
+            .scroll_buffer_spill_to_disk
+            .or(self.scroll_buffer_spill_to_disk);
         let mirror_session = other.mirror_session.or(self.mirror_session);
         let simplified_ui = other.simplified_ui.or(self.simplified_ui);
         let default_mode = other.default_mode.or(self.default_mode);
@@ -178,6 +216,8 @@ impl Options {
         let on_force_close = other.on_force_close.or(self.on_force_close);
         let scroll_buffer_size = other.scroll_buffer_size.or(self.scroll_buffer_size);
         let copy_command = other.copy_command.or_else(|| self.copy_command.clone());
+        let link_opener = other.link_opener.or_else(|| self.link_opener.clone());
+        let hint_patterns = other.hint_patterns.or_else(|| self.hint_patterns.clone());
         let copy_clipboard = other.copy_clipboard.or(self.copy_clipboard);
         let copy_on_select = other.copy_on_select.or(self.copy_on_select);
         let scrollback_editor = other
@@ -209,6 +249,11 @@ impl Options {
             session_name,
             attach_to_session,
             auto_layout,
+            support_kitty_keyboard_protocol,
+            styled_underlines,
+            scroll_buffer_spill_to_disk,
+            link_opener,
+            hint_patterns,
         }
     }
 
@@ -231,6 +276,15 @@ impl Options {
         let mouse_mode = merge_bool(other.mouse_mode, self.mouse_mode);
         let pane_frames = merge_bool(other.pane_frames, self.pane_frames);
         let auto_layout = merge_bool(other.auto_layout, self.auto_layout);
+        let support_kitty_keyboard_protocol = merge_bool(
+            other.support_kitty_keyboard_protocol,
+            self.support_kitty_keyboard_protocol,
+        );
+        let styled_underlines = merge_bool(other.styled_underlines, self.styled_underlines);
+        let scroll_buffer_spill_to_disk = merge_bool(
+            other.scroll_buffer_spill_to_disk,
+            self.scroll_buffer_spill_to_disk,
+        );
         let mirror_session = merge_bool(other.mirror_session, self.mirror_session);
 
         let default_mode = other.default_mode.or(self.default_mode);
@@ -243,6 +297,8 @@ impl Options {
         let on_force_close = other.on_force_close.or(self.on_force_close);
         let scroll_buffer_size = other.scroll_buffer_size.or(self.scroll_buffer_size);
         let copy_command = other.copy_command.or_else(|| self.copy_command.clone());
+        let link_opener = other.link_opener.or_else(|| self.link_opener.clone());
+        let hint_patterns = other.hint_patterns.or_else(|| self.hint_patterns.clone());
         let copy_clipboard = other.copy_clipboard.or(self.copy_clipboard);
         let copy_on_select = other.copy_on_select.or(self.copy_on_select);
         let scrollback_editor = other
@@ -274,6 +330,11 @@ impl Options {
             session_name,
             attach_to_session,
             auto_layout,
+            support_kitty_keyboard_protocol,
+            styled_underlines,
+            scroll_buffer_spill_to_disk,
+            link_opener,
+            hint_patterns,
         }
     }
 
@@ -333,6 +394,11 @@ impl From<CliOptions> for Options {
             session_name: opts.session_name,
             attach_to_session: opts.attach_to_session,
             auto_layout: opts.auto_layout,
+            support_kitty_keyboard_protocol: opts.support_kitty_keyboard_protocol,
+            styled_underlines: opts.styled_underlines,
+            scroll_buffer_spill_to_disk: opts.scroll_buffer_spill_to_disk,
+            link_opener: opts.link_opener,
+            hint_patterns: opts.hint_patterns,
             ..Default::default()
         }
     }
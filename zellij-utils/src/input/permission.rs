@@ -0,0 +1,95 @@
+//! Permissions the user granted to plugins, cached on disk per plugin url.
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt, fs,
+    path::PathBuf,
+    str::FromStr,
+};
+
+use kdl::KdlDocument;
+
+use crate::{data::PermissionType, errors::prelude::*};
+
+pub type GrantedPermissions = BTreeMap<String, BTreeSet<PermissionType>>; // plugin url -> permissions
+
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct PermissionCache {
+    path: PathBuf,
+    granted: GrantedPermissions,
+}
+
+impl PermissionCache {
+    /// Loads the cache from `path`, starting empty if it does not exist or cannot be parsed.
+    pub fn from_path(path: PathBuf) -> Self {
+        let granted = match fs::read_to_string(&path) {
+            Ok(raw_cache) => PermissionCache::from_string(&raw_cache).unwrap_or_else(|e| {
+                log::error!(
+                    "Failed to parse plugin permission cache {:?}: {:?}",
+                    path,
+                    e
+                );
+                GrantedPermissions::new()
+            }),
+            Err(_) => GrantedPermissions::new(),
+        };
+        PermissionCache { path, granted }
+    }
+    pub fn get_permissions(&self, plugin_url: &str) -> Option<&BTreeSet<PermissionType>> {
+        self.granted.get(plugin_url)
+    }
+    /// Adds `permissions` to the ones granted to the plugin at `plugin_url` and writes the cache
+    /// to disk.
+    pub fn cache(
+        &mut self,
+        plugin_url: String,
+        permissions: impl IntoIterator<Item = PermissionType>,
+    ) -> Result<()> {
+        let err_context = || format!("failed to cache permissions for plugin {plugin_url}");
+        self.granted
+            .entry(plugin_url.clone())
+            .or_default()
+            .extend(permissions);
+        if let Some(parent_dir) = self.path.parent() {
+            fs::create_dir_all(parent_dir).with_context(err_context)?;
+        }
+        fs::write(&self.path, self.to_string()).with_context(err_context)
+    }
+    pub fn from_string(raw_cache: &str) -> Result<GrantedPermissions> {
+        let err_context = || "failed to parse plugin permission cache";
+        let kdl_document: KdlDocument = raw_cache
+            .parse()
+            .map_err(|e| anyhow!("{:?}", e))
+            .with_context(err_context)?;
+        let mut granted = GrantedPermissions::new();
+        for plugin_node in kdl_document.nodes() {
+            let plugin_url = plugin_node.name().value().to_owned();
+            let permissions = granted.entry(plugin_url).or_default();
+            if let Some(children) = plugin_node.children() {
+                for permission_node in children.nodes() {
+                    let permission = PermissionType::from_str(permission_node.name().value())
+                        .map_err(|e| anyhow!("{}: {}", e, permission_node.name().value()))
+                        .with_context(err_context)?;
+                    permissions.insert(permission);
+                }
+            }
+        }
+        Ok(granted)
+    }
+}
+
+impl fmt::Display for PermissionCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (plugin_url, permissions) in &self.granted {
+            writeln!(f, "{:?} {{", plugin_url)?;
+            for permission in permissions {
+                writeln!(f, "    {}", permission.to_string())?;
+            }
+            writeln!(f, "}}")?;
+        }
+        Ok(())
+    }
+}
+
+#[path = "./unit/permission_test.rs"]
+#[cfg(test)]
+mod permission_test;
@@ -8,7 +8,7 @@ use thiserror::Error;
 use serde::{Deserialize, Serialize};
 use url::Url;
 
-use super::layout::{RunPlugin, RunPluginLocation};
+use super::layout::{PluginUserConfiguration, RunPlugin, RunPluginLocation};
 #[cfg(not(target_family = "wasm"))]
 use crate::consts::ASSET_MAP;
 pub use crate::data::PluginTag;
@@ -48,10 +48,16 @@ impl PluginsConfig {
                 run: PluginType::Pane(None),
                 _allow_exec_host_cmd: run._allow_exec_host_cmd,
                 location: run.location.clone(),
+                userspace_configuration: run.configuration.clone(),
             }),
-            RunPluginLocation::Zellij(tag) => self.0.get(tag).cloned().map(|plugin| PluginConfig {
-                _allow_exec_host_cmd: run._allow_exec_host_cmd,
-                ..plugin
+            RunPluginLocation::Zellij(tag) => self.0.get(tag).cloned().map(|plugin| {
+                let mut userspace_configuration = plugin.userspace_configuration.clone();
+                userspace_configuration.merge(&run.configuration);
+                PluginConfig {
+                    _allow_exec_host_cmd: run._allow_exec_host_cmd,
+                    userspace_configuration,
+                    ..plugin
+                }
             }),
         }
     }
@@ -86,6 +92,9 @@ pub struct PluginConfig {
     pub _allow_exec_host_cmd: bool,
     /// Original location of the
     pub location: RunPluginLocation,
+    /// Custom configuration for this plugin
+    #[serde(default)]
+    pub userspace_configuration: PluginUserConfiguration,
 }
 
 impl PluginConfig {
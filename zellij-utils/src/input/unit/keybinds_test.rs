@@ -1,6 +1,6 @@
 use super::super::actions::*;
 use super::super::keybinds::*;
-use crate::data::{self, CharOrArrow, Direction, Key};
+use crate::data::{self, BareKey, CharOrArrow, Direction, Key, KeyModifiers};
 use crate::input::config::Config;
 use insta::assert_snapshot;
 use strum::IntoEnumIterator;
@@ -516,6 +516,178 @@ fn keybindings_unbinds_happen_after_binds() {
     assert_eq!(z_in_pane_mode, None, "Key was ultimately unbound");
 }
 
+#[test]
+fn can_define_keybindings_with_kitty_keyboard_protocol_modifiers() {
+    let config_contents = r#"
+        keybinds {
+            normal {
+                bind "Shift Enter" { SwitchToMode "Locked"; }
+                bind "Ctrl Alt a" { SwitchToMode "Pane"; }
+                bind "Super t" { SwitchToMode "Tab"; }
+                bind "Ctrl Shift Left" { SwitchToMode "Resize"; }
+            }
+        }
+    "#;
+    let config = Config::from_kdl(config_contents, None).unwrap();
+    let actions_for_key = |bare_key: BareKey, modifiers: KeyModifiers| {
+        config
+            .keybinds
+            .get_actions_for_key_in_mode(
+                &InputMode::Normal,
+                &Key::from_bare_key_and_modifiers(bare_key, modifiers),
+            )
+            .cloned()
+    };
+    assert_eq!(
+        actions_for_key(
+            BareKey::Enter,
+            KeyModifiers {
+                shift: true,
+                ..Default::default()
+            }
+        ),
+        Some(vec![Action::SwitchToMode(InputMode::Locked)]),
+        "Shift Enter bound"
+    );
+    assert_eq!(
+        actions_for_key(
+            BareKey::Char('a'),
+            KeyModifiers {
+                ctrl: true,
+                alt: true,
+                ..Default::default()
+            }
+        ),
+        Some(vec![Action::SwitchToMode(InputMode::Pane)]),
+        "Ctrl Alt a bound"
+    );
+    assert_eq!(
+        actions_for_key(
+            BareKey::Char('t'),
+            KeyModifiers {
+                super_key: true,
+                ..Default::default()
+            }
+        ),
+        Some(vec![Action::SwitchToMode(InputMode::Tab)]),
+        "Super t bound"
+    );
+    assert_eq!(
+        actions_for_key(
+            BareKey::Left,
+            KeyModifiers {
+                ctrl: true,
+                shift: true,
+                ..Default::default()
+            }
+        ),
+        Some(vec![Action::SwitchToMode(InputMode::Resize)]),
+        "Ctrl Shift Left bound"
+    );
+    assert_eq!(
+        actions_for_key(BareKey::Enter, KeyModifiers::default()),
+        None,
+        "Enter without modifiers not bound"
+    );
+}
+
+#[test]
+fn legacy_keys_are_preferred_for_kitty_keyboard_protocol_modifiers() {
+    let ctrl = KeyModifiers {
+        ctrl: true,
+        ..Default::default()
+    };
+    let alt = KeyModifiers {
+        alt: true,
+        ..Default::default()
+    };
+    let shift = KeyModifiers {
+        shift: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        Key::from_bare_key_and_modifiers(BareKey::Char('g'), ctrl),
+        Key::Ctrl('g')
+    );
+    assert_eq!(
+        Key::from_bare_key_and_modifiers(BareKey::Char('h'), alt),
+        Key::Alt(CharOrArrow::Char('h'))
+    );
+    assert_eq!(
+        Key::from_bare_key_and_modifiers(BareKey::Left, alt),
+        Key::Alt(CharOrArrow::Direction(Direction::Left))
+    );
+    assert_eq!(
+        Key::from_bare_key_and_modifiers(BareKey::Char('a'), shift),
+        Key::Char('A')
+    );
+    assert_eq!(
+        Key::from_bare_key_and_modifiers(BareKey::Enter, KeyModifiers::default()),
+        Key::Char('\n')
+    );
+    assert_eq!(
+        Key::from_bare_key_and_modifiers(BareKey::Enter, shift),
+        Key::Modified(BareKey::Enter, shift)
+    );
+    assert_eq!(
+        Key::Modified(BareKey::Enter, shift).bare_key_and_modifiers(),
+        (BareKey::Enter, shift)
+    );
+    assert_eq!(
+        Key::Ctrl('g').bare_key_and_modifiers(),
+        (BareKey::Char('g'), ctrl)
+    );
+    assert_eq!(
+        Key::from_bare_key_and_modifiers(BareKey::Tab, KeyModifiers::default()),
+        Key::Char('\t')
+    );
+    assert_eq!(
+        Key::Char('\t').bare_key_and_modifiers(),
+        (BareKey::Tab, KeyModifiers::default())
+    );
+    assert_eq!(
+        Key::from_bare_key_and_modifiers(BareKey::Tab, shift),
+        Key::BackTab
+    );
+    assert_eq!(Key::BackTab.bare_key_and_modifiers(), (BareKey::Tab, shift));
+}
+
+#[test]
+fn can_define_keybindings_that_switch_session() {
+    let config_contents = r#"
+        keybinds {
+            session {
+                bind "a" { SwitchSession "api"; }
+                bind "b" { SwitchSession "backend" { tab_position 2; pane_id 7; }; }
+            }
+        }
+    "#;
+    let config = Config::from_kdl(config_contents, None).unwrap();
+    let a_session_mode_action = config
+        .keybinds
+        .get_actions_for_key_in_mode(&InputMode::Session, &Key::Char('a'));
+    let b_session_mode_action = config
+        .keybinds
+        .get_actions_for_key_in_mode(&InputMode::Session, &Key::Char('b'));
+    assert_eq!(
+        a_session_mode_action,
+        Some(&vec![Action::SwitchSession(data::ConnectToSession::new(
+            "api"
+        ))]),
+        "Session switch without focus defined in config"
+    );
+    assert_eq!(
+        b_session_mode_action,
+        Some(&vec![Action::SwitchSession(data::ConnectToSession {
+            name: "backend".to_owned(),
+            tab_position: Some(2),
+            pane_id: Some(data::PaneId::Terminal(7)),
+            layout: None,
+        })]),
+        "Session switch with focus defined in config"
+    );
+}
+
 #[test]
 fn error_received_on_unknown_input_mode() {
     let config_contents = r#"
@@ -0,0 +1,165 @@
+use super::*;
+use crate::data::{CharOrArrow, Direction};
+
+fn encoded(key: Key, event_type: KeyEventType, flags: u8) -> Option<String> {
+    encode_key(&key, event_type, flags).map(|bytes| String::from_utf8(bytes).unwrap())
+}
+
+fn with_modifiers(bare_key: BareKey, shift: bool, alt: bool, ctrl: bool) -> Key {
+    Key::from_bare_key_and_modifiers(
+        bare_key,
+        KeyModifiers {
+            shift,
+            alt,
+            ctrl,
+            super_key: false,
+        },
+    )
+}
+
+#[test]
+fn legacy_encoding_of_keys() {
+    let press = KeyEventType::Press;
+    assert_eq!(encoded(Key::Char('a'), press, 0), Some("a".to_owned()));
+    assert_eq!(encoded(Key::Ctrl('a'), press, 0), Some("\u{1}".to_owned()));
+    assert_eq!(
+        encoded(Key::Alt(CharOrArrow::Char('a')), press, 0),
+        Some("\u{1b}a".to_owned())
+    );
+    assert_eq!(
+        encoded(
+            with_modifiers(BareKey::Char('a'), false, true, true),
+            press,
+            0
+        ),
+        Some("\u{1b}\u{1}".to_owned()),
+        "Ctrl Alt a"
+    );
+    assert_eq!(encoded(Key::Char('\n'), press, 0), Some("\r".to_owned()));
+    assert_eq!(
+        encoded(with_modifiers(BareKey::Enter, true, false, false), press, 0),
+        Some("\r".to_owned()),
+        "Shift Enter has no legacy encoding of its own"
+    );
+    assert_eq!(encoded(Key::Char('\t'), press, 0), Some("\t".to_owned()));
+    assert_eq!(encoded(Key::BackTab, press, 0), Some("\u{1b}[Z".to_owned()));
+    assert_eq!(encoded(Key::Esc, press, 0), Some("\u{1b}".to_owned()));
+    assert_eq!(encoded(Key::Left, press, 0), Some("\u{1b}[D".to_owned()));
+    assert_eq!(
+        encoded(Key::Alt(CharOrArrow::Direction(Direction::Left)), press, 0),
+        Some("\u{1b}[1;3D".to_owned())
+    );
+    assert_eq!(encoded(Key::F(1), press, 0), Some("\u{1b}OP".to_owned()));
+    assert_eq!(encoded(Key::F(5), press, 0), Some("\u{1b}[15~".to_owned()));
+    assert_eq!(
+        encoded(
+            with_modifiers(BareKey::PageUp, false, false, true),
+            press,
+            0
+        ),
+        Some("\u{1b}[5;5~".to_owned())
+    );
+    assert_eq!(encoded(Key::Char('a'), KeyEventType::Release, 0), None);
+}
+
+#[test]
+fn kitty_encoding_with_disambiguated_escape_codes() {
+    let press = KeyEventType::Press;
+    let flags = DISAMBIGUATE_ESCAPE_CODES;
+    assert_eq!(encoded(Key::Char('a'), press, flags), Some("a".to_owned()));
+    assert_eq!(encoded(Key::Char('A'), press, flags), Some("A".to_owned()));
+    assert_eq!(
+        encoded(Key::Char('\t'), press, flags),
+        Some("\t".to_owned())
+    );
+    assert_eq!(
+        encoded(Key::BackTab, press, flags),
+        Some("\u{1b}[9;2u".to_owned())
+    );
+    assert_eq!(
+        encoded(Key::Ctrl('a'), press, flags),
+        Some("\u{1b}[97;5u".to_owned())
+    );
+    assert_eq!(
+        encoded(
+            with_modifiers(BareKey::Char('a'), false, true, true),
+            press,
+            flags
+        ),
+        Some("\u{1b}[97;7u".to_owned())
+    );
+    assert_eq!(
+        encoded(Key::Esc, press, flags),
+        Some("\u{1b}[27u".to_owned())
+    );
+    assert_eq!(
+        encoded(Key::Char('\n'), press, flags),
+        Some("\r".to_owned())
+    );
+    assert_eq!(
+        encoded(
+            with_modifiers(BareKey::Enter, true, false, false),
+            press,
+            flags
+        ),
+        Some("\u{1b}[13;2u".to_owned())
+    );
+    assert_eq!(
+        encoded(Key::Left, press, flags),
+        Some("\u{1b}[D".to_owned())
+    );
+    assert_eq!(
+        encoded(
+            with_modifiers(BareKey::Left, false, false, true),
+            press,
+            flags
+        ),
+        Some("\u{1b}[1;5D".to_owned())
+    );
+    assert_eq!(
+        encoded(Key::F(3), press, flags),
+        Some("\u{1b}[13~".to_owned())
+    );
+    assert_eq!(
+        encoded(Key::Char('a'), KeyEventType::Release, flags),
+        None,
+        "releases are only reported when requested"
+    );
+}
+
+#[test]
+fn kitty_encoding_with_event_types_and_all_keys_as_escape_codes() {
+    let flags = DISAMBIGUATE_ESCAPE_CODES | REPORT_EVENT_TYPES | REPORT_ALL_KEYS_AS_ESCAPE_CODES;
+    assert_eq!(
+        encoded(Key::Char('a'), KeyEventType::Press, flags),
+        Some("\u{1b}[97u".to_owned())
+    );
+    assert_eq!(
+        encoded(Key::Char('A'), KeyEventType::Press, flags),
+        Some("\u{1b}[97;2u".to_owned())
+    );
+    assert_eq!(
+        encoded(Key::Char('a'), KeyEventType::Repeat, flags),
+        Some("\u{1b}[97;1:2u".to_owned())
+    );
+    assert_eq!(
+        encoded(Key::Char('\n'), KeyEventType::Release, flags),
+        Some("\u{1b}[13;1:3u".to_owned())
+    );
+    assert_eq!(
+        encoded(Key::Up, KeyEventType::Release, flags),
+        Some("\u{1b}[1;1:3A".to_owned())
+    );
+    assert_eq!(
+        encoded(Key::Delete, KeyEventType::Repeat, flags),
+        Some("\u{1b}[3;1:2~".to_owned())
+    );
+}
+
+#[test]
+fn unsupported_kitty_enhancements_fall_back_to_legacy_encoding() {
+    assert_eq!(
+        encoded(Key::Ctrl('a'), KeyEventType::Press, REPORT_ASSOCIATED_TEXT),
+        Some("\u{1}".to_owned())
+    );
+}
@@ -1,5 +1,6 @@
 use super::super::layout::*;
 use insta::assert_snapshot;
+use std::collections::BTreeMap;
 
 #[test]
 fn empty_layout() {
@@ -494,6 +495,44 @@ fn layout_with_command_panes_and_start_suspended() {
     assert_snapshot!(format!("{:#?}", layout));
 }
 
+#[test]
+fn start_suspended_can_be_overridden_in_nested_command_panes() {
+    let kdl_layout = r#"
+        layout {
+            pane {
+                pane command="htop" {
+                    start_suspended true
+                }
+                pane command="tail"
+            }
+            floating_panes {
+                pane command="top" {
+                    start_suspended true
+                }
+            }
+        }
+    "#;
+    let mut layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let (tiled_panes, floating_panes) = layout.template.as_mut().unwrap();
+    tiled_panes.add_start_suspended(Some(false));
+    for floating_pane in floating_panes.iter_mut() {
+        floating_pane.add_start_suspended(Some(false));
+    }
+    let mut runs: Vec<&Run> = tiled_panes.children[0]
+        .children
+        .iter()
+        .filter_map(|pane| pane.run.as_ref())
+        .collect();
+    runs.extend(floating_panes.iter().filter_map(|pane| pane.run.as_ref()));
+    assert_eq!(runs.len(), 3);
+    for run in runs {
+        match run {
+            Run::Command(run_command) => assert!(!run_command.hold_on_start),
+            _ => panic!("expected a command pane"),
+        }
+    }
+}
+
 #[test]
 fn layout_with_plugin_panes() {
     let kdl_layout = r#"
@@ -515,6 +554,7 @@ fn layout_with_plugin_panes() {
                         run: Some(Run::Plugin(RunPlugin {
                             location: RunPluginLocation::Zellij(PluginTag::new("tab-bar")),
                             _allow_exec_host_cmd: false,
+                            configuration: Default::default(),
                         })),
                         ..Default::default()
                     },
@@ -524,6 +564,7 @@ fn layout_with_plugin_panes() {
                                 "/path/to/my/plugin.wasm",
                             )),
                             _allow_exec_host_cmd: false,
+                            configuration: Default::default(),
                         })),
                         ..Default::default()
                     },
@@ -537,6 +578,78 @@ fn layout_with_plugin_panes() {
     assert_eq!(layout, expected_layout);
 }
 
+#[test]
+fn layout_with_plugin_configuration() {
+    let kdl_layout = r#"
+        layout {
+            pane {
+                plugin location="zellij:strider" start_path="/tmp" {
+                    hide_hidden_files true
+                    max_depth 3
+                }
+            }
+            pane {
+                plugin location="zellij:strider" {
+                    start_path "/home"
+                }
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let mut first_configuration = BTreeMap::new();
+    first_configuration.insert("start_path".to_owned(), "/tmp".to_owned());
+    first_configuration.insert("hide_hidden_files".to_owned(), "true".to_owned());
+    first_configuration.insert("max_depth".to_owned(), "3".to_owned());
+    let mut second_configuration = BTreeMap::new();
+    second_configuration.insert("start_path".to_owned(), "/home".to_owned());
+    let expected_layout = Layout {
+        template: Some((
+            TiledPaneLayout {
+                children: vec![
+                    TiledPaneLayout {
+                        run: Some(Run::Plugin(RunPlugin {
+                            location: RunPluginLocation::Zellij(PluginTag::new("strider")),
+                            _allow_exec_host_cmd: false,
+                            configuration: PluginUserConfiguration::new(first_configuration),
+                        })),
+                        ..Default::default()
+                    },
+                    TiledPaneLayout {
+                        run: Some(Run::Plugin(RunPlugin {
+                            location: RunPluginLocation::Zellij(PluginTag::new("strider")),
+                            _allow_exec_host_cmd: false,
+                            configuration: PluginUserConfiguration::new(second_configuration),
+                        })),
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            },
+            vec![],
+        )),
+        ..Default::default()
+    };
+    assert_eq!(layout, expected_layout);
+}
+
+#[test]
+fn plugin_configuration_without_a_value_is_error() {
+    let kdl_layout = r#"
+        layout {
+            pane {
+                plugin location="zellij:strider" {
+                    start_path
+                }
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None);
+    assert!(
+        layout.is_err(),
+        "plugin configuration without a value is an error"
+    );
+}
+
 #[test]
 fn layout_with_borderless_panes() {
     let kdl_layout = r#"
@@ -2015,6 +2128,7 @@ fn run_plugin_location_parsing() {
                     TiledPaneLayout {
                         run: Some(Run::Plugin(RunPlugin {
                             _allow_exec_host_cmd: false,
+                            configuration: Default::default(),
                             location: RunPluginLocation::Zellij(PluginTag::new("tab-bar")),
                         })),
                         ..Default::default()
@@ -2022,6 +2136,7 @@ fn run_plugin_location_parsing() {
                     TiledPaneLayout {
                         run: Some(Run::Plugin(RunPlugin {
                             _allow_exec_host_cmd: false,
+                            configuration: Default::default(),
                             location: RunPluginLocation::File(PathBuf::from(
                                 "/path/to/my/plugin.wasm",
                             )),
@@ -2031,6 +2146,7 @@ fn run_plugin_location_parsing() {
                     TiledPaneLayout {
                         run: Some(Run::Plugin(RunPlugin {
                             _allow_exec_host_cmd: false,
+                            configuration: Default::default(),
                             location: RunPluginLocation::File(PathBuf::from("plugin.wasm")),
                         })),
                         ..Default::default()
@@ -2038,6 +2154,7 @@ fn run_plugin_location_parsing() {
                     TiledPaneLayout {
                         run: Some(Run::Plugin(RunPlugin {
                             _allow_exec_host_cmd: false,
+                            configuration: Default::default(),
                             location: RunPluginLocation::File(PathBuf::from(
                                 "relative/with space/plugin.wasm",
                             )),
@@ -2047,6 +2164,7 @@ fn run_plugin_location_parsing() {
                     TiledPaneLayout {
                         run: Some(Run::Plugin(RunPlugin {
                             _allow_exec_host_cmd: false,
+                            configuration: Default::default(),
                             location: RunPluginLocation::File(PathBuf::from(
                                 "/absolute/with space/plugin.wasm",
                             )),
@@ -2056,6 +2174,7 @@ fn run_plugin_location_parsing() {
                     TiledPaneLayout {
                         run: Some(Run::Plugin(RunPlugin {
                             _allow_exec_host_cmd: false,
+                            configuration: Default::default(),
                             location: RunPluginLocation::File(PathBuf::from(
                                 "c:/absolute/windows/plugin.wasm",
                             )),
@@ -2128,3 +2247,23 @@ fn env_var_missing() {
     let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None);
     assert!(layout.is_err(), "invalid env var lookup should fail");
 }
+
+#[test]
+fn available_layout_names_include_built_in_layouts_and_layouts_in_dir() {
+    let layout_dir = tempfile::tempdir().unwrap();
+    for file_name in ["work.kdl", "work.swap.kdl", "compact.kdl", "notes.txt"] {
+        std::fs::write(layout_dir.path().join(file_name), "layout").unwrap();
+    }
+    let layout_names = Layout::available_layout_names(Some(&layout_dir.path().to_path_buf()));
+    assert_eq!(
+        layout_names,
+        vec![
+            "default",
+            "compact",
+            "strider",
+            "disable-status-bar",
+            "work"
+        ],
+        "layouts in the dir are listed once after the built-in ones, without swap layouts"
+    );
+}
@@ -0,0 +1,61 @@
+use super::super::permission::*;
+use crate::data::PermissionType;
+use std::collections::BTreeSet;
+use tempfile::tempdir;
+
+#[test]
+fn granted_permissions_are_cached_to_disk() {
+    let cache_dir = tempdir().unwrap();
+    let cache_path = cache_dir.path().join("permissions.kdl");
+    let plugin_url = "file:/path/to/my/plugin.wasm".to_owned();
+    let mut permission_cache = PermissionCache::from_path(cache_path.clone());
+    permission_cache
+        .cache(
+            plugin_url.clone(),
+            vec![
+                PermissionType::ReadApplicationState,
+                PermissionType::RunCommands,
+            ],
+        )
+        .unwrap();
+    let permission_cache_from_disk = PermissionCache::from_path(cache_path);
+    assert_eq!(
+        permission_cache, permission_cache_from_disk,
+        "permission cache survived a round trip to disk"
+    );
+    assert_eq!(
+        permission_cache_from_disk.get_permissions(&plugin_url),
+        Some(&BTreeSet::from([
+            PermissionType::ReadApplicationState,
+            PermissionType::RunCommands,
+        ]))
+    );
+    assert_eq!(
+        permission_cache_from_disk.get_permissions("file:/path/to/another/plugin.wasm"),
+        None
+    );
+}
+
+#[test]
+fn caching_permissions_adds_to_previous_grants() {
+    let cache_dir = tempdir().unwrap();
+    let cache_path = cache_dir.path().join("permissions.kdl");
+    let plugin_url = "zellij:strider".to_owned();
+    let mut permission_cache = PermissionCache::from_path(cache_path);
+    permission_cache
+        .cache(plugin_url.clone(), vec![PermissionType::OpenFiles])
+        .unwrap();
+    permission_cache
+        .cache(plugin_url, vec![PermissionType::WriteToStdin])
+        .unwrap();
+    assert_eq!(
+        permission_cache.to_string(),
+        "\"zellij:strider\" {\n    OpenFiles\n    WriteToStdin\n}\n"
+    );
+}
+
+#[test]
+fn unparsable_permission_cache_is_err() {
+    let raw_cache = "\"file:/path/to/my/plugin.wasm\" {\n    FlyToTheMoon\n}\n";
+    assert!(PermissionCache::from_string(raw_cache).is_err());
+}
@@ -1,7 +1,7 @@
 //! IPC stuff for starting to split things into a client and server model.
 use crate::{
     cli::CliArgs,
-    data::{ClientId, InputMode, Style},
+    data::{ClientId, ConnectToSession, InputMode, PaneId, SessionInfo, Style},
     errors::{get_current_ctx, prelude::*, ErrorContext},
     input::keybinds::Keybinds,
     input::{actions::Action, layout::Layout, options::Options, plugins::PluginsConfig},
@@ -31,9 +31,9 @@ pub struct Session {
 }
 
 // How do we want to connect to a session?
-#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ClientType {
-    Reader,
+    Reader, // only watches the session, its actions (besides detaching) are rejected
     Writer,
 }
 
@@ -88,12 +88,19 @@ pub enum ClientToServerMsg {
         Box<Layout>,
         Option<PluginsConfig>,
     ),
-    AttachClient(ClientAttributes, Options),
+    AttachClient(
+        ClientAttributes,
+        Options,
+        ClientType,
+        Option<usize>,  // tab position to focus
+        Option<PaneId>, // pane to focus
+    ),
     Action(Action, Option<ClientId>),
     ClientExited,
     KillSession,
     ConnStatus,
     ListClients,
+    QuerySessionInfo,
 }
 
 // Types of messages sent from the server to the client
@@ -110,6 +117,9 @@ pub enum ServerToClientMsg {
     Connected,
     ActiveClients(Vec<ClientId>),
     Log(Vec<String>),
+    CliPipeOutput(String), // output a plugin wrote to the cli pipe
+    SwitchSession(ConnectToSession),
+    SessionInfo(SessionInfo),
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -2,9 +2,9 @@ use crate::input::{
     command::RunCommand,
     config::ConfigError,
     layout::{
-        FloatingPaneLayout, Layout, LayoutConstraint, PercentOrFixed, Run, RunPlugin,
-        RunPluginLocation, SplitDirection, SplitSize, SwapFloatingLayout, SwapTiledLayout,
-        TiledPaneLayout,
+        FloatingPaneLayout, Layout, LayoutConstraint, PercentOrFixed, PluginUserConfiguration, Run,
+        RunPlugin, RunPluginLocation, SplitDirection, SplitSize, SwapFloatingLayout,
+        SwapTiledLayout, TiledPaneLayout,
     },
 };
 
@@ -304,9 +304,11 @@ impl<'a> KdlLayoutParser<'a> {
                 url_node.span().len(),
             )
         })?;
+        let configuration = PluginUserConfiguration::from_kdl(plugin_block, &["location"])?;
         Ok(Some(Run::Plugin(RunPlugin {
             _allow_exec_host_cmd,
             location,
+            configuration,
         })))
     }
     fn parse_args(&self, pane_node: &KdlNode) -> Result<Option<Vec<String>>, ConfigError> {
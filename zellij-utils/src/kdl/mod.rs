@@ -1,25 +1,29 @@
 mod kdl_layout_parser;
-use crate::data::{Direction, InputMode, Key, Palette, PaletteColor, Resize};
+use crate::data::{
+    ConnectToSession, Direction, InputMode, Key, Palette, PaletteColor, PaneId, Resize,
+};
 use crate::envs::EnvironmentVariables;
 use crate::input::config::{Config, ConfigError, KdlError};
 use crate::input::keybinds::Keybinds;
-use crate::input::layout::{Layout, RunPlugin, RunPluginLocation};
+use crate::input::layout::{Layout, PluginUserConfiguration, RunPlugin, RunPluginLocation};
 use crate::input::options::{Clipboard, OnForceClose, Options};
 use crate::input::plugins::{PluginConfig, PluginTag, PluginType, PluginsConfig};
 use crate::input::theme::{FrameConfig, Theme, Themes, UiConfig};
 use crate::setup::{find_default_config_dir, get_layout_dir};
 use kdl_layout_parser::KdlLayoutParser;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use strum::IntoEnumIterator;
 
 use miette::NamedSource;
 
-use kdl::{KdlDocument, KdlEntry, KdlNode};
+use kdl::{KdlDocument, KdlEntry, KdlNode, KdlValue};
 
 use std::path::PathBuf;
 use std::str::FromStr;
 
-use crate::input::actions::{Action, SearchDirection, SearchOption};
+use crate::input::actions::{
+    Action, CopyModeMotion, CopyModeSelection, SearchDirection, SearchOption,
+};
 use crate::input::command::RunCommandAction;
 
 #[macro_export]
@@ -42,6 +46,9 @@ macro_rules! parse_kdl_action_arguments {
                 "ScrollDown" => Ok(Action::ScrollDown),
                 "ScrollToBottom" => Ok(Action::ScrollToBottom),
                 "ScrollToTop" => Ok(Action::ScrollToTop),
+                "ScrollToPreviousPrompt" => Ok(Action::ScrollToPreviousPrompt),
+                "ScrollToNextPrompt" => Ok(Action::ScrollToNextPrompt),
+                "SelectLastCommandOutput" => Ok(Action::SelectLastCommandOutput),
                 "PageScrollUp" => Ok(Action::PageScrollUp),
                 "PageScrollDown" => Ok(Action::PageScrollDown),
                 "HalfPageScrollUp" => Ok(Action::HalfPageScrollUp),
@@ -350,6 +357,16 @@ pub fn kdl_child_bool_value_for_entry<'a>(
         .and_then(|cwd_value| cwd_value.value().as_bool())
 }
 
+pub fn kdl_child_integer_value_for_entry<'a>(
+    command_metadata: &'a KdlDocument,
+    entry_name: &'a str,
+) -> Option<i64> {
+    command_metadata
+        .get(entry_name)
+        .and_then(|entry| entry.entries().iter().next())
+        .and_then(|entry_value| entry_value.value().as_i64())
+}
+
 impl Action {
     pub fn new_from_bytes(
         action_name: &str,
@@ -361,6 +378,7 @@ impl Action {
             "PaneNameInput" => Ok(Action::PaneNameInput(bytes)),
             "TabNameInput" => Ok(Action::TabNameInput(bytes)),
             "SearchInput" => Ok(Action::SearchInput(bytes)),
+            "HintInput" => Ok(Action::HintInput(bytes)),
             "GoToTab" => {
                 let tab_index = *bytes.get(0).ok_or_else(|| {
                     ConfigError::new_kdl_error(
@@ -454,7 +472,7 @@ impl Action {
                 }
             },
             "MovePaneBackwards" => Ok(Action::MovePaneBackwards),
-            "DumpScreen" => Ok(Action::DumpScreen(string, false)),
+            "DumpScreen" => Ok(Action::DumpScreen(string, false, false)),
             "NewPane" => {
                 if string.is_empty() {
                     return Ok(Action::NewPane(None, None));
@@ -490,6 +508,26 @@ impl Action {
                     })?;
                 Ok(Action::Search(search_direction))
             },
+            "CopyModeMove" => {
+                let motion = CopyModeMotion::from_str(string.as_str()).map_err(|_| {
+                    ConfigError::new_kdl_error(
+                        format!("Invalid copy mode motion: '{}'", string),
+                        action_node.span().offset(),
+                        action_node.span().len(),
+                    )
+                })?;
+                Ok(Action::CopyModeMove(motion))
+            },
+            "CopyModeSelect" => {
+                let selection = CopyModeSelection::from_str(string.as_str()).map_err(|_| {
+                    ConfigError::new_kdl_error(
+                        format!("Invalid copy mode selection: '{}'", string),
+                        action_node.span().offset(),
+                        action_node.span().len(),
+                    )
+                })?;
+                Ok(Action::CopyModeSelect(selection))
+            },
             _ => Err(ConfigError::new_kdl_error(
                 format!("Unsupported action: {}", action_name),
                 action_node.span().offset(),
@@ -642,6 +680,15 @@ impl TryFrom<(&KdlNode, &Options)> for Action {
             "ScrollToTop" => {
                 parse_kdl_action_arguments!(action_name, action_arguments, kdl_action)
             },
+            "ScrollToPreviousPrompt" => {
+                parse_kdl_action_arguments!(action_name, action_arguments, kdl_action)
+            },
+            "ScrollToNextPrompt" => {
+                parse_kdl_action_arguments!(action_name, action_arguments, kdl_action)
+            },
+            "SelectLastCommandOutput" => {
+                parse_kdl_action_arguments!(action_name, action_arguments, kdl_action)
+            },
             "PageScrollUp" => {
                 parse_kdl_action_arguments!(action_name, action_arguments, kdl_action)
             },
@@ -836,11 +883,24 @@ impl TryFrom<(&KdlNode, &Options)> for Action {
             "SearchInput" => {
                 parse_kdl_action_u8_arguments!(action_name, action_arguments, kdl_action)
             },
+            "HintInput" => {
+                parse_kdl_action_u8_arguments!(action_name, action_arguments, kdl_action)
+            },
             "SearchToggleOption" => parse_kdl_action_char_or_string_arguments!(
                 action_name,
                 action_arguments,
                 kdl_action
             ),
+            "CopyModeMove" => parse_kdl_action_char_or_string_arguments!(
+                action_name,
+                action_arguments,
+                kdl_action
+            ),
+            "CopyModeSelect" => parse_kdl_action_char_or_string_arguments!(
+                action_name,
+                action_arguments,
+                kdl_action
+            ),
             "Run" => {
                 let arguments = action_arguments.iter().copied();
                 let mut args = kdl_arguments_that_are_strings(arguments)?;
@@ -896,9 +956,35 @@ impl TryFrom<(&KdlNode, &Options)> for Action {
                 let run_plugin = RunPlugin {
                     location,
                     _allow_exec_host_cmd: false,
+                    configuration: PluginUserConfiguration::default(),
                 };
                 Ok(Action::LaunchOrFocusPlugin(run_plugin, should_float))
             },
+            "SwitchSession" => {
+                let arguments = action_arguments.iter().copied();
+                let mut args = kdl_arguments_that_are_strings(arguments)?;
+                if args.is_empty() {
+                    return Err(ConfigError::new_kdl_error(
+                        "No session name found in SwitchSession".into(),
+                        kdl_action.span().offset(),
+                        kdl_action.span().len(),
+                    ));
+                }
+                let name = args.remove(0);
+                let session_metadata = action_children.iter().next();
+                let tab_position = session_metadata
+                    .and_then(|s_m| kdl_child_integer_value_for_entry(s_m, "tab_position"))
+                    .map(|tab_position| tab_position as usize);
+                let pane_id = session_metadata
+                    .and_then(|s_m| kdl_child_integer_value_for_entry(s_m, "pane_id"))
+                    .map(|pane_id| PaneId::Terminal(pane_id as u32));
+                Ok(Action::SwitchSession(ConnectToSession {
+                    name,
+                    tab_position,
+                    pane_id,
+                    layout: None,
+                }))
+            },
             "PreviousSwapLayout" => Ok(Action::PreviousSwapLayout),
             "NextSwapLayout" => Ok(Action::NextSwapLayout),
             _ => Err(ConfigError::new_kdl_error(
@@ -1376,6 +1462,28 @@ impl Options {
         let attach_to_session =
             kdl_property_first_arg_as_bool_or_error!(kdl_options, "attach_to_session")
                 .map(|(v, _)| v);
+        let support_kitty_keyboard_protocol = kdl_property_first_arg_as_bool_or_error!(
+            kdl_options,
+            "support_kitty_keyboard_protocol"
+        )
+        .map(|(v, _)| v);
+        let styled_underlines =
+            kdl_property_first_arg_as_bool_or_error!(kdl_options, "styled_underlines")
+                .map(|(v, _)| v);
+        let scroll_buffer_spill_to_disk =
+            kdl_property_first_arg_as_bool_or_error!(kdl_options, "scroll_buffer_spill_to_disk")
+                .map(|(v, _)| v);
+        let link_opener = kdl_property_first_arg_as_string_or_error!(kdl_options, "link_opener")
+            .map(|(link_opener, _entry)| link_opener.to_string());
+        let hint_patterns = match kdl_options.get("hint_patterns") {
+            Some(hint_patterns) => Some(
+                kdl_string_arguments!(hint_patterns)
+                    .iter()
+                    .map(|hint_pattern| hint_pattern.to_string())
+                    .collect(),
+            ),
+            None => None,
+        };
         Ok(Options {
             simplified_ui,
             theme,
@@ -1397,6 +1505,11 @@ impl Options {
             session_name,
             attach_to_session,
             auto_layout,
+            support_kitty_keyboard_protocol,
+            styled_underlines,
+            scroll_buffer_spill_to_disk,
+            link_opener,
+            hint_patterns,
         })
     }
 }
@@ -1419,12 +1532,57 @@ impl RunPlugin {
                 kdl_node.span().len(),
             )
         })?;
+        let configuration = PluginUserConfiguration::from_kdl(kdl_node, &["location"])?;
         Ok(RunPlugin {
             _allow_exec_host_cmd,
             location,
+            configuration,
         })
     }
 }
+impl PluginUserConfiguration {
+    /// Collects the properties and child nodes of a plugin block into key/value pairs, skipping
+    /// the `reserved_keys` (eg. "location") as well as the "_allow_exec_host_cmd" flag.
+    pub fn from_kdl(plugin_block: &KdlNode, reserved_keys: &[&str]) -> Result<Self, ConfigError> {
+        let is_reserved =
+            |name: &str| name == "_allow_exec_host_cmd" || reserved_keys.contains(&name);
+        let mut configuration = BTreeMap::new();
+        for entry in plugin_block.entries() {
+            if let Some(name) = entry.name() {
+                let name = name.value();
+                if !is_reserved(name) {
+                    configuration.insert(name.to_owned(), kdl_value_to_string(entry.value()));
+                }
+            }
+        }
+        if let Some(children) = plugin_block.children() {
+            for config_node in children.nodes() {
+                let name = kdl_name!(config_node);
+                if is_reserved(name) {
+                    continue;
+                }
+                let value = config_node
+                    .entries()
+                    .iter()
+                    .next()
+                    .map(|entry| kdl_value_to_string(entry.value()))
+                    .ok_or(ConfigError::new_kdl_error(
+                        format!("Plugin configuration \"{}\" must have a value", name),
+                        config_node.span().offset(),
+                        config_node.span().len(),
+                    ))?;
+                configuration.insert(name.to_owned(), value);
+            }
+        }
+        Ok(PluginUserConfiguration::new(configuration))
+    }
+}
+fn kdl_value_to_string(value: &KdlValue) -> String {
+    match value.as_string() {
+        Some(string_value) => string_value.to_owned(),
+        None => value.to_string(),
+    }
+}
 impl Layout {
     pub fn from_kdl(
         raw_layout: &str,
@@ -1707,11 +1865,14 @@ impl PluginsConfig {
             let allow_exec_host_cmd =
                 kdl_children_property_first_arg_as_bool!(plugin_config, "_allow_exec_host_cmd")
                     .unwrap_or(false);
+            let userspace_configuration =
+                PluginUserConfiguration::from_kdl(plugin_config, &["path"])?;
             let plugin_config = PluginConfig {
                 path,
                 run: PluginType::Pane(None),
                 location: RunPluginLocation::Zellij(plugin_tag.clone()),
                 _allow_exec_host_cmd: allow_exec_host_cmd,
+                userspace_configuration,
             };
             plugins.insert(plugin_tag, plugin_config);
         }
@@ -7,6 +7,7 @@ pub mod input;
 pub mod kdl;
 pub mod pane_size;
 pub mod position;
+pub mod session_serialization;
 pub mod setup;
 pub mod shared;
 
@@ -17,9 +18,11 @@ pub mod channels; // Requires async_std
 pub mod ipc; // Requires interprocess
 #[cfg(not(target_family = "wasm"))]
 pub mod logging; // Requires log4rs
+#[cfg(not(target_family = "wasm"))]
+pub mod sessions; // Requires interprocess
 
 #[cfg(not(target_family = "wasm"))]
 pub use ::{
-    anyhow, async_channel, async_std, clap, interprocess, lazy_static, libc, miette, nix, notify,
-    regex, serde, signal_hook, tempfile, termwiz, vte,
+    anyhow, async_channel, async_std, clap, interprocess, isahc, lazy_static, libc, miette, nix,
+    notify, regex, serde, serde_json, signal_hook, strum, tempfile, termwiz, vte,
 };
@@ -0,0 +1,505 @@
+//! Serialization of a running session into a KDL layout.
+//
+//  The server periodically turns the state of each of its tabs into a manifest (the position
+//  and size of every pane, what runs in it and in which folder) and serializes it here into a
+//  layout file. If the server dies, the session can later be resurrected by loading this file as
+//  a regular layout.
+use crate::{
+    consts::VERSION,
+    input::{
+        command::RunCommand,
+        layout::{
+            FloatingPaneLayout, PercentOrFixed, Run, SplitDirection, SplitSize, TiledPaneLayout,
+        },
+    },
+    pane_size::PaneGeom,
+};
+use std::path::{Path, PathBuf};
+
+const INDENT: &str = "    ";
+
+#[derive(Default, Debug, Clone)]
+pub struct GlobalLayoutManifest {
+    pub default_shell: Option<PathBuf>,
+    pub tabs: Vec<(String, TabLayoutManifest)>,
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct TabLayoutManifest {
+    pub tiled_panes: Vec<PaneLayoutManifest>,
+    pub floating_panes: Vec<PaneLayoutManifest>, // geoms are relative to the tab's viewport
+    pub is_focused: bool,
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct PaneLayoutManifest {
+    pub geom: PaneGeom,
+    pub run: Option<Run>,
+    pub cwd: Option<PathBuf>,
+    pub is_borderless: bool,
+    pub title: Option<String>, // only set if the user explicitly named the pane
+    pub is_focused: bool,
+}
+
+/// The location of the serialized layout of the session named `session_name`.
+pub fn session_layout_file_path(data_dir: &Path, session_name: &str) -> PathBuf {
+    data_dir
+        .join("session_layouts")
+        .join(VERSION)
+        .join(format!("{}.kdl", session_name))
+}
+
+pub fn serialize_session_layout(
+    global_layout_manifest: GlobalLayoutManifest,
+) -> Result<String, &'static str> {
+    let mut kdl_string = String::from("layout {\n");
+    for (tab_name, tab_layout_manifest) in &global_layout_manifest.tabs {
+        let tiled_panes_layout = tiled_panes_layout_from_manifest(
+            &tab_layout_manifest.tiled_panes,
+            &global_layout_manifest.default_shell,
+        )?;
+        let floating_panes_layout = floating_panes_layout_from_manifest(
+            &tab_layout_manifest.floating_panes,
+            &global_layout_manifest.default_shell,
+        );
+        stringify_tab(
+            &mut kdl_string,
+            tab_name,
+            tab_layout_manifest.is_focused,
+            &tiled_panes_layout,
+            &floating_panes_layout,
+        );
+    }
+    kdl_string.push_str("}\n");
+    Ok(kdl_string)
+}
+
+/// Reconstruct the tree of splits that produced the given tiled panes from their geometries.
+pub fn tiled_panes_layout_from_manifest(
+    tiled_panes: &[PaneLayoutManifest],
+    default_shell: &Option<PathBuf>,
+) -> Result<TiledPaneLayout, &'static str> {
+    let total_cols = tiled_panes
+        .iter()
+        .map(|p| p.geom.x + p.geom.cols.as_usize())
+        .max()
+        .unwrap_or(0);
+    let total_rows = tiled_panes
+        .iter()
+        .map(|p| p.geom.y + p.geom.rows.as_usize())
+        .max()
+        .unwrap_or(0);
+    split_tiled_panes(tiled_panes, (total_cols, total_rows), default_shell)
+}
+
+pub fn floating_panes_layout_from_manifest(
+    floating_panes: &[PaneLayoutManifest],
+    default_shell: &Option<PathBuf>,
+) -> Vec<FloatingPaneLayout> {
+    floating_panes
+        .iter()
+        .map(|pane| FloatingPaneLayout {
+            name: pane.title.clone(),
+            height: Some(PercentOrFixed::Fixed(pane.geom.rows.as_usize())),
+            width: Some(PercentOrFixed::Fixed(pane.geom.cols.as_usize())),
+            x: Some(PercentOrFixed::Fixed(pane.geom.x)),
+            y: Some(PercentOrFixed::Fixed(pane.geom.y)),
+            run: run_from_manifest(pane, default_shell),
+            focus: if pane.is_focused { Some(true) } else { None },
+        })
+        .collect()
+}
+
+fn split_tiled_panes(
+    panes: &[PaneLayoutManifest],
+    total_size: (usize, usize), // (cols, rows)
+    default_shell: &Option<PathBuf>,
+) -> Result<TiledPaneLayout, &'static str> {
+    match panes {
+        [] => Err("Cannot serialize a tab without panes"),
+        [pane] => Ok(leaf_from_manifest(pane, default_shell)),
+        _ if is_a_single_stack(panes) => {
+            let mut stacked_panes: Vec<&PaneLayoutManifest> = panes.iter().collect();
+            stacked_panes.sort_by_key(|p| p.geom.y);
+            let children = stacked_panes
+                .iter()
+                .map(|pane| {
+                    let mut child = leaf_from_manifest(pane, default_shell);
+                    child.is_expanded_in_stack = pane.geom.rows.as_usize() > 1;
+                    child
+                })
+                .collect();
+            Ok(TiledPaneLayout {
+                children_split_direction: SplitDirection::Horizontal,
+                children,
+                children_are_stacked: true,
+                ..Default::default()
+            })
+        },
+        _ => {
+            for split_direction in [SplitDirection::Vertical, SplitDirection::Horizontal] {
+                let groups = split_into_groups(panes, split_direction);
+                if groups.len() < 2 {
+                    continue;
+                }
+                let total_space = match split_direction {
+                    SplitDirection::Vertical => total_size.0,
+                    SplitDirection::Horizontal => total_size.1,
+                };
+                let fixed_space: usize = groups
+                    .iter()
+                    .filter(|(_, group)| is_fixed_group(group, split_direction))
+                    .map(|(extent, _)| extent)
+                    .sum();
+                // percent sizes are relative to the total space minus that of the fixed panes
+                // in the same split (see layout::split_space)
+                let flexible_space = total_space.saturating_sub(fixed_space).max(1);
+                let mut children = vec![];
+                for (extent, group) in &groups {
+                    let mut child = split_tiled_panes(group, total_size, default_shell)?;
+                    child.split_size = if is_fixed_group(group, split_direction) {
+                        Some(SplitSize::Fixed(*extent))
+                    } else {
+                        let percent =
+                            (*extent as f64 / flexible_space as f64 * 100.0).round() as usize;
+                        Some(SplitSize::Percent(percent.max(1).min(100)))
+                    };
+                    children.push(child);
+                }
+                // the last flexible pane takes up whatever space is left, this way rounding
+                // errors do not accumulate
+                if let Some(last_flexible_child) = children
+                    .iter_mut()
+                    .rev()
+                    .find(|c| matches!(c.split_size, Some(SplitSize::Percent(_))))
+                {
+                    last_flexible_child.split_size = None;
+                }
+                return Ok(TiledPaneLayout {
+                    children_split_direction: split_direction,
+                    children,
+                    ..Default::default()
+                });
+            }
+            Err("Failed to find a split between tiled panes")
+        },
+    }
+}
+
+fn pane_start_and_extent(
+    pane: &PaneLayoutManifest,
+    split_direction: SplitDirection,
+) -> (usize, usize) {
+    match split_direction {
+        SplitDirection::Vertical => (pane.geom.x, pane.geom.cols.as_usize()),
+        SplitDirection::Horizontal => (pane.geom.y, pane.geom.rows.as_usize()),
+    }
+}
+
+fn split_into_groups(
+    panes: &[PaneLayoutManifest],
+    split_direction: SplitDirection,
+) -> Vec<(usize, Vec<PaneLayoutManifest>)> {
+    // a cut is a line across the whole space that does not cross any pane (or any stack)
+    let cut_is_valid = |cut: usize| {
+        let crosses_pane = panes.iter().any(|p| {
+            let (start, extent) = pane_start_and_extent(p, split_direction);
+            start < cut && cut < start + extent
+        });
+        let separates_stacked_panes = split_direction == SplitDirection::Horizontal
+            && panes.iter().any(|above| {
+                above.geom.is_stacked
+                    && above.geom.y + above.geom.rows.as_usize() == cut
+                    && panes.iter().any(|below| {
+                        below.geom.is_stacked
+                            && below.geom.y == cut
+                            && below.geom.x == above.geom.x
+                            && below.geom.cols == above.geom.cols
+                    })
+            });
+        !crosses_pane && !separates_stacked_panes
+    };
+    let mut cuts: Vec<usize> = panes
+        .iter()
+        .map(|p| {
+            let (start, extent) = pane_start_and_extent(p, split_direction);
+            start + extent
+        })
+        .filter(|cut| cut_is_valid(*cut))
+        .collect();
+    cuts.sort_unstable();
+    cuts.dedup();
+
+    let mut groups = vec![];
+    let mut group_start = panes
+        .iter()
+        .map(|p| pane_start_and_extent(p, split_direction).0)
+        .min()
+        .unwrap_or(0);
+    for cut in cuts {
+        let group: Vec<PaneLayoutManifest> = panes
+            .iter()
+            .filter(|p| {
+                let (start, extent) = pane_start_and_extent(p, split_direction);
+                start >= group_start && start + extent <= cut
+            })
+            .cloned()
+            .collect();
+        if !group.is_empty() {
+            groups.push((cut - group_start, group));
+        }
+        group_start = cut;
+    }
+    groups
+}
+
+fn is_fixed_group(group: &[PaneLayoutManifest], split_direction: SplitDirection) -> bool {
+    match group {
+        [pane] => match split_direction {
+            SplitDirection::Vertical => pane.geom.cols.is_fixed(),
+            SplitDirection::Horizontal => pane.geom.rows.is_fixed(),
+        },
+        _ => false,
+    }
+}
+
+fn is_a_single_stack(panes: &[PaneLayoutManifest]) -> bool {
+    match panes.first() {
+        Some(first) => panes.iter().all(|p| {
+            p.geom.is_stacked && p.geom.x == first.geom.x && p.geom.cols == first.geom.cols
+        }),
+        None => false,
+    }
+}
+
+fn leaf_from_manifest(
+    pane: &PaneLayoutManifest,
+    default_shell: &Option<PathBuf>,
+) -> TiledPaneLayout {
+    TiledPaneLayout {
+        name: pane.title.clone(),
+        run: run_from_manifest(pane, default_shell),
+        borderless: pane.is_borderless,
+        focus: if pane.is_focused { Some(true) } else { None },
+        ..Default::default()
+    }
+}
+
+fn run_from_manifest(pane: &PaneLayoutManifest, default_shell: &Option<PathBuf>) -> Option<Run> {
+    match &pane.run {
+        Some(Run::Command(run_command)) => {
+            let is_default_shell =
+                run_command.args.is_empty() && default_shell.as_ref() == Some(&run_command.command);
+            let cwd = pane.cwd.clone().or_else(|| run_command.cwd.clone());
+            if is_default_shell {
+                cwd.map(Run::Cwd)
+            } else {
+                // commands are not re-run until the user asks for it
+                Some(Run::Command(RunCommand {
+                    cwd,
+                    hold_on_start: true,
+                    ..run_command.clone()
+                }))
+            }
+        },
+        Some(Run::EditFile(file, line_number, cwd)) => Some(Run::EditFile(
+            file.clone(),
+            *line_number,
+            pane.cwd.clone().or_else(|| cwd.clone()),
+        )),
+        Some(Run::Plugin(run_plugin)) => Some(Run::Plugin(run_plugin.clone())),
+        Some(Run::Cwd(cwd)) => Some(Run::Cwd(pane.cwd.clone().unwrap_or_else(|| cwd.clone()))),
+        None => pane.cwd.clone().map(Run::Cwd),
+    }
+}
+
+fn stringify_tab(
+    kdl_string: &mut String,
+    tab_name: &str,
+    is_focused: bool,
+    tiled_panes_layout: &TiledPaneLayout,
+    floating_panes_layout: &[FloatingPaneLayout],
+) {
+    let mut tab_attributes = vec![format!("name={}", kdl_quote(tab_name))];
+    if is_focused {
+        tab_attributes.push("focus=true".to_owned());
+    }
+    let tab_children = if tiled_panes_layout.children.is_empty() {
+        std::slice::from_ref(tiled_panes_layout)
+    } else {
+        if tiled_panes_layout.children_split_direction == SplitDirection::Vertical {
+            tab_attributes.push("split_direction=\"vertical\"".to_owned());
+        }
+        &tiled_panes_layout.children[..]
+    };
+    kdl_string.push_str(&format!("{}tab {} {{\n", INDENT, tab_attributes.join(" ")));
+    for child in tab_children {
+        stringify_tiled_pane(kdl_string, child, 2);
+    }
+    if !floating_panes_layout.is_empty() {
+        kdl_string.push_str(&format!("{}floating_panes {{\n", INDENT.repeat(2)));
+        for floating_pane in floating_panes_layout {
+            stringify_floating_pane(kdl_string, floating_pane, 3);
+        }
+        kdl_string.push_str(&format!("{}}}\n", INDENT.repeat(2)));
+    }
+    kdl_string.push_str(&format!("{}}}\n", INDENT));
+}
+
+fn stringify_tiled_pane(kdl_string: &mut String, layout: &TiledPaneLayout, depth: usize) {
+    let mut attributes = vec![];
+    if let Some(name) = &layout.name {
+        attributes.push(format!("name={}", kdl_quote(name)));
+    }
+    match layout.split_size {
+        Some(SplitSize::Fixed(size)) => attributes.push(format!("size={}", size)),
+        Some(SplitSize::Percent(percent)) => attributes.push(format!("size=\"{}%\"", percent)),
+        None => {},
+    }
+    if layout.borderless {
+        attributes.push("borderless=true".to_owned());
+    }
+    if layout.focus == Some(true) {
+        attributes.push("focus=true".to_owned());
+    }
+    if !layout.children.is_empty() && layout.children_split_direction == SplitDirection::Vertical {
+        attributes.push("split_direction=\"vertical\"".to_owned());
+    }
+    if layout.children_are_stacked {
+        attributes.push("stacked=true".to_owned());
+    }
+    if layout.is_expanded_in_stack {
+        attributes.push("expanded=true".to_owned());
+    }
+    let child_nodes = stringify_run(&layout.run, &mut attributes);
+    stringify_node(kdl_string, "pane", attributes, depth, |kdl_string| {
+        for child_node in &child_nodes {
+            kdl_string.push_str(&format!("{}{}\n", INDENT.repeat(depth + 1), child_node));
+        }
+        for child in &layout.children {
+            stringify_tiled_pane(kdl_string, child, depth + 1);
+        }
+        !child_nodes.is_empty() || !layout.children.is_empty()
+    });
+}
+
+fn stringify_floating_pane(kdl_string: &mut String, layout: &FloatingPaneLayout, depth: usize) {
+    let mut attributes = vec![];
+    if let Some(name) = &layout.name {
+        attributes.push(format!("name={}", kdl_quote(name)));
+    }
+    for (attribute_name, value) in [
+        ("x", &layout.x),
+        ("y", &layout.y),
+        ("width", &layout.width),
+        ("height", &layout.height),
+    ] {
+        match value {
+            Some(PercentOrFixed::Fixed(fixed)) => {
+                attributes.push(format!("{}={}", attribute_name, fixed))
+            },
+            Some(PercentOrFixed::Percent(percent)) => {
+                attributes.push(format!("{}=\"{}%\"", attribute_name, percent))
+            },
+            None => {},
+        }
+    }
+    if layout.focus == Some(true) {
+        attributes.push("focus=true".to_owned());
+    }
+    let child_nodes = stringify_run(&layout.run, &mut attributes);
+    stringify_node(kdl_string, "pane", attributes, depth, |kdl_string| {
+        for child_node in &child_nodes {
+            kdl_string.push_str(&format!("{}{}\n", INDENT.repeat(depth + 1), child_node));
+        }
+        !child_nodes.is_empty()
+    });
+}
+
+// pushes the properties of `run` to `attributes` and returns the child nodes it needs
+fn stringify_run(run: &Option<Run>, attributes: &mut Vec<String>) -> Vec<String> {
+    let mut child_nodes = vec![];
+    match run {
+        Some(Run::Command(run_command)) => {
+            attributes.push(format!(
+                "command={}",
+                kdl_quote(&run_command.command.display().to_string())
+            ));
+            if let Some(cwd) = &run_command.cwd {
+                attributes.push(format!("cwd={}", kdl_quote(&cwd.display().to_string())));
+            }
+            if !run_command.args.is_empty() {
+                let args: Vec<String> = run_command.args.iter().map(|a| kdl_quote(a)).collect();
+                child_nodes.push(format!("args {}", args.join(" ")));
+            }
+            if !run_command.hold_on_close {
+                child_nodes.push("close_on_exit true".to_owned());
+            }
+            if run_command.hold_on_start {
+                child_nodes.push("start_suspended true".to_owned());
+            }
+        },
+        Some(Run::EditFile(file, _line_number, cwd)) => {
+            attributes.push(format!("edit={}", kdl_quote(&file.display().to_string())));
+            if let Some(cwd) = cwd {
+                attributes.push(format!("cwd={}", kdl_quote(&cwd.display().to_string())));
+            }
+        },
+        Some(Run::Cwd(cwd)) => {
+            attributes.push(format!("cwd={}", kdl_quote(&cwd.display().to_string())));
+        },
+        Some(Run::Plugin(run_plugin)) => {
+            let url: url::Url = (&run_plugin.location).into();
+            let mut plugin_node = format!("plugin location={}", kdl_quote(url.as_str()));
+            for (key, value) in run_plugin.configuration.inner() {
+                plugin_node.push_str(&format!(" {}={}", kdl_quote(key), kdl_quote(value)));
+            }
+            child_nodes.push(plugin_node);
+        },
+        None => {},
+    }
+    child_nodes
+}
+
+fn stringify_node(
+    kdl_string: &mut String,
+    node_name: &str,
+    attributes: Vec<String>,
+    depth: usize,
+    stringify_children: impl FnOnce(&mut String) -> bool,
+) {
+    let indentation = INDENT.repeat(depth);
+    let mut node = format!("{}{}", indentation, node_name);
+    for attribute in attributes {
+        node.push(' ');
+        node.push_str(&attribute);
+    }
+    let mut children = String::new();
+    if stringify_children(&mut children) {
+        kdl_string.push_str(&format!("{} {{\n{}{}}}\n", node, children, indentation));
+    } else {
+        kdl_string.push_str(&format!("{}\n", node));
+    }
+}
+
+fn kdl_quote(s: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{{{:x}}}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+// The unit test location.
+#[path = "./unit/session_serialization_tests.rs"]
+#[cfg(test)]
+mod session_serialization_tests;
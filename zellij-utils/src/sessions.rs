@@ -0,0 +1,88 @@
+//! Querying and controlling running sessions through their server sockets.
+
+use crate::{
+    consts::ZELLIJ_SOCK_DIR,
+    data::SessionInfo,
+    ipc::{ClientToServerMsg, IpcReceiverWithContext, IpcSenderWithContext, ServerToClientMsg},
+};
+use interprocess::local_socket::LocalSocketStream;
+use std::mem::ManuallyDrop;
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::io::{AsRawFd, FromRawFd};
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::time::Duration;
+use std::{fs, io};
+
+// how long we wait for a session to describe itself before giving up on it
+const SESSION_INFO_TIMEOUT_MS: u64 = 1000;
+
+/// Asks the server of the session `name` to describe its session, sessions that do not answer in
+/// time (eg. because they are still starting up or are stuck) are `None`
+pub fn get_session_info(name: &str) -> Option<SessionInfo> {
+    query_session_info(&ZELLIJ_SOCK_DIR.join(name))
+}
+
+fn query_session_info(path: &Path) -> Option<SessionInfo> {
+    match LocalSocketStream::connect(path) {
+        Ok(stream) => {
+            set_read_timeout(&stream, Duration::from_millis(SESSION_INFO_TIMEOUT_MS)).ok()?;
+            let mut sender = IpcSenderWithContext::new(stream);
+            let _ = sender.send(ClientToServerMsg::QuerySessionInfo);
+            let mut receiver: IpcReceiverWithContext<ServerToClientMsg> = sender.get_receiver();
+            match receiver.recv() {
+                Some((ServerToClientMsg::SessionInfo(session_info), _)) => Some(session_info),
+                None | Some((_, _)) => None,
+            }
+        },
+        Err(_) => None,
+    }
+}
+
+fn set_read_timeout(stream: &LocalSocketStream, timeout: Duration) -> io::Result<()> {
+    // the stream still owns the socket, so this must not close it when dropped
+    let unix_stream = ManuallyDrop::new(unsafe { UnixStream::from_raw_fd(stream.as_raw_fd()) });
+    unix_stream.set_read_timeout(Some(timeout))
+}
+
+/// The info of all the sessions that answer on their socket, sorted by name
+pub fn get_all_session_infos() -> Vec<SessionInfo> {
+    let mut session_infos: Vec<SessionInfo> = match fs::read_dir(&*ZELLIJ_SOCK_DIR) {
+        Ok(files) => files
+            .filter_map(|file| file.ok())
+            .filter(|file| {
+                file.file_type()
+                    .map(|file_type| file_type.is_socket())
+                    .unwrap_or(false)
+            })
+            .filter_map(|file| file.file_name().into_string().ok())
+            .filter_map(|session_name| get_session_info(&session_name))
+            .collect(),
+        Err(_) => vec![],
+    };
+    session_infos.sort_by(|a, b| a.name.cmp(&b.name));
+    session_infos
+}
+
+/// Session names become the file names of the session sockets, so they cannot lead outside of
+/// the socket directory.
+pub fn is_valid_session_name(name: &str) -> bool {
+    !(name.trim().is_empty() || name == "." || name == ".." || name.contains('/'))
+}
+
+pub fn kill_session(name: &str) -> io::Result<()> {
+    if !is_valid_session_name(name) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("invalid session name {name:?}"),
+        ));
+    }
+    let path = &*ZELLIJ_SOCK_DIR.join(name);
+    let stream = LocalSocketStream::connect(path)?;
+    let _ = IpcSenderWithContext::new(stream).send(ClientToServerMsg::KillSession);
+    Ok(())
+}
+
+#[path = "./unit/sessions_tests.rs"]
+#[cfg(test)]
+mod sessions_tests;
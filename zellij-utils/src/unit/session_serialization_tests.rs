@@ -0,0 +1,231 @@
+use super::*;
+use crate::data::PluginTag;
+use crate::input::layout::{Layout, PluginUserConfiguration, RunPlugin, RunPluginLocation};
+use crate::pane_size::Dimension;
+use std::collections::BTreeMap;
+
+fn fixed(size: usize) -> Dimension {
+    Dimension::fixed(size)
+}
+
+fn percent(percent: f64, inner: usize) -> Dimension {
+    let mut dimension = Dimension::percent(percent);
+    dimension.set_inner(inner);
+    dimension
+}
+
+fn geom(x: usize, y: usize, cols: Dimension, rows: Dimension) -> PaneGeom {
+    PaneGeom {
+        x,
+        y,
+        cols,
+        rows,
+        is_stacked: false,
+    }
+}
+
+fn plugin_pane(plugin: &str, geom: PaneGeom) -> PaneLayoutManifest {
+    PaneLayoutManifest {
+        geom,
+        run: Some(Run::Plugin(RunPlugin {
+            _allow_exec_host_cmd: false,
+            location: RunPluginLocation::Zellij(PluginTag::new(plugin)),
+            configuration: PluginUserConfiguration::default(),
+        })),
+        is_borderless: true,
+        ..Default::default()
+    }
+}
+
+fn terminal_pane(cwd: &str, geom: PaneGeom) -> PaneLayoutManifest {
+    PaneLayoutManifest {
+        geom,
+        cwd: Some(PathBuf::from(cwd)),
+        ..Default::default()
+    }
+}
+
+// the default layout with its main area split vertically, on a 100x24 screen
+fn default_layout_panes() -> Vec<PaneLayoutManifest> {
+    vec![
+        plugin_pane("tab-bar", geom(0, 0, percent(100.0, 100), fixed(1))),
+        terminal_pane("/tmp", geom(0, 1, percent(50.0, 50), percent(100.0, 21))),
+        PaneLayoutManifest {
+            is_focused: true,
+            ..terminal_pane("/var", geom(50, 1, percent(50.0, 50), percent(100.0, 21)))
+        },
+        plugin_pane("status-bar", geom(0, 22, percent(100.0, 100), fixed(2))),
+    ]
+}
+
+#[test]
+fn tiled_panes_are_reconstructed_into_splits() {
+    let layout = tiled_panes_layout_from_manifest(&default_layout_panes(), &None).unwrap();
+    assert_eq!(layout.children_split_direction, SplitDirection::Horizontal);
+    assert_eq!(
+        layout.children.len(),
+        3,
+        "tab-bar, main area and status-bar"
+    );
+    assert_eq!(layout.children[0].split_size, Some(SplitSize::Fixed(1)));
+    assert!(layout.children[0].borderless);
+    assert_eq!(
+        layout.children[1].split_size, None,
+        "last flexible part takes up the remaining space"
+    );
+    assert_eq!(layout.children[2].split_size, Some(SplitSize::Fixed(2)));
+
+    let main_area = &layout.children[1];
+    assert_eq!(main_area.children_split_direction, SplitDirection::Vertical);
+    assert_eq!(main_area.children.len(), 2);
+    assert_eq!(
+        main_area.children[0].split_size,
+        Some(SplitSize::Percent(50))
+    );
+    assert_eq!(
+        main_area.children[0].run,
+        Some(Run::Cwd(PathBuf::from("/tmp")))
+    );
+    assert_eq!(main_area.children[1].split_size, None);
+    assert_eq!(main_area.children[1].focus, Some(true));
+}
+
+#[test]
+fn serialized_session_can_be_parsed_as_a_layout() {
+    let tiled_panes = default_layout_panes();
+    let floating_panes = vec![PaneLayoutManifest {
+        title: Some("my floating pane".into()),
+        ..terminal_pane("/tmp", geom(10, 5, fixed(40), fixed(10)))
+    }];
+    let global_layout_manifest = GlobalLayoutManifest {
+        default_shell: None,
+        tabs: vec![
+            (
+                "first tab".into(),
+                TabLayoutManifest {
+                    tiled_panes: tiled_panes.clone(),
+                    floating_panes: vec![],
+                    is_focused: false,
+                },
+            ),
+            (
+                "second \"tab\"".into(),
+                TabLayoutManifest {
+                    tiled_panes: tiled_panes.clone(),
+                    floating_panes: floating_panes.clone(),
+                    is_focused: true,
+                },
+            ),
+        ],
+    };
+    let kdl_layout = serialize_session_layout(global_layout_manifest).unwrap();
+    let layout = Layout::from_kdl(&kdl_layout, "session_layout".into(), None, None).unwrap();
+    assert_eq!(layout.tabs.len(), 2);
+    assert_eq!(layout.focused_tab_index, Some(1));
+    assert_eq!(layout.tabs[1].0, Some("second \"tab\"".to_owned()));
+    assert_eq!(
+        layout.tabs[0].1,
+        tiled_panes_layout_from_manifest(&tiled_panes, &None).unwrap()
+    );
+    assert_eq!(
+        layout.tabs[1].2,
+        floating_panes_layout_from_manifest(&floating_panes, &None)
+    );
+}
+
+#[test]
+fn plugin_configuration_is_serialized() {
+    let mut configuration = BTreeMap::new();
+    configuration.insert("start_path".to_owned(), "/tmp/my \"dir\"".to_owned());
+    configuration.insert("hide-hidden".to_owned(), "true".to_owned());
+    let run_plugin = RunPlugin {
+        _allow_exec_host_cmd: false,
+        location: RunPluginLocation::Zellij(PluginTag::new("strider")),
+        configuration: PluginUserConfiguration::new(configuration),
+    };
+    let tiled_panes = vec![PaneLayoutManifest {
+        run: Some(Run::Plugin(run_plugin.clone())),
+        ..plugin_pane(
+            "strider",
+            geom(0, 0, percent(100.0, 100), percent(100.0, 24)),
+        )
+    }];
+    let global_layout_manifest = GlobalLayoutManifest {
+        default_shell: None,
+        tabs: vec![(
+            "tab".into(),
+            TabLayoutManifest {
+                tiled_panes,
+                floating_panes: vec![],
+                is_focused: true,
+            },
+        )],
+    };
+    let kdl_layout = serialize_session_layout(global_layout_manifest).unwrap();
+    let layout = Layout::from_kdl(&kdl_layout, "session_layout".into(), None, None).unwrap();
+    assert!(
+        layout.tabs[0]
+            .1
+            .extract_run_instructions()
+            .contains(&Some(Run::Plugin(run_plugin))),
+        "plugin configuration survived serialization"
+    );
+}
+
+#[test]
+fn commands_are_suspended_and_default_shells_are_omitted() {
+    let default_shell = Some(PathBuf::from("/bin/bash"));
+    let command_pane = PaneLayoutManifest {
+        run: Some(Run::Command(RunCommand {
+            command: PathBuf::from("htop"),
+            args: vec!["-d".into(), "10".into()],
+            hold_on_close: true,
+            ..Default::default()
+        })),
+        ..terminal_pane("/home", geom(0, 0, percent(50.0, 50), percent(100.0, 20)))
+    };
+    let shell_pane = PaneLayoutManifest {
+        run: Some(Run::Command(RunCommand {
+            command: PathBuf::from("/bin/bash"),
+            ..Default::default()
+        })),
+        ..terminal_pane("/tmp", geom(50, 0, percent(50.0, 50), percent(100.0, 20)))
+    };
+    let layout =
+        tiled_panes_layout_from_manifest(&[command_pane, shell_pane], &default_shell).unwrap();
+    assert_eq!(
+        layout.children[0].run,
+        Some(Run::Command(RunCommand {
+            command: PathBuf::from("htop"),
+            args: vec!["-d".into(), "10".into()],
+            cwd: Some(PathBuf::from("/home")),
+            hold_on_close: true,
+            hold_on_start: true,
+        }))
+    );
+    assert_eq!(
+        layout.children[1].run,
+        Some(Run::Cwd(PathBuf::from("/tmp")))
+    );
+}
+
+#[test]
+fn stacked_panes_are_serialized_as_a_stack() {
+    let stacked = |y: usize, rows: Dimension| PaneGeom {
+        is_stacked: true,
+        ..geom(50, y, percent(50.0, 50), rows)
+    };
+    let panes = vec![
+        terminal_pane("/tmp", geom(0, 0, percent(50.0, 50), percent(100.0, 20))),
+        terminal_pane("/tmp", stacked(0, fixed(1))),
+        terminal_pane("/tmp", stacked(1, percent(100.0, 18))),
+        terminal_pane("/tmp", stacked(19, fixed(1))),
+    ];
+    let layout = tiled_panes_layout_from_manifest(&panes, &None).unwrap();
+    assert_eq!(layout.children_split_direction, SplitDirection::Vertical);
+    let stack = &layout.children[1];
+    assert!(stack.children_are_stacked);
+    assert_eq!(stack.children.len(), 3);
+    assert!(stack.children[1].is_expanded_in_stack);
+    assert!(!stack.children[0].is_expanded_in_stack);
+}
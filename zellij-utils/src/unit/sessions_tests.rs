@@ -0,0 +1,39 @@
+use super::*;
+use interprocess::local_socket::LocalSocketListener;
+use std::time::Instant;
+
+#[test]
+fn sessions_that_do_not_answer_are_given_up_on() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let socket_path = temp_dir.path().join("stuck-session");
+    let listener = LocalSocketListener::bind(&*socket_path).unwrap();
+    let server = std::thread::spawn(move || {
+        // accept the connection but never answer it
+        let stream = listener.accept().unwrap();
+        std::thread::sleep(Duration::from_millis(SESSION_INFO_TIMEOUT_MS * 3));
+        drop(stream);
+    });
+    let start = Instant::now();
+    assert_eq!(query_session_info(&socket_path), None);
+    assert!(start.elapsed() < Duration::from_millis(SESSION_INFO_TIMEOUT_MS * 2));
+    server.join().unwrap();
+}
+
+#[test]
+fn session_names_cannot_lead_outside_of_the_socket_directory() {
+    for name in [
+        "",
+        " ",
+        ".",
+        "..",
+        "../other-user/session",
+        "nested/session",
+    ] {
+        assert!(!is_valid_session_name(name), "{name:?} is not valid");
+    }
+    assert!(is_valid_session_name("my-session.2"));
+    assert_eq!(
+        kill_session("../session").unwrap_err().kind(),
+        io::ErrorKind::InvalidInput
+    );
+}